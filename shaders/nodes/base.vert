@@ -11,6 +11,9 @@ layout (push_constant) uniform NodePC {
   float scale;
   vec2 viewport_dims;
   uint texture_period;
+  uint selection_only;
+  float unselected_dim;
+  float overlay_opacity;
 } node_uniform;
 
 void main() {
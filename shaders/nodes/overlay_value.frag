@@ -24,19 +24,42 @@ layout (push_constant) uniform NodePC {
   float scale;
   vec2 viewport_dims;
   uint texture_period;
+  uint selection_only;
+  float unselected_dim;
+  float overlay_opacity;
 } node_uniform;
 
 void main() {
   uint is_selected = selection.flag[node_id - 1];
 
+  // when the overlay is scoped to the selection, the nodes outside
+  // the active group have already been drawn using the theme
+  if (node_uniform.selection_only == 1 && (is_selected & 1) == 0) {
+    discard;
+  }
+
   f_id = uint(node_id);
 
-  if ((is_selected & 1) == 1) {
-    f_mask = vec4(1.0, 1.0, 1.0, 1.0);
+  // bit 1 is set for nodes in any selection group, with the outline
+  // color of the group in the upper 24 bits
+  if ((is_selected & 2) == 2) {
+    vec3 outline = vec3((is_selected >> 24) & 0xFFu,
+                        (is_selected >> 16) & 0xFFu,
+                        (is_selected >> 8) & 0xFFu) / 255.0;
+    f_mask = vec4(outline, 1.0);
   } else {
     f_mask = vec4(0.0, 0.0, 0.0, 0.0);
   }
 
   float node_val = node_value.value[node_id];
-  f_color = texture(overlay, node_val);
+
+  // nodes without a value are drawn in a neutral gray rather than
+  // with a color from the scheme
+  if (isnan(node_val)) {
+    f_color = vec4(0.5, 0.5, 0.5, node_uniform.overlay_opacity);
+    return;
+  }
+
+  vec4 color = texture(overlay, node_val);
+  f_color = vec4(color.rgb, color.a * node_uniform.overlay_opacity);
 }
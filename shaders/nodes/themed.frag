@@ -24,6 +24,9 @@ layout (push_constant) uniform NodePC {
   float scale;
   vec2 viewport_dims;
   uint texture_period;
+  uint selection_only;
+  float unselected_dim;
+  float overlay_opacity;
 } node_uniform;
 
 void main() {
@@ -31,12 +34,22 @@ void main() {
 
   f_id = uint(node_id);
 
-  if ((is_selected & 1) == 1) {
-    f_mask = vec4(1.0, 1.0, 1.0, 1.0);
+  // bit 1 is set for nodes in any selection group, with the outline
+  // color of the group in the upper 24 bits
+  if ((is_selected & 2) == 2) {
+    vec3 outline = vec3((is_selected >> 24) & 0xFFu,
+                        (is_selected >> 16) & 0xFFu,
+                        (is_selected >> 8) & 0xFFu) / 255.0;
+    f_mask = vec4(outline, 1.0);
   } else {
     f_mask = vec4(0.0, 0.0, 0.0, 0.0);
   }
 
   float color_u = float((node_id - 1) % node_uniform.texture_period) / node_uniform.texture_period;
   f_color = texture(theme_sampler, color_u);
+
+  // fade the nodes that the selection-scoped overlay isn't drawn over
+  if (node_uniform.selection_only == 1 && (is_selected & 1) == 0) {
+    f_color.rgb = mix(f_color.rgb, vec3(0.5), node_uniform.unselected_dim);
+  }
 }
@@ -15,7 +15,7 @@ vec2 uv_coord(vec2 coord) {
 }
 
 
-float edge_ver(vec4 fc, vec2 uv) {
+vec3 edge_ver(vec4 fc, vec2 uv) {
 
   float row0[3];
   row0[0] = 1.0;
@@ -32,21 +32,21 @@ float edge_ver(vec4 fc, vec2 uv) {
   row2[1] = 0.0;
   row2[2] = -1.0;
 
-  float result = texture(u_color_sampler, uv).r * row1[1];
+  vec3 result = texture(u_color_sampler, uv).rgb * row1[1];
 
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, -1.0))).r * row0[0];
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, 1.0))).r * row0[2];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, -1.0))).rgb * row0[0];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, 1.0))).rgb * row0[2];
 
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(0.0, -1.0))).r * row1[0];
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(0.0, 1.0))).r * row1[2];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(0.0, -1.0))).rgb * row1[0];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(0.0, 1.0))).rgb * row1[2];
 
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, -1.0))).r * row2[0];
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, 1.0))).r * row2[2];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, -1.0))).rgb * row2[0];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, 1.0))).rgb * row2[2];
 
   return result;
 }
 
-float edge_hor(vec4 fc, vec2 uv) {
+vec3 edge_hor(vec4 fc, vec2 uv) {
 
   float row0[3];
   row0[0] = 1.0;
@@ -58,15 +58,15 @@ float edge_hor(vec4 fc, vec2 uv) {
   row2[1] = -2.0;
   row2[2] = -1.0;
 
-  float result = 0.0;
+  vec3 result = vec3(0.0);
 
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, -1.0))).r * row0[0];
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, 0.0))).r * row0[1];
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, 1.0))).r * row0[2];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, -1.0))).rgb * row0[0];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, 0.0))).rgb * row0[1];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(-1.0, 1.0))).rgb * row0[2];
 
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, -1.0))).r * row2[0];
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, 0.0))).r * row2[1];
-  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, 1.0))).r * row2[2];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, -1.0))).rgb * row2[0];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, 0.0))).rgb * row2[1];
+  result += texture(u_color_sampler, uv_coord(fc.xy + vec2(1.0, 1.0))).rgb * row2[2];
 
   return result;
 }
@@ -79,12 +79,15 @@ void main() {
   vec4 color = texture(u_color_sampler, uv);
 
   if (dims.enabled) {
-    float ver = abs(edge_ver(fc, uv));
-    float hor = abs(edge_hor(fc, uv));
+    // edges are detected per channel, so that each selection group
+    // keeps its outline color
+    vec3 ver = abs(edge_ver(fc, uv));
+    vec3 hor = abs(edge_hor(fc, uv));
 
-    float result = max(hor, ver);
+    vec3 result = max(hor, ver);
+    float alpha = max(result.r, max(result.g, result.b));
 
-    f_color = vec4(result, result, result, result);
+    f_color = vec4(result, alpha);
 
   } else {
     f_color = color;
@@ -2,7 +2,11 @@ use std::{collections::HashMap, sync::Arc};
 
 use anyhow::Result;
 
-use crossbeam::atomic::AtomicCell;
+use crossbeam::{
+    atomic::AtomicCell,
+    channel::{Receiver, Sender},
+};
+use futures::future::RemoteHandle;
 use handlegraph::{
     handle::{Handle, NodeId},
     pathhandlegraph::*,
@@ -14,6 +18,7 @@ use bstr::ByteSlice;
 
 use rustc_hash::{FxHashMap, FxHashSet};
 
+use crate::reactor::Reactor;
 use crate::{geometry::*, universe::Node, view::*};
 
 use nalgebra as na;
@@ -21,9 +26,13 @@ use nalgebra_glm as glm;
 
 pub mod bed;
 pub mod gff;
+pub mod node_metadata;
+pub mod node_table;
 
 pub use bed::*;
 pub use gff::*;
+pub use node_metadata::*;
+pub use node_table::*;
 
 #[derive(Debug, Clone)]
 pub struct AnnotationLabelSet {
@@ -109,6 +118,27 @@ pub enum AnnotationFileType {
     Bed,
 }
 
+impl AnnotationFileType {
+    pub const ALL: [AnnotationFileType; 2] =
+        [AnnotationFileType::Gff3, AnnotationFileType::Bed];
+
+    /// The name used to refer to the file type in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnnotationFileType::Gff3 => "gff3",
+            AnnotationFileType::Bed => "bed",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "gff3" | "gff" => Some(AnnotationFileType::Gff3),
+            "bed" => Some(AnnotationFileType::Bed),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum AnnotationColumn {
     Gff3(Gff3Column),
@@ -123,6 +153,8 @@ pub struct Annotations {
     bed_annotations: HashMap<String, Arc<BedRecords>>,
 
     label_sets: HashMap<String, Arc<AnnotationLabelSet>>,
+
+    node_metadata: Vec<Arc<NodeMetadata>>,
 }
 
 impl Annotations {
@@ -187,6 +219,31 @@ impl Annotations {
     pub fn label_sets(&self) -> &HashMap<String, Arc<AnnotationLabelSet>> {
         &self.label_sets
     }
+
+    /// Adds a node metadata table, replacing any loaded table with
+    /// the same file name
+    pub fn insert_node_metadata(&mut self, metadata: NodeMetadata) {
+        let metadata = Arc::new(metadata);
+
+        if let Some(existing) = self
+            .node_metadata
+            .iter_mut()
+            .find(|m| m.file_name() == metadata.file_name())
+        {
+            *existing = metadata;
+        } else {
+            self.node_metadata.push(metadata);
+        }
+    }
+
+    pub fn get_node_metadata(&self, name: &str) -> Option<&Arc<NodeMetadata>> {
+        self.node_metadata.iter().find(|m| m.file_name() == name)
+    }
+
+    /// The loaded node metadata tables, in the order they were loaded
+    pub fn node_metadata(&self) -> &[Arc<NodeMetadata>] {
+        &self.node_metadata
+    }
 }
 
 pub trait ColumnKey:
@@ -332,37 +389,128 @@ pub fn path_name_offset(path_name: &[u8]) -> Option<usize> {
     */
 }
 
+/// Returns the steps of a path whose nodes overlap the half-open
+/// range `start..end`, with `start` and `end` given in the coordinates
+/// of the path name. A record that starts partway through a node
+/// includes that node.
+///
+/// `offset` is the start position of the path, e.g. as parsed by
+/// `path_name_offset` from a path named `chr1:1000-2000`; if `None`,
+/// the path starts at position 0. `steps` must be sorted by position,
+/// as returned by `GraphQuery::path_pos_steps`, and `path_len` is the
+/// length of the path in base pairs, where the last step ends.
+///
+/// Ranges that extend past either end of the path are clamped to it,
+/// and empty ranges and ranges entirely outside the path produce an
+/// empty slice. Returns `None` if `end` is less than `start`.
 pub fn path_step_range(
     steps: &[(Handle, StepPtr, usize)],
+    path_len: usize,
     offset: Option<usize>,
     start: usize,
     end: usize,
 ) -> Option<&[(Handle, StepPtr, usize)]> {
+    if end < start {
+        return None;
+    }
+
     let offset = offset.unwrap_or(0);
 
-    let len = end - start;
+    let start = start.saturating_sub(offset);
+    let end = end.saturating_sub(offset).min(path_len);
 
-    let start = start.checked_sub(offset).unwrap_or(0);
-    let end = end.checked_sub(offset).unwrap_or(start + len);
+    if start >= end {
+        return Some(&steps[0..0]);
+    }
 
-    let (start, end) = {
-        let start = steps.binary_search_by_key(&start, |(_, _, p)| *p);
+    // the node containing `start` is the last step at or before it;
+    // any zero-length steps at the same position are included too
+    let start_ix = match first_step_at_or_after(steps, start + 1) {
+        0 => 0,
+        ix => first_step_at_or_after(steps, steps[ix - 1].2),
+    };
+    let end_ix = first_step_at_or_after(steps, end);
 
-        let end = steps.binary_search_by_key(&end, |(_, _, p)| *p);
+    Some(&steps[start_ix..end_ix])
+}
 
-        let (start, end) = match (start, end) {
-            (Ok(s), Ok(e)) => (s, e),
-            (Ok(s), Err(e)) => (s, e),
-            (Err(s), Ok(e)) => (s, e),
-            (Err(s), Err(e)) => (s, e),
-        };
+/// The index of the first step with position greater than or equal
+/// to `pos`, or `steps.len()` if there is none
+fn first_step_at_or_after(
+    steps: &[(Handle, StepPtr, usize)],
+    pos: usize,
+) -> usize {
+    use std::cmp::Ordering;
+
+    // never returns `Equal`, so the search always ends in `Err` at
+    // the partition point, even if there are several steps at `pos`
+    steps
+        .binary_search_by(|(_, _, p)| {
+            if *p < pos {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        })
+        .unwrap_or_else(|ix| ix)
+}
+
+/// Whether the range `start..end`, given in the coordinates of the
+/// path name, lies entirely within a path of length `path_len`.
+/// `offset` is the start position of the path, as in
+/// `path_step_range`.
+pub fn range_within_path(
+    path_len: usize,
+    offset: Option<usize>,
+    start: usize,
+    end: usize,
+) -> bool {
+    let offset = offset.unwrap_or(0);
+    start >= offset && end <= offset + path_len && start <= end
+}
 
-        let end = end.min(steps.len());
+/// How many records of an annotation file don't fit in a path, e.g.
+/// because they were made for another reference or assembly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathRangeReport {
+    pub records: usize,
+    pub out_of_range: usize,
+}
+
+impl PathRangeReport {
+    pub fn new<C: AnnotationCollection>(
+        records: &C,
+        path_len: usize,
+        offset: Option<usize>,
+    ) -> Self {
+        let out_of_range = records
+            .records()
+            .iter()
+            .filter(|record| {
+                !range_within_path(
+                    path_len,
+                    offset,
+                    record.start(),
+                    record.end(),
+                )
+            })
+            .count();
 
-        Some((start, end))
-    }?;
+        Self {
+            records: records.len(),
+            out_of_range,
+        }
+    }
+}
 
-    Some(&steps[start..end])
+impl std::fmt::Display for PathRangeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} records fell outside the path range",
+            self.out_of_range, self.records
+        )
+    }
 }
 
 pub fn path_step_radius(
@@ -409,7 +557,12 @@ pub struct ClusterCache {
     pub node_labels: FxHashMap<NodeId, ClusterIndices>,
 
     pub view_scale: f32,
+    pub view_aspect: f32,
     pub radius: f32,
+
+    /// False while the clusters are still being streamed in from a
+    /// `ClusterCacheLoader`
+    pub complete: bool,
 }
 
 impl ClusterCache {
@@ -427,103 +580,52 @@ impl ClusterCache {
     }
     */
 
-    pub fn new_cluster(
-        steps: &[(Handle, StepPtr, usize)],
-        nodes: &[Node],
+    /// A cache without any clusters, to be filled using `push_cluster`
+    pub fn empty(
         label_set: &Arc<AnnotationLabelSet>,
         view: View,
         radius: f32,
     ) -> Self {
-        let mut node_label_indices: FxHashMap<NodeId, ClusterIndices> =
-            FxHashMap::default();
-        let mut cluster_offsets: Vec<Point> = Vec::new();
-
-        let mut cluster_range_ix: Option<(usize, usize)> = None;
-        let mut cluster_start_pos: Option<Point> = None;
-        let mut current_cluster: Vec<usize> = Vec::new();
-
-        let mut clusters: FxHashMap<(usize, usize), Vec<usize>> =
-            FxHashMap::default();
-
-        let view_matrix = view.to_scaled_matrix();
-        let to_screen = |p: Point| {
-            let v = glm::vec4(p.x, p.y, 0.0, 1.0);
-            let v_ = view_matrix * v;
-            Point::new(v_[0], v_[1])
-        };
-
-        for (ix, (handle, _, _)) in steps.iter().enumerate() {
-            let node = handle.id();
-
-            if let Some(label_indices) = label_set.labels.get(&node) {
-                let node_ix = (node.0 - 1) as usize;
-                let node_pos = to_screen(nodes[node_ix].center());
+        Self {
+            label_set: label_set.clone(),
+            cluster_offsets: Vec::new(),
+            node_labels: FxHashMap::default(),
 
-                if let Some(start_pos) = cluster_start_pos {
-                    if node_pos.dist(start_pos) <= radius {
-                        cluster_range_ix.as_mut().map(|(_, end)| *end = ix);
-                        current_cluster.extend_from_slice(label_indices);
-                    } else {
-                        clusters.insert(
-                            cluster_range_ix.unwrap(),
-                            current_cluster.clone(),
-                        );
-                        current_cluster.clear();
-
-                        cluster_start_pos = Some(node_pos);
-                        cluster_range_ix = Some((ix, ix));
-
-                        current_cluster.extend_from_slice(label_indices);
-                    }
-                } else {
-                    cluster_start_pos = Some(node_pos);
-                    cluster_range_ix = Some((ix, ix));
+            view_scale: view.scale,
+            view_aspect: view.aspect,
+            radius,
 
-                    current_cluster.extend_from_slice(label_indices);
-                }
-            }
+            complete: false,
         }
+    }
 
-        for ((start, end), cluster_label_indices) in clusters {
-            let slice = &steps[start..=end];
-            let (mid_handle, _, _) = slice[slice.len() / 2];
-
-            let (start_h, _, _) = steps[start];
-            let (end_h, _, _) = steps[end];
-
-            let s_ix = (start_h.id().0 - 1) as usize;
-            let e_ix = (end_h.id().0 - 1) as usize;
-
-            let start_p = nodes[s_ix].p0;
-            let end_p = nodes[e_ix].p1;
-
-            let start_v = glm::vec2(start_p.x, start_p.y);
-            let end_v = glm::vec2(end_p.x, end_p.y);
-
-            let del = end_v - start_v;
-            let rot_del = glm::rotate_vec2(&del, std::f32::consts::PI / 2.0);
-
-            let rot_del_norm = rot_del.normalize();
+    pub fn new_cluster(
+        steps: &[(Handle, StepPtr, usize)],
+        nodes: &[Node],
+        label_set: &Arc<AnnotationLabelSet>,
+        view: View,
+        radius: f32,
+    ) -> Self {
+        let mut cache = Self::empty(label_set, view, radius);
 
-            let offset = Point::new(rot_del_norm[0], rot_del_norm[1]);
+        let mut builder = ClusterBuilder::new(view, radius);
+        for cluster in builder.step(steps, nodes, label_set, steps.len()) {
+            cache.push_cluster(cluster);
+        }
 
-            let cluster_indices = ClusterIndices {
-                label_indices: cluster_label_indices,
-                offset_ix: cluster_offsets.len(),
-            };
+        cache.complete = true;
 
-            node_label_indices.insert(mid_handle.id(), cluster_indices);
-            cluster_offsets.push(offset);
-        }
+        cache
+    }
 
-        Self {
-            label_set: label_set.clone(),
-            cluster_offsets,
-            node_labels: node_label_indices,
+    pub fn push_cluster(&mut self, cluster: LabelCluster) {
+        let cluster_indices = ClusterIndices {
+            label_indices: cluster.label_indices,
+            offset_ix: self.cluster_offsets.len(),
+        };
 
-            view_scale: view.scale,
-            radius,
-        }
+        self.node_labels.insert(cluster.node, cluster_indices);
+        self.cluster_offsets.push(cluster.offset);
     }
 
     pub fn rebuild_cluster(
@@ -533,99 +635,306 @@ impl ClusterCache {
         view: View,
         radius: f32,
     ) -> bool {
+        if !self.complete {
+            return false;
+        }
+
         if (view.scale - self.view_scale).abs() < 0.0001
+            && (view.aspect - self.view_aspect).abs() < 0.0001
             && radius == self.radius
         {
             return false;
         }
 
         self.view_scale = view.scale;
+        self.view_aspect = view.aspect;
         self.radius = radius;
 
         self.cluster_offsets.clear();
         self.node_labels.clear();
 
-        let mut cluster_range_ix: Option<(usize, usize)> = None;
-        let mut cluster_start_pos: Option<Point> = None;
-        let mut current_cluster: Vec<usize> = Vec::new();
+        let label_set = self.label_set.clone();
+
+        let mut builder = ClusterBuilder::new(view, radius);
+        for cluster in builder.step(steps, nodes, &label_set, steps.len()) {
+            self.push_cluster(cluster);
+        }
+
+        true
+    }
+}
+
+/// A group of labels that are close to each other on the screen,
+/// drawn together at a single node
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelCluster {
+    pub node: NodeId,
+    pub offset: Point,
+    pub label_indices: Vec<usize>,
+}
+
+/// Groups the labels of a label set into clusters by walking along
+/// the path steps, a chunk of steps at a time, so that the clusters
+/// can be produced incrementally
+#[derive(Debug, Clone)]
+pub struct ClusterBuilder {
+    view_matrix: glm::Mat4,
+    radius: f32,
+
+    next_step: usize,
+
+    cluster_range_ix: Option<(usize, usize)>,
+    cluster_start_pos: Option<Point>,
+    current_cluster: Vec<usize>,
+}
+
+impl ClusterBuilder {
+    pub fn new(view: View, radius: f32) -> Self {
+        Self {
+            view_matrix: view.to_scaled_matrix(),
+            radius,
+
+            next_step: 0,
+
+            cluster_range_ix: None,
+            cluster_start_pos: None,
+            current_cluster: Vec::new(),
+        }
+    }
+
+    pub fn is_done(&self, steps: &[(Handle, StepPtr, usize)]) -> bool {
+        self.next_step >= steps.len()
+    }
 
-        let label_set = &self.label_set;
+    /// Processes up to `count` more steps, returning the clusters
+    /// that were completed. The last cluster is returned when the
+    /// end of the path is reached.
+    pub fn step(
+        &mut self,
+        steps: &[(Handle, StepPtr, usize)],
+        nodes: &[Node],
+        label_set: &AnnotationLabelSet,
+        count: usize,
+    ) -> Vec<LabelCluster> {
+        let mut clusters = Vec::new();
 
-        let mut clusters: FxHashMap<(usize, usize), Vec<usize>> =
-            FxHashMap::default();
+        if self.is_done(steps) {
+            return clusters;
+        }
 
-        let view_matrix = view.to_scaled_matrix();
+        let view_matrix = self.view_matrix;
         let to_screen = |p: Point| {
             let v = glm::vec4(p.x, p.y, 0.0, 1.0);
             let v_ = view_matrix * v;
             Point::new(v_[0], v_[1])
         };
 
-        for (ix, (handle, _, _)) in steps.iter().enumerate() {
+        let start = self.next_step;
+        let end = (start + count).min(steps.len());
+
+        for (ix, (handle, _, _)) in steps[start..end]
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (start + i, s))
+        {
             let node = handle.id();
 
             if let Some(label_indices) = label_set.labels.get(&node) {
                 let node_ix = (node.0 - 1) as usize;
                 let node_pos = to_screen(nodes[node_ix].center());
 
-                if let Some(start_pos) = cluster_start_pos {
-                    if node_pos.dist(start_pos) <= radius {
-                        cluster_range_ix.as_mut().map(|(_, end)| *end = ix);
-                        current_cluster.extend_from_slice(label_indices);
-                    } else {
-                        clusters.insert(
-                            cluster_range_ix.unwrap(),
-                            current_cluster.clone(),
-                        );
-                        current_cluster.clear();
-
-                        cluster_start_pos = Some(node_pos);
-                        cluster_range_ix = Some((ix, ix));
-
-                        current_cluster.extend_from_slice(label_indices);
+                if let Some(start_pos) = self.cluster_start_pos {
+                    if node_pos.dist(start_pos) <= self.radius {
+                        if let Some((_, end)) = self.cluster_range_ix.as_mut() {
+                            *end = ix;
+                        }
+                        self.current_cluster.extend_from_slice(label_indices);
+                        continue;
                     }
-                } else {
-                    cluster_start_pos = Some(node_pos);
-                    cluster_range_ix = Some((ix, ix));
 
-                    current_cluster.extend_from_slice(label_indices);
+                    if let Some(range) = self.cluster_range_ix {
+                        let label_indices =
+                            std::mem::take(&mut self.current_cluster);
+                        clusters.push(Self::finish_cluster(
+                            steps,
+                            nodes,
+                            range,
+                            label_indices,
+                        ));
+                    }
                 }
+
+                self.cluster_start_pos = Some(node_pos);
+                self.cluster_range_ix = Some((ix, ix));
+                self.current_cluster.extend_from_slice(label_indices);
             }
         }
 
-        for ((start, end), cluster_label_indices) in clusters {
-            let slice = &steps[start..=end];
-            let (mid_handle, _, _) = slice[slice.len() / 2];
+        self.next_step = end;
+
+        if self.is_done(steps) {
+            if let Some(range) = self.cluster_range_ix.take() {
+                let label_indices = std::mem::take(&mut self.current_cluster);
+                clusters.push(Self::finish_cluster(
+                    steps,
+                    nodes,
+                    range,
+                    label_indices,
+                ));
+            }
+        }
 
-            let (start_h, _, _) = steps[start];
-            let (end_h, _, _) = steps[end];
+        clusters
+    }
 
-            let s_ix = (start_h.id().0 - 1) as usize;
-            let e_ix = (end_h.id().0 - 1) as usize;
+    /// Places the cluster at the middle step of its range, offset
+    /// perpendicular to the direction of the path over the range
+    fn finish_cluster(
+        steps: &[(Handle, StepPtr, usize)],
+        nodes: &[Node],
+        (start, end): (usize, usize),
+        label_indices: Vec<usize>,
+    ) -> LabelCluster {
+        let slice = &steps[start..=end];
+        let (mid_handle, _, _) = slice[slice.len() / 2];
 
-            let start_p = nodes[s_ix].p0;
-            let end_p = nodes[e_ix].p1;
+        let (start_h, _, _) = steps[start];
+        let (end_h, _, _) = steps[end];
 
-            let start_v = glm::vec2(start_p.x, start_p.y);
-            let end_v = glm::vec2(end_p.x, end_p.y);
+        let s_ix = (start_h.id().0 - 1) as usize;
+        let e_ix = (end_h.id().0 - 1) as usize;
 
-            let del = end_v - start_v;
-            let rot_del = glm::rotate_vec2(&del, std::f32::consts::PI / 2.0);
+        let start_p = nodes[s_ix].p0;
+        let end_p = nodes[e_ix].p1;
 
-            let rot_del_norm = rot_del.normalize();
+        let start_v = glm::vec2(start_p.x, start_p.y);
+        let end_v = glm::vec2(end_p.x, end_p.y);
 
-            let offset = Point::new(rot_del_norm[0], rot_del_norm[1]);
+        let del = end_v - start_v;
+        let rot_del = glm::rotate_vec2(&del, std::f32::consts::PI / 2.0);
+
+        let rot_del_norm = rot_del.normalize();
 
-            let cluster_indices = ClusterIndices {
-                label_indices: cluster_label_indices,
-                offset_ix: self.cluster_offsets.len(),
-            };
+        let offset = Point::new(rot_del_norm[0], rot_del_norm[1]);
 
-            self.node_labels.insert(mid_handle.id(), cluster_indices);
-            self.cluster_offsets.push(offset);
+        LabelCluster {
+            node: mid_handle.id(),
+            offset,
+            label_indices,
         }
+    }
+}
 
-        true
+struct ClusterBatch {
+    label_set_name: String,
+    clusters: Vec<LabelCluster>,
+    done: bool,
+}
+
+/// Builds the label clusters of new label sets on the thread pool,
+/// sending them to the main thread in batches as they are found, so
+/// that large label sets show up progressively rather than all at once
+pub struct ClusterCacheLoader {
+    cluster_tx: Sender<ClusterBatch>,
+    cluster_rx: Receiver<ClusterBatch>,
+
+    running: FxHashMap<String, RemoteHandle<()>>,
+}
+
+impl std::default::Default for ClusterCacheLoader {
+    fn default() -> Self {
+        let (cluster_tx, cluster_rx) = crossbeam::channel::unbounded();
+
+        Self {
+            cluster_tx,
+            cluster_rx,
+            running: FxHashMap::default(),
+        }
+    }
+}
+
+impl ClusterCacheLoader {
+    /// The number of path steps processed between each batch
+    const BATCH_STEPS: usize = 20_000;
+
+    pub fn is_loading(&self, label_set_name: &str) -> bool {
+        self.running.contains_key(label_set_name)
+    }
+
+    /// Starts building the clusters of `label_set`, returning the
+    /// empty cache they will be added to by `receive`
+    pub fn load(
+        &mut self,
+        reactor: &mut Reactor,
+        steps: Vec<(Handle, StepPtr, usize)>,
+        nodes: Vec<Node>,
+        label_set: &Arc<AnnotationLabelSet>,
+        view: View,
+        radius: f32,
+    ) -> Result<ClusterCache> {
+        let label_set_name = label_set.name().to_string();
+
+        let cluster_tx = self.cluster_tx.clone();
+        let label_set_ = label_set.clone();
+        let name = label_set_name.clone();
+
+        let handle = reactor.spawn(async move {
+            let mut builder = ClusterBuilder::new(view, radius);
+
+            loop {
+                let clusters = builder.step(
+                    &steps,
+                    &nodes,
+                    &label_set_,
+                    Self::BATCH_STEPS,
+                );
+                let done = builder.is_done(&steps);
+
+                let batch = ClusterBatch {
+                    label_set_name: name.clone(),
+                    clusters,
+                    done,
+                };
+
+                if cluster_tx.send(batch).is_err() || done {
+                    break;
+                }
+            }
+        })?;
+
+        self.running.insert(label_set_name, handle);
+
+        Ok(ClusterCache::empty(label_set, view, radius))
+    }
+
+    /// Adds the clusters received since the last call to their
+    /// caches, returning true if any clusters were added
+    pub fn receive(
+        &mut self,
+        cluster_caches: &mut HashMap<String, ClusterCache>,
+    ) -> bool {
+        let mut received = false;
+
+        while let Ok(batch) = self.cluster_rx.try_recv() {
+            if batch.done {
+                self.running.remove(&batch.label_set_name);
+            }
+
+            // the label set may have been removed while loading
+            if let Some(cache) = cluster_caches.get_mut(&batch.label_set_name) {
+                received |= !batch.clusters.is_empty();
+
+                for cluster in batch.clusters {
+                    cache.push_cluster(cluster);
+                }
+
+                if batch.done {
+                    cache.complete = true;
+                }
+            }
+        }
+
+        received
     }
 }
 
@@ -739,3 +1048,242 @@ where
 
     Some(rgb::RGBA::new(r, g, b, 1.0))
 }
+
+/// The value of the column that `record_column_hash_color` hashes,
+/// as text, for use in overlay legends
+pub fn record_column_label<R, K>(record: &R, column: &K) -> String
+where
+    R: AnnotationRecord<ColumnKey = K>,
+    K: ColumnKey,
+{
+    if column == &K::start() {
+        record.start().to_string()
+    } else if column == &K::end() {
+        record.end().to_string()
+    } else {
+        record
+            .get_all(column)
+            .into_iter()
+            .map(|value| value.to_str_lossy())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use handlegraph::packedgraph::index::OneBasedIndex;
+
+    /// Steps on five nodes of length 10, with the path starting at 0
+    fn steps() -> Vec<(Handle, StepPtr, usize)> {
+        (0..5)
+            .map(|ix| {
+                let handle = Handle::pack(NodeId::from(ix as u64 + 1), false);
+                let step = StepPtr::from_zero_based(ix as u64);
+                (handle, step, ix * 10)
+            })
+            .collect()
+    }
+
+    const PATH_LEN: usize = 50;
+
+    fn ids(
+        steps: &[(Handle, StepPtr, usize)],
+        offset: Option<usize>,
+        start: usize,
+        end: usize,
+    ) -> Option<Vec<u64>> {
+        let range = path_step_range(steps, PATH_LEN, offset, start, end)?;
+        Some(range.iter().map(|(h, _, _)| h.id().0).collect())
+    }
+
+    #[test]
+    fn range_without_offset() {
+        let steps = steps();
+
+        assert_eq!(ids(&steps, None, 10, 30), Some(vec![2, 3]));
+        assert_eq!(ids(&steps, None, 10, 31), Some(vec![2, 3, 4]));
+    }
+
+    #[test]
+    fn range_starting_within_node() {
+        let steps = steps();
+
+        assert_eq!(ids(&steps, None, 5, 25), Some(vec![1, 2, 3]));
+        assert_eq!(ids(&steps, None, 15, 16), Some(vec![2]));
+        assert_eq!(ids(&steps, None, 45, 1000), Some(vec![5]));
+        assert_eq!(ids(&steps, Some(1000), 1019, 1021), Some(vec![2, 3]));
+    }
+
+    #[test]
+    fn range_with_offset() {
+        let steps = steps();
+
+        assert_eq!(ids(&steps, Some(1000), 1010, 1030), Some(vec![2, 3]));
+        assert_eq!(ids(&steps, Some(0), 10, 30), Some(vec![2, 3]));
+
+        // starts before the path
+        assert_eq!(ids(&steps, Some(1000), 500, 1020), Some(vec![1, 2]));
+
+        // entirely before the path
+        assert_eq!(ids(&steps, Some(1000), 10, 500), Some(vec![]));
+    }
+
+    #[test]
+    fn range_at_path_boundaries() {
+        let steps = steps();
+
+        assert_eq!(ids(&steps, None, 0, 10), Some(vec![1]));
+        assert_eq!(ids(&steps, None, 40, 50), Some(vec![5]));
+        assert_eq!(ids(&steps, None, 0, 50), Some(vec![1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn range_exceeding_path() {
+        let steps = steps();
+
+        assert_eq!(ids(&steps, None, 30, 1000), Some(vec![4, 5]));
+        assert_eq!(ids(&steps, None, 0, 1000), Some(vec![1, 2, 3, 4, 5]));
+        assert_eq!(ids(&steps, None, 50, 1000), Some(vec![]));
+        assert_eq!(ids(&steps, None, 100, 1000), Some(vec![]));
+
+        let empty = path_step_range(&[], 0, None, 0, 1000).map(|r| r.len());
+        assert_eq!(empty, Some(0));
+    }
+
+    #[test]
+    fn empty_and_reversed_ranges() {
+        let steps = steps();
+
+        assert_eq!(ids(&steps, None, 20, 20), Some(vec![]));
+        assert_eq!(ids(&steps, None, 25, 25), Some(vec![]));
+        assert_eq!(ids(&steps, None, 30, 10), None);
+        assert_eq!(ids(&steps, Some(1000), 1030, 1010), None);
+    }
+
+    #[test]
+    fn duplicate_positions() {
+        let mut steps = steps();
+        let dup = (
+            Handle::pack(NodeId::from(6), false),
+            StepPtr::from_zero_based(5u64),
+            20,
+        );
+        steps.insert(3, dup);
+
+        assert_eq!(ids(&steps, None, 20, 30), Some(vec![3, 6]));
+        assert_eq!(ids(&steps, None, 25, 30), Some(vec![3, 6]));
+        assert_eq!(ids(&steps, None, 10, 20), Some(vec![2]));
+    }
+
+    #[test]
+    fn ranges_within_path() {
+        assert!(range_within_path(50, None, 0, 50));
+        assert!(range_within_path(50, Some(1000), 1010, 1020));
+
+        assert!(!range_within_path(50, None, 40, 60));
+        assert!(!range_within_path(50, Some(1000), 10, 20));
+        assert!(!range_within_path(50, Some(1000), 990, 1010));
+        assert!(!range_within_path(50, None, 30, 10));
+    }
+
+    /// Nodes of width 10 laid out left to right along the x-axis,
+    /// matching the steps above
+    fn nodes() -> Vec<Node> {
+        (0..5)
+            .map(|ix| {
+                let x = ix as f32 * 10.0;
+                Node {
+                    p0: Point::new(x, 0.0),
+                    p1: Point::new(x + 10.0, 0.0),
+                }
+            })
+            .collect()
+    }
+
+    /// One label on each of the nodes 1, 2, 4, and 5
+    fn label_set() -> AnnotationLabelSet {
+        let labels = [1u64, 2, 4, 5]
+            .iter()
+            .enumerate()
+            .map(|(label_ix, &id)| (NodeId::from(id), vec![label_ix]))
+            .collect();
+
+        AnnotationLabelSet {
+            annotation_name: "annotations.bed".to_string(),
+            label_set_name: "labels".to_string(),
+            column_str: "name".to_string(),
+            column: AnnotationColumn::Bed(BedColumn::Name),
+            path_id: PathId(0),
+            path_name: "ref".to_string(),
+
+            show: Arc::new(AtomicCell::new(true)),
+
+            label_strings: (0..4).map(|ix| ix.to_string()).collect(),
+            labels,
+        }
+    }
+
+    fn view(scale: f32) -> View {
+        View {
+            center: Point::ZERO,
+            scale,
+            aspect: 1.0,
+        }
+    }
+
+    #[test]
+    fn nearby_labels_are_clustered() {
+        let (steps, nodes, label_set) = (steps(), nodes(), label_set());
+
+        // node centers are 10 pixels apart, so nodes 1 & 2 and 4 & 5
+        // are each within the radius, but not 2 & 4
+        let mut builder = ClusterBuilder::new(view(1.0), 15.0);
+        let clusters = builder.step(&steps, &nodes, &label_set, steps.len());
+
+        assert!(builder.is_done(&steps));
+        assert_eq!(clusters.len(), 2);
+
+        assert_eq!(clusters[0].node, NodeId::from(2));
+        assert_eq!(clusters[0].label_indices, vec![0, 1]);
+        assert_eq!(clusters[1].node, NodeId::from(5));
+        assert_eq!(clusters[1].label_indices, vec![2, 3]);
+
+        // offset perpendicular to the path
+        for cluster in clusters.iter() {
+            assert!(cluster.offset.x.abs() < 0.0001);
+            assert!((cluster.offset.y.abs() - 1.0).abs() < 0.0001);
+        }
+
+        // zoomed out, all labels end up in one cluster
+        let mut builder = ClusterBuilder::new(view(10.0), 15.0);
+        let clusters = builder.step(&steps, &nodes, &label_set, steps.len());
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].label_indices, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn clustering_in_chunks() {
+        let (steps, nodes, label_set) = (steps(), nodes(), label_set());
+
+        let mut builder = ClusterBuilder::new(view(1.0), 15.0);
+        let expected = builder.step(&steps, &nodes, &label_set, steps.len());
+
+        for chunk_size in 1..=steps.len() {
+            let mut builder = ClusterBuilder::new(view(1.0), 15.0);
+            let mut clusters = Vec::new();
+
+            while !builder.is_done(&steps) {
+                clusters.extend(
+                    builder.step(&steps, &nodes, &label_set, chunk_size),
+                );
+            }
+
+            assert_eq!(clusters, expected);
+            assert!(builder.step(&steps, &nodes, &label_set, 1).is_empty());
+        }
+    }
+}
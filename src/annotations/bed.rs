@@ -11,6 +11,8 @@ use super::{
 #[derive(Debug, Clone, Default)]
 pub struct BedRecords {
     file_name: String,
+    /// The path the records were loaded from
+    file_path: std::path::PathBuf,
 
     pub records: Vec<BedRecord>,
 
@@ -64,6 +66,10 @@ impl ColumnKey for BedColumn {
 }
 
 impl BedRecords {
+    pub fn file_path(&self) -> &std::path::Path {
+        &self.file_path
+    }
+
     pub fn parse_bed_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
         use std::fs::File;
 
@@ -71,6 +77,7 @@ impl BedRecords {
 
         let file_name = path.as_ref().file_name().unwrap();
         let file_name = file_name.to_str().unwrap().to_string();
+        let file_path = path.as_ref().to_path_buf();
 
         let file = File::open(path)?;
 
@@ -131,6 +138,7 @@ impl BedRecords {
 
         Ok(Self {
             file_name,
+            file_path,
             records,
             column_keys,
 
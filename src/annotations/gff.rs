@@ -13,6 +13,8 @@ use super::{
 #[derive(Debug, Clone, Default)]
 pub struct Gff3Records {
     file_name: String,
+    /// The path the records were loaded from
+    file_path: std::path::PathBuf,
 
     pub records: Vec<Gff3Record>,
 
@@ -252,12 +254,17 @@ impl std::fmt::Display for Gff3Column {
 }
 
 impl Gff3Records {
+    pub fn file_path(&self) -> &std::path::Path {
+        &self.file_path
+    }
+
     pub fn parse_gff3_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
         use std::fs::File;
         use std::io::{BufRead, BufReader};
 
         let file_name = path.as_ref().file_name().unwrap();
         let file_name = file_name.to_str().unwrap().to_string();
+        let file_path = path.as_ref().to_path_buf();
 
         let file = File::open(path)?;
 
@@ -301,6 +308,7 @@ impl Gff3Records {
 
         Ok(Self {
             file_name,
+            file_path,
 
             records,
             attribute_keys,
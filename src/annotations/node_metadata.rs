@@ -0,0 +1,270 @@
+use handlegraph::{handle::NodeId, handlegraph::*, packedgraph::PackedGraph};
+
+use anyhow::Result;
+use rustc_hash::FxHashMap;
+
+use std::hash::{Hash, Hasher};
+
+use crate::gui::windows::OverlayCreatorMsg;
+use crate::overlays::{hash_node_color, OverlayData, OverlayLegend};
+
+use super::node_table::{parse_value, NodeTable, NodeTableReport};
+
+/// Arbitrary values attached to nodes, loaded from a TSV file with a
+/// header line, where the first column holds the node IDs, and the
+/// remaining columns can contain anything
+#[derive(Debug, Clone, Default)]
+pub struct NodeMetadata {
+    file_name: String,
+
+    columns: Vec<String>,
+    rows: FxHashMap<NodeId, Vec<String>>,
+}
+
+impl NodeMetadata {
+    pub fn parse_tsv_file<P: AsRef<std::path::Path>>(
+        path: P,
+        graph: &PackedGraph,
+    ) -> Result<(Self, NodeTableReport)> {
+        let table = NodeTable::parse_tsv_file(path)?;
+        Ok(Self::from_table(&table, graph))
+    }
+
+    /// Uses the first line of the table as the header, and the first
+    /// column as the node IDs
+    pub fn from_table(
+        table: &NodeTable,
+        graph: &PackedGraph,
+    ) -> (Self, NodeTableReport) {
+        let columns = table.columns(true).split_off(1);
+
+        let mut report = NodeTableReport {
+            node_count: graph.node_count(),
+            ..NodeTableReport::default()
+        };
+
+        let mut rows: FxHashMap<NodeId, Vec<String>> = FxHashMap::default();
+
+        for row in table.data_rows(true) {
+            report.rows += 1;
+
+            let node_id = row
+                .first()
+                .and_then(|field| field.parse::<u64>().ok())
+                .map(NodeId::from)
+                .filter(|&id| graph.has_node(id));
+
+            let node_id = match node_id {
+                Some(node_id) => node_id,
+                None => {
+                    report.invalid_ids += 1;
+                    continue;
+                }
+            };
+
+            let mut values = row[1..].to_vec();
+            values.resize(columns.len(), String::new());
+
+            if rows.insert(node_id, values).is_some() {
+                report.duplicates += 1;
+            }
+        }
+
+        report.nodes_covered = rows.len();
+
+        let metadata = Self {
+            file_name: table.file_name().to_string(),
+            columns,
+            rows,
+        };
+
+        (metadata, report)
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// The column names and values for the node, leaving out empty
+    /// values
+    pub fn node_values(
+        &self,
+        node: NodeId,
+    ) -> impl Iterator<Item = (&str, &str)> + '_ {
+        let values = self.rows.get(&node).map(|values| values.as_slice());
+
+        self.columns
+            .iter()
+            .zip(values.unwrap_or_default())
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(column, value)| (column.as_str(), value.as_str()))
+    }
+
+    /// Creates an overlay from a column; if every value in the column
+    /// is a finite number, it's a value overlay, otherwise each
+    /// distinct value is given its own color. Nodes without a value
+    /// are transparent.
+    pub fn overlay_msg(
+        &self,
+        column: &str,
+        node_count: usize,
+    ) -> Result<OverlayCreatorMsg> {
+        let col_ix =
+            self.columns.iter().position(|c| c == column).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Node metadata '{}' has no column '{}', expected one of: {}",
+                    self.file_name,
+                    column,
+                    self.columns.join(", ")
+                )
+            })?;
+
+        let name = format!("{} - {}", self.file_name, column);
+
+        let column_values = || {
+            self.rows.iter().filter_map(move |(node, values)| {
+                let ix = (node.0 - 1) as usize;
+                let value = values[col_ix].as_str();
+                (ix < node_count && !value.is_empty()).then(|| (ix, value))
+            })
+        };
+
+        let numeric = column_values().all(|(_, v)| parse_value(v).is_some());
+
+        if numeric {
+            let mut values = vec![std::f32::NAN; node_count];
+
+            for (ix, value) in column_values() {
+                values[ix] = parse_value(value).unwrap_or(std::f32::NAN);
+            }
+
+            Ok(OverlayCreatorMsg::NewValueOverlay { name, values })
+        } else {
+            let mut colors =
+                vec![rgb::RGBA::new(0.0, 0.0, 0.0, 0.0); node_count];
+
+            let mut categories: FxHashMap<&str, rgb::RGBA<f32>> =
+                FxHashMap::default();
+
+            for (ix, value) in column_values() {
+                let color = *categories.entry(value).or_insert_with(|| {
+                    let mut hasher = rustc_hash::FxHasher::default();
+                    value.hash(&mut hasher);
+
+                    let (r, g, b) = hash_node_color(hasher.finish());
+                    rgb::RGBA::new(r, g, b, 1.0)
+                });
+
+                colors[ix] = color;
+            }
+
+            let legend = OverlayLegend::from_categories(
+                categories
+                    .into_iter()
+                    .map(|(value, color)| (value.to_string(), color)),
+            );
+
+            Ok(OverlayCreatorMsg::NewOverlay {
+                name,
+                data: OverlayData::RGB(colors),
+                legend: Some(legend),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use handlegraph::mutablehandlegraph::*;
+
+    fn graph() -> PackedGraph {
+        let mut graph = PackedGraph::default();
+
+        for id in 1..=3u64 {
+            graph.create_handle(b"ACGT", id);
+        }
+
+        graph
+    }
+
+    fn metadata(tsv: &str) -> (NodeMetadata, NodeTableReport) {
+        let table =
+            NodeTable::read("meta.tsv".to_string(), tsv.as_bytes()).unwrap();
+        NodeMetadata::from_table(&table, &graph())
+    }
+
+    #[test]
+    fn rows_are_checked_against_the_graph() {
+        let (metadata, report) = metadata(
+            "#node\tclass\tdepth\n\
+             1\ta\t1.5\n\
+             2\t\t2\n\
+             5\tb\t3\n\
+             x\tc\t4\n\
+             1\td\n",
+        );
+
+        assert_eq!(metadata.columns(), ["class", "depth"]);
+
+        assert_eq!(report.rows, 5);
+        assert_eq!(report.invalid_ids, 2);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.nodes_covered, 2);
+        assert_eq!(report.node_count, 3);
+
+        // the last row for node 1 is used, and empty values are skipped
+        let values = metadata.node_values(NodeId::from(1)).collect::<Vec<_>>();
+        assert_eq!(values, [("class", "d")]);
+
+        let values = metadata.node_values(NodeId::from(2)).collect::<Vec<_>>();
+        assert_eq!(values, [("depth", "2")]);
+
+        assert_eq!(metadata.node_values(NodeId::from(3)).count(), 0);
+    }
+
+    #[test]
+    fn numeric_columns_give_value_overlays() {
+        let (metadata, _) = metadata("node\tdepth\n1\t0.5\n3\t2\n");
+
+        match metadata.overlay_msg("depth", 3).unwrap() {
+            OverlayCreatorMsg::NewValueOverlay { name, values } => {
+                assert_eq!(name, "meta.tsv - depth");
+                assert_eq!(values[0], 0.5);
+                assert!(values[1].is_nan());
+                assert_eq!(values[2], 2.0);
+            }
+            _ => panic!("expected a value overlay"),
+        }
+    }
+
+    #[test]
+    fn non_finite_values_are_categories() {
+        let (metadata, _) = metadata("node\tdepth\n1\t0.5\n2\tinf\n");
+
+        match metadata.overlay_msg("depth", 3).unwrap() {
+            OverlayCreatorMsg::NewOverlay {
+                data: OverlayData::RGB(colors),
+                legend,
+                ..
+            } => {
+                assert_eq!(colors[0].a, 1.0);
+                assert_eq!(colors[1].a, 1.0);
+                assert_eq!(colors[2].a, 0.0);
+                assert!(legend.is_some());
+            }
+            _ => panic!("expected a color overlay"),
+        }
+    }
+
+    #[test]
+    fn missing_column_is_an_error() {
+        let (metadata, _) = metadata("node\tdepth\n1\t0.5\n");
+        assert!(metadata.overlay_msg("class", 3).is_err());
+    }
+}
@@ -0,0 +1,561 @@
+use handlegraph::{handle::NodeId, handlegraph::*, packedgraph::PackedGraph};
+
+use anyhow::Result;
+use rustc_hash::FxHashMap;
+
+use crate::gui::windows::OverlayCreatorMsg;
+use crate::overlays::{OverlayData, OverlayLegend};
+
+/// Color overlays get a legend only if they have at most this many
+/// distinct colors
+const MAX_LEGEND_COLORS: usize = 32;
+
+/// A per-node attribute table, as written by `odgi` and similar
+/// tools: tab-separated, with one column holding node IDs and the
+/// others anything. Which column holds the IDs, and which one holds
+/// the colors or values an overlay is made from, are picked when the
+/// overlay is created
+#[derive(Debug, Clone, Default)]
+pub struct NodeTable {
+    file_name: String,
+
+    /// The fields of the first line, which may be a header
+    first_row: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// How the values of the chosen column are turned into an overlay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeTableValues {
+    /// Numbers if every value is a number, otherwise hex colors
+    Auto,
+    /// Hex colors, `#RRGGBB` or `#RRGGBBAA`, with or without the `#`,
+    /// giving an RGB overlay
+    Color,
+    /// Numbers, giving a value overlay
+    Numeric,
+}
+
+impl NodeTableValues {
+    /// The name used to refer to the value kind in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeTableValues::Auto => "auto",
+            NodeTableValues::Color => "color",
+            NodeTableValues::Numeric => "numeric",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(NodeTableValues::Auto),
+            "color" => Some(NodeTableValues::Color),
+            "numeric" => Some(NodeTableValues::Numeric),
+            _ => None,
+        }
+    }
+}
+
+/// The columns of a node table used to create an overlay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeTableMapping {
+    pub has_header: bool,
+    pub id_column: usize,
+    pub value_column: usize,
+    pub values: NodeTableValues,
+}
+
+/// Summary of how well a node table matches the graph, for an
+/// overlay created from the table, or loaded node metadata
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeTableReport {
+    pub rows: usize,
+    /// Rows whose ID field isn't the ID of a node in the graph
+    pub invalid_ids: usize,
+    /// Rows whose value field is empty or couldn't be parsed, or is
+    /// a number that isn't finite
+    pub invalid_values: usize,
+    /// Rows for nodes that already had a row; the last row is used
+    pub duplicates: usize,
+
+    pub nodes_covered: usize,
+    pub node_count: usize,
+}
+
+impl std::fmt::Display for NodeTableReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percent = if self.node_count == 0 {
+            0.0
+        } else {
+            100.0 * self.nodes_covered as f64 / self.node_count as f64
+        };
+
+        write!(
+            f,
+            "{} of {} nodes ({:.1}%) have a value; {} rows, {} invalid node IDs, {} invalid values, {} duplicates",
+            self.nodes_covered,
+            self.node_count,
+            percent,
+            self.rows,
+            self.invalid_ids,
+            self.invalid_values,
+            self.duplicates
+        )
+    }
+}
+
+/// Parses a hex color, `RRGGBB` or `RRGGBBAA`, optionally starting
+/// with `#` or `0x`
+pub fn parse_hex_color(text: &str) -> Option<rgb::RGBA<f32>> {
+    let hex = text.trim();
+    let hex = hex
+        .strip_prefix('#')
+        .or_else(|| hex.strip_prefix("0x"))
+        .unwrap_or(hex);
+
+    if !(hex.len() == 6 || hex.len() == 8)
+        || !hex.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+
+    let channel = |ix: usize| -> Option<f32> {
+        let byte = u8::from_str_radix(hex.get(ix..ix + 2)?, 16).ok()?;
+        Some(byte as f32 / 255.0)
+    };
+
+    let alpha = if hex.len() == 8 { channel(6)? } else { 1.0 };
+
+    Some(rgb::RGBA::new(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+/// Parses a numeric value; infinite and NaN values are rejected, as
+/// they can't be normalized when the overlay is created
+pub(super) fn parse_value(text: &str) -> Option<f32> {
+    text.parse::<f32>().ok().filter(|v| v.is_finite())
+}
+
+impl NodeTable {
+    pub fn parse_tsv_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let file_name = path
+            .as_ref()
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        let file = std::fs::File::open(path)?;
+        Self::read(file_name, std::io::BufReader::new(file))
+    }
+
+    /// Reads the tab-separated lines of `input`, skipping empty lines
+    pub fn read<R: std::io::BufRead>(
+        file_name: String,
+        input: R,
+    ) -> Result<Self> {
+        let mut rows = Vec::new();
+
+        for line in input.lines() {
+            let line = line?;
+            let line = line.trim_end_matches(&['\r', '\n'][..]);
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields = line
+                .split('\t')
+                .map(|field| field.trim().to_string())
+                .collect::<Vec<_>>();
+
+            rows.push(fields);
+        }
+
+        if rows.is_empty() {
+            anyhow::bail!("Node table file is empty");
+        }
+
+        let first_row = rows.remove(0);
+
+        if first_row.len() < 2 {
+            anyhow::bail!(
+                "Node table must have a node ID column and at least one value column"
+            );
+        }
+
+        Ok(Self {
+            file_name,
+            first_row,
+            rows,
+        })
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Guesses whether the first line is a header: data rows have a
+    /// node ID, so a line without any integer field is a header
+    pub fn guess_header(&self) -> bool {
+        !self
+            .first_row
+            .iter()
+            .any(|field| field.trim_start_matches('#').parse::<u64>().is_ok())
+    }
+
+    /// The names of the columns; the header fields, with any leading
+    /// `#` removed, or the 1-based column numbers if there's no header
+    pub fn columns(&self, has_header: bool) -> Vec<String> {
+        let count = self.column_count();
+
+        (0..count)
+            .map(|ix| {
+                let name = has_header
+                    .then(|| self.first_row.get(ix))
+                    .flatten()
+                    .map(|name| name.trim_start_matches('#'))
+                    .filter(|name| !name.is_empty());
+
+                match name {
+                    Some(name) => name.to_string(),
+                    None => (ix + 1).to_string(),
+                }
+            })
+            .collect()
+    }
+
+    /// The index of the column with the given name, as returned by
+    /// `columns`
+    pub fn column_index(&self, has_header: bool, name: &str) -> Option<usize> {
+        self.columns(has_header).iter().position(|c| c == name)
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.len())
+            .chain(std::iter::once(self.first_row.len()))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// The data rows, skipping the header if there is one
+    pub fn data_rows(
+        &self,
+        has_header: bool,
+    ) -> impl Iterator<Item = &[String]> + '_ {
+        let first = (!has_header).then(|| self.first_row.as_slice());
+        first
+            .into_iter()
+            .chain(self.rows.iter().map(|row| row.as_slice()))
+    }
+
+    /// Creates an overlay from the value column of the mapping.
+    /// Nodes without a valid value are transparent in color
+    /// overlays, and gray in value overlays.
+    pub fn overlay_msg(
+        &self,
+        name: &str,
+        mapping: NodeTableMapping,
+        graph: &PackedGraph,
+    ) -> Result<(OverlayCreatorMsg, NodeTableReport)> {
+        let column_count = self.column_count();
+
+        if mapping.id_column >= column_count
+            || mapping.value_column >= column_count
+        {
+            anyhow::bail!(
+                "Node table '{}' has only {} columns",
+                self.file_name,
+                column_count
+            );
+        }
+
+        if mapping.id_column == mapping.value_column {
+            anyhow::bail!("The node ID and value columns must be different");
+        }
+
+        let node_count = graph.node_count();
+
+        let mut report = NodeTableReport {
+            node_count,
+            ..NodeTableReport::default()
+        };
+
+        let mut entries: Vec<(usize, &str)> = Vec::new();
+        let mut seen = vec![false; node_count];
+
+        for row in self.data_rows(mapping.has_header) {
+            report.rows += 1;
+
+            let node_id = row
+                .get(mapping.id_column)
+                .and_then(|field| field.parse::<u64>().ok())
+                .map(NodeId::from)
+                .filter(|&id| graph.has_node(id));
+
+            let node_id = match node_id {
+                Some(node_id) => node_id,
+                None => {
+                    report.invalid_ids += 1;
+                    continue;
+                }
+            };
+
+            let ix = (node_id.0 - 1) as usize;
+
+            if seen[ix] {
+                report.duplicates += 1;
+            }
+            seen[ix] = true;
+
+            match row.get(mapping.value_column) {
+                Some(value) if !value.is_empty() => {
+                    entries.push((ix, value.as_str()));
+                }
+                _ => report.invalid_values += 1,
+            }
+        }
+
+        let values = match mapping.values {
+            NodeTableValues::Auto => {
+                if entries.iter().all(|(_, v)| parse_value(v).is_some()) {
+                    NodeTableValues::Numeric
+                } else {
+                    NodeTableValues::Color
+                }
+            }
+            values => values,
+        };
+
+        let mut covered = vec![false; node_count];
+
+        let msg = match values {
+            NodeTableValues::Numeric => {
+                let mut values = vec![std::f32::NAN; node_count];
+
+                for &(ix, value) in entries.iter() {
+                    match parse_value(value) {
+                        Some(value) => {
+                            values[ix] = value;
+                            covered[ix] = true;
+                        }
+                        None => report.invalid_values += 1,
+                    }
+                }
+
+                OverlayCreatorMsg::NewValueOverlay {
+                    name: name.to_string(),
+                    values,
+                }
+            }
+            _ => {
+                let mut colors =
+                    vec![rgb::RGBA::new(0.0, 0.0, 0.0, 0.0); node_count];
+
+                let mut legend: FxHashMap<&str, rgb::RGBA<f32>> =
+                    FxHashMap::default();
+
+                for &(ix, value) in entries.iter() {
+                    match parse_hex_color(value) {
+                        Some(color) => {
+                            colors[ix] = color;
+                            covered[ix] = true;
+
+                            if legend.len() <= MAX_LEGEND_COLORS {
+                                legend.insert(value, color);
+                            }
+                        }
+                        None => report.invalid_values += 1,
+                    }
+                }
+
+                let legend = (legend.len() <= MAX_LEGEND_COLORS).then(|| {
+                    OverlayLegend::from_categories(
+                        legend
+                            .into_iter()
+                            .map(|(value, color)| (value.to_string(), color)),
+                    )
+                });
+
+                OverlayCreatorMsg::NewOverlay {
+                    name: name.to_string(),
+                    data: OverlayData::RGB(colors),
+                    legend,
+                }
+            }
+        };
+
+        report.nodes_covered = covered.into_iter().filter(|&c| c).count();
+
+        if report.nodes_covered == 0 {
+            anyhow::bail!(
+                "No rows of node table '{}' had both a valid node ID and value ({})",
+                self.file_name,
+                report
+            );
+        }
+
+        Ok((msg, report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use handlegraph::mutablehandlegraph::*;
+
+    fn graph() -> PackedGraph {
+        let mut graph = PackedGraph::default();
+
+        for id in 1..=3u64 {
+            graph.create_handle(b"ACGT", id);
+        }
+
+        graph
+    }
+
+    fn table(lines: &[&str]) -> NodeTable {
+        let mut rows = lines
+            .iter()
+            .map(|line| line.split('\t').map(String::from).collect())
+            .collect::<Vec<Vec<String>>>();
+
+        let first_row = rows.remove(0);
+
+        NodeTable {
+            file_name: "test.tsv".to_string(),
+            first_row,
+            rows,
+        }
+    }
+
+    fn mapping(values: NodeTableValues) -> NodeTableMapping {
+        NodeTableMapping {
+            has_header: true,
+            id_column: 0,
+            value_column: 1,
+            values,
+        }
+    }
+
+    #[test]
+    fn hex_colors() {
+        let red = parse_hex_color("#ff0000").unwrap();
+        assert_eq!(red, rgb::RGBA::new(1.0, 0.0, 0.0, 1.0));
+
+        let blue = parse_hex_color("0x0000FF00").unwrap();
+        assert_eq!(blue, rgb::RGBA::new(0.0, 0.0, 1.0, 0.0));
+
+        assert_eq!(parse_hex_color(" 00ff00 "), parse_hex_color("#00ff00"));
+
+        assert!(parse_hex_color("#fff").is_none());
+        assert!(parse_hex_color("#gg0000").is_none());
+        assert!(parse_hex_color("#ff00000").is_none());
+        assert!(parse_hex_color("").is_none());
+    }
+
+    #[test]
+    fn header_is_guessed() {
+        assert!(table(&["#node\tcolor", "1\t#ff0000"]).guess_header());
+        assert!(!table(&["1\t#ff0000", "2\t#00ff00"]).guess_header());
+    }
+
+    #[test]
+    fn auto_picks_numeric() {
+        let table = table(&["node\tdepth", "1\t0.5", "3\t2"]);
+        let (msg, report) = table
+            .overlay_msg("depth", mapping(NodeTableValues::Auto), &graph())
+            .unwrap();
+
+        match msg {
+            OverlayCreatorMsg::NewValueOverlay { values, .. } => {
+                assert_eq!(values[0], 0.5);
+                assert!(values[1].is_nan());
+                assert_eq!(values[2], 2.0);
+            }
+            _ => panic!("expected a value overlay"),
+        }
+
+        assert_eq!(report.nodes_covered, 2);
+        assert_eq!(report.node_count, 3);
+    }
+
+    #[test]
+    fn auto_picks_color() {
+        let table = table(&["node\tcolor", "1\t#ff0000", "2\t10"]);
+        let (msg, report) = table
+            .overlay_msg("color", mapping(NodeTableValues::Auto), &graph())
+            .unwrap();
+
+        match msg {
+            OverlayCreatorMsg::NewOverlay {
+                data: OverlayData::RGB(colors),
+                legend,
+                ..
+            } => {
+                assert_eq!(colors[0], rgb::RGBA::new(1.0, 0.0, 0.0, 1.0));
+                assert_eq!(colors[1].a, 0.0);
+                assert!(legend.is_some());
+            }
+            _ => panic!("expected a color overlay"),
+        }
+
+        assert_eq!(report.nodes_covered, 1);
+        assert_eq!(report.invalid_values, 1);
+    }
+
+    #[test]
+    fn non_finite_values_are_invalid() {
+        let table = table(&["node\tdepth", "1\tinf", "2\tNaN", "3\t1.5"]);
+
+        let (msg, report) = table
+            .overlay_msg("depth", mapping(NodeTableValues::Numeric), &graph())
+            .unwrap();
+
+        match msg {
+            OverlayCreatorMsg::NewValueOverlay { values, .. } => {
+                assert!(values[0].is_nan());
+                assert_eq!(values[2], 1.5);
+            }
+            _ => panic!("expected a value overlay"),
+        }
+
+        assert_eq!(report.invalid_values, 2);
+        assert_eq!(report.nodes_covered, 1);
+    }
+
+    #[test]
+    fn invalid_rows_are_counted() {
+        let table = table(&[
+            "node\tdepth",
+            "1\t1",
+            "0\t1",
+            "4\t1",
+            "node2\t1",
+            "2\t",
+            "3",
+            "3\tmany",
+        ]);
+
+        let (_, report) = table
+            .overlay_msg("depth", mapping(NodeTableValues::Numeric), &graph())
+            .unwrap();
+
+        assert_eq!(report.rows, 7);
+        assert_eq!(report.invalid_ids, 3);
+        assert_eq!(report.invalid_values, 3);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.nodes_covered, 1);
+    }
+
+    #[test]
+    fn no_valid_rows_is_an_error() {
+        let table = table(&["node\tdepth", "5\t1", "1\t"]);
+
+        assert!(table
+            .overlay_msg("depth", mapping(NodeTableValues::Numeric), &graph())
+            .is_err());
+    }
+}
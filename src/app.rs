@@ -1,12 +1,16 @@
 pub mod channels;
 pub mod mainview;
+pub mod saved_selection;
 pub mod selection;
 pub mod settings;
 pub mod shared_state;
 pub mod theme;
+pub mod view_link;
 
 use crossbeam::channel::Sender;
 
+use std::path::PathBuf;
+
 use handlegraph::pathhandlegraph::PathId;
 use rustc_hash::{FxHashMap, FxHashSet};
 
@@ -26,6 +30,7 @@ use crate::{
     universe::Node,
 };
 
+use selection::SelectionGroups;
 use theme::*;
 
 pub use channels::*;
@@ -44,7 +49,12 @@ pub struct App {
     selected_nodes: FxHashSet<NodeId>,
     selection_changed: bool,
 
+    selection_groups: SelectionGroups,
+
     pub selected_nodes_bounding_box: Option<(Point, Point)>,
+    /// The selection bounding box the view last moved to when
+    /// following the selection
+    followed_bounding_box: Option<(Point, Point)>,
 
     annotations: Annotations,
 }
@@ -96,6 +106,30 @@ pub enum Select {
 pub enum AppMsg {
     Selection(Select),
     GotoSelection,
+
+    /// Add an empty selection group and make it active, using the
+    /// next palette color if no color is given
+    NewSelectionGroup {
+        name: String,
+        color: Option<rgb::RGB<f32>>,
+    },
+    /// Make the selection group with the given index the one that
+    /// selections and translations apply to
+    SetActiveSelectionGroup(usize),
+    SetSelectionGroupColor {
+        group: usize,
+        color: rgb::RGB<f32>,
+    },
+    RemoveSelectionGroup(usize),
+
+    ZoomIn,
+    ZoomOut,
+    ResetView,
+    /// Set the ratio of the view's y-axis scale to its x-axis scale
+    SetViewAspect(f32),
+
+    /// Compute a new layout for the graph and replace the node positions
+    RunLayout(crate::universe::LayoutAlgorithm),
     RectSelect(Rect),
     TranslateSelected(Point),
 
@@ -111,6 +145,97 @@ pub enum AppMsg {
         name: String,
         label_set: AnnotationLabelSet,
     },
+
+    AddNodeMetadata(crate::annotations::NodeMetadata),
+    /// Create an overlay from a column of a loaded node metadata
+    /// table
+    NodeMetadataOverlay {
+        file_name: String,
+        column: String,
+    },
+
+    /// Create a value overlay with the distance of each node from
+    /// the selected nodes; nodes further than `max_distance` away are
+    /// left without a value
+    DistanceOverlay {
+        metric: crate::graph_query::DistanceMetric,
+        max_distance: usize,
+    },
+
+    /// Create an overlay from the value column of a node table
+    NodeTableOverlay {
+        name: String,
+        table: std::sync::Arc<crate::annotations::NodeTable>,
+        mapping: crate::annotations::NodeTableMapping,
+    },
+
+    ExportSvg(PathBuf),
+    /// Write the selected nodes, the edges between them, and the
+    /// parts of the paths on them to a GFA file, with the graph's
+    /// header
+    ExportSelectionGfa(PathBuf),
+    /// Write the IDs of the selected nodes to a file, with the node
+    /// count and checksum of the graph
+    SaveSelection(PathBuf),
+    /// Restore a selection saved with `SaveSelection`; if the node
+    /// IDs of the loaded graph don't match the saved ones, the
+    /// selection is cleared instead
+    LoadSelection(PathBuf),
+    /// Write the mouse bindings, default annotation columns, and other
+    /// settings that aren't saved otherwise to a console script, which can be run
+    /// at startup with `--run-script` to restore them
+    SaveSettings(PathBuf),
+    /// Write the path coverage of each node to a TSV file, on a
+    /// worker thread
+    ExportPathCoverage {
+        path: PathBuf,
+        format: crate::export::CoverageFormat,
+    },
+    /// Write the layout, overlays, annotations, label sets, and view
+    /// to a bundle directory that can be imported to recreate the
+    /// session
+    ExportBundle(PathBuf),
+    ImportBundle(PathBuf),
+
+    /// Save every `every`th frame as a numbered PNG in the directory,
+    /// until the capture is stopped
+    StartFrameCapture {
+        dir: PathBuf,
+        every: usize,
+    },
+    StopFrameCapture,
+
+    CopyViewLink,
+    OpenViewLink(String),
+    OpenViewLinkFromClipboard,
+
+    /// Show the given nodes in the temporary highlight overlay
+    Highlight(Vec<NodeId>),
+    ClearHighlight,
+    /// Draw the edges of the given path colored from its start to its
+    /// end, and highlight its nodes; `None` clears both
+    SetPathEdges(Option<PathId>),
+    /// Show only the given path, with its nodes laid out in a line;
+    /// `None` restores the full layout
+    SetPathTube(Option<PathId>),
+
+    RenameOverlay {
+        overlay_id: usize,
+        name: String,
+    },
+    DeleteOverlay {
+        overlay_id: usize,
+    },
+    /// Make the overlay with the given name the active one, and show
+    /// it if overlays were hidden; `None` leaves no overlay active,
+    /// and hides overlays
+    SetActiveOverlay(Option<String>),
+    /// Show or hide the active overlay
+    SetOverlayVisible(bool),
+    /// Recompute the overlay with the given name from the inputs kept
+    /// with it, in place; path depth overlays keep their paths, and
+    /// script overlays their script, which is run again
+    RecomputeOverlay(String),
 }
 
 impl App {
@@ -128,7 +253,10 @@ impl App {
             selected_nodes: FxHashSet::default(),
             selection_changed: false,
 
+            selection_groups: SelectionGroups::default(),
+
             selected_nodes_bounding_box: None,
+            followed_bounding_box: None,
 
             // overlay_state: OverlayState::default(),
             settings: AppSettings::default(),
@@ -157,6 +285,12 @@ impl App {
         self.selection_changed
     }
 
+    /// The current selection, without resetting the selection
+    /// changed flag
+    pub fn selection(&self) -> &FxHashSet<NodeId> {
+        &self.selected_nodes
+    }
+
     pub fn selected_nodes(&mut self) -> Option<&FxHashSet<NodeId>> {
         if self.selected_nodes.is_empty() {
             self.selection_changed = false;
@@ -167,6 +301,44 @@ impl App {
         }
     }
 
+    pub fn selection_groups(&self) -> &SelectionGroups {
+        &self.selection_groups
+    }
+
+    /// Recomputes the bounding box of the selection, e.g. after
+    /// switching to another selection group
+    fn update_selection_bounding_box(&mut self, node_positions: &[Node]) {
+        self.selected_nodes_bounding_box = None;
+
+        for &node in self.selected_nodes.iter() {
+            let pos = match node_positions.get((node.0 - 1) as usize) {
+                Some(pos) => pos,
+                None => continue,
+            };
+
+            let min = Point {
+                x: pos.p0.x.min(pos.p1.x),
+                y: pos.p0.y.min(pos.p1.y),
+            };
+
+            let max = Point {
+                x: pos.p0.x.max(pos.p1.x),
+                y: pos.p0.y.max(pos.p1.y),
+            };
+
+            let (top_left, bottom_right) =
+                match self.selected_nodes_bounding_box {
+                    Some((tl, br)) => (
+                        Point::new(tl.x.min(min.x), tl.y.min(min.y)),
+                        Point::new(br.x.max(max.x), br.y.max(max.y)),
+                    ),
+                    None => (min, max),
+                };
+
+            self.selected_nodes_bounding_box = Some((top_left, bottom_right));
+        }
+    }
+
     pub fn annotations(&self) -> &Annotations {
         &self.annotations
     }
@@ -190,10 +362,55 @@ impl App {
         node_positions: &[Node],
         msg: AppMsg,
     ) {
+        let selection_msg = matches!(
+            msg,
+            AppMsg::Selection(_)
+                | AppMsg::NewSelectionGroup { .. }
+                | AppMsg::SetActiveSelectionGroup(_)
+                | AppMsg::RemoveSelectionGroup(_)
+        );
+
         match msg {
             AppMsg::RectSelect(_rect) => {
                 //
             }
+            AppMsg::ExportSvg(_path) | AppMsg::ExportSelectionGfa(_path) => {
+                //
+            }
+            AppMsg::SaveSelection(_)
+            | AppMsg::LoadSelection(_)
+            | AppMsg::SaveSettings(_) => {
+                //
+            }
+            AppMsg::ExportPathCoverage { .. } => {
+                //
+            }
+            AppMsg::ExportBundle(_) | AppMsg::ImportBundle(_) => {
+                //
+            }
+            AppMsg::StartFrameCapture { .. } | AppMsg::StopFrameCapture => {
+                //
+            }
+            AppMsg::CopyViewLink
+            | AppMsg::OpenViewLink(_)
+            | AppMsg::OpenViewLinkFromClipboard => {
+                //
+            }
+            AppMsg::Highlight(_)
+            | AppMsg::ClearHighlight
+            | AppMsg::SetPathEdges(_)
+            | AppMsg::SetPathTube(_) => {
+                //
+            }
+            AppMsg::RenameOverlay { .. }
+            | AppMsg::DeleteOverlay { .. }
+            | AppMsg::SetActiveOverlay(_)
+            | AppMsg::RecomputeOverlay(_) => {
+                //
+            }
+            AppMsg::RunLayout(_) => {
+                //
+            }
             AppMsg::TranslateSelected(delta) => {
                 if let Some(bounds) = self.selected_nodes_bounding_box {
                     let min = bounds.0 + delta;
@@ -212,8 +429,64 @@ impl App {
                     main_view_msg_tx.send(MainViewMsg::GotoView(view)).unwrap();
                 }
             }
+            AppMsg::ZoomIn => {
+                self.zoom_step(main_view_msg_tx, true);
+            }
+            AppMsg::ZoomOut => {
+                self.zoom_step(main_view_msg_tx, false);
+            }
+            AppMsg::ResetView => {
+                self.reset_view(main_view_msg_tx, node_positions);
+            }
+            AppMsg::SetViewAspect(aspect) => {
+                main_view_msg_tx
+                    .send(MainViewMsg::SetAspect(aspect))
+                    .unwrap();
+            }
             AppMsg::HoverNode(id) => self.shared_state.hover_node.store(id),
 
+            AppMsg::NewSelectionGroup { name, color } => {
+                let color =
+                    color.unwrap_or_else(|| self.selection_groups.next_color());
+                let group = self.selection_groups.add_group(&name, color);
+
+                if self
+                    .selection_groups
+                    .set_active(group, &mut self.selected_nodes)
+                {
+                    self.update_selection_bounding_box(node_positions);
+                }
+                self.selection_changed = true;
+            }
+            AppMsg::SetActiveSelectionGroup(group) => {
+                if self
+                    .selection_groups
+                    .set_active(group, &mut self.selected_nodes)
+                {
+                    self.update_selection_bounding_box(node_positions);
+                    self.selection_changed = true;
+                }
+            }
+            AppMsg::SetSelectionGroupColor { group, color } => {
+                if self.selection_groups.set_color(group, color) {
+                    self.selection_changed = true;
+                }
+            }
+            AppMsg::RemoveSelectionGroup(group) => {
+                let was_active = group == self.selection_groups.active();
+
+                let removed = self
+                    .selection_groups
+                    .remove_group(group, &mut self.selected_nodes);
+
+                if removed.is_some() {
+                    if was_active {
+                        self.update_selection_bounding_box(node_positions);
+                    }
+                    self.selection_changed = true;
+                }
+            }
+
             AppMsg::Selection(sel) => match sel {
                 Select::Clear => {
                     self.selection_changed = true;
@@ -341,13 +614,113 @@ impl App {
             AppMsg::NewNodeLabels { name, label_set } => {
                 self.annotations.insert_label_set(&name, label_set);
             }
+            AppMsg::AddNodeMetadata(metadata) => {
+                self.annotations.insert_node_metadata(metadata);
+            }
+            AppMsg::NodeMetadataOverlay { .. }
+            | AppMsg::DistanceOverlay { .. }
+            | AppMsg::NodeTableOverlay { .. } => {
+                //
+            }
             AppMsg::ToggleDarkMode => {
                 self.toggle_dark_mode(gui_msg);
             }
             AppMsg::ToggleOverlay => {
                 self.shared_state.overlay_state.toggle_overlay();
             }
+            AppMsg::SetOverlayVisible(visible) => {
+                self.shared_state.overlay_state.set_use_overlay(visible);
+            }
+        }
+
+        if selection_msg {
+            self.follow_selection(main_view_msg_tx);
+        }
+    }
+
+    /// Animate the view to the selection if following the selection
+    /// is enabled and the selection has moved since the view last
+    /// followed it
+    fn follow_selection(&mut self, main_view_msg_tx: &Sender<MainViewMsg>) {
+        let bounds = self.selected_nodes_bounding_box;
+
+        if bounds == self.followed_bounding_box {
+            return;
+        }
+
+        self.followed_bounding_box = bounds;
+
+        let (min, max) = match bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        if !self.settings.follow_selection().load() {
+            return;
         }
+
+        let view = match self.settings.follow_zoom().load() {
+            FollowZoom::Fit => {
+                View::from_dims_and_target(self.dims(), min, max)
+            }
+            FollowZoom::KeepScale => {
+                let mut view = self.shared_state.view();
+                view.center = min + (max - min) * 0.5;
+                view
+            }
+        };
+
+        main_view_msg_tx.send(MainViewMsg::GotoView(view)).unwrap();
+    }
+
+    /// Zoom the view by the configured zoom step, keeping the center
+    /// of the current selection fixed on the screen if there is one,
+    /// otherwise zooming around the view center
+    fn zoom_step(&self, main_view_msg_tx: &Sender<MainViewMsg>, zoom_in: bool) {
+        let step = self.settings.zoom_step().load();
+
+        let factor = if zoom_in { 1.0 / step } else { step };
+
+        let focus = self
+            .selected_nodes_bounding_box
+            .map(|(min, max)| min + (max - min) * 0.5);
+
+        main_view_msg_tx
+            .send(MainViewMsg::Zoom { factor, focus })
+            .unwrap();
+    }
+
+    fn reset_view(
+        &self,
+        main_view_msg_tx: &Sender<MainViewMsg>,
+        node_positions: &[Node],
+    ) {
+        let msg = match self.settings.reset_view_mode().load() {
+            ResetViewMode::Initial => MainViewMsg::ResetView,
+            ResetViewMode::FitGraph => {
+                let mut min = Point::new(std::f32::MAX, std::f32::MAX);
+                let mut max = Point::new(std::f32::MIN, std::f32::MIN);
+
+                for node in node_positions {
+                    for p in [node.p0, node.p1].iter() {
+                        min.x = min.x.min(p.x);
+                        min.y = min.y.min(p.y);
+                        max.x = max.x.max(p.x);
+                        max.y = max.y.max(p.y);
+                    }
+                }
+
+                if node_positions.is_empty() {
+                    MainViewMsg::ResetView
+                } else {
+                    let view =
+                        View::from_dims_and_target(self.dims(), min, max);
+                    MainViewMsg::GotoView(view)
+                }
+            }
+        };
+
+        main_view_msg_tx.send(msg).unwrap();
     }
 
     fn toggle_dark_mode(&self, gui_msg: &Sender<GuiMsg>) {
@@ -374,6 +747,7 @@ impl App {
                         self.selection_changed = true;
                         self.selected_nodes.clear();
                         self.selected_nodes_bounding_box = None;
+                        self.followed_bounding_box = None;
                     }
                 }
                 AppInput::KeyToggleTheme => {
@@ -13,7 +13,10 @@ use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::view::{ScreenDims, View};
 use crate::{
-    app::{selection::SelectionBuffer, NodeWidth},
+    app::{
+        selection::{SelectionBuffer, SelectionGroups},
+        NodeWidth,
+    },
     overlays::OverlayKind,
     vulkan::texture::GradientTexture,
 };
@@ -35,7 +38,10 @@ pub mod view;
 
 use view::*;
 
-use super::{AppChannels, AppSettings, SharedState};
+use super::{
+    AppChannels, AppSettings, BindableModifier, MouseBindings, OverlayMode,
+    RulerPoint, SharedState,
+};
 
 pub struct MainView {
     pub node_draw_system: NodePipelines,
@@ -57,7 +63,20 @@ pub struct MainView {
 
 #[derive(Debug, Clone, Copy)]
 pub enum MainViewMsg {
+    /// Animate to the given view center and scale, keeping the
+    /// current aspect
     GotoView(View),
+    /// Animate to the given view, including its aspect
+    SetView(View),
+    Zoom {
+        factor: f32,
+        focus: Option<Point>,
+    },
+    /// Animate the view back to the initial view
+    ResetView,
+    /// Animate the view to the given ratio of the y-axis scale to
+    /// the x-axis scale
+    SetAspect(f32),
 }
 
 impl MainView {
@@ -149,6 +168,75 @@ impl MainView {
                 };
                 self.anim_handler.send_anim_def(anim_def);
             }
+            MainViewMsg::SetView(view) => {
+                use std::time::Duration;
+
+                let anim_def = AnimationDef {
+                    kind: AnimationKind::Absolute,
+                    order: AnimationOrder::Full {
+                        center: view.center,
+                        scale: view.scale,
+                        aspect: view.aspect,
+                    },
+                    duration: Duration::from_millis(500),
+                };
+                self.anim_handler.send_anim_def(anim_def);
+            }
+            MainViewMsg::Zoom { factor, focus } => {
+                use std::time::Duration;
+
+                let view = self.shared_state.view();
+
+                let min_scale = self.settings.min_view_scale().load();
+                let max_scale = self.settings.max_view_scale().load();
+
+                let scale = (view.scale * factor).max(min_scale).min(max_scale);
+
+                // keep the focus point at the same position on the
+                // screen by scaling its offset to the view center
+                let center = if let Some(focus) = focus {
+                    let ratio = scale / view.scale;
+                    focus + (view.center - focus) * ratio
+                } else {
+                    view.center
+                };
+
+                let anim_def = AnimationDef {
+                    kind: AnimationKind::Absolute,
+                    order: AnimationOrder::Transform { center, scale },
+                    duration: Duration::from_millis(150),
+                };
+                self.anim_handler.send_anim_def(anim_def);
+            }
+            MainViewMsg::ResetView => {
+                use std::time::Duration;
+
+                let view = self.anim_handler.initial_view.load();
+
+                let anim_def = AnimationDef {
+                    kind: AnimationKind::Absolute,
+                    order: AnimationOrder::Transform {
+                        center: view.center,
+                        scale: view.scale,
+                    },
+                    duration: Duration::from_millis(500),
+                };
+                self.anim_handler.send_anim_def(anim_def);
+            }
+            MainViewMsg::SetAspect(aspect) => {
+                use std::time::Duration;
+
+                if !(aspect.is_finite() && aspect > 0.0) {
+                    return;
+                }
+
+                let anim_def = AnimationDef {
+                    kind: AnimationKind::Absolute,
+                    order: AnimationOrder::Aspect { aspect },
+                    duration: Duration::from_millis(150),
+                };
+                self.anim_handler.send_anim_def(anim_def);
+            }
         }
     }
 
@@ -160,7 +248,11 @@ impl MainView {
         let old_init_view = self.anim_handler.initial_view.load();
         let center = center.unwrap_or(old_init_view.center);
         let scale = scale.unwrap_or(old_init_view.scale);
-        self.anim_handler.initial_view.store(View { center, scale });
+        self.anim_handler.initial_view.store(View {
+            center,
+            scale,
+            ..old_init_view
+        });
     }
 
     pub fn reset_view(&self) {
@@ -208,6 +300,29 @@ impl MainView {
             .read(self.node_draw_system.device(), x, y)
     }
 
+    /// The node width used by the node renderer at the given view
+    pub fn scaled_node_width(&self, view: View) -> f32 {
+        let min = self.node_width.min_node_width();
+        let max = self.node_width.max_node_width();
+
+        let min_scale = self.node_width.min_node_scale();
+        let max_scale = self.node_width.max_node_scale();
+
+        let norm_scale = (view.scale - min_scale) / (max_scale - min_scale);
+
+        let easing_val =
+            EasingExpoOut::value_at_normalized_time(norm_scale as f64) as f32;
+
+        let mut width = min + easing_val * (max - min);
+
+        if view.scale > max_scale {
+            width *= view.scale / (min_scale - max_scale);
+        } else if view.scale < min_scale {
+            width = min
+        }
+        width
+    }
+
     pub fn draw_nodes(
         &mut self,
         cmd_buf: vk::CommandBuffer,
@@ -221,28 +336,7 @@ impl MainView {
     ) -> Result<()> {
         let view = self.shared_state.view();
 
-        let node_width = {
-            let min = self.node_width.min_node_width();
-            let max = self.node_width.max_node_width();
-
-            let min_scale = self.node_width.min_node_scale();
-            let max_scale = self.node_width.max_node_scale();
-
-            let norm_scale = (view.scale - min_scale) / (max_scale - min_scale);
-
-            let easing_val =
-                EasingExpoOut::value_at_normalized_time(norm_scale as f64)
-                    as f32;
-
-            let mut width = min + easing_val * (max - min);
-
-            if view.scale > max_scale {
-                width *= view.scale / (min_scale - max_scale);
-            } else if view.scale < min_scale {
-                width = min
-            }
-            width
-        };
+        let node_width = self.scaled_node_width(view);
 
         let background_color = if self.shared_state.dark_mode.load() {
             self.settings.background_color_dark().load()
@@ -251,6 +345,31 @@ impl MainView {
         };
 
         if use_overlay {
+            let overlay_state = self.shared_state.overlay_state();
+            let scope = overlay_state.scope();
+            let unselected_dim = overlay_state.unselected_dim();
+
+            if overlay_state.mode() == OverlayMode::Stack {
+                let layers = overlay_state.layers();
+
+                self.node_draw_system.draw_overlay_stack(
+                    cmd_buf,
+                    render_pass,
+                    framebuffers,
+                    screen_dims,
+                    node_width,
+                    view,
+                    offset,
+                    background_color,
+                    &layers,
+                    color_scheme,
+                    scope,
+                    unselected_dim,
+                )?;
+
+                return Ok(());
+            }
+
             if let Some(overlay) = overlay {
                 self.node_draw_system.draw_overlay_new(
                     cmd_buf,
@@ -263,6 +382,8 @@ impl MainView {
                     background_color,
                     overlay,
                     color_scheme,
+                    scope,
+                    unselected_dim,
                 )?;
 
                 return Ok(());
@@ -283,11 +404,12 @@ impl MainView {
     pub fn update_node_selection(
         &mut self,
         new_selection: &FxHashSet<NodeId>,
+        groups: &SelectionGroups,
     ) -> Result<()> {
         let device = self.node_draw_system.device();
         let selection = &mut self.selection_buffer;
 
-        selection.update_selection(device, new_selection)
+        selection.update_selection(device, new_selection, groups)
     }
 
     pub fn clear_node_selection(&mut self) -> Result<()> {
@@ -310,6 +432,12 @@ impl MainView {
         self.shared_state.set_view(view);
     }
 
+    pub fn set_view_aspect(&self, aspect: f32) {
+        let mut view = self.shared_state.view();
+        view.aspect = aspect;
+        self.shared_state.set_view(view);
+    }
+
     pub fn update_view_animation<D: Into<ScreenDims>>(
         &self,
         screen_dims: D,
@@ -359,7 +487,23 @@ impl MainView {
                     }
                     In::KeyResetView => {
                         if pressed {
-                            self.reset_view();
+                            use crate::app::AppMsg;
+                            self.channels
+                                .app_tx
+                                .send(AppMsg::ResetView)
+                                .unwrap();
+                        }
+                    }
+                    In::KeyZoomIn => {
+                        if pressed {
+                            use crate::app::AppMsg;
+                            self.channels.app_tx.send(AppMsg::ZoomIn).unwrap();
+                        }
+                    }
+                    In::KeyZoomOut => {
+                        if pressed {
+                            use crate::app::AppMsg;
+                            self.channels.app_tx.send(AppMsg::ZoomOut).unwrap();
                         }
                     }
                     _ => (),
@@ -411,7 +555,24 @@ impl MainView {
                             self.view_input_state.mouse_released();
                         }
                     }
-                    In::ButtonSelect => {
+                    In::ButtonSelect | In::ButtonAddSelect
+                        if self.shared_state.ruler.enabled() =>
+                    {
+                        if !pressed {
+                            let view = self.shared_state.view();
+                            let world =
+                                view.screen_point_to_world(screen_dims, pos);
+
+                            let node = self
+                                .read_node_id_at(pos)
+                                .map(|nid| NodeId::from(nid as u64));
+
+                            self.shared_state
+                                .ruler
+                                .place(RulerPoint { world, node });
+                        }
+                    }
+                    In::ButtonSelect | In::ButtonAddSelect => {
                         use crate::app::AppMsg;
                         use crate::app::Select;
 
@@ -424,7 +585,7 @@ impl MainView {
                                 .app_tx
                                 .send(AppMsg::Selection(Select::One {
                                     node,
-                                    clear: false,
+                                    clear: payload == In::ButtonSelect,
                                 }))
                                 .unwrap();
                         }
@@ -467,19 +628,56 @@ impl MainView {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum MainViewInput {
     ButtonMousePan,
+    /// Select the clicked node, replacing the selection
     ButtonSelect,
+    /// Add the clicked node to the selection
+    ButtonAddSelect,
     ButtonRectangleSelect,
     KeyPanUp,
     KeyPanRight,
     KeyPanDown,
     KeyPanLeft,
     KeyResetView,
+    KeyZoomIn,
+    KeyZoomOut,
     WheelZoom,
 }
 
+impl MainViewInput {
+    /// The select and pan mouse button bindings for the given
+    /// settings
+    pub fn mouse_binds(
+        bindings: MouseBindings,
+    ) -> FxHashMap<winit::event::MouseButton, Vec<MouseButtonBind<Self>>> {
+        use MainViewInput as Input;
+
+        let mut mouse_binds: FxHashMap<_, Vec<_>> = FxHashMap::default();
+
+        let pan = mouse_binds.entry(bindings.pan.to_winit()).or_default();
+        pan.push(MouseButtonBind::new(Input::ButtonMousePan));
+        pan.push(MouseButtonBind::with_modifiers(
+            Input::ButtonRectangleSelect,
+            bindings.rect_select.to_winit(),
+        ));
+
+        let select = mouse_binds.entry(bindings.select.to_winit()).or_default();
+
+        if bindings.add_select == BindableModifier::None {
+            select.push(MouseButtonBind::new(Input::ButtonAddSelect));
+        } else {
+            select.push(MouseButtonBind::new(Input::ButtonSelect));
+            select.push(MouseButtonBind::with_modifiers(
+                Input::ButtonAddSelect,
+                bindings.add_select.to_winit(),
+            ));
+        }
+
+        mouse_binds
+    }
+}
+
 impl BindableInput for MainViewInput {
     fn default_binds() -> SystemInputBindings<Self> {
-        use winit::event;
         use winit::event::VirtualKeyCode as Key;
         use MainViewInput as Input;
 
@@ -489,36 +687,18 @@ impl BindableInput for MainViewInput {
             (Key::Left, Input::KeyPanLeft),
             (Key::Right, Input::KeyPanRight),
             (Key::Space, Input::KeyResetView),
+            (Key::Home, Input::KeyResetView),
+            (Key::Equals, Input::KeyZoomIn),
+            (Key::NumpadAdd, Input::KeyZoomIn),
+            (Key::Minus, Input::KeyZoomOut),
+            (Key::NumpadSubtract, Input::KeyZoomOut),
         ]
         .iter()
         .copied()
         .map(|(k, i)| (k, vec![KeyBind::new(i)]))
         .collect::<FxHashMap<_, _>>();
 
-        let shift_mod = winit::event::ModifiersState::SHIFT;
-
-        let mouse_binds: FxHashMap<
-            event::MouseButton,
-            Vec<MouseButtonBind<Input>>,
-        > = [
-            (
-                event::MouseButton::Left,
-                vec![
-                    MouseButtonBind::new(Input::ButtonMousePan),
-                    MouseButtonBind::with_modifiers(
-                        Input::ButtonRectangleSelect,
-                        shift_mod,
-                    ),
-                ],
-            ),
-            (
-                event::MouseButton::Right,
-                vec![MouseButtonBind::new(Input::ButtonSelect)],
-            ),
-        ]
-        .iter()
-        .cloned()
-        .collect();
+        let mouse_binds = Self::mouse_binds(MouseBindings::default());
 
         let wheel_bind = Some(WheelBind::new(true, 0.45, Input::WheelZoom));
 
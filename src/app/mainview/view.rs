@@ -25,9 +25,25 @@ impl AnimationKind {
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AnimationOrder {
-    Transform { center: Point, scale: f32 },
-    Translate { center: Point },
-    Scale { scale: f32 },
+    Transform {
+        center: Point,
+        scale: f32,
+    },
+    Translate {
+        center: Point,
+    },
+    Scale {
+        scale: f32,
+    },
+    Aspect {
+        aspect: f32,
+    },
+    /// Like `Transform`, but also sets the aspect
+    Full {
+        center: Point,
+        scale: f32,
+        aspect: f32,
+    },
 }
 
 impl AnimationOrder {
@@ -36,6 +52,8 @@ impl AnimationOrder {
             AnimationOrder::Transform { center, .. } => Some(*center),
             AnimationOrder::Translate { center } => Some(*center),
             AnimationOrder::Scale { .. } => None,
+            AnimationOrder::Aspect { .. } => None,
+            AnimationOrder::Full { center, .. } => Some(*center),
         }
     }
 
@@ -44,6 +62,16 @@ impl AnimationOrder {
             AnimationOrder::Transform { scale, .. } => Some(*scale),
             AnimationOrder::Translate { .. } => None,
             AnimationOrder::Scale { scale } => Some(*scale),
+            AnimationOrder::Aspect { .. } => None,
+            AnimationOrder::Full { scale, .. } => Some(*scale),
+        }
+    }
+
+    pub fn aspect(&self) -> Option<f32> {
+        match self {
+            AnimationOrder::Aspect { aspect } => Some(*aspect),
+            AnimationOrder::Full { aspect, .. } => Some(*aspect),
+            _ => None,
         }
     }
 }
@@ -56,7 +84,7 @@ pub struct AnimationDef {
 }
 
 impl AnimationDef {
-    pub fn pan_key(scale: f32, h: isize, v: isize) -> Self {
+    pub fn pan_key(scale: Point, h: isize, v: isize) -> Self {
         let kind = AnimationKind::Relative;
 
         let mult = 10.0f32;
@@ -79,7 +107,8 @@ impl AnimationDef {
             Point { x, y }
         };
 
-        let center = center * (mult * scale);
+        let center =
+            Point::new(center.x * mult * scale.x, center.y * mult * scale.y);
 
         let order = AnimationOrder::Translate { center };
 
@@ -165,18 +194,21 @@ pub struct ViewLerp {
 
     origin_delta: Point,
     scale_delta: f32,
+    aspect_delta: f32,
 }
 
 impl ViewLerp {
     pub fn new(start: View, end: View) -> Self {
         let origin_delta = end.center - start.center;
         let scale_delta = end.scale - start.scale;
+        let aspect_delta = end.aspect - start.aspect;
 
         Self {
             start,
             end,
             origin_delta,
             scale_delta,
+            aspect_delta,
         }
     }
 
@@ -188,7 +220,12 @@ impl ViewLerp {
         } else {
             let center = self.start.center + self.origin_delta * (t as f32);
             let scale = self.start.scale + self.scale_delta * (t as f32);
-            View { center, scale }
+            let aspect = self.start.aspect + self.aspect_delta * (t as f32);
+            View {
+                center,
+                scale,
+                aspect,
+            }
         }
     }
 }
@@ -250,11 +287,13 @@ impl<E: EasingFunction> ViewAnimation<E> {
             AnimationKind::Absolute => View {
                 center: anim.order.center().unwrap_or(start.center),
                 scale: anim.order.scale().unwrap_or(start.scale),
+                aspect: anim.order.aspect().unwrap_or(start.aspect),
             },
             AnimationKind::Relative => View {
                 center: start.center
                     + anim.order.center().unwrap_or(Point::ZERO),
                 scale: start.scale + anim.order.scale().unwrap_or(0.0),
+                aspect: start.aspect + anim.order.aspect().unwrap_or(0.0),
             },
         };
 
@@ -416,7 +455,7 @@ impl AnimHandler {
 
     pub fn pan_key(
         &self,
-        scale: f32,
+        scale: Point,
         up: bool,
         right: bool,
         down: bool,
@@ -474,7 +513,7 @@ impl KeyPanState {
         self.up() || self.right() || self.down() || self.left()
     }
 
-    pub fn animation_def(&self, scale: f32) -> Option<AnimationDef> {
+    pub fn animation_def(&self, scale: Point) -> Option<AnimationDef> {
         if !self.active() {
             return None;
         }
@@ -507,7 +546,7 @@ impl KeyPanState {
 
         let mult = 10.0;
 
-        let center = Point::new(d_x * mult * scale, d_y * mult * scale);
+        let center = Point::new(d_x * mult * scale.x, d_y * mult * scale.y);
 
         let order = AnimationOrder::Translate { center };
 
@@ -582,7 +621,7 @@ impl MousePanState {
 
     pub fn animation_def<D: Into<ScreenDims>>(
         &self,
-        scale: f32,
+        scale: Point,
         screen_dims: D,
         cur_mouse_screen: Point,
         cur_mouse_world: Point,
@@ -596,13 +635,11 @@ impl MousePanState {
 
                 let mouse_delta = cur_mouse_screen - mouse_screen_origin;
 
-                let mouse_norm = Point {
-                    x: mouse_delta.x / dims.width,
-                    y: mouse_delta.y / dims.height,
+                let center = Point {
+                    x: scale.x * mouse_delta.x / dims.width,
+                    y: scale.y * mouse_delta.y / dims.height,
                 };
 
-                let center = mouse_norm * scale;
-
                 let kind = AnimationKind::Relative;
                 let order = AnimationOrder::Translate { center };
 
@@ -734,13 +771,13 @@ impl ViewInputState {
             anim_def
         } else if mouse_pan.active() {
             mouse_pan.animation_def(
-                view.scale,
+                view.scale_xy(),
                 screen_dims,
                 cur_mouse_screen,
                 cur_mouse_world,
             )
         } else {
-            self.key_pan.animation_def(view.scale)
+            self.key_pan.animation_def(view.scale_xy())
         }
     }
 
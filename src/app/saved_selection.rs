@@ -0,0 +1,196 @@
+use handlegraph::handle::NodeId;
+
+use anyhow::Result;
+
+/// A selection stored as node IDs, together with the node count and
+/// checksum of the graph it was made on, so that it can be restored
+/// after the graph has been regenerated and loaded again, as long as
+/// the node IDs still refer to the same nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedSelection {
+    pub node_count: usize,
+    pub checksum: u64,
+    pub nodes: Vec<NodeId>,
+}
+
+impl SavedSelection {
+    const HEADER: &'static str = "# gfaestus selection v1";
+
+    pub fn new<'a>(
+        node_count: usize,
+        checksum: u64,
+        nodes: impl IntoIterator<Item = &'a NodeId>,
+    ) -> Self {
+        let mut nodes = nodes.into_iter().copied().collect::<Vec<_>>();
+        nodes.sort_unstable();
+
+        Self {
+            node_count,
+            checksum,
+            nodes,
+        }
+    }
+
+    /// Writes the selection as a header with the graph's node count
+    /// and checksum, followed by one node ID per line
+    pub fn write<W: std::io::Write>(&self, mut out: W) -> Result<()> {
+        writeln!(out, "{}", Self::HEADER)?;
+        writeln!(out, "nodes\t{}", self.node_count)?;
+        writeln!(out, "checksum\t{:016x}", self.checksum)?;
+
+        for node in self.nodes.iter() {
+            writeln!(out, "{}", node.0)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read<R: std::io::BufRead>(input: R) -> Result<Self> {
+        let mut lines = input.lines();
+
+        let mut next_line = || -> Result<String> {
+            lines
+                .next()
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Saved selection file is truncated")
+                })?
+                .map_err(anyhow::Error::from)
+        };
+
+        if next_line()?.trim() != Self::HEADER {
+            anyhow::bail!("Not a gfaestus selection file");
+        }
+
+        let node_count = next_line()?
+            .strip_prefix("nodes\t")
+            .ok_or_else(|| anyhow::anyhow!("Missing node count"))?
+            .trim()
+            .parse::<usize>()?;
+
+        let checksum = next_line()?
+            .strip_prefix("checksum\t")
+            .ok_or_else(|| anyhow::anyhow!("Missing graph checksum"))?
+            .trim()
+            .to_string();
+        let checksum = u64::from_str_radix(&checksum, 16)?;
+
+        let mut nodes = Vec::new();
+
+        for line in lines {
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let id = line.parse::<u64>()?;
+
+            if id == 0 || id as usize > node_count {
+                anyhow::bail!(
+                    "Saved selection contains invalid node ID {}",
+                    id
+                );
+            }
+
+            nodes.push(NodeId::from(id));
+        }
+
+        Ok(Self {
+            node_count,
+            checksum,
+            nodes,
+        })
+    }
+
+    pub fn save_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut writer = std::io::BufWriter::new(file);
+        self.write(&mut writer)?;
+        std::io::Write::flush(&mut writer)?;
+        Ok(())
+    }
+
+    pub fn load_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Self::read(std::io::BufReader::new(file))
+    }
+
+    /// Checks that the node IDs can be used with the loaded graph,
+    /// i.e. that it has the same node count and checksum as the graph
+    /// the selection was saved from
+    pub fn validate(&self, node_count: usize, checksum: u64) -> Result<()> {
+        if self.node_count != node_count {
+            anyhow::bail!(
+                "Selection was saved from a graph with {} nodes, but the loaded graph has {}",
+                self.node_count,
+                node_count
+            );
+        }
+
+        if self.checksum != checksum {
+            anyhow::bail!(
+                "Selection was saved from a graph with different node lengths or edges; its node IDs are not stable"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_str(text: &str) -> Result<SavedSelection> {
+        SavedSelection::read(text.as_bytes())
+    }
+
+    #[test]
+    fn write_then_read() {
+        let nodes = [7u64, 2, 5]
+            .iter()
+            .map(|&id| NodeId::from(id))
+            .collect::<Vec<_>>();
+        let selection = SavedSelection::new(10, 0xdead_beef, nodes.iter());
+
+        let mut out = Vec::new();
+        selection.write(&mut out).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(
+            text,
+            "# gfaestus selection v1\nnodes\t10\nchecksum\t00000000deadbeef\n2\n5\n7\n"
+        );
+
+        let read = read_str(&text).unwrap();
+        assert_eq!(read, selection);
+        assert!(read.validate(10, 0xdead_beef).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_other_graphs() {
+        let selection =
+            SavedSelection::new(10, 0x1234, [NodeId::from(1)].iter());
+
+        assert!(selection.validate(10, 0x1234).is_ok());
+        assert!(selection.validate(11, 0x1234).is_err());
+        assert!(selection.validate(10, 0x1235).is_err());
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        let header =
+            "# gfaestus selection v1\nnodes\t4\nchecksum\t00000000000000ff\n";
+
+        assert!(read_str(&format!("{}1\n4\n", header)).is_ok());
+
+        // node IDs are 1-based and bounded by the node count
+        assert!(read_str(&format!("{}0\n", header)).is_err());
+        assert!(read_str(&format!("{}5\n", header)).is_err());
+        assert!(read_str(&format!("{}x\n", header)).is_err());
+
+        assert!(read_str("nodes\t4\nchecksum\t00000000000000ff\n").is_err());
+        assert!(read_str("# gfaestus selection v1\nnodes\t4\n").is_err());
+    }
+}
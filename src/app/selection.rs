@@ -83,9 +83,188 @@ impl NodeSelection {
     }
 }
 
+/// A named set of nodes, drawn with its own selection outline color
+#[derive(Debug, Clone)]
+pub struct SelectionGroup {
+    pub name: String,
+    pub color: rgb::RGB<f32>,
+    pub nodes: FxHashSet<NodeId>,
+}
+
+/// The selection groups, all of which are drawn at once. Selecting,
+/// translating, and other operations on the selection apply to the
+/// active group only.
+///
+/// The nodes of the active group are owned by `App`, so the
+/// `nodes` set of the active group is kept empty here.
+#[derive(Debug, Clone)]
+pub struct SelectionGroups {
+    groups: Vec<SelectionGroup>,
+    active: usize,
+}
+
+impl std::default::Default for SelectionGroups {
+    fn default() -> Self {
+        Self {
+            groups: vec![SelectionGroup {
+                name: "Selection".to_string(),
+                color: rgb::RGB::new(1.0, 1.0, 1.0),
+                nodes: FxHashSet::default(),
+            }],
+            active: 0,
+        }
+    }
+}
+
+impl SelectionGroups {
+    /// Colors assigned to new groups, in order
+    const PALETTE: [(f32, f32, f32); 6] = [
+        (0.9, 0.2, 0.2),
+        (0.2, 0.4, 0.9),
+        (0.2, 0.8, 0.3),
+        (0.9, 0.7, 0.1),
+        (0.7, 0.3, 0.9),
+        (0.1, 0.8, 0.8),
+    ];
+
+    pub fn groups(&self) -> &[SelectionGroup] {
+        &self.groups
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_group(&self) -> &SelectionGroup {
+        &self.groups[self.active]
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.groups.iter().position(|group| group.name == name)
+    }
+
+    /// The color used for the next new group
+    pub fn next_color(&self) -> rgb::RGB<f32> {
+        let (r, g, b) =
+            Self::PALETTE[(self.groups.len() - 1) % Self::PALETTE.len()];
+        rgb::RGB::new(r, g, b)
+    }
+
+    /// Adds an empty group, returning its index. If a group with
+    /// the same name already exists, its index is returned instead.
+    pub fn add_group(&mut self, name: &str, color: rgb::RGB<f32>) -> usize {
+        if let Some(ix) = self.find(name) {
+            return ix;
+        }
+
+        self.groups.push(SelectionGroup {
+            name: name.to_string(),
+            color,
+            nodes: FxHashSet::default(),
+        });
+
+        self.groups.len() - 1
+    }
+
+    pub fn set_color(&mut self, ix: usize, color: rgb::RGB<f32>) -> bool {
+        if let Some(group) = self.groups.get_mut(ix) {
+            group.color = color;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Makes `ix` the active group, swapping `active_nodes`, the
+    /// nodes of the current active group, with the nodes of the new
+    /// active group
+    pub fn set_active(
+        &mut self,
+        ix: usize,
+        active_nodes: &mut FxHashSet<NodeId>,
+    ) -> bool {
+        if ix >= self.groups.len() || ix == self.active {
+            return false;
+        }
+
+        std::mem::swap(&mut self.groups[self.active].nodes, active_nodes);
+        std::mem::swap(&mut self.groups[ix].nodes, active_nodes);
+        self.active = ix;
+
+        true
+    }
+
+    /// Removes the group at `ix`, returning it. The last remaining
+    /// group can't be removed; if the active group is removed, the
+    /// first group becomes active, and its nodes are swapped into
+    /// `active_nodes`.
+    pub fn remove_group(
+        &mut self,
+        ix: usize,
+        active_nodes: &mut FxHashSet<NodeId>,
+    ) -> Option<SelectionGroup> {
+        if ix >= self.groups.len() || self.groups.len() == 1 {
+            return None;
+        }
+
+        if ix == self.active {
+            let mut removed = self.groups.remove(ix);
+            std::mem::swap(&mut removed.nodes, active_nodes);
+
+            self.active = 0;
+            std::mem::swap(&mut self.groups[0].nodes, active_nodes);
+
+            Some(removed)
+        } else {
+            let removed = self.groups.remove(ix);
+            if ix < self.active {
+                self.active -= 1;
+            }
+            Some(removed)
+        }
+    }
+
+    /// The inactive groups that contain nodes, with their selection
+    /// flags, in drawing order
+    fn inactive_flags(
+        &self,
+    ) -> impl Iterator<Item = (u32, &FxHashSet<NodeId>)> + '_ {
+        let active = self.active;
+        self.groups
+            .iter()
+            .enumerate()
+            .filter(move |(ix, group)| *ix != active && !group.nodes.is_empty())
+            .map(|(_, group)| {
+                (selection_flag(group.color, false), &group.nodes)
+            })
+    }
+}
+
+/// The selection buffer holds one flag per node. The lowest bit is
+/// set if the node is in the active selection group, the second bit
+/// if the node is in any group, and the upper 24 bits hold the RGB
+/// outline color of the group the node is drawn with.
+pub fn selection_flag(color: rgb::RGB<f32>, active: bool) -> u32 {
+    let channel = |c: f32| ((c.clamp(0.0, 1.0) * 255.0).round() as u32) & 0xFF;
+
+    let rgb = (channel(color.r) << 24)
+        | (channel(color.g) << 16)
+        | (channel(color.b) << 8);
+
+    rgb | 0b10 | (active as u32)
+}
+
 pub struct SelectionBuffer {
     latest_selection: FxHashSet<NodeId>,
 
+    /// The flag the active group was last written with, or `None`
+    /// if the buffer must be rewritten in full on the next update
+    latest_flag: Option<u32>,
+
     pub buffer: vk::Buffer,
     memory: vk::DeviceMemory,
     pub size: vk::DeviceSize,
@@ -108,11 +287,14 @@ impl SelectionBuffer {
             app.create_buffer(size, usage, mem_props)?;
 
         app.set_debug_object_name(buffer, "Node Selection Flag Buffer")?;
+        app.gpu_allocations
+            .record("Node Selection Flag Buffer", size);
 
         let latest_selection = FxHashSet::default();
 
         Ok(Self {
             latest_selection,
+            latest_flag: None,
 
             buffer,
             memory,
@@ -144,7 +326,7 @@ impl SelectionBuffer {
             self.latest_selection.extend(
                 sel_slice.iter().enumerate().filter_map(|(ix, &val)| {
                     let node_id = NodeId::from((ix + 1) as u64);
-                    if val == 1 {
+                    if (val & 1) == 1 {
                         Some(node_id)
                     } else {
                         None
@@ -157,6 +339,10 @@ impl SelectionBuffer {
 
         self.latest_selection.shrink_to_fit();
 
+        // the buffer was overwritten, e.g. by the GPU rectangle
+        // selection, so the other groups must be written again
+        self.latest_flag = None;
+
         Ok(())
     }
 
@@ -255,10 +441,85 @@ impl SelectionBuffer {
         Ok(())
     }
 
+    /// Writes the selection groups to the buffer, with
+    /// `new_selection` as the nodes of the active group
     pub fn update_selection(
         &mut self,
         device: &Device,
         new_selection: &FxHashSet<NodeId>,
+        groups: &SelectionGroups,
+    ) -> Result<()> {
+        let active_flag = selection_flag(groups.active_group().color, true);
+
+        let has_inactive = groups.inactive_flags().next().is_some();
+
+        if has_inactive || self.latest_flag != Some(active_flag) {
+            self.write_groups(device, new_selection, active_flag, groups)?;
+        } else {
+            self.write_changed(device, new_selection, active_flag)?;
+        }
+
+        self.latest_selection.clone_from(new_selection);
+        self.latest_flag = Some(active_flag);
+
+        Ok(())
+    }
+
+    /// Rewrites the entire buffer; groups later in the list are drawn
+    /// over earlier ones, and the active group is drawn over all
+    fn write_groups(
+        &mut self,
+        device: &Device,
+        new_selection: &FxHashSet<NodeId>,
+        active_flag: u32,
+        groups: &SelectionGroups,
+    ) -> Result<()> {
+        let node_count = (self.size / 4) as usize;
+
+        unsafe {
+            let data_ptr = device.map_memory(
+                self.memory,
+                0,
+                self.size,
+                vk::MemoryMapFlags::empty(),
+            )?;
+
+            let val_ptr = data_ptr as *mut u32;
+            std::ptr::write_bytes(val_ptr, 0u8, node_count);
+
+            let flags = std::slice::from_raw_parts_mut(val_ptr, node_count);
+
+            let group_flags = groups
+                .inactive_flags()
+                .chain(std::iter::once((active_flag, new_selection)));
+
+            for (flag, nodes) in group_flags {
+                for &node in nodes.iter() {
+                    let ix = (node.0 - 1) as usize;
+
+                    if ix >= node_count {
+                        panic!(
+                            "attempted to select a node that does not exist"
+                        );
+                    }
+
+                    flags[ix] = flag;
+                }
+            }
+
+            device.unmap_memory(self.memory);
+        }
+
+        Ok(())
+    }
+
+    /// Only updates the flags of the nodes that were added to or
+    /// removed from the active group, when there are no other groups
+    fn write_changed(
+        &mut self,
+        device: &Device,
+        new_selection: &FxHashSet<NodeId>,
+        active_flag: u32,
     ) -> Result<()> {
         let removed = self.latest_selection.difference(new_selection);
         let added = new_selection.difference(&self.latest_selection);
@@ -292,14 +553,112 @@ impl SelectionBuffer {
                 }
 
                 let val_ptr = val_ptr.add(ix);
-                val_ptr.write(1);
+                val_ptr.write(active_flag);
             }
 
             device.unmap_memory(self.memory);
         }
 
-        self.latest_selection.clone_from(new_selection);
-
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_set(ids: &[u64]) -> FxHashSet<NodeId> {
+        ids.iter().map(|&id| NodeId::from(id)).collect()
+    }
+
+    fn red() -> rgb::RGB<f32> {
+        rgb::RGB::new(1.0, 0.0, 0.0)
+    }
+
+    #[test]
+    fn selection_flags() {
+        assert_eq!(
+            selection_flag(rgb::RGB::new(1.0, 1.0, 1.0), true),
+            0xFFFF_FF03
+        );
+        assert_eq!(selection_flag(red(), true), 0xFF00_0003);
+        assert_eq!(selection_flag(red(), false), 0xFF00_0002);
+
+        // out of range channels are clamped
+        assert_eq!(
+            selection_flag(rgb::RGB::new(2.0, -1.0, 0.5), false),
+            0xFF00_8002
+        );
+    }
+
+    #[test]
+    fn switching_groups_swaps_nodes() {
+        let mut groups = SelectionGroups::default();
+        let mut active_nodes = node_set(&[1, 2]);
+
+        let other = groups.add_group("other", red());
+        assert_eq!(other, 1);
+        assert_eq!(groups.add_group("other", red()), other);
+        assert_eq!(groups.find("other"), Some(other));
+
+        assert!(groups.set_active(other, &mut active_nodes));
+        assert!(active_nodes.is_empty());
+        assert_eq!(groups.groups()[0].nodes, node_set(&[1, 2]));
+
+        active_nodes.insert(NodeId::from(5));
+
+        assert!(groups.set_active(0, &mut active_nodes));
+        assert_eq!(active_nodes, node_set(&[1, 2]));
+        assert_eq!(groups.groups()[other].nodes, node_set(&[5]));
+        assert!(groups.active_group().nodes.is_empty());
+
+        assert!(!groups.set_active(0, &mut active_nodes));
+        assert!(!groups.set_active(2, &mut active_nodes));
+
+        // only the inactive group with nodes is drawn separately
+        let inactive = groups.inactive_flags().collect::<Vec<_>>();
+        assert_eq!(inactive.len(), 1);
+        assert_eq!(inactive[0].0, selection_flag(red(), false));
+    }
+
+    #[test]
+    fn removing_groups() {
+        let mut groups = SelectionGroups::default();
+        let mut active_nodes = node_set(&[1]);
+
+        assert!(groups.remove_group(0, &mut active_nodes).is_none());
+
+        let a = groups.add_group("a", red());
+        let b = groups.add_group("b", red());
+
+        groups.set_active(b, &mut active_nodes);
+        active_nodes.insert(NodeId::from(3));
+
+        // removing a group before the active one shifts the index
+        let removed = groups.remove_group(a, &mut active_nodes).unwrap();
+        assert_eq!(removed.name, "a");
+        assert_eq!(groups.active(), 1);
+        assert_eq!(active_nodes, node_set(&[3]));
+
+        // removing the active group makes the first group active
+        let removed = groups.remove_group(1, &mut active_nodes).unwrap();
+        assert_eq!(removed.nodes, node_set(&[3]));
+        assert_eq!(groups.active(), 0);
+        assert_eq!(active_nodes, node_set(&[1]));
+        assert_eq!(groups.len(), 1);
+    }
+
+    #[test]
+    fn palette_colors_cycle() {
+        let mut groups = SelectionGroups::default();
+
+        let first = groups.next_color();
+
+        for ix in 0..SelectionGroups::PALETTE.len() {
+            let color = groups.next_color();
+            groups.add_group(&ix.to_string(), color);
+        }
+
+        assert_eq!(groups.next_color(), first);
+    }
+}
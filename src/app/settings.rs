@@ -1,6 +1,10 @@
 use crossbeam::atomic::AtomicCell;
+use parking_lot::RwLock;
 use std::sync::Arc;
 
+use anyhow::Result;
+
+use crate::annotations::AnnotationFileType;
 use crate::vulkan::draw_system::edges::EdgesUBO;
 
 #[derive(Debug, Clone)]
@@ -10,6 +14,30 @@ pub struct AppSettings {
     edge_renderer: Arc<AtomicCell<EdgesUBO>>,
 
     label_radius: Arc<AtomicCell<f32>>,
+    max_label_clusters: Arc<AtomicCell<usize>>,
+    label_priority: Arc<AtomicCell<LabelPriority>>,
+
+    zoom_step: Arc<AtomicCell<f32>>,
+    min_view_scale: Arc<AtomicCell<f32>>,
+    max_view_scale: Arc<AtomicCell<f32>>,
+
+    reset_view_mode: Arc<AtomicCell<ResetViewMode>>,
+
+    follow_selection: Arc<AtomicCell<bool>>,
+    follow_zoom: Arc<AtomicCell<FollowZoom>>,
+
+    node_label_mode: Arc<AtomicCell<NodeLabelMode>>,
+
+    mouse_bindings: Arc<AtomicCell<MouseBindings>>,
+
+    hover_debounce_ms: Arc<AtomicCell<f32>>,
+
+    url_templates: Arc<RwLock<Vec<UrlTemplate>>>,
+
+    gff3_default_columns: Arc<RwLock<DefaultColumns>>,
+    bed_default_columns: Arc<RwLock<DefaultColumns>>,
+
+    console_output_to_log: Arc<AtomicCell<bool>>,
 
     background_color_light: Arc<AtomicCell<rgb::RGB<f32>>>,
     background_color_dark: Arc<AtomicCell<rgb::RGB<f32>>>,
@@ -21,6 +49,39 @@ impl std::default::Default for AppSettings {
             node_width: Default::default(),
             edge_renderer: Default::default(),
             label_radius: Arc::new(50.0.into()),
+            max_label_clusters: Arc::new(250.into()),
+            label_priority: Arc::new(LabelPriority::NearestCenter.into()),
+
+            zoom_step: Arc::new(1.5.into()),
+            min_view_scale: Arc::new(0.01.into()),
+            max_view_scale: Arc::new(1_000_000.0.into()),
+
+            reset_view_mode: Arc::new(ResetViewMode::Initial.into()),
+
+            follow_selection: Arc::new(false.into()),
+            follow_zoom: Arc::new(FollowZoom::Fit.into()),
+
+            node_label_mode: Arc::new(NodeLabelMode::NodeId.into()),
+
+            mouse_bindings: Arc::new(MouseBindings::default().into()),
+
+            hover_debounce_ms: Arc::new(150.0.into()),
+
+            url_templates: Arc::new(RwLock::new(vec![UrlTemplate::new(
+                "UCSC (hg38)",
+                "https://genome.ucsc.edu/cgi-bin/hgTracks?db=hg38&position={chr}:{start}-{end}",
+            )])),
+
+            gff3_default_columns: Arc::new(RwLock::new(DefaultColumns::new(
+                &["source", "type", "frame"],
+                &["seq_id", "start", "end", "strand"],
+            ))),
+            bed_default_columns: Arc::new(RwLock::new(DefaultColumns::new(
+                &[],
+                &["chr", "start", "end"],
+            ))),
+
+            console_output_to_log: Arc::new(false.into()),
 
             background_color_light: Arc::new(
                 rgb::RGB::new(1.0, 1.0, 1.0).into(),
@@ -49,6 +110,84 @@ impl AppSettings {
         &self.label_radius
     }
 
+    /// The most label clusters drawn at once, across all visible
+    /// label sets; 0 means there is no limit
+    pub fn max_label_clusters(&self) -> &Arc<AtomicCell<usize>> {
+        &self.max_label_clusters
+    }
+
+    /// Which label clusters are kept when there are more than
+    /// `max_label_clusters`
+    pub fn label_priority(&self) -> &Arc<AtomicCell<LabelPriority>> {
+        &self.label_priority
+    }
+
+    pub fn zoom_step(&self) -> &Arc<AtomicCell<f32>> {
+        &self.zoom_step
+    }
+
+    pub fn min_view_scale(&self) -> &Arc<AtomicCell<f32>> {
+        &self.min_view_scale
+    }
+
+    pub fn max_view_scale(&self) -> &Arc<AtomicCell<f32>> {
+        &self.max_view_scale
+    }
+
+    pub fn reset_view_mode(&self) -> &Arc<AtomicCell<ResetViewMode>> {
+        &self.reset_view_mode
+    }
+
+    /// If true, the view animates to the selection whenever the
+    /// selection changes
+    pub fn follow_selection(&self) -> &Arc<AtomicCell<bool>> {
+        &self.follow_selection
+    }
+
+    pub fn follow_zoom(&self) -> &Arc<AtomicCell<FollowZoom>> {
+        &self.follow_zoom
+    }
+
+    pub fn node_label_mode(&self) -> &Arc<AtomicCell<NodeLabelMode>> {
+        &self.node_label_mode
+    }
+
+    /// The mouse buttons and modifiers used to select and pan in the
+    /// main view; changes are picked up by the input manager
+    pub fn mouse_bindings(&self) -> &Arc<AtomicCell<MouseBindings>> {
+        &self.mouse_bindings
+    }
+
+    /// How long the cursor must stay on a node, in milliseconds,
+    /// before the hovered node is queried for the tooltip
+    pub fn hover_debounce_ms(&self) -> &Arc<AtomicCell<f32>> {
+        &self.hover_debounce_ms
+    }
+
+    /// The external links annotation records can be opened in
+    pub fn url_templates(&self) -> &Arc<RwLock<Vec<UrlTemplate>>> {
+        &self.url_templates
+    }
+
+    /// The columns that are shown, and hidden from the column picker,
+    /// when a file of the given type is first opened in its record
+    /// list
+    pub fn default_columns(
+        &self,
+        file_type: AnnotationFileType,
+    ) -> &Arc<RwLock<DefaultColumns>> {
+        match file_type {
+            AnnotationFileType::Gff3 => &self.gff3_default_columns,
+            AnnotationFileType::Bed => &self.bed_default_columns,
+        }
+    }
+
+    /// If true, every line written to the console output is also
+    /// written to the application log
+    pub fn console_output_to_log(&self) -> &Arc<AtomicCell<bool>> {
+        &self.console_output_to_log
+    }
+
     pub fn background_color_light(&self) -> &Arc<AtomicCell<rgb::RGB<f32>>> {
         &self.background_color_light
     }
@@ -56,6 +195,389 @@ impl AppSettings {
     pub fn background_color_dark(&self) -> &Arc<AtomicCell<rgb::RGB<f32>>> {
         &self.background_color_dark
     }
+
+    /// Writes the settings that are only kept in memory, i.e. the
+    /// mouse bindings and default annotation columns, as a console
+    /// script that restores them when run at startup with
+    /// `--run-script`, or with `:exec`
+    pub fn write_script<W: std::io::Write>(&self, mut out: W) -> Result<()> {
+        writeln!(out, "// gfaestus settings")?;
+
+        let bindings = self.mouse_bindings.load();
+
+        writeln!(
+            out,
+            "set_mouse_bindings({}, {}, {}, {});",
+            script_string(bindings.select.name()),
+            script_string(bindings.pan.name()),
+            script_string(bindings.rect_select.name()),
+            script_string(bindings.add_select.name()),
+        )?;
+
+        for &file_type in AnnotationFileType::ALL.iter() {
+            let columns = self.default_columns(file_type).read();
+
+            writeln!(
+                out,
+                "set_default_columns({}, {}, {});",
+                script_string(file_type.name()),
+                script_array(&columns.enabled),
+                script_array(&columns.hidden),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn save_script<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut writer = std::io::BufWriter::new(file);
+        self.write_script(&mut writer)?;
+        std::io::Write::flush(&mut writer)?;
+        Ok(())
+    }
+}
+
+fn script_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+fn script_array(values: &[String]) -> String {
+    let values = values
+        .iter()
+        .map(|value| script_string(value))
+        .collect::<Vec<_>>();
+
+    format!("[{}]", values.join(", "))
+}
+
+/// Annotation columns referred to by their names, as displayed in
+/// the column picker, since the columns of e.g. a BED file depend on
+/// its header
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DefaultColumns {
+    /// Columns that are shown in the record list
+    pub enabled: Vec<String>,
+    /// Columns that are left out of the column picker
+    pub hidden: Vec<String>,
+}
+
+impl DefaultColumns {
+    pub fn new(enabled: &[&str], hidden: &[&str]) -> Self {
+        Self {
+            enabled: enabled.iter().map(|c| c.to_string()).collect(),
+            hidden: hidden.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    pub fn is_enabled(&self, column: &str) -> bool {
+        self.enabled.iter().any(|c| c == column)
+    }
+
+    pub fn is_hidden(&self, column: &str) -> bool {
+        self.hidden.iter().any(|c| c == column)
+    }
+}
+
+/// A link to an external site, such as a genome browser, that is
+/// filled in using the range of an annotation record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlTemplate {
+    pub name: String,
+    pub template: String,
+}
+
+impl UrlTemplate {
+    pub fn new(name: &str, template: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            template: template.to_string(),
+        }
+    }
+
+    /// Replaces the `{chr}`, `{start}`, and `{end}` placeholders in
+    /// the template; the coordinates are used as they are in the
+    /// annotation file. The sequence name is percent-encoded, as it
+    /// can contain characters such as `#` and `&` that would change
+    /// the meaning of the URL.
+    pub fn fill(&self, chr: &str, start: usize, end: usize) -> String {
+        self.template
+            .replace("{chr}", &Self::encode_component(chr))
+            .replace("{start}", &start.to_string())
+            .replace("{end}", &end.to_string())
+    }
+
+    /// Percent-encodes everything except the unreserved characters
+    /// of RFC 3986
+    fn encode_component(text: &str) -> String {
+        let mut encoded = String::with_capacity(text.len());
+
+        for byte in text.bytes() {
+            match byte {
+                b'A'..=b'Z'
+                | b'a'..=b'z'
+                | b'0'..=b'9'
+                | b'-'
+                | b'_'
+                | b'.'
+                | b'~' => encoded.push(byte as char),
+                _ => encoded.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+
+        encoded
+    }
+}
+
+/// What the view is reset to when using the reset view key or the
+/// `reset_view()` console function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetViewMode {
+    /// The view the graph was first shown with
+    Initial,
+    /// Fit the current positions of all nodes on the screen
+    FitGraph,
+}
+
+/// How nodes are referred to in tooltips, detail windows, and the
+/// node list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeLabelMode {
+    /// Show the node ID
+    NodeId,
+    /// Show the position of the node on the reference path, falling
+    /// back to the node ID if there is no reference path, or the node
+    /// isn't on it
+    PathCoordinate,
+}
+
+impl NodeLabelMode {
+    /// The name used to refer to the mode in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeLabelMode::NodeId => "node_id",
+            NodeLabelMode::PathCoordinate => "path_coordinate",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "node_id" => Some(NodeLabelMode::NodeId),
+            "path_coordinate" => Some(NodeLabelMode::PathCoordinate),
+            _ => None,
+        }
+    }
+}
+
+/// A mouse button that main view gestures can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindableButton {
+    Left,
+    Right,
+    Middle,
+}
+
+impl BindableButton {
+    pub const ALL: [BindableButton; 3] = [
+        BindableButton::Left,
+        BindableButton::Right,
+        BindableButton::Middle,
+    ];
+
+    /// The name used to refer to the button in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            BindableButton::Left => "left",
+            BindableButton::Right => "right",
+            BindableButton::Middle => "middle",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|b| b.name() == name)
+    }
+
+    pub fn to_winit(&self) -> winit::event::MouseButton {
+        use winit::event::MouseButton;
+
+        match self {
+            BindableButton::Left => MouseButton::Left,
+            BindableButton::Right => MouseButton::Right,
+            BindableButton::Middle => MouseButton::Middle,
+        }
+    }
+}
+
+/// A modifier key that is held with a mouse button to change what
+/// the button does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindableModifier {
+    None,
+    Shift,
+    Ctrl,
+    Alt,
+}
+
+impl BindableModifier {
+    pub const ALL: [BindableModifier; 4] = [
+        BindableModifier::None,
+        BindableModifier::Shift,
+        BindableModifier::Ctrl,
+        BindableModifier::Alt,
+    ];
+
+    /// The name used to refer to the modifier in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            BindableModifier::None => "none",
+            BindableModifier::Shift => "shift",
+            BindableModifier::Ctrl => "ctrl",
+            BindableModifier::Alt => "alt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|m| m.name() == name)
+    }
+
+    pub fn to_winit(&self) -> winit::event::ModifiersState {
+        use winit::event::ModifiersState;
+
+        match self {
+            BindableModifier::None => ModifiersState::empty(),
+            BindableModifier::Shift => ModifiersState::SHIFT,
+            BindableModifier::Ctrl => ModifiersState::CTRL,
+            BindableModifier::Alt => ModifiersState::ALT,
+        }
+    }
+}
+
+/// Which mouse buttons select and pan in the main view, and which
+/// modifiers change them to rectangle select and add to selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseBindings {
+    /// Clicking a node with this button selects it
+    pub select: BindableButton,
+    /// Dragging with this button pans the view, or moves the
+    /// selection if started on a node
+    pub pan: BindableButton,
+    /// Held while dragging with the pan button to select the nodes
+    /// in a rectangle
+    pub rect_select: BindableModifier,
+    /// Held while clicking with the select button to add the node to
+    /// the selection; if `None`, every select click adds to the
+    /// selection
+    pub add_select: BindableModifier,
+}
+
+impl MouseBindings {
+    /// Checks that every gesture can be told apart from the others
+    pub fn validate(&self) -> Result<()> {
+        if self.rect_select == BindableModifier::None {
+            anyhow::bail!("rectangle select needs a modifier");
+        }
+
+        if self.select == self.pan {
+            if self.add_select == BindableModifier::None {
+                anyhow::bail!(
+                    "adding to the selection needs a modifier when select \
+                     and pan use the same button"
+                );
+            }
+
+            if self.add_select == self.rect_select {
+                anyhow::bail!(
+                    "adding to the selection and rectangle select use the \
+                     same button and modifier"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::default::Default for MouseBindings {
+    fn default() -> Self {
+        Self {
+            select: BindableButton::Right,
+            pan: BindableButton::Left,
+            rect_select: BindableModifier::Shift,
+            add_select: BindableModifier::None,
+        }
+    }
+}
+
+/// The order in which label clusters are drawn when the number of
+/// clusters is capped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelPriority {
+    /// Keep the clusters closest to the center of the view
+    NearestCenter,
+    /// Keep the clusters with the most labels
+    LargestCluster,
+}
+
+impl LabelPriority {
+    /// The name used to refer to the priority in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            LabelPriority::NearestCenter => "nearest_center",
+            LabelPriority::LargestCluster => "largest_cluster",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "nearest_center" => Some(LabelPriority::NearestCenter),
+            "largest_cluster" => Some(LabelPriority::LargestCluster),
+            _ => None,
+        }
+    }
+}
+
+/// How the view is zoomed when following the selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowZoom {
+    /// Zoom to fit the whole selection, like "Goto selection"
+    Fit,
+    /// Center the selection, keeping the current zoom level
+    KeepScale,
+}
+
+impl FollowZoom {
+    /// The name used to refer to the zoom policy in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            FollowZoom::Fit => "fit",
+            FollowZoom::KeepScale => "keep_scale",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fit" => Some(FollowZoom::Fit),
+            "keep_scale" => Some(FollowZoom::KeepScale),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -111,3 +633,44 @@ impl std::default::Default for NodeWidth {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_template_placeholders() {
+        let template = UrlTemplate::new(
+            "UCSC",
+            "https://genome.ucsc.edu/cgi-bin/hgTracks?db=hg38&position={chr}:{start}-{end}",
+        );
+
+        assert_eq!(
+            template.fill("chr1", 100, 200),
+            "https://genome.ucsc.edu/cgi-bin/hgTracks?db=hg38&position=chr1:100-200"
+        );
+
+        // placeholders can be repeated or left out
+        let template = UrlTemplate::new("test", "{chr}/{chr}?s={start}");
+        assert_eq!(template.fill("chrX", 5, 10), "chrX/chrX?s=5");
+
+        let template = UrlTemplate::new("test", "https://example.com");
+        assert_eq!(template.fill("chr1", 1, 2), "https://example.com");
+    }
+
+    #[test]
+    fn url_template_escapes_sequence_names() {
+        let template = UrlTemplate::new("test", "q={chr}:{start}-{end}");
+
+        assert_eq!(
+            template.fill("HG002#1#chr1", 1, 2),
+            "q=HG002%231%23chr1:1-2"
+        );
+        assert_eq!(template.fill("a&b=c d", 1, 2), "q=a%26b%3Dc%20d:1-2");
+        assert_eq!(
+            template.fill("chr1_KI270706v1.1~", 1, 2),
+            "q=chr1_KI270706v1.1~:1-2"
+        );
+        assert_eq!(template.fill("é", 1, 2), "q=%C3%A9:1-2");
+    }
+}
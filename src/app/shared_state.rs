@@ -2,8 +2,16 @@ use std::sync::Arc;
 
 use crossbeam::atomic::AtomicCell;
 use handlegraph::handle::NodeId;
-
-use crate::overlays::OverlayKind;
+use handlegraph::pathhandlegraph::PathId;
+use parking_lot::RwLock;
+use rustc_hash::FxHashMap;
+
+use crate::graph_query::OverlayColorsTask;
+use crate::overlays::{OverlayKind, OverlayLegend};
+use crate::script::OverlayScript;
+use crate::universe::{
+    LayoutAlgorithm, MAX_LAYOUT_SPACING, MIN_LAYOUT_SPACING,
+};
 use crate::{geometry::*, gui::GuiFocusState};
 use crate::{view::*, vulkan::texture::GradientName};
 
@@ -16,8 +24,14 @@ pub struct SharedState {
 
     pub hover_node: Arc<AtomicCell<Option<NodeId>>>,
 
+    /// The path used by default for coordinate based operations,
+    /// such as mapping annotations to nodes
+    pub reference_path: Arc<AtomicCell<Option<PathId>>>,
+
     pub mouse_rect: MouseRect,
 
+    pub ruler: Ruler,
+
     pub overlay_state: OverlayState,
 
     pub gui_focus_state: GuiFocusState,
@@ -25,6 +39,34 @@ pub struct SharedState {
     pub edges_enabled: Arc<AtomicCell<bool>>,
 
     pub dark_mode: Arc<AtomicCell<bool>>,
+
+    /// The layout algorithm currently running, if any, and its progress
+    pub layout_progress: Arc<AtomicCell<Option<(LayoutAlgorithm, f32)>>>,
+
+    /// The progress of the running path coverage export, if any
+    pub coverage_export_progress: Arc<AtomicCell<Option<f32>>>,
+
+    /// The number of frames saved by the running frame capture, if
+    /// any
+    pub frame_capture: Arc<AtomicCell<Option<usize>>>,
+
+    /// The overlay whose colors are being built on a worker, if any
+    pub overlay_build: Arc<RwLock<Option<OverlayBuild>>>,
+
+    /// How much the spacing between the nodes should be scaled,
+    /// relative to the loaded or most recently computed layout
+    pub layout_spacing: Arc<AtomicCell<f32>>,
+}
+
+/// An overlay being built on a worker
+pub struct OverlayBuild {
+    /// The name the overlay will be created with
+    pub name: String,
+    pub task: OverlayColorsTask,
+    /// The script the overlay is built from, which is kept with it
+    pub script: Option<OverlayScript>,
+    /// The overlay to update in place, when recomputing one
+    pub overlay_id: Option<usize>,
 }
 
 impl SharedState {
@@ -37,14 +79,28 @@ impl SharedState {
 
             hover_node: Arc::new(None.into()),
 
+            reference_path: Arc::new(None.into()),
+
             mouse_rect: MouseRect::default(),
 
+            ruler: Ruler::default(),
+
             overlay_state: OverlayState::default(),
 
             gui_focus_state: GuiFocusState::default(),
 
             edges_enabled: Arc::new(true.into()),
             dark_mode: Arc::new(false.into()),
+
+            layout_progress: Arc::new(None.into()),
+
+            coverage_export_progress: Arc::new(None.into()),
+
+            frame_capture: Arc::new(None.into()),
+
+            overlay_build: Arc::new(RwLock::new(None)),
+
+            layout_spacing: Arc::new(1.0.into()),
         }
     }
 
@@ -64,6 +120,107 @@ impl SharedState {
         self.hover_node.load()
     }
 
+    pub fn reference_path(&self) -> Option<PathId> {
+        self.reference_path.load()
+    }
+
+    pub fn set_reference_path(&self, path: Option<PathId>) {
+        self.reference_path.store(path);
+    }
+
+    pub fn clone_reference_path(&self) -> Arc<AtomicCell<Option<PathId>>> {
+        self.reference_path.clone()
+    }
+
+    pub fn layout_progress(&self) -> Option<(LayoutAlgorithm, f32)> {
+        self.layout_progress.load()
+    }
+
+    pub fn clone_layout_progress(
+        &self,
+    ) -> Arc<AtomicCell<Option<(LayoutAlgorithm, f32)>>> {
+        self.layout_progress.clone()
+    }
+
+    pub fn coverage_export_progress(&self) -> Option<f32> {
+        self.coverage_export_progress.load()
+    }
+
+    pub fn clone_coverage_export_progress(
+        &self,
+    ) -> Arc<AtomicCell<Option<f32>>> {
+        self.coverage_export_progress.clone()
+    }
+
+    /// The name and progress of the overlay being built, if any
+    pub fn overlay_build(&self) -> Option<(String, f32)> {
+        self.overlay_build
+            .read()
+            .as_ref()
+            .map(|build| (build.name.clone(), build.task.progress()))
+    }
+
+    /// Starts tracking an overlay build, cancelling the previous one
+    /// if it's still running
+    pub fn set_overlay_build(&self, name: &str, task: OverlayColorsTask) {
+        self.start_overlay_build(OverlayBuild {
+            name: name.to_string(),
+            task,
+            script: None,
+            overlay_id: None,
+        });
+    }
+
+    /// Like `set_overlay_build`, for an overlay built from a script;
+    /// if `overlay_id` is given, that overlay is recomputed in place
+    pub fn set_script_overlay_build(
+        &self,
+        name: &str,
+        task: OverlayColorsTask,
+        script: OverlayScript,
+        overlay_id: Option<usize>,
+    ) {
+        self.start_overlay_build(OverlayBuild {
+            name: name.to_string(),
+            task,
+            script: Some(script),
+            overlay_id,
+        });
+    }
+
+    fn start_overlay_build(&self, build: OverlayBuild) {
+        let mut overlay_build = self.overlay_build.write();
+
+        if let Some(prev) = overlay_build.as_ref() {
+            prev.task.cancel();
+        }
+
+        *overlay_build = Some(build);
+    }
+
+    pub fn cancel_overlay_build(&self) {
+        if let Some(build) = self.overlay_build.read().as_ref() {
+            build.task.cancel();
+        }
+    }
+
+    pub fn clone_overlay_build(&self) -> Arc<RwLock<Option<OverlayBuild>>> {
+        self.overlay_build.clone()
+    }
+
+    pub fn layout_spacing(&self) -> f32 {
+        self.layout_spacing.load()
+    }
+
+    pub fn set_layout_spacing(&self, spacing: f32) {
+        self.layout_spacing
+            .store(spacing.max(MIN_LAYOUT_SPACING).min(MAX_LAYOUT_SPACING));
+    }
+
+    pub fn clone_layout_spacing(&self) -> Arc<AtomicCell<f32>> {
+        self.layout_spacing.clone()
+    }
+
     pub fn overlay_state(&self) -> &OverlayState {
         &self.overlay_state
     }
@@ -140,6 +297,14 @@ impl SharedState {
     pub fn is_started_mouse_rect(&self) -> bool {
         self.mouse_rect.screen_pos.load().is_some()
     }
+
+    pub fn ruler(&self) -> &Ruler {
+        &self.ruler
+    }
+
+    pub fn frame_capture(&self) -> Option<usize> {
+        self.frame_capture.load()
+    }
 }
 
 #[derive(Clone)]
@@ -157,12 +322,89 @@ impl std::default::Default for MouseRect {
     }
 }
 
+/// An end of the ruler, in world space, with the node it was placed
+/// on, if any
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RulerPoint {
+    pub world: Point,
+    pub node: Option<NodeId>,
+}
+
+/// The state of the measurement tool. While it's enabled, select
+/// clicks in the main view place the ends of the ruler instead of
+/// selecting nodes
+#[derive(Debug, Default, Clone)]
+pub struct Ruler {
+    enabled: Arc<AtomicCell<bool>>,
+    start: Arc<AtomicCell<Option<RulerPoint>>>,
+    end: Arc<AtomicCell<Option<RulerPoint>>>,
+}
+
+impl Ruler {
+    pub fn enabled(&self) -> bool {
+        self.enabled.load()
+    }
+
+    /// Disabling the ruler also clears the measurement
+    pub fn set_enabled(&self, to: bool) {
+        self.enabled.store(to);
+
+        if !to {
+            self.clear();
+        }
+    }
+
+    pub fn toggle(&self) {
+        self.set_enabled(!self.enabled());
+    }
+
+    /// Places the next end of the ruler; placing a point after both
+    /// ends are set starts a new measurement
+    pub fn place(&self, point: RulerPoint) {
+        if self.start.load().is_none() || self.end.load().is_some() {
+            self.start.store(Some(point));
+            self.end.store(None);
+        } else {
+            self.end.store(Some(point));
+        }
+    }
+
+    pub fn start(&self) -> Option<RulerPoint> {
+        self.start.load()
+    }
+
+    pub fn end(&self) -> Option<RulerPoint> {
+        self.end.load()
+    }
+
+    pub fn clear(&self) {
+        self.start.store(None);
+        self.end.store(None);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OverlayState {
     use_overlay: Arc<AtomicCell<bool>>,
     current_overlay: Arc<AtomicCell<Option<(usize, OverlayKind)>>>,
 
     gradient: Arc<AtomicCell<GradientName>>,
+
+    scope: Arc<AtomicCell<OverlayScope>>,
+    unselected_dim: Arc<AtomicCell<f32>>,
+
+    mode: Arc<AtomicCell<OverlayMode>>,
+    /// The overlays drawn in stack mode, from top to bottom
+    layers: Arc<RwLock<Vec<OverlayLayer>>>,
+
+    legends: Arc<RwLock<FxHashMap<usize, OverlayLegend>>>,
+    /// The paths counted by each path depth overlay
+    overlay_paths: Arc<RwLock<FxHashMap<usize, Vec<PathId>>>>,
+    /// The script each script overlay was built from
+    overlay_scripts: Arc<RwLock<FxHashMap<usize, OverlayScript>>>,
+
+    show_legend: Arc<AtomicCell<bool>>,
+    legend_anchor: Arc<AtomicCell<LegendAnchor>>,
 }
 
 impl OverlayState {
@@ -196,6 +438,216 @@ impl OverlayState {
     pub fn set_gradient(&self, gradient: GradientName) {
         self.gradient.store(gradient);
     }
+
+    pub fn scope(&self) -> OverlayScope {
+        self.scope.load()
+    }
+
+    pub fn set_scope(&self, scope: OverlayScope) {
+        self.scope.store(scope);
+    }
+
+    /// How much the nodes outside the selection are faded when the
+    /// overlay is scoped to the selection, between 0.0 and 1.0
+    pub fn unselected_dim(&self) -> f32 {
+        self.unselected_dim.load()
+    }
+
+    pub fn set_unselected_dim(&self, dim: f32) {
+        self.unselected_dim.store(dim.clamp(0.0, 1.0));
+    }
+
+    pub fn mode(&self) -> OverlayMode {
+        self.mode.load()
+    }
+
+    pub fn set_mode(&self, mode: OverlayMode) {
+        self.mode.store(mode);
+    }
+
+    /// The overlay stack, ordered from the top layer to the bottom
+    pub fn layers(&self) -> Vec<OverlayLayer> {
+        self.layers.read().clone()
+    }
+
+    /// Updates the stack to match the existing overlays; removed
+    /// overlays are dropped from the stack, and new overlays are
+    /// placed on top, visible and fully opaque
+    pub fn sync_layers(
+        &self,
+        overlays: impl Iterator<Item = (usize, OverlayKind)>,
+    ) {
+        let overlays = overlays.collect::<Vec<_>>();
+
+        let mut layers = self.layers.write();
+
+        layers.retain(|layer| overlays.contains(&layer.overlay));
+
+        for overlay in overlays {
+            if !layers.iter().any(|layer| layer.overlay == overlay) {
+                layers.insert(0, OverlayLayer::new(overlay));
+            }
+        }
+    }
+
+    /// Moves the layer of the given overlay to `index` in the stack,
+    /// where 0 is the top
+    pub fn move_layer(&self, overlay_id: usize, index: usize) {
+        let mut layers = self.layers.write();
+
+        if let Some(from) = layers
+            .iter()
+            .position(|layer| layer.overlay.0 == overlay_id)
+        {
+            let layer = layers.remove(from);
+            let index = index.min(layers.len());
+            layers.insert(index, layer);
+        }
+    }
+
+    pub fn set_layer_visible(&self, overlay_id: usize, visible: bool) {
+        let mut layers = self.layers.write();
+
+        if let Some(layer) = layers
+            .iter_mut()
+            .find(|layer| layer.overlay.0 == overlay_id)
+        {
+            layer.visible = visible;
+        }
+    }
+
+    pub fn set_layer_opacity(&self, overlay_id: usize, opacity: f32) {
+        let mut layers = self.layers.write();
+
+        if let Some(layer) = layers
+            .iter_mut()
+            .find(|layer| layer.overlay.0 == overlay_id)
+        {
+            layer.opacity = opacity.clamp(0.0, 1.0);
+        }
+    }
+
+    pub fn legend(&self, overlay_id: usize) -> Option<OverlayLegend> {
+        self.legends.read().get(&overlay_id).cloned()
+    }
+
+    /// Sets the legend of the overlay, or removes it if `legend` is
+    /// `None`
+    pub fn set_legend(&self, overlay_id: usize, legend: Option<OverlayLegend>) {
+        let mut legends = self.legends.write();
+
+        if let Some(legend) = legend {
+            legends.insert(overlay_id, legend);
+        } else {
+            legends.remove(&overlay_id);
+        }
+    }
+
+    /// Sets the units shown next to the range of a value overlay's
+    /// legend; returns `false` if the overlay has no range legend
+    pub fn set_legend_units(&self, overlay_id: usize, new_units: &str) -> bool {
+        let mut legends = self.legends.write();
+
+        match legends.get_mut(&overlay_id) {
+            Some(OverlayLegend::Range { units, .. }) => {
+                *units = new_units.to_string();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The paths counted by the path depth overlay, or `None` if
+    /// it's not a path depth overlay
+    pub fn overlay_paths(&self, overlay_id: usize) -> Option<Vec<PathId>> {
+        self.overlay_paths.read().get(&overlay_id).cloned()
+    }
+
+    pub fn set_overlay_paths(
+        &self,
+        overlay_id: usize,
+        paths: Option<Vec<PathId>>,
+    ) {
+        let mut overlay_paths = self.overlay_paths.write();
+
+        if let Some(paths) = paths {
+            overlay_paths.insert(overlay_id, paths);
+        } else {
+            overlay_paths.remove(&overlay_id);
+        }
+    }
+
+    /// The script the overlay was built from, or `None` if it wasn't
+    /// built from a script
+    pub fn overlay_script(&self, overlay_id: usize) -> Option<OverlayScript> {
+        self.overlay_scripts.read().get(&overlay_id).cloned()
+    }
+
+    pub fn set_overlay_script(
+        &self,
+        overlay_id: usize,
+        script: Option<OverlayScript>,
+    ) {
+        let mut overlay_scripts = self.overlay_scripts.write();
+
+        if let Some(script) = script {
+            overlay_scripts.insert(overlay_id, script);
+        } else {
+            overlay_scripts.remove(&overlay_id);
+        }
+    }
+
+    /// The IDs of the path depth overlays, in ascending order
+    pub fn path_set_overlays(&self) -> Vec<usize> {
+        let mut overlays = self
+            .overlay_paths
+            .read()
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        overlays.sort();
+        overlays
+    }
+
+    /// The overlays whose colors are currently shown, in the order
+    /// their legends should be listed: the active overlay in single
+    /// mode, or the visible layers from the top in stack mode
+    pub fn shown_overlays(&self) -> Vec<(usize, OverlayKind)> {
+        if !self.use_overlay() {
+            return Vec::new();
+        }
+
+        match self.mode() {
+            OverlayMode::Single => self.current_overlay().into_iter().collect(),
+            OverlayMode::Stack => self
+                .layers
+                .read()
+                .iter()
+                .filter(|layer| layer.visible)
+                .map(|layer| layer.overlay)
+                .collect(),
+        }
+    }
+
+    pub fn show_legend(&self) -> bool {
+        self.show_legend.load()
+    }
+
+    pub fn set_show_legend(&self, show: bool) {
+        self.show_legend.store(show);
+    }
+
+    pub fn toggle_legend(&self) {
+        self.show_legend.fetch_xor(true);
+    }
+
+    pub fn legend_anchor(&self) -> LegendAnchor {
+        self.legend_anchor.load()
+    }
+
+    pub fn set_legend_anchor(&self, anchor: LegendAnchor) {
+        self.legend_anchor.store(anchor);
+    }
 }
 
 impl std::default::Default for OverlayState {
@@ -205,10 +657,159 @@ impl std::default::Default for OverlayState {
 
         let gradient = Arc::new(AtomicCell::new(GradientName::Magma));
 
+        let scope = Arc::new(AtomicCell::new(OverlayScope::Global));
+        let unselected_dim = Arc::new(AtomicCell::new(0.0));
+
+        let mode = Arc::new(AtomicCell::new(OverlayMode::Single));
+        let layers = Arc::new(RwLock::new(Vec::new()));
+
+        let legends = Arc::new(RwLock::new(FxHashMap::default()));
+        let overlay_paths = Arc::new(RwLock::new(FxHashMap::default()));
+        let overlay_scripts = Arc::new(RwLock::new(FxHashMap::default()));
+        let show_legend = Arc::new(AtomicCell::new(false));
+        let legend_anchor = Arc::new(AtomicCell::new(LegendAnchor::TopRight));
+
         Self {
             use_overlay,
             current_overlay,
             gradient,
+            scope,
+            unselected_dim,
+            mode,
+            layers,
+            legends,
+            overlay_paths,
+            overlay_scripts,
+            show_legend,
+            legend_anchor,
+        }
+    }
+}
+
+/// Which nodes the active overlay is applied to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayScope {
+    /// Color all nodes using the overlay
+    Global,
+    /// Color the nodes in the active selection group using the
+    /// overlay, and the rest using the theme
+    Selection,
+}
+
+impl OverlayScope {
+    /// The name used to refer to the scope in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverlayScope::Global => "global",
+            OverlayScope::Selection => "selection",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "global" => Some(OverlayScope::Global),
+            "selection" => Some(OverlayScope::Selection),
+            _ => None,
+        }
+    }
+}
+
+/// How overlays are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayMode {
+    /// Draw the active overlay
+    Single,
+    /// Draw all visible overlays in the stack on top of the theme,
+    /// from the bottom layer up, blended using their opacity
+    Stack,
+}
+
+impl OverlayMode {
+    /// The name used to refer to the mode in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverlayMode::Single => "single",
+            OverlayMode::Stack => "stack",
         }
     }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "single" => Some(OverlayMode::Single),
+            "stack" => Some(OverlayMode::Stack),
+            _ => None,
+        }
+    }
+}
+
+/// An overlay's place in the overlay stack
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OverlayLayer {
+    pub overlay: (usize, OverlayKind),
+    pub visible: bool,
+    /// Multiplies the alpha of the overlay colors, between 0.0 and 1.0
+    pub opacity: f32,
+}
+
+impl OverlayLayer {
+    pub fn new(overlay: (usize, OverlayKind)) -> Self {
+        Self {
+            overlay,
+            visible: true,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// The corner of the screen the overlay legend is placed in, both in
+/// the GUI and in exported SVGs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegendAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl LegendAnchor {
+    pub const ALL: [LegendAnchor; 4] = [
+        LegendAnchor::TopLeft,
+        LegendAnchor::TopRight,
+        LegendAnchor::BottomLeft,
+        LegendAnchor::BottomRight,
+    ];
+
+    /// The name used to refer to the anchor in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            LegendAnchor::TopLeft => "top_left",
+            LegendAnchor::TopRight => "top_right",
+            LegendAnchor::BottomLeft => "bottom_left",
+            LegendAnchor::BottomRight => "bottom_right",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|anchor| anchor.name() == name)
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            LegendAnchor::TopLeft => "Top left",
+            LegendAnchor::TopRight => "Top right",
+            LegendAnchor::BottomLeft => "Bottom left",
+            LegendAnchor::BottomRight => "Bottom right",
+        }
+    }
+
+    pub fn is_left(&self) -> bool {
+        matches!(self, LegendAnchor::TopLeft | LegendAnchor::BottomLeft)
+    }
+
+    pub fn is_top(&self) -> bool {
+        matches!(self, LegendAnchor::TopLeft | LegendAnchor::TopRight)
+    }
 }
@@ -94,6 +94,10 @@ impl AppThemes {
         self.active_theme
     }
 
+    pub fn active_theme_def(&self) -> Option<&ThemeDef> {
+        self.theme_definitions.get(&self.active_theme)
+    }
+
     pub fn toggle_previous_theme(&mut self) {
         std::mem::swap(&mut self.active_theme, &mut self.previous_theme);
     }
@@ -0,0 +1,280 @@
+use handlegraph::handle::NodeId;
+
+use anyhow::Result;
+
+use crate::geometry::Point;
+use crate::view::View;
+
+/// The state needed to reproduce what is shown in the main view,
+/// assuming the same graph and layout are loaded, encodable as a
+/// compact string that can be shared through the clipboard
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewLink {
+    pub graph_name: String,
+    pub node_count: usize,
+    pub view: View,
+    pub overlay: Option<String>,
+    pub selection: Vec<NodeId>,
+}
+
+impl ViewLink {
+    const PREFIX: &'static str = "gfaestus:v1:";
+
+    pub fn encode(&self) -> String {
+        let mut nodes = self.selection.iter().map(|n| n.0).collect::<Vec<_>>();
+        nodes.sort_unstable();
+        nodes.dedup();
+
+        // store the selection as a list of node ID ranges
+        let mut ranges: Vec<String> = Vec::new();
+        let mut iter = nodes.into_iter().peekable();
+
+        while let Some(start) = iter.next() {
+            let mut end = start;
+            while iter.peek() == Some(&(end + 1)) {
+                end = iter.next().unwrap();
+            }
+
+            if start == end {
+                ranges.push(start.to_string());
+            } else {
+                ranges.push(format!("{}-{}", start, end));
+            }
+        }
+
+        let payload = [
+            self.graph_name.clone(),
+            self.node_count.to_string(),
+            self.view.center.x.to_string(),
+            self.view.center.y.to_string(),
+            self.view.scale.to_string(),
+            self.overlay.clone().unwrap_or_default(),
+            ranges.join(","),
+            self.view.aspect.to_string(),
+        ]
+        .join("\n");
+
+        format!("{}{}", Self::PREFIX, base64_encode(payload.as_bytes()))
+    }
+
+    pub fn decode(link: &str) -> Result<Self> {
+        let link = link.trim();
+
+        if !link.starts_with(Self::PREFIX) {
+            anyhow::bail!("Not a gfaestus view link");
+        }
+
+        let bytes = base64_decode(&link[Self::PREFIX.len()..])?;
+        let payload = String::from_utf8(bytes)?;
+
+        let fields = payload.split('\n').collect::<Vec<_>>();
+
+        // links created before per-axis zoom was added lack the
+        // aspect field
+        if fields.len() != 7 && fields.len() != 8 {
+            anyhow::bail!(
+                "View link has {} fields, expected 7 or 8",
+                fields.len()
+            );
+        }
+
+        let graph_name = fields[0].to_string();
+        let node_count = fields[1].parse::<usize>()?;
+
+        let center = Point::new(fields[2].parse::<f32>()?, fields[3].parse()?);
+        let scale = fields[4].parse::<f32>()?;
+        let aspect = match fields.get(7) {
+            Some(aspect) => aspect.parse::<f32>()?,
+            None => 1.0,
+        };
+
+        if !(scale.is_finite() && scale > 0.0)
+            || !(aspect.is_finite() && aspect > 0.0)
+            || !(center.x.is_finite() && center.y.is_finite())
+        {
+            anyhow::bail!("View link contains an invalid view");
+        }
+
+        let view = View {
+            center,
+            scale,
+            aspect,
+        };
+
+        let overlay = if fields[5].is_empty() {
+            None
+        } else {
+            Some(fields[5].to_string())
+        };
+
+        let mut selection = Vec::new();
+
+        for range in fields[6].split(',').filter(|r| !r.is_empty()) {
+            let mut ends = range.splitn(2, '-');
+
+            let start = ends.next().unwrap().parse::<u64>()?;
+            let end = match ends.next() {
+                Some(end) => end.parse::<u64>()?,
+                None => start,
+            };
+
+            if start == 0 || end < start || end as usize > node_count {
+                anyhow::bail!(
+                    "View link contains invalid node range {}",
+                    range
+                );
+            }
+
+            selection.extend((start..=end).map(NodeId::from));
+        }
+
+        Ok(Self {
+            graph_name,
+            node_count,
+            view,
+            overlay,
+            selection,
+        })
+    }
+}
+
+const BASE64_CHARS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// URL-safe base64 without padding
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() * 4 + 2) / 3);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | (b[2] as u32);
+
+        for i in 0..(chunk.len() + 1) {
+            let ix = (n >> (18 - 6 * i)) & 0x3F;
+            out.push(BASE64_CHARS[ix as usize] as char);
+        }
+    }
+
+    out
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+
+    let mut buf: u32 = 0;
+    let mut bits = 0;
+
+    for c in text.bytes() {
+        let val = BASE64_CHARS
+            .iter()
+            .position(|&b| b == c)
+            .ok_or_else(|| anyhow::anyhow!("Invalid character in view link"))?;
+
+        buf = (buf << 6) | val as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+            buf &= (1 << bits) - 1;
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(selection: &[u64]) -> ViewLink {
+        ViewLink {
+            graph_name: "graph.gfa".to_string(),
+            node_count: 20,
+            view: View {
+                center: Point::new(12.5, -3.25),
+                scale: 0.75,
+                aspect: 2.0,
+            },
+            overlay: Some("Path depth".to_string()),
+            selection: selection.iter().map(|&id| NodeId::from(id)).collect(),
+        }
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        for len in 0..8 {
+            let bytes =
+                (0..len).map(|i| (200 + i * 37) as u8).collect::<Vec<_>>();
+            let encoded = base64_encode(&bytes);
+
+            assert!(!encoded.contains('='));
+            assert_eq!(base64_decode(&encoded).unwrap(), bytes);
+        }
+
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+        assert!(base64_decode("TW+u").is_err());
+    }
+
+    #[test]
+    fn encode_then_decode() {
+        let original = link(&[1, 2, 3, 5, 9, 10]);
+        let encoded = original.encode();
+
+        assert!(encoded.starts_with(ViewLink::PREFIX));
+        assert_eq!(ViewLink::decode(&encoded).unwrap(), original);
+
+        let mut no_overlay = link(&[]);
+        no_overlay.overlay = None;
+        let decoded = ViewLink::decode(&no_overlay.encode()).unwrap();
+        assert_eq!(decoded, no_overlay);
+    }
+
+    #[test]
+    fn selection_is_stored_as_ranges() {
+        let decoded =
+            ViewLink::decode(&link(&[5, 3, 4, 3, 8]).encode()).unwrap();
+
+        let ids = decoded.selection.iter().map(|n| n.0).collect::<Vec<_>>();
+        assert_eq!(ids, vec![3, 4, 5, 8]);
+    }
+
+    #[test]
+    fn links_without_aspect_are_accepted() {
+        let payload = "graph.gfa\n20\n1\n2\n0.5\n\n1-3";
+        let encoded = format!(
+            "{}{}",
+            ViewLink::PREFIX,
+            base64_encode(payload.as_bytes())
+        );
+
+        let decoded = ViewLink::decode(&encoded).unwrap();
+        assert_eq!(decoded.view.aspect, 1.0);
+        assert_eq!(decoded.overlay, None);
+        assert_eq!(decoded.selection.len(), 3);
+    }
+
+    #[test]
+    fn invalid_links_are_rejected() {
+        let encode = |payload: &str| {
+            format!("{}{}", ViewLink::PREFIX, base64_encode(payload.as_bytes()))
+        };
+
+        assert!(ViewLink::decode("https://example.com").is_err());
+        assert!(ViewLink::decode(&encode("graph.gfa\n20\n1\n2")).is_err());
+
+        // zero or negative scale
+        assert!(ViewLink::decode(&encode("g\n20\n1\n2\n0\n\n")).is_err());
+        assert!(ViewLink::decode(&encode("g\n20\n1\n2\n-1\n\n")).is_err());
+
+        // node IDs outside the graph, or backwards ranges
+        assert!(ViewLink::decode(&encode("g\n20\n1\n2\n1\n\n0")).is_err());
+        assert!(ViewLink::decode(&encode("g\n20\n1\n2\n1\n\n19-21")).is_err());
+        assert!(ViewLink::decode(&encode("g\n20\n1\n2\n1\n\n5-4")).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+pub mod bundle;
+pub mod coverage;
+pub mod gfa;
+pub mod png;
+pub mod svg;
+
+pub use self::png::*;
+pub use bundle::*;
+pub use coverage::*;
+pub use gfa::*;
+pub use svg::*;
@@ -0,0 +1,679 @@
+use handlegraph::{
+    handle::NodeId, packedgraph::PackedGraph, pathhandlegraph::*,
+};
+
+use anyhow::Result;
+use rustc_hash::FxHashMap;
+
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+
+use crate::annotations::{
+    AnnotationCollection, AnnotationFileType, AnnotationLabelSet,
+    AnnotationRecord, ColumnKey,
+};
+use crate::overlays::{OverlayData, OverlayKind, OverlayLegend};
+use crate::universe::FlatLayout;
+
+/// The files in a session bundle directory, relative to the bundle
+pub const BUNDLE_MANIFEST: &str = "manifest.tsv";
+pub const BUNDLE_LAYOUT: &str = "layout.tsv";
+pub const BUNDLE_SCRIPT: &str = "session.rhai";
+
+pub const BUNDLE_OVERLAY_DIR: &str = "overlays";
+pub const BUNDLE_ANNOTATION_DIR: &str = "annotations";
+pub const BUNDLE_LABEL_DIR: &str = "labels";
+
+/// Describes the contents of a session bundle, a directory holding
+/// everything needed to recreate what's shown in gfaestus, except for
+/// the graph itself, which is only referred to by path.
+///
+/// Stored as `manifest.tsv`, with one tab-separated entry per line,
+/// the first field being the kind of entry.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BundleManifest {
+    /// The path to the GFA when the bundle was exported
+    pub gfa: String,
+    pub node_count: usize,
+    /// An encoded `ViewLink`, holding the view, the active overlay,
+    /// and the selection
+    pub view_link: String,
+
+    pub overlays: Vec<BundleOverlay>,
+    pub annotations: Vec<BundleAnnotation>,
+    pub label_sets: Vec<BundleLabelSet>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleOverlay {
+    pub file: String,
+    pub kind: OverlayKind,
+    pub name: String,
+    pub legend: Option<OverlayLegend>,
+    /// The names of the paths counted by a path depth overlay
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleAnnotation {
+    pub file: String,
+    pub kind: AnnotationFileType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleLabelSet {
+    pub file: String,
+    pub name: String,
+    /// The file name of the annotations the labels were created from
+    pub annotation: String,
+    pub column: String,
+    pub path: String,
+}
+
+impl BundleManifest {
+    const VERSION: &'static str = "1";
+
+    pub fn write<W: Write>(&self, out: W) -> Result<()> {
+        let mut out = BufWriter::new(out);
+
+        // names can't contain the field separator
+        let field = |s: &str| s.replace(&['\t', '\n'][..], " ");
+
+        writeln!(out, "gfaestus_bundle\t{}", Self::VERSION)?;
+        writeln!(out, "gfa\t{}", field(&self.gfa))?;
+        writeln!(out, "node_count\t{}", self.node_count)?;
+        writeln!(out, "view\t{}", self.view_link)?;
+
+        for overlay in self.overlays.iter() {
+            let kind = match overlay.kind {
+                OverlayKind::RGB => "rgb",
+                OverlayKind::Value => "value",
+            };
+
+            writeln!(
+                out,
+                "overlay\t{}\t{}\t{}",
+                overlay.file,
+                kind,
+                field(&overlay.name)
+            )?;
+
+            // legends and path sets refer to the overlay by file
+            match &overlay.legend {
+                Some(OverlayLegend::Range { min, max, units }) => {
+                    writeln!(
+                        out,
+                        "overlay_range\t{}\t{}\t{}\t{}",
+                        overlay.file,
+                        min,
+                        max,
+                        field(units)
+                    )?;
+                }
+                Some(OverlayLegend::Categories(categories)) => {
+                    for (label, c) in categories.iter() {
+                        writeln!(
+                            out,
+                            "overlay_category\t{}\t{}\t{}\t{}\t{}\t{}",
+                            overlay.file,
+                            field(label),
+                            c.r,
+                            c.g,
+                            c.b,
+                            c.a
+                        )?;
+                    }
+                }
+                None => (),
+            }
+
+            for path in overlay.paths.iter() {
+                writeln!(
+                    out,
+                    "overlay_path\t{}\t{}",
+                    overlay.file,
+                    field(path)
+                )?;
+            }
+        }
+
+        for annotation in self.annotations.iter() {
+            let kind = match annotation.kind {
+                AnnotationFileType::Gff3 => "gff3",
+                AnnotationFileType::Bed => "bed",
+            };
+
+            writeln!(out, "annotation\t{}\t{}", annotation.file, kind)?;
+        }
+
+        for label_set in self.label_sets.iter() {
+            writeln!(
+                out,
+                "label_set\t{}\t{}\t{}\t{}\t{}",
+                label_set.file,
+                field(&label_set.name),
+                field(&label_set.annotation),
+                field(&label_set.column),
+                field(&label_set.path),
+            )?;
+        }
+
+        out.flush()?;
+
+        Ok(())
+    }
+
+    /// The overlay entry stored in `file`, which must come before
+    /// the entries that refer to it
+    fn overlay_mut(&mut self, file: &str) -> Result<&mut BundleOverlay> {
+        self.overlays
+            .iter_mut()
+            .find(|overlay| overlay.file == file)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Bundle manifest refers to unknown overlay '{}'",
+                    file
+                )
+            })
+    }
+
+    pub fn read<R: Read>(input: R) -> Result<Self> {
+        let reader = BufReader::new(input);
+
+        let mut manifest = Self::default();
+        let mut version = None;
+
+        for (line_ix, line) in reader.lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields = line.split('\t').collect::<Vec<_>>();
+
+            let expect = |count: usize| -> Result<()> {
+                if fields.len() != count {
+                    anyhow::bail!(
+                        "Bundle manifest line {} has {} fields, expected {}",
+                        line_ix + 1,
+                        fields.len(),
+                        count
+                    );
+                }
+                Ok(())
+            };
+
+            match fields[0] {
+                "gfaestus_bundle" => {
+                    expect(2)?;
+                    version = Some(fields[1].to_string());
+                }
+                "gfa" => {
+                    expect(2)?;
+                    manifest.gfa = fields[1].to_string();
+                }
+                "node_count" => {
+                    expect(2)?;
+                    manifest.node_count = fields[1].parse()?;
+                }
+                "view" => {
+                    expect(2)?;
+                    manifest.view_link = fields[1].to_string();
+                }
+                "overlay" => {
+                    expect(4)?;
+                    let kind = match fields[2] {
+                        "rgb" => OverlayKind::RGB,
+                        "value" => OverlayKind::Value,
+                        other => {
+                            anyhow::bail!("Unknown overlay kind '{}'", other)
+                        }
+                    };
+
+                    manifest.overlays.push(BundleOverlay {
+                        file: fields[1].to_string(),
+                        kind,
+                        name: fields[3].to_string(),
+                        legend: None,
+                        paths: Vec::new(),
+                    });
+                }
+                "overlay_range" => {
+                    expect(5)?;
+                    let overlay = manifest.overlay_mut(fields[1])?;
+                    overlay.legend = Some(OverlayLegend::Range {
+                        min: fields[2].parse()?,
+                        max: fields[3].parse()?,
+                        units: fields[4].to_string(),
+                    });
+                }
+                "overlay_category" => {
+                    expect(7)?;
+                    let color = rgb::RGBA::new(
+                        fields[3].parse()?,
+                        fields[4].parse()?,
+                        fields[5].parse()?,
+                        fields[6].parse()?,
+                    );
+
+                    let overlay = manifest.overlay_mut(fields[1])?;
+                    let entry = (fields[2].to_string(), color);
+
+                    match &mut overlay.legend {
+                        Some(OverlayLegend::Categories(categories)) => {
+                            categories.push(entry);
+                        }
+                        _ => {
+                            overlay.legend =
+                                Some(OverlayLegend::Categories(vec![entry]));
+                        }
+                    }
+                }
+                "overlay_path" => {
+                    expect(3)?;
+                    let overlay = manifest.overlay_mut(fields[1])?;
+                    overlay.paths.push(fields[2].to_string());
+                }
+                "annotation" => {
+                    expect(3)?;
+                    let kind = match fields[2] {
+                        "gff3" => AnnotationFileType::Gff3,
+                        "bed" => AnnotationFileType::Bed,
+                        other => {
+                            anyhow::bail!("Unknown annotation kind '{}'", other)
+                        }
+                    };
+
+                    manifest.annotations.push(BundleAnnotation {
+                        file: fields[1].to_string(),
+                        kind,
+                    });
+                }
+                "label_set" => {
+                    expect(6)?;
+                    manifest.label_sets.push(BundleLabelSet {
+                        file: fields[1].to_string(),
+                        name: fields[2].to_string(),
+                        annotation: fields[3].to_string(),
+                        column: fields[4].to_string(),
+                        path: fields[5].to_string(),
+                    });
+                }
+                other => {
+                    log::warn!(
+                        "Ignoring unknown bundle manifest entry '{}'",
+                        other
+                    );
+                }
+            }
+        }
+
+        match version.as_deref() {
+            Some(Self::VERSION) => Ok(manifest),
+            Some(other) => {
+                anyhow::bail!("Unsupported bundle version '{}'", other)
+            }
+            None => anyhow::bail!("Not a gfaestus bundle manifest"),
+        }
+    }
+}
+
+/// Writes the layout in the same TSV format as the layout files
+/// loaded at startup, so the bundle's layout can be passed on the
+/// command line
+pub fn write_bundle_layout<W: Write>(
+    layout: &FlatLayout,
+    out: W,
+) -> Result<()> {
+    let mut out = BufWriter::new(out);
+
+    writeln!(out, "idx\tX\tY\tcomponent")?;
+
+    for (ix, node) in layout.nodes().iter().enumerate() {
+        let component = layout.node_component(NodeId::from((ix + 1) as u64));
+
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}",
+            ix * 2,
+            node.p0.x,
+            node.p0.y,
+            component
+        )?;
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}",
+            ix * 2 + 1,
+            node.p1.x,
+            node.p1.y,
+            component
+        )?;
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Writes an overlay as CSV, with one row per node, in node ID order
+pub fn write_bundle_overlay<W: Write>(
+    data: &OverlayData,
+    out: W,
+) -> Result<()> {
+    let mut out = BufWriter::new(out);
+
+    match data {
+        OverlayData::RGB(colors) => {
+            writeln!(out, "node_id,r,g,b,a")?;
+            for (ix, c) in colors.iter().enumerate() {
+                writeln!(out, "{},{},{},{},{}", ix + 1, c.r, c.g, c.b, c.a)?;
+            }
+        }
+        OverlayData::Value(values) => {
+            writeln!(out, "node_id,value")?;
+            for (ix, v) in values.iter().enumerate() {
+                writeln!(out, "{},{}", ix + 1, v)?;
+            }
+        }
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Reads an overlay written by `write_bundle_overlay`; nodes missing
+/// from the file are transparent, or have value 0
+pub fn read_bundle_overlay<R: Read>(
+    kind: OverlayKind,
+    node_count: usize,
+    input: R,
+) -> Result<OverlayData> {
+    let reader = BufReader::new(input);
+
+    let mut colors = Vec::new();
+    let mut values = Vec::new();
+
+    match kind {
+        OverlayKind::RGB => {
+            colors = vec![rgb::RGBA::new(0.0, 0.0, 0.0, 0.0); node_count]
+        }
+        OverlayKind::Value => values = vec![0.0f32; node_count],
+    }
+
+    // skip the header
+    for line in reader.lines().skip(1) {
+        let line = line?;
+
+        let fields = line.trim().split(',').collect::<Vec<_>>();
+        if fields.len() < 2 {
+            continue;
+        }
+
+        let ix = match fields[0].parse::<usize>()? {
+            0 => continue,
+            id if id > node_count => continue,
+            id => id - 1,
+        };
+
+        match kind {
+            OverlayKind::RGB => {
+                if fields.len() != 5 {
+                    anyhow::bail!("Expected 5 fields in RGB overlay row");
+                }
+                colors[ix] = rgb::RGBA::new(
+                    fields[1].parse()?,
+                    fields[2].parse()?,
+                    fields[3].parse()?,
+                    fields[4].parse()?,
+                );
+            }
+            OverlayKind::Value => {
+                values[ix] = fields[1].parse()?;
+            }
+        }
+    }
+
+    match kind {
+        OverlayKind::RGB => Ok(OverlayData::RGB(colors)),
+        OverlayKind::Value => Ok(OverlayData::Value(values)),
+    }
+}
+
+/// Writes the labels of a label set as `node_id, label` TSV rows
+pub fn write_bundle_labels<W: Write>(
+    label_set: &AnnotationLabelSet,
+    out: W,
+) -> Result<()> {
+    let mut out = BufWriter::new(out);
+
+    let strings = label_set.label_strings();
+
+    let mut nodes = label_set.labels().keys().copied().collect::<Vec<_>>();
+    nodes.sort();
+
+    writeln!(out, "node_id\tlabel")?;
+
+    for node in nodes {
+        for &label_ix in label_set.labels()[&node].iter() {
+            let label = strings[label_ix].replace(&['\t', '\n'][..], " ");
+            writeln!(out, "{}\t{}", node.0, label)?;
+        }
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Reads labels written by `write_bundle_labels`, as the label
+/// strings and the label indices for each node
+pub fn read_bundle_labels<R: Read>(
+    input: R,
+) -> Result<(Vec<String>, FxHashMap<NodeId, Vec<usize>>)> {
+    let reader = BufReader::new(input);
+
+    let mut strings = Vec::new();
+    let mut labels: FxHashMap<NodeId, Vec<usize>> = FxHashMap::default();
+
+    for line in reader.lines().skip(1) {
+        let line = line?;
+
+        let mut fields = line.splitn(2, '\t');
+
+        let node = match fields.next().map(|f| f.parse::<u64>()) {
+            Some(Ok(id)) => NodeId::from(id),
+            _ => continue,
+        };
+
+        let label = fields.next().unwrap_or_default().to_string();
+
+        labels.entry(node).or_default().push(strings.len());
+        strings.push(label);
+    }
+
+    Ok((strings, labels))
+}
+
+/// Recreates a label set from its bundle entry and labels file,
+/// using the annotations it was originally created from
+pub fn read_bundle_label_set<C, R, K, I>(
+    annotations: &C,
+    graph: &PackedGraph,
+    entry: &BundleLabelSet,
+    input: I,
+) -> Result<AnnotationLabelSet>
+where
+    C: AnnotationCollection<ColumnKey = K, Record = R>,
+    R: AnnotationRecord<ColumnKey = K>,
+    K: ColumnKey,
+    I: Read,
+{
+    let column = annotations
+        .all_columns()
+        .into_iter()
+        .find(|column| column.to_string() == entry.column)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Column '{}' not found in '{}'",
+                entry.column,
+                entry.annotation
+            )
+        })?;
+
+    let path_id = graph
+        .get_path_id(entry.path.as_bytes())
+        .ok_or_else(|| anyhow::anyhow!("Path '{}' not found", entry.path))?;
+
+    let (label_strings, labels) = read_bundle_labels(input)?;
+
+    Ok(AnnotationLabelSet::new(
+        annotations,
+        path_id,
+        entry.path.as_bytes(),
+        &column,
+        &entry.name,
+        label_strings,
+        labels,
+    ))
+}
+
+/// Writes a script that imports the bundle when run with the bundle
+/// as the script directory
+pub fn write_bundle_script<W: Write>(
+    manifest: &BundleManifest,
+    out: W,
+) -> Result<()> {
+    let mut out = BufWriter::new(out);
+
+    writeln!(out, "// Recreates an exported gfaestus session, run with:")?;
+    writeln!(
+        out,
+        "//   gfaestus {} <bundle>/{} --script-dir <bundle> --run-script <bundle>/{}",
+        manifest.gfa, BUNDLE_LAYOUT, BUNDLE_SCRIPT
+    )?;
+    writeln!(out, "import_bundle(\".\");")?;
+
+    out.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> BundleManifest {
+        BundleManifest {
+            gfa: "/data/graph.gfa".to_string(),
+            node_count: 3,
+            view_link: "gfaestus:v1:abc".to_string(),
+            overlays: vec![
+                BundleOverlay {
+                    file: "overlays/0.csv".to_string(),
+                    kind: OverlayKind::Value,
+                    name: "Path depth".to_string(),
+                    legend: Some(OverlayLegend::Range {
+                        min: 0.0,
+                        max: 1.5,
+                        units: "fraction of 2 paths".to_string(),
+                    }),
+                    paths: vec!["a#1".to_string(), "b#1".to_string()],
+                },
+                BundleOverlay {
+                    file: "overlays/1.csv".to_string(),
+                    kind: OverlayKind::RGB,
+                    name: "Genes".to_string(),
+                    legend: Some(OverlayLegend::Categories(vec![
+                        (
+                            "exon".to_string(),
+                            rgb::RGBA::new(1.0, 0.5, 0.0, 1.0),
+                        ),
+                        (
+                            "gene".to_string(),
+                            rgb::RGBA::new(0.1, 0.2, 0.3, 0.4),
+                        ),
+                    ])),
+                    paths: Vec::new(),
+                },
+                BundleOverlay {
+                    file: "overlays/2.csv".to_string(),
+                    kind: OverlayKind::RGB,
+                    name: "Plain".to_string(),
+                    legend: None,
+                    paths: Vec::new(),
+                },
+            ],
+            annotations: vec![BundleAnnotation {
+                file: "annotations/genes.gff3".to_string(),
+                kind: AnnotationFileType::Gff3,
+            }],
+            label_sets: vec![BundleLabelSet {
+                file: "labels/0.tsv".to_string(),
+                name: "Gene names".to_string(),
+                annotation: "genes.gff3".to_string(),
+                column: "Name".to_string(),
+                path: "a#1".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn manifest_round_trip() {
+        let manifest = manifest();
+
+        let mut buf = Vec::new();
+        manifest.write(&mut buf).unwrap();
+
+        let read = BundleManifest::read(buf.as_slice()).unwrap();
+        assert_eq!(read, manifest);
+    }
+
+    #[test]
+    fn manifest_errors() {
+        let wrong_version = "gfaestus_bundle\t99\n";
+        assert!(BundleManifest::read(wrong_version.as_bytes()).is_err());
+
+        let no_header = "gfa\t/data/graph.gfa\n";
+        assert!(BundleManifest::read(no_header.as_bytes()).is_err());
+
+        let unknown_overlay =
+            "gfaestus_bundle\t1\noverlay_path\toverlays/5.csv\ta#1\n";
+        assert!(BundleManifest::read(unknown_overlay.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn overlay_round_trip() {
+        let values = vec![0.0, 0.25, std::f32::NAN];
+
+        let mut buf = Vec::new();
+        write_bundle_overlay(&OverlayData::Value(values.clone()), &mut buf)
+            .unwrap();
+
+        match read_bundle_overlay(OverlayKind::Value, 3, buf.as_slice()) {
+            Ok(OverlayData::Value(read)) => {
+                assert_eq!(read[..2], values[..2]);
+                assert!(read[2].is_nan());
+            }
+            _ => panic!("expected a value overlay"),
+        }
+
+        let colors = vec![
+            rgb::RGBA::new(1.0, 0.0, 0.0, 1.0),
+            rgb::RGBA::new(0.2, 0.4, 0.6, 0.8),
+        ];
+
+        let mut buf = Vec::new();
+        write_bundle_overlay(&OverlayData::RGB(colors.clone()), &mut buf)
+            .unwrap();
+
+        // nodes missing from the file are transparent
+        match read_bundle_overlay(OverlayKind::RGB, 3, buf.as_slice()) {
+            Ok(OverlayData::RGB(read)) => {
+                assert_eq!(read[..2], colors[..]);
+                assert_eq!(read[2], rgb::RGBA::new(0.0, 0.0, 0.0, 0.0));
+            }
+            _ => panic!("expected an RGB overlay"),
+        }
+    }
+}
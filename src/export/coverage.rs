@@ -0,0 +1,314 @@
+use handlegraph::{
+    handlegraph::*, packedgraph::PackedGraph, pathhandlegraph::*,
+};
+
+use anyhow::Result;
+use crossbeam::atomic::AtomicCell;
+use rustc_hash::FxHashMap;
+
+use std::{
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use crate::reactor::{Host, Outbox, Reactor};
+
+/// How the path coverage table is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageFormat {
+    /// One row per node and one column per path
+    Matrix,
+    /// One `node, path, count` row for each node traversed by a
+    /// path, leaving out the zeroes
+    Triplets,
+}
+
+impl CoverageFormat {
+    pub const ALL: [CoverageFormat; 2] =
+        [CoverageFormat::Matrix, CoverageFormat::Triplets];
+
+    /// The name used to refer to the format in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoverageFormat::Matrix => "matrix",
+            CoverageFormat::Triplets => "triplets",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+    }
+}
+
+/// Writes the number of times each path traverses each node, in
+/// node ID order, as TSV.
+///
+/// `progress` is called with values between 0.0 and 1.0 as the
+/// nodes are processed.
+pub fn write_path_coverage<W, F>(
+    graph: &PackedGraph,
+    format: CoverageFormat,
+    out: W,
+    progress: F,
+) -> Result<()>
+where
+    W: Write,
+    F: Fn(f32),
+{
+    let mut out = BufWriter::new(out);
+
+    let mut paths = graph.path_ids().collect::<Vec<_>>();
+    paths.sort();
+
+    let path_names = paths
+        .iter()
+        .map(|&path| {
+            graph
+                .get_path_name_vec(path)
+                .map(|name| String::from_utf8_lossy(&name).into_owned())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    let columns: FxHashMap<PathId, usize> = paths
+        .iter()
+        .enumerate()
+        .map(|(ix, &path)| (path, ix))
+        .collect();
+
+    match format {
+        CoverageFormat::Matrix => {
+            write!(out, "node_id")?;
+            for name in path_names.iter() {
+                write!(out, "\t{}", name)?;
+            }
+            writeln!(out)?;
+        }
+        CoverageFormat::Triplets => {
+            writeln!(out, "node_id\tpath\tcount")?;
+        }
+    }
+
+    let mut handles = graph.handles().collect::<Vec<_>>();
+    handles.sort();
+
+    let node_count = handles.len();
+    let report_every = (node_count / 100).max(1);
+
+    let mut counts = vec![0usize; paths.len()];
+
+    for (ix, handle) in handles.into_iter().enumerate() {
+        counts.iter_mut().for_each(|c| *c = 0);
+
+        if let Some(steps) = graph.steps_on_handle(handle) {
+            for (path, _) in steps {
+                if let Some(&col) = columns.get(&path) {
+                    counts[col] += 1;
+                }
+            }
+        }
+
+        let node_id = handle.id().0;
+
+        match format {
+            CoverageFormat::Matrix => {
+                write!(out, "{}", node_id)?;
+                for count in counts.iter() {
+                    write!(out, "\t{}", count)?;
+                }
+                writeln!(out)?;
+            }
+            CoverageFormat::Triplets => {
+                for (col, &count) in counts.iter().enumerate() {
+                    if count > 0 {
+                        writeln!(
+                            out,
+                            "{}\t{}\t{}",
+                            node_id, path_names[col], count
+                        )?;
+                    }
+                }
+            }
+        }
+
+        if (ix + 1) % report_every == 0 {
+            progress((ix + 1) as f32 / node_count as f32);
+        }
+    }
+
+    out.flush()?;
+
+    progress(1.0);
+
+    Ok(())
+}
+
+/// Writes path coverage tables on the reactor thread pool, one at a
+/// time, reporting the progress of the current export through
+/// `progress`
+pub struct CoverageExporter {
+    host: Host<(PathBuf, CoverageFormat), (PathBuf, Result<()>)>,
+    running: Option<PathBuf>,
+
+    progress: Arc<AtomicCell<Option<f32>>>,
+}
+
+impl CoverageExporter {
+    pub fn new(
+        reactor: &mut Reactor,
+        progress: Arc<AtomicCell<Option<f32>>>,
+    ) -> Self {
+        let graph_query = reactor.graph_query.clone();
+        let progress_ = progress.clone();
+
+        let host = reactor.create_host(
+            move |_outbox: &Outbox<(PathBuf, Result<()>)>,
+                  (path, format): (PathBuf, CoverageFormat)| {
+                let graph = graph_query.graph();
+
+                let result = std::fs::File::create(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|file| {
+                        write_path_coverage(graph, format, file, |p| {
+                            progress_.store(Some(p));
+                        })
+                    });
+
+                (path, result)
+            },
+        );
+
+        Self {
+            host,
+            running: None,
+            progress,
+        }
+    }
+
+    /// The file currently being written, if any
+    pub fn running(&self) -> Option<&PathBuf> {
+        self.running.as_ref()
+    }
+
+    /// Starts writing the coverage table to `path`; returns `false`
+    /// if an export is already running
+    pub fn run(
+        &mut self,
+        path: PathBuf,
+        format: CoverageFormat,
+    ) -> Result<bool> {
+        if self.running.is_some() {
+            return Ok(false);
+        }
+
+        self.progress.store(Some(0.0));
+        self.host.call((path.clone(), format))?;
+        self.running = Some(path);
+
+        Ok(true)
+    }
+
+    /// Returns the path and result of the finished export, if the
+    /// running export is done
+    pub fn take_result(&mut self) -> Option<(PathBuf, Result<()>)> {
+        let result = self.host.take()?;
+
+        self.running = None;
+        self.progress.store(None);
+
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use handlegraph::mutablehandlegraph::*;
+
+    /// Three nodes in a line, with path `a` visiting node 1 twice,
+    /// and path `b` covering nodes 2 and 3
+    fn graph() -> PackedGraph {
+        let mut graph = PackedGraph::default();
+
+        let handles = [&b"ACGT"[..], b"GG", b"TTT"]
+            .iter()
+            .enumerate()
+            .map(|(ix, seq)| graph.create_handle(seq, ix as u64 + 1))
+            .collect::<Vec<_>>();
+
+        for pair in handles.windows(2) {
+            graph.create_edge(Edge(pair[0], pair[1]));
+        }
+        graph.create_edge(Edge(handles[1], handles[0]));
+
+        let a = graph.create_path(b"a", false).unwrap();
+        for &ix in [0, 1, 0].iter() {
+            graph.path_append_step(a, handles[ix]);
+        }
+
+        let b = graph.create_path(b"b", false).unwrap();
+        for &ix in [1, 2].iter() {
+            graph.path_append_step(b, handles[ix]);
+        }
+
+        graph
+    }
+
+    fn coverage_tsv(format: CoverageFormat) -> (Vec<String>, Vec<f32>) {
+        let progress = std::cell::RefCell::new(Vec::new());
+
+        let mut out = Vec::new();
+        write_path_coverage(&graph(), format, &mut out, |p| {
+            progress.borrow_mut().push(p)
+        })
+        .unwrap();
+
+        let lines = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+
+        (lines, progress.into_inner())
+    }
+
+    #[test]
+    fn matrix_coverage() {
+        let (lines, progress) = coverage_tsv(CoverageFormat::Matrix);
+
+        assert_eq!(lines, &["node_id\ta\tb", "1\t2\t0", "2\t1\t1", "3\t0\t1"]);
+
+        assert_eq!(progress.last(), Some(&1.0));
+        assert!(progress.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn triplet_coverage() {
+        let (lines, _) = coverage_tsv(CoverageFormat::Triplets);
+
+        assert_eq!(
+            lines,
+            &[
+                "node_id\tpath\tcount",
+                "1\ta\t2",
+                "2\ta\t1",
+                "2\tb\t1",
+                "3\tb\t1"
+            ]
+        );
+    }
+
+    #[test]
+    fn format_names() {
+        for &format in CoverageFormat::ALL.iter() {
+            assert_eq!(CoverageFormat::from_name(format.name()), Some(format));
+        }
+
+        assert_eq!(CoverageFormat::from_name("csv"), None);
+    }
+}
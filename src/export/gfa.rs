@@ -0,0 +1,218 @@
+use handlegraph::{
+    handle::{Edge, Handle, NodeId},
+    handlegraph::*,
+    packedgraph::PackedGraph,
+    pathhandlegraph::*,
+};
+
+use anyhow::Result;
+use rustc_hash::FxHashSet;
+
+use std::io::{BufWriter, Write};
+
+use crate::gfa::header::GfaHeader;
+
+fn orient(handle: Handle) -> char {
+    if handle.is_reverse() {
+        '-'
+    } else {
+        '+'
+    }
+}
+
+/// Writes the subgraph made up of `nodes` and the edges between
+/// them as GFA 1, starting with `header`.
+///
+/// Each path is split into the runs of consecutive steps on `nodes`,
+/// which are written as separate paths named `<path>:<start>-<end>`,
+/// after the base range of the run on the original path.
+pub fn write_subgraph_gfa<W: Write>(
+    graph: &PackedGraph,
+    header: &GfaHeader,
+    nodes: &FxHashSet<NodeId>,
+    out: W,
+) -> Result<()> {
+    let mut out = BufWriter::new(out);
+
+    writeln!(out, "{}", header.to_gfa_line())?;
+
+    let mut node_ids = nodes.iter().copied().collect::<Vec<_>>();
+    node_ids.sort();
+
+    for &id in node_ids.iter() {
+        let seq = graph.sequence_vec(Handle::pack(id, false));
+        writeln!(out, "S\t{}\t{}", id.0, String::from_utf8_lossy(&seq))?;
+    }
+
+    for Edge(from, to) in graph.edges() {
+        if nodes.contains(&from.id()) && nodes.contains(&to.id()) {
+            writeln!(
+                out,
+                "L\t{}\t{}\t{}\t{}\t0M",
+                from.id().0,
+                orient(from),
+                to.id().0,
+                orient(to)
+            )?;
+        }
+    }
+
+    let mut paths = graph.path_ids().collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let name = graph
+            .get_path_name_vec(path)
+            .map(|name| String::from_utf8_lossy(&name).into_owned())
+            .unwrap_or_default();
+
+        let steps = if let Some(steps) = graph.path_steps(path) {
+            steps
+        } else {
+            continue;
+        };
+
+        let mut write_run =
+            |run: &mut Vec<Handle>, start: usize, end: usize| -> Result<()> {
+                if run.is_empty() {
+                    return Ok(());
+                }
+
+                let segments = run
+                    .iter()
+                    .map(|&h| format!("{}{}", h.id().0, orient(h)))
+                    .collect::<Vec<_>>();
+
+                writeln!(
+                    out,
+                    "P\t{}:{}-{}\t{}\t*",
+                    name,
+                    start,
+                    end,
+                    segments.join(",")
+                )?;
+
+                run.clear();
+                Ok(())
+            };
+
+        let mut run: Vec<Handle> = Vec::new();
+        let mut run_start = 0;
+        let mut pos = 0;
+
+        for step in steps {
+            let handle = step.handle();
+
+            if nodes.contains(&handle.id()) {
+                if run.is_empty() {
+                    run_start = pos;
+                }
+                run.push(handle);
+            } else {
+                write_run(&mut run, run_start, pos)?;
+            }
+
+            pos += graph.node_len(handle);
+        }
+
+        write_run(&mut run, run_start, pos)?;
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use handlegraph::mutablehandlegraph::*;
+
+    /// Four nodes in a line, of lengths 4, 2, 3, and 1, with one path
+    /// over all of them
+    fn graph() -> PackedGraph {
+        let mut graph = PackedGraph::default();
+
+        let handles = [&b"ACGT"[..], b"GG", b"TTT", b"A"]
+            .iter()
+            .enumerate()
+            .map(|(ix, seq)| graph.create_handle(seq, ix as u64 + 1))
+            .collect::<Vec<_>>();
+
+        for pair in handles.windows(2) {
+            graph.create_edge(Edge(pair[0], pair[1]));
+        }
+
+        let path = graph.create_path(b"ref", false).unwrap();
+
+        for &handle in handles.iter() {
+            graph.path_append_step(path, handle);
+        }
+
+        graph
+    }
+
+    fn subgraph_gfa(nodes: &[u64]) -> Vec<String> {
+        let nodes = nodes.iter().map(|&id| NodeId::from(id)).collect();
+
+        let mut out = Vec::new();
+        write_subgraph_gfa(&graph(), &GfaHeader::default(), &nodes, &mut out)
+            .unwrap();
+
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn path_runs_are_split() {
+        let lines = subgraph_gfa(&[1, 2, 4]);
+
+        assert_eq!(lines[0], "H\tVN:Z:1.0");
+        assert_eq!(&lines[1..4], &["S\t1\tACGT", "S\t2\tGG", "S\t4\tA"]);
+
+        // only the edge between nodes 1 and 2 is in the subgraph
+        let links = lines
+            .iter()
+            .filter(|l| l.starts_with('L'))
+            .collect::<Vec<_>>();
+        assert_eq!(links.len(), 1);
+        assert!(
+            links[0] == "L\t1\t+\t2\t+\t0M" || links[0] == "L\t2\t-\t1\t-\t0M"
+        );
+
+        let paths = lines
+            .iter()
+            .filter(|l| l.starts_with('P'))
+            .collect::<Vec<_>>();
+        assert_eq!(paths, &["P\tref:0-6\t1+,2+\t*", "P\tref:9-10\t4+\t*"]);
+    }
+
+    #[test]
+    fn whole_path_is_one_run() {
+        let lines = subgraph_gfa(&[1, 2, 3, 4]);
+
+        let paths = lines
+            .iter()
+            .filter(|l| l.starts_with('P'))
+            .collect::<Vec<_>>();
+        assert_eq!(paths, &["P\tref:0-10\t1+,2+,3+,4+\t*"]);
+    }
+
+    #[test]
+    fn run_in_the_middle() {
+        let lines = subgraph_gfa(&[3]);
+
+        assert_eq!(lines[1], "S\t3\tTTT");
+        assert!(!lines.iter().any(|l| l.starts_with('L')));
+
+        let paths = lines
+            .iter()
+            .filter(|l| l.starts_with('P'))
+            .collect::<Vec<_>>();
+        assert_eq!(paths, &["P\tref:6-9\t3+\t*"]);
+    }
+}
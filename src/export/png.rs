@@ -0,0 +1,37 @@
+use anyhow::Result;
+
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Writes 8-bit RGBA pixels, row by row from the top left, to a PNG
+/// file
+pub fn write_png<P: AsRef<Path>>(
+    path: P,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) -> Result<()> {
+    let expected = (width * height * 4) as usize;
+
+    if rgba.len() != expected {
+        anyhow::bail!(
+            "Expected {} bytes of pixel data for a {}x{} image, got {}",
+            expected,
+            width,
+            height,
+            rgba.len()
+        );
+    }
+
+    let file = std::fs::File::create(path)?;
+    let out = BufWriter::new(file);
+
+    let mut encoder = png::Encoder::new(out, width, height);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgba)?;
+
+    Ok(())
+}
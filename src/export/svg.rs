@@ -0,0 +1,485 @@
+#[allow(unused_imports)]
+use handlegraph::{
+    handle::{Direction, Edge, Handle, NodeId},
+    handlegraph::*,
+    packedgraph::PackedGraph,
+};
+
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::app::LegendAnchor;
+use crate::geometry::Point;
+use crate::overlays::{format_legend_value, OverlayLegend};
+use crate::universe::{FlatLayout, GraphLayout, Node};
+use crate::view::{ScreenDims, View};
+
+/// A label to be drawn in an exported SVG, anchored at a point in
+/// world space and offset in screen space
+#[derive(Debug, Clone)]
+pub struct SvgLabel {
+    pub world: Point,
+    pub screen_offset: Point,
+    /// The direction from the text to its anchor point, as used when
+    /// drawing labels with `gui::text::draw_text_at_node_anchor`
+    pub anchor_dir: Point,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SvgEdgeStyle {
+    pub color: rgb::RGB<f32>,
+    pub width: f32,
+}
+
+/// The overlay legend to draw in a corner of an exported SVG,
+/// matching the legend window
+#[derive(Debug, Clone)]
+pub struct SvgLegend {
+    pub anchor: LegendAnchor,
+    /// The name and legend of each shown overlay
+    pub entries: Vec<(String, OverlayLegend)>,
+    /// Colors sampled evenly along the active gradient, used for
+    /// range legends
+    pub gradient: Vec<rgb::RGB<f32>>,
+}
+
+impl SvgLegend {
+    const MARGIN: f32 = 10.0;
+    const PADDING: f32 = 8.0;
+    const WIDTH: f32 = 220.0;
+    const LINE_HEIGHT: f32 = 16.0;
+    const BAR_HEIGHT: f32 = 15.0;
+    const SWATCH_SIZE: f32 = 12.0;
+
+    /// Only this many categories are listed per overlay, as in the
+    /// legend window
+    const MAX_CATEGORIES: usize = 24;
+
+    fn entry_height(legend: &OverlayLegend) -> f32 {
+        let lines = match legend {
+            OverlayLegend::Range { units, .. } => {
+                if units.is_empty() {
+                    1
+                } else {
+                    2
+                }
+            }
+            OverlayLegend::Categories(categories) => {
+                let shown = categories.len().min(Self::MAX_CATEGORIES);
+                if categories.len() > shown {
+                    shown + 1
+                } else {
+                    shown
+                }
+            }
+        };
+
+        let bar = match legend {
+            OverlayLegend::Range { .. } => Self::BAR_HEIGHT + 4.0,
+            OverlayLegend::Categories(_) => 0.0,
+        };
+
+        // the title line, then the entry itself
+        Self::LINE_HEIGHT * (1 + lines) as f32 + bar + Self::PADDING
+    }
+
+    fn write<W: Write>(&self, out: &mut W, dims: ScreenDims) -> Result<()> {
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+
+        let height = self
+            .entries
+            .iter()
+            .map(|(_, legend)| Self::entry_height(legend))
+            .sum::<f32>()
+            + Self::PADDING;
+
+        let x0 = if self.anchor.is_left() {
+            Self::MARGIN
+        } else {
+            dims.width - Self::MARGIN - Self::WIDTH
+        };
+
+        let y0 = if self.anchor.is_top() {
+            Self::MARGIN
+        } else {
+            dims.height - Self::MARGIN - height
+        };
+
+        writeln!(
+            out,
+            r#"<g id="legend" font-family="sans-serif" font-size="12">"#
+        )?;
+
+        if !self.gradient.is_empty() {
+            writeln!(out, r#"<defs><linearGradient id="legend_gradient">"#)?;
+
+            let last = (self.gradient.len() - 1).max(1) as f32;
+
+            for (ix, &color) in self.gradient.iter().enumerate() {
+                writeln!(
+                    out,
+                    r#"<stop offset="{:.3}" stop-color="{}"/>"#,
+                    ix as f32 / last,
+                    hex_color(color)
+                )?;
+            }
+
+            writeln!(out, "</linearGradient></defs>")?;
+        }
+
+        writeln!(
+            out,
+            r##"<rect x="{:.2}" y="{:.2}" width="{}" height="{:.2}" fill="#ffffff" fill-opacity="0.85" stroke="#808080"/>"##,
+            x0,
+            y0,
+            Self::WIDTH,
+            height
+        )?;
+
+        let x = x0 + Self::PADDING;
+        let inner_width = Self::WIDTH - 2.0 * Self::PADDING;
+
+        let mut y = y0 + Self::PADDING;
+
+        let text = |out: &mut W, x: f32, y: f32, anchor: &str, text: &str| {
+            writeln!(
+                out,
+                r#"<text x="{:.2}" y="{:.2}" text-anchor="{}" dominant-baseline="hanging">{}</text>"#,
+                x,
+                y,
+                anchor,
+                escape_xml(text)
+            )
+        };
+
+        for (name, legend) in self.entries.iter() {
+            writeln!(
+                out,
+                r#"<text x="{:.2}" y="{:.2}" font-weight="bold" dominant-baseline="hanging">{}</text>"#,
+                x,
+                y,
+                escape_xml(name)
+            )?;
+            y += Self::LINE_HEIGHT;
+
+            match legend {
+                OverlayLegend::Range { min, max, units } => {
+                    writeln!(
+                        out,
+                        r#"<rect x="{:.2}" y="{:.2}" width="{}" height="{}" fill="url(#legend_gradient)"/>"#,
+                        x,
+                        y,
+                        inner_width,
+                        Self::BAR_HEIGHT
+                    )?;
+                    y += Self::BAR_HEIGHT + 4.0;
+
+                    text(out, x, y, "start", &format_legend_value(*min))?;
+                    text(
+                        out,
+                        x + inner_width,
+                        y,
+                        "end",
+                        &format_legend_value(*max),
+                    )?;
+                    y += Self::LINE_HEIGHT;
+
+                    if !units.is_empty() {
+                        text(out, x, y, "start", units)?;
+                        y += Self::LINE_HEIGHT;
+                    }
+                }
+                OverlayLegend::Categories(categories) => {
+                    for (label, color) in
+                        categories.iter().take(Self::MAX_CATEGORIES)
+                    {
+                        writeln!(
+                            out,
+                            r#"<rect x="{:.2}" y="{:.2}" width="{s}" height="{s}" fill="{}"/>"#,
+                            x,
+                            y,
+                            hex_color(color.rgb()),
+                            s = Self::SWATCH_SIZE
+                        )?;
+
+                        text(
+                            out,
+                            x + Self::SWATCH_SIZE + 6.0,
+                            y,
+                            "start",
+                            label,
+                        )?;
+                        y += Self::LINE_HEIGHT;
+                    }
+
+                    if categories.len() > Self::MAX_CATEGORIES {
+                        let more = format!(
+                            "... and {} more",
+                            categories.len() - Self::MAX_CATEGORIES
+                        );
+                        text(out, x, y, "start", &more)?;
+                        y += Self::LINE_HEIGHT;
+                    }
+                }
+            }
+
+            y += Self::PADDING;
+        }
+
+        writeln!(out, "</g>")?;
+
+        Ok(())
+    }
+}
+
+/// Writes the part of the graph visible in `view` as an SVG image,
+/// using the same screen space transformation as the main view
+pub struct SvgExport<'a> {
+    pub view: View,
+    pub dims: ScreenDims,
+    pub background: rgb::RGB<f32>,
+
+    /// The node width in pixels, as used by the node renderer
+    pub node_width: f32,
+
+    pub layout: &'a FlatLayout,
+    pub edges: Option<(&'a PackedGraph, SvgEdgeStyle)>,
+
+    pub legend: Option<SvgLegend>,
+}
+
+impl<'a> SvgExport<'a> {
+    /// Nodes and edges farther than this many pixels outside the
+    /// screen are not included in the output
+    const MARGIN: f32 = 50.0;
+
+    fn to_screen(&self, world: Point) -> Point {
+        let half_dims = Point::new(self.dims.width, self.dims.height) * 0.5;
+        let offset = world - self.view.center;
+        let scale = self.view.scale_xy();
+        Point::new(offset.x / scale.x, offset.y / scale.y) + half_dims
+    }
+
+    fn is_visible(&self, p: Point) -> bool {
+        let m = Self::MARGIN;
+        p.x >= -m
+            && p.y >= -m
+            && p.x <= self.dims.width + m
+            && p.y <= self.dims.height + m
+    }
+
+    fn node_endpoint(nodes: &[Node], vertex_ix: usize) -> Point {
+        let node = nodes[vertex_ix / 2];
+        if vertex_ix % 2 == 0 {
+            node.p0
+        } else {
+            node.p1
+        }
+    }
+
+    pub fn write<W, F>(
+        &self,
+        mut out: W,
+        node_color: F,
+        labels: &[SvgLabel],
+    ) -> Result<()>
+    where
+        W: Write,
+        F: Fn(NodeId) -> rgb::RGB<f32>,
+    {
+        let width = self.dims.width;
+        let height = self.dims.height;
+
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height
+        )?;
+
+        writeln!(
+            out,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            hex_color(self.background)
+        )?;
+
+        let nodes = self.layout.nodes();
+
+        if let Some((graph, style)) = self.edges {
+            writeln!(
+                out,
+                r#"<g id="edges" fill="none" stroke="{}" stroke-width="{}">"#,
+                hex_color(style.color),
+                style.width
+            )?;
+
+            for Edge(left, right) in graph.edges() {
+                if self.layout.node_component(left.id())
+                    != self.layout.node_component(right.id())
+                {
+                    continue;
+                }
+
+                // same endpoint selection as the edge renderer
+                let left_l = ((left.id().0 - 1) * 2) as usize;
+                let left_r = left_l + 1;
+
+                let right_l = ((right.id().0 - 1) * 2) as usize;
+                let right_r = right_l + 1;
+
+                let (left_ix, right_ix) =
+                    match (left.is_reverse(), right.is_reverse()) {
+                        (false, false) => (left_r, right_l),
+                        (true, false) => (left_l, right_l),
+                        (false, true) => (left_r, right_r),
+                        (true, true) => (left_l, right_r),
+                    };
+
+                let p0 = self.to_screen(Self::node_endpoint(nodes, left_ix));
+                let p1 = self.to_screen(Self::node_endpoint(nodes, right_ix));
+
+                if !self.is_visible(p0) && !self.is_visible(p1) {
+                    continue;
+                }
+
+                // the tessellation shader offsets the curve by
+                // 0.8 * u * (1 - u) times the rotated edge vector,
+                // which is a quadratic bezier with this control point
+                let diff = p1 - p0;
+                let mid = p0 + diff * 0.5;
+                let ctrl = mid + Point::new(-diff.y, diff.x) * 0.4;
+
+                writeln!(
+                    out,
+                    r#"<path d="M {:.2} {:.2} Q {:.2} {:.2} {:.2} {:.2}"/>"#,
+                    p1.x, p1.y, ctrl.x, ctrl.y, p0.x, p0.y
+                )?;
+            }
+
+            writeln!(out, "</g>")?;
+        }
+
+        writeln!(
+            out,
+            r#"<g id="nodes" stroke-width="{}" stroke-linecap="round">"#,
+            self.node_width
+        )?;
+
+        for (ix, node) in nodes.iter().enumerate() {
+            let p0 = self.to_screen(node.p0);
+            let p1 = self.to_screen(node.p1);
+
+            if !self.is_visible(p0) && !self.is_visible(p1) {
+                continue;
+            }
+
+            let node_id = NodeId::from((ix + 1) as u64);
+            let color = node_color(node_id);
+
+            writeln!(
+                out,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{}"/>"#,
+                p0.x,
+                p0.y,
+                p1.x,
+                p1.y,
+                hex_color(color)
+            )?;
+        }
+
+        writeln!(out, "</g>")?;
+
+        writeln!(
+            out,
+            r#"<g id="labels" font-family="sans-serif" font-size="14">"#
+        )?;
+
+        for label in labels {
+            let pos = self.to_screen(label.world) + label.screen_offset;
+
+            if !self.is_visible(pos) {
+                continue;
+            }
+
+            let (anchor, baseline) = text_alignment(label.anchor_dir);
+
+            writeln!(
+                out,
+                r#"<text x="{:.2}" y="{:.2}" text-anchor="{}" dominant-baseline="{}">{}</text>"#,
+                pos.x,
+                pos.y,
+                anchor,
+                baseline,
+                escape_xml(&label.text)
+            )?;
+        }
+
+        writeln!(out, "</g>")?;
+
+        if let Some(legend) = &self.legend {
+            legend.write(&mut out, self.dims)?;
+        }
+
+        writeln!(out, "</svg>")?;
+
+        Ok(())
+    }
+}
+
+/// Matches the alignment used by `gui::text::offset_align`
+fn text_alignment(dir: Point) -> (&'static str, &'static str) {
+    let len = dir.length();
+
+    if len == 0.0 {
+        return ("middle", "middle");
+    }
+
+    let norm = dir / len;
+
+    let anchor = if norm.x > 0.67 {
+        "end"
+    } else if norm.x < -0.67 {
+        "start"
+    } else {
+        "middle"
+    };
+
+    let baseline = if norm.y > 0.67 {
+        "text-after-edge"
+    } else if norm.y < -0.67 {
+        "hanging"
+    } else {
+        "middle"
+    };
+
+    (anchor, baseline)
+}
+
+fn hex_color(color: rgb::RGB<f32>) -> String {
+    let to_u8 = |v: f32| (v.max(0.0).min(1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        to_u8(color.r),
+        to_u8(color.g),
+        to_u8(color.b)
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
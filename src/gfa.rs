@@ -1,3 +1,4 @@
+pub mod header;
 pub mod load;
 
 #[allow(unused_imports)]
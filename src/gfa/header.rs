@@ -0,0 +1,197 @@
+use bstr::ByteSlice;
+
+/// The GFA version written when a header has no `VN` tag
+pub const DEFAULT_GFA_VERSION: &str = "1.0";
+
+/// An optional field of a GFA header line, e.g. `VN:Z:1.0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderTag {
+    pub tag: String,
+    pub kind: char,
+    pub value: String,
+}
+
+impl HeaderTag {
+    pub fn new(tag: &str, kind: char, value: &str) -> Self {
+        Self {
+            tag: tag.to_string(),
+            kind,
+            value: value.to_string(),
+        }
+    }
+
+    fn parse(field: &[u8]) -> Option<Self> {
+        let mut parts = field.splitn(3, |&b| b == b':');
+
+        let tag = parts.next()?.to_str().ok()?;
+        let kind = parts.next()?.to_str().ok()?;
+        let value = parts.next()?.to_str().ok()?;
+
+        let mut kind_chars = kind.chars();
+        let kind = kind_chars.next()?;
+
+        if tag.len() != 2 || kind_chars.next().is_some() {
+            return None;
+        }
+
+        Some(Self::new(tag, kind, value))
+    }
+}
+
+impl std::fmt::Display for HeaderTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.tag, self.kind, self.value)
+    }
+}
+
+/// The tags of the header (`H`) lines of a GFA file, in the order
+/// they appeared; tags repeated across header lines are merged, with
+/// the last one kept
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GfaHeader {
+    tags: Vec<HeaderTag>,
+}
+
+impl GfaHeader {
+    /// Parses the header lines at the start of a GFA file, stopping
+    /// at the first line that isn't a header or a comment, so that
+    /// the rest of the file isn't scanned
+    pub fn from_gfa_bytes(bytes: &[u8]) -> Self {
+        let mut header = Self::default();
+
+        for line in bytes.lines() {
+            if line.is_empty() || line.starts_with(b"#") {
+                continue;
+            }
+
+            if !line.starts_with(b"H") {
+                break;
+            }
+
+            for field in line.split_str("\t").skip(1) {
+                match HeaderTag::parse(field) {
+                    Some(tag) => header.set_tag(tag),
+                    None => {
+                        log::warn!(
+                            "Skipping malformed GFA header field {}",
+                            field.as_bstr()
+                        );
+                    }
+                }
+            }
+        }
+
+        header
+    }
+
+    pub fn tags(&self) -> &[HeaderTag] {
+        &self.tags
+    }
+
+    pub fn get(&self, tag: &str) -> Option<&HeaderTag> {
+        self.tags.iter().find(|t| t.tag == tag)
+    }
+
+    /// The GFA version, from the `VN` tag
+    pub fn version(&self) -> Option<&str> {
+        self.get("VN").map(|t| t.value.as_str())
+    }
+
+    /// The program that produced the file, from the `PG` tag
+    pub fn program(&self) -> Option<&str> {
+        self.get("PG").map(|t| t.value.as_str())
+    }
+
+    /// Adds `tag`, replacing any existing tag with the same name
+    pub fn set_tag(&mut self, tag: HeaderTag) {
+        if let Some(existing) = self.tags.iter_mut().find(|t| t.tag == tag.tag)
+        {
+            *existing = tag;
+        } else {
+            self.tags.push(tag);
+        }
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) {
+        self.tags.retain(|t| t.tag != tag);
+    }
+
+    /// The header as a single GFA line, without the trailing newline;
+    /// a `VN` tag is added if there is none, so that files written
+    /// with it are well-formed
+    pub fn to_gfa_line(&self) -> String {
+        let mut line = String::from("H");
+
+        if self.version().is_none() {
+            line.push_str("\tVN:Z:");
+            line.push_str(DEFAULT_GFA_VERSION);
+        }
+
+        for tag in self.tags.iter() {
+            line.push('\t');
+            line.push_str(&tag.to_string());
+        }
+
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_tags_are_merged() {
+        let gfa = b"H\tVN:Z:1.0\tPG:Z:first\n\
+                    # a comment\n\
+                    H\tPG:Z:second\tXX:i:3\n\
+                    S\t1\tACGT\n\
+                    H\tZZ:Z:after_segments\n";
+
+        let header = GfaHeader::from_gfa_bytes(gfa);
+
+        assert_eq!(
+            header.tags(),
+            &[
+                HeaderTag::new("VN", 'Z', "1.0"),
+                HeaderTag::new("PG", 'Z', "second"),
+                HeaderTag::new("XX", 'i', "3"),
+            ]
+        );
+        assert_eq!(header.version(), Some("1.0"));
+        assert_eq!(header.program(), Some("second"));
+    }
+
+    #[test]
+    fn malformed_fields() {
+        assert_eq!(HeaderTag::parse(b"VN"), None);
+        assert_eq!(HeaderTag::parse(b"VN:Z"), None);
+        assert_eq!(HeaderTag::parse(b"VNN:Z:1.0"), None);
+        assert_eq!(HeaderTag::parse(b"VN:ZZ:1.0"), None);
+        assert_eq!(HeaderTag::parse(b"VN::1.0"), None);
+
+        assert_eq!(
+            HeaderTag::parse(b"PG:Z:a:b"),
+            Some(HeaderTag::new("PG", 'Z', "a:b"))
+        );
+
+        // malformed fields are skipped, the rest of the line is kept
+        let header = GfaHeader::from_gfa_bytes(b"H\tbad\tVN:Z:1.1\tX:Z:y\n");
+        assert_eq!(header.tags(), &[HeaderTag::new("VN", 'Z', "1.1")]);
+    }
+
+    #[test]
+    fn gfa_line_adds_default_version() {
+        assert_eq!(GfaHeader::default().to_gfa_line(), "H\tVN:Z:1.0");
+
+        let mut header = GfaHeader::default();
+        header.set_tag(HeaderTag::new("PG", 'Z', "gfaestus"));
+        assert_eq!(header.to_gfa_line(), "H\tVN:Z:1.0\tPG:Z:gfaestus");
+
+        header.set_tag(HeaderTag::new("VN", 'Z', "2.0"));
+        assert_eq!(header.to_gfa_line(), "H\tPG:Z:gfaestus\tVN:Z:2.0");
+
+        header.remove_tag("VN");
+        assert_eq!(header.to_gfa_line(), "H\tVN:Z:1.0\tPG:Z:gfaestus");
+    }
+}
@@ -18,11 +18,16 @@ use crossbeam::{
     channel::{self, Receiver},
 };
 
+use parking_lot::RwLock;
+use rustc_hash::FxHashSet;
+
 use std::sync::Arc;
 
 use anyhow::Result;
 
 use crate::asynchronous::AsyncResult;
+use crate::gfa::header::GfaHeader;
+use crate::overlays::OverlayData;
 
 pub struct GraphQueryWorker {
     graph_query: Arc<GraphQuery>,
@@ -53,20 +58,155 @@ impl GraphQueryWorker {
     pub fn graph(&self) -> &GraphQuery {
         &self.graph_query
     }
+
+    pub fn graph_query_arc(&self) -> &Arc<GraphQuery> {
+        &self.graph_query
+    }
+
+    /// Runs `GraphQuery::build_overlay_colors_with_progress` on the
+    /// worker thread pool; the overlay data, or `None` if the build
+    /// was cancelled, is sent on the task's channel when it's done
+    pub fn build_overlay_colors<F>(&self, f: F) -> OverlayColorsTask
+    where
+        F: FnMut(&PackedGraph, Handle) -> rgb::RGB<f32> + Send + 'static,
+    {
+        self.build_overlay(f, |colors| {
+            let colors = colors
+                .into_iter()
+                .map(|c| rgb::RGBA::new(c.r, c.g, c.b, 1.0))
+                .collect();
+
+            Ok(OverlayData::RGB(colors))
+        })
+    }
+
+    /// Like `build_overlay_colors`, but `f` can return any value for
+    /// each node, such as the result of an overlay script, and
+    /// `finish` turns the values into the overlay data once all nodes
+    /// have been processed
+    pub fn build_overlay<T, F, G>(&self, f: F, finish: G) -> OverlayColorsTask
+    where
+        T: Send + 'static,
+        F: FnMut(&PackedGraph, Handle) -> T + Send + 'static,
+        G: FnOnce(Vec<T>) -> Result<OverlayData> + Send + 'static,
+    {
+        self.spawn_overlay_build(move |graph_query, progress| {
+            graph_query
+                .build_overlay_colors_with_progress(f, progress)
+                .map(finish)
+        })
+    }
+
+    /// Runs `build` on the worker thread pool, with the progress and
+    /// cancellation of the returned task; `build` returns `None` if
+    /// it was cancelled
+    pub fn spawn_overlay_build<F>(&self, build: F) -> OverlayColorsTask
+    where
+        F: FnOnce(
+                &GraphQuery,
+                &OverlayBuildProgress,
+            ) -> Option<Result<OverlayData>>
+            + Send
+            + 'static,
+    {
+        let progress = Arc::new(OverlayBuildProgress::default());
+        let (result_tx, result_rx) = channel::bounded(1);
+
+        let graph_query = self.graph_query.clone();
+        let progress_ = progress.clone();
+
+        self.thread_pool.spawn_ok(async move {
+            let data = build(&graph_query, &progress_);
+            // the task may have been dropped in the meantime
+            let _ = result_tx.send(data);
+        });
+
+        OverlayColorsTask {
+            progress,
+            result_rx,
+        }
+    }
+}
+
+/// The progress of an overlay color build, shared between the worker
+/// running it and whoever started it
+#[derive(Debug, Default)]
+pub struct OverlayBuildProgress {
+    progress: AtomicCell<f32>,
+    cancelled: AtomicCell<bool>,
+}
+
+impl OverlayBuildProgress {
+    /// The fraction of the nodes that have been processed
+    pub fn progress(&self) -> f32 {
+        self.progress.load()
+    }
+
+    /// Stops the build at the next progress report
+    pub fn cancel(&self) {
+        self.cancelled.store(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load()
+    }
+}
+
+/// An overlay color build running on a `GraphQueryWorker`
+pub struct OverlayColorsTask {
+    progress: Arc<OverlayBuildProgress>,
+    result_rx: Receiver<Option<Result<OverlayData>>>,
+}
+
+impl OverlayColorsTask {
+    pub fn progress(&self) -> f32 {
+        self.progress.progress()
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancel()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.progress.is_cancelled()
+    }
+
+    /// Returns the result of the build if it's done; the inner
+    /// `None` means it was cancelled
+    pub fn take_result(&self) -> Option<Option<Result<OverlayData>>> {
+        self.result_rx.try_recv().ok()
+    }
 }
 
 pub struct GraphQuery {
     pub graph: Arc<PackedGraph>,
     pub path_positions: Arc<PathPositionMap>,
     query_thread: QueryThread,
+
+    /// The header tags of the loaded GFA, which can be edited and are
+    /// written with subgraphs exported as GFA
+    header: Arc<RwLock<GfaHeader>>,
 }
 
 impl GraphQuery {
     pub fn load_gfa(gfa_path: &str) -> Result<Self> {
         let mut mmap = gfa::mmap::MmapGFA::new(gfa_path)?;
+        let header = GfaHeader::from_gfa_bytes(mmap.get_ref());
         let graph = crate::gfa::load::packed_graph_from_mmap(&mut mmap)?;
         let path_positions = PathPositionMap::index_paths(&graph);
-        Ok(Self::new(graph, path_positions))
+
+        let graph_query = Self::new(graph, path_positions);
+        *graph_query.header.write() = header;
+
+        Ok(graph_query)
+    }
+
+    pub fn header(&self) -> GfaHeader {
+        self.header.read().clone()
+    }
+
+    pub fn header_arc(&self) -> &Arc<RwLock<GfaHeader>> {
+        &self.header
     }
 
     pub fn node_count(&self) -> usize {
@@ -77,6 +217,31 @@ impl GraphQuery {
         self.graph.edge_count()
     }
 
+    /// A quick fingerprint of the node IDs and sequence lengths, and
+    /// the edge count, used to check that node IDs saved with an
+    /// earlier version of the graph still refer to the same nodes
+    pub fn node_checksum(&self) -> u64 {
+        use std::hash::Hasher;
+
+        let mut hasher = rustc_hash::FxHasher::default();
+
+        hasher.write_usize(self.node_count());
+        hasher.write_usize(self.edge_count());
+
+        for node_id in 1..=(self.node_count() as u64) {
+            let handle = Handle::pack(NodeId::from(node_id), false);
+            hasher.write_usize(self.graph.node_len(handle));
+        }
+
+        hasher.finish()
+    }
+
+    /// Whether `node_id` is a node in the graph; node IDs are compact,
+    /// so they run from 1 up to the node count
+    pub fn has_node(&self, node_id: NodeId) -> bool {
+        node_in_graph(&self.graph, node_id)
+    }
+
     pub fn new(graph: PackedGraph, path_positions: PathPositionMap) -> Self {
         let graph = Arc::new(graph);
         let path_positions = Arc::new(path_positions);
@@ -85,6 +250,8 @@ impl GraphQuery {
             graph,
             path_positions,
             query_thread,
+
+            header: Arc::new(RwLock::new(GfaHeader::default())),
         }
     }
 
@@ -128,6 +295,237 @@ impl GraphQuery {
         result
     }
 
+    /// Like `build_overlay_colors`, but reports the fraction of the
+    /// nodes processed through `progress`, and returns `None` if the
+    /// build is cancelled before it's done.
+    ///
+    /// Meant for large graphs, where the pass over all nodes can take
+    /// a while; see `GraphQueryWorker::build_overlay_colors`.
+    pub fn build_overlay_colors_with_progress<T, F>(
+        &self,
+        mut f: F,
+        progress: &OverlayBuildProgress,
+    ) -> Option<Vec<T>>
+    where
+        F: FnMut(&PackedGraph, Handle) -> T,
+    {
+        let mut result = Vec::with_capacity(self.graph.node_count());
+
+        let mut handles = self.graph.handles().collect::<Vec<_>>();
+        handles.sort();
+
+        let node_count = handles.len();
+        let report_every = (node_count / 100).max(1);
+
+        progress.progress.store(0.0);
+
+        for (ix, handle) in handles.into_iter().enumerate() {
+            let color = f(&self.graph, handle);
+            result.push(color);
+
+            if (ix + 1) % report_every == 0 {
+                if progress.is_cancelled() {
+                    return None;
+                }
+
+                progress.progress.store((ix + 1) as f32 / node_count as f32);
+            }
+        }
+
+        progress.progress.store(1.0);
+
+        Some(result)
+    }
+
+    /// Like `build_overlay_colors_with_progress`, but processes the
+    /// nodes in chunks in parallel on `rayon_pool`. Each chunk gets
+    /// its own clone of `state`, which `f` can use as scratch space,
+    /// such as the scope of an overlay script.
+    pub fn build_overlay_par_with_progress<T, S, F>(
+        &self,
+        rayon_pool: &rayon::ThreadPool,
+        state: S,
+        f: F,
+        progress: &OverlayBuildProgress,
+    ) -> Option<Vec<T>>
+    where
+        T: Send,
+        S: Clone + Send,
+        F: Fn(&mut S, &PackedGraph, Handle) -> T + Send + Sync,
+    {
+        use rayon::prelude::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut handles = self.graph.handles().collect::<Vec<_>>();
+        handles.sort();
+
+        let node_count = handles.len();
+        let chunk_size = (node_count / 100).max(1);
+
+        let done = AtomicUsize::new(0);
+
+        progress.progress.store(0.0);
+
+        let chunks = rayon_pool.install(|| {
+            handles
+                .par_chunks(chunk_size)
+                .map_with(state, |state, chunk| {
+                    if progress.is_cancelled() {
+                        return None;
+                    }
+
+                    let values = chunk
+                        .iter()
+                        .map(|&handle| f(state, &self.graph, handle))
+                        .collect::<Vec<_>>();
+
+                    let done = done.fetch_add(chunk.len(), Ordering::Relaxed)
+                        + chunk.len();
+                    progress.progress.store(done as f32 / node_count as f32);
+
+                    Some(values)
+                })
+                .collect::<Option<Vec<_>>>()
+        })?;
+
+        progress.progress.store(1.0);
+
+        Some(chunks.into_iter().flatten().collect())
+    }
+
+    /// For each node, in node ID order, the number of the given
+    /// paths that traverse it at least once
+    pub fn path_depths(&self, paths: &[PathId]) -> Vec<usize> {
+        let paths = paths.iter().copied().collect::<FxHashSet<_>>();
+
+        let mut handles = self.graph.handles().collect::<Vec<_>>();
+        handles.sort();
+
+        let mut on_node: Vec<PathId> = Vec::new();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                on_node.clear();
+
+                if let Some(steps) = self.graph.steps_on_handle(handle) {
+                    on_node.extend(
+                        steps
+                            .map(|(path, _)| path)
+                            .filter(|path| paths.contains(path)),
+                    );
+                }
+
+                on_node.sort();
+                on_node.dedup();
+                on_node.len()
+            })
+            .collect()
+    }
+
+    /// The values of a path depth overlay: for each node, in node ID
+    /// order, the fraction of the given paths that traverse it
+    pub fn path_depth_fractions(&self, paths: &[PathId]) -> Vec<f32> {
+        let count = paths.len().max(1) as f32;
+
+        self.path_depths(paths)
+            .into_iter()
+            .map(|depth| depth as f32 / count)
+            .collect()
+    }
+
+    /// For each node, in node ID order, its distance from the closest
+    /// of the `sources`, with edges followed in either direction.
+    /// Nodes further than `max_distance` away, or that can't be
+    /// reached, are `None`
+    pub fn node_distances(
+        &self,
+        sources: &[NodeId],
+        metric: DistanceMetric,
+        max_distance: usize,
+    ) -> Vec<Option<usize>> {
+        use std::cmp::Reverse;
+        use std::collections::BinaryHeap;
+
+        let mut dists: Vec<Option<usize>> = vec![None; self.node_count()];
+
+        let ix = |node: NodeId| (node.0 - 1) as usize;
+
+        let is_source = sources.iter().copied().collect::<FxHashSet<_>>();
+
+        let mut queue = BinaryHeap::new();
+
+        for &source in sources.iter().filter(|&&n| self.has_node(n)) {
+            dists[ix(source)] = Some(0);
+            queue.push(Reverse((0, source)));
+        }
+
+        while let Some(Reverse((dist, node))) = queue.pop() {
+            if dists[ix(node)].map(|d| dist > d).unwrap_or(false) {
+                continue;
+            }
+
+            let handle = Handle::pack(node, false);
+
+            // base pair distances count the nodes passed through, so
+            // the neighbors of a source are at distance 0
+            let next = match metric {
+                DistanceMetric::Hops => dist + 1,
+                DistanceMetric::BasePairs if is_source.contains(&node) => 0,
+                DistanceMetric::BasePairs => dist + self.graph.node_len(handle),
+            };
+
+            if next > max_distance {
+                continue;
+            }
+
+            let neighbors = self
+                .graph
+                .neighbors(handle, Direction::Left)
+                .chain(self.graph.neighbors(handle, Direction::Right));
+
+            for other in neighbors {
+                let other = other.id();
+                let slot = &mut dists[ix(other)];
+
+                if slot.map(|d| next < d).unwrap_or(true) {
+                    *slot = Some(next);
+                    queue.push(Reverse((next, other)));
+                }
+            }
+        }
+
+        dists
+    }
+
+    /// The nodes whose sequence contains `pattern` on either strand,
+    /// ignoring case, in node ID order. Only matches within a single
+    /// node are found.
+    pub fn nodes_with_sequence(&self, pattern: &[u8]) -> Vec<NodeId> {
+        use bstr::ByteSlice;
+
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+
+        let pattern = pattern.to_ascii_uppercase();
+
+        let mut handles = self.graph.handles().collect::<Vec<_>>();
+        handles.sort();
+
+        handles
+            .into_iter()
+            .filter(|&handle| {
+                [handle, handle.flip()].iter().any(|&handle| {
+                    let mut seq = self.graph.sequence_vec(handle);
+                    seq.make_ascii_uppercase();
+                    seq.find(&pattern).is_some()
+                })
+            })
+            .map(|handle| handle.id())
+            .collect()
+    }
+
     pub fn handle_positions(
         &self,
         handle: Handle,
@@ -226,6 +624,37 @@ impl GraphQuery {
     }
 }
 
+fn node_in_graph(graph: &PackedGraph, node_id: NodeId) -> bool {
+    node_id.0 != 0 && node_id.0 as usize <= graph.node_count()
+}
+
+/// How distances between nodes are measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+    /// The number of edges between the nodes
+    Hops,
+    /// The total length of the nodes between them
+    BasePairs,
+}
+
+impl DistanceMetric {
+    /// The name used to refer to the metric in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            DistanceMetric::Hops => "hops",
+            DistanceMetric::BasePairs => "bp",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hops" => Some(DistanceMetric::Hops),
+            "bp" => Some(DistanceMetric::BasePairs),
+            _ => None,
+        }
+    }
+}
+
 struct QueryThread {
     resp_rx: channel::Receiver<GraphQueryResp>,
     req_tx: channel::Sender<GraphQueryRequest>,
@@ -256,6 +685,11 @@ impl QueryThread {
                         path_count: graph.path_count(),
                         total_len: graph.total_length(),
                     },
+                    Req::NodeStats(node_id) | Req::NodeSeq(node_id)
+                        if !node_in_graph(&graph, node_id) =>
+                    {
+                        Resp::NodeNotFound(node_id)
+                    }
                     Req::NodeStats(node_id) => {
                         let handle = Handle::pack(node_id, false);
 
@@ -334,6 +768,9 @@ pub enum GraphQueryResp {
         seq: Vec<u8>,
         len: usize,
     },
+    /// Sent in place of `NodeStats` or `NodeSeq` when the requested
+    /// node isn't in the graph
+    NodeNotFound(NodeId),
     // Neighbors {
     //     node_id: NodeId,
     //     left: Vec<NodeId>,
@@ -22,14 +22,17 @@ use crate::{
         AnnotationFileType, Annotations, BedColumn, BedRecords, Gff3Column,
         Gff3Records,
     },
-    app::{AppChannels, AppMsg, AppSettings, SharedState},
+    app::{
+        selection::SelectionGroups, AppChannels, AppMsg, AppSettings,
+        SharedState,
+    },
     graph_query::GraphQueryWorker,
     reactor::Reactor,
     vulkan::{render_pass::Framebuffers, texture::Gradients},
 };
 use crate::{app::OverlayState, geometry::*};
 
-use crate::overlays::OverlayKind;
+use crate::overlays::{OverlayData, OverlayKind};
 
 use crate::graph_query::GraphQuery;
 
@@ -68,6 +71,8 @@ pub struct Gui {
     pub draw_system: GuiPipeline,
 
     hover_node_id: Option<NodeId>,
+    node_tooltip: NodeTooltip,
+    ruler_view: RulerView,
 
     open_windows: OpenWindows,
 
@@ -82,12 +87,20 @@ pub struct Gui {
 
     dropped_file: Arc<std::sync::Mutex<Option<PathBuf>>>,
 
-    clipboard_ctx: ClipboardContext,
+    /// `None` if no clipboard is available, in which case copying
+    /// and pasting are no-ops
+    clipboard_ctx: Option<ClipboardContext>,
+    clipboard_notice: Option<std::time::Instant>,
 
     gff3_list: RecordList<Gff3Records>,
     bed_list: RecordList<BedRecords>,
 
     path_picker_source: PathPickerSource,
+    reference_path_picker: ReferencePathPicker,
+    selection_group_list: SelectionGroupList,
+    sequence_search: SequenceSearch,
+    graph_info: GraphInfo,
+    frame_capture: FrameCaptureWindow,
 
     annotation_file_list: AnnotationFileList,
 
@@ -104,6 +117,10 @@ pub enum Windows {
     // ViewInfo,
     Nodes,
     NodeDetails,
+    SelectionGroups,
+    SequenceSearch,
+    GraphInfo,
+    FrameCapture,
 
     Paths,
 
@@ -176,6 +193,9 @@ pub struct AppViewState {
     path_list: ViewStateChannel<PathList, PathListMsg>,
     path_details: ViewStateChannel<PathDetails, ()>,
 
+    pinned_nodes: Vec<NodeDetails>,
+    pinned_paths: Vec<PathDetails>,
+
     // theme_editor: ThemeEditor,
     // theme_list: ThemeList,
     overlay_creator: ViewStateChannel<OverlayCreator, OverlayCreatorMsg>,
@@ -228,7 +248,8 @@ impl AppViewState {
             overlay_list_state,
         );
 
-        let overlay_creator_state = OverlayCreator::new(reactor).unwrap();
+        let overlay_creator_state =
+            OverlayCreator::new(reactor, shared_state).unwrap();
         let overlay_creator = ViewStateChannel::<
             OverlayCreator,
             OverlayCreatorMsg,
@@ -246,11 +267,36 @@ impl AppViewState {
             path_list,
             path_details,
 
+            pinned_nodes: Vec::new(),
+            pinned_paths: Vec::new(),
+
             overlay_list,
             overlay_creator,
         }
     }
 
+    pub fn pin_node(&mut self, node_id: NodeId) {
+        let pinned = self
+            .pinned_nodes
+            .iter()
+            .any(|details| details.node_id_cell().load() == Some(node_id));
+
+        if !pinned {
+            self.pinned_nodes.push(NodeDetails::pinned(node_id));
+        }
+    }
+
+    pub fn pin_path(&mut self, reactor: &mut Reactor, path_id: PathId) {
+        let pinned = self.pinned_paths.iter().any(|details| {
+            details.path_details.path_id_cell().load() == Some(path_id)
+        });
+
+        if !pinned {
+            self.pinned_paths
+                .push(PathDetails::pinned(reactor, path_id));
+        }
+    }
+
     pub fn fps(&self) -> &ViewStateChannel<FrameRate, FrameRateMsg> {
         &self.fps
     }
@@ -269,6 +315,12 @@ impl AppViewState {
         &self.node_details
     }
 
+    pub fn overlay_list(
+        &self,
+    ) -> &ViewStateChannel<OverlayList, OverlayListMsg> {
+        &self.overlay_list
+    }
+
     pub fn apply_received(&mut self) {
         self.fps.apply_received(|state, msg| {
             *state = FrameRate::apply_msg(state, msg);
@@ -289,6 +341,10 @@ impl AppViewState {
         self.path_list.apply_received(|state, msg| {
             state.apply_msg(msg);
         });
+
+        self.overlay_list.apply_received(|state, msg| {
+            state.apply_msg(msg);
+        });
     }
 }
 
@@ -302,6 +358,10 @@ pub struct OpenWindows {
 
     nodes: bool,
     node_details: bool,
+    selection_groups: bool,
+    sequence_search: bool,
+    graph_info: bool,
+    frame_capture: bool,
 
     paths: bool,
     path_details: bool,
@@ -309,6 +369,8 @@ pub struct OpenWindows {
     themes: bool,
     overlays: bool,
     overlay_creator: bool,
+
+    reference_path: bool,
 }
 
 impl std::default::Default for OpenWindows {
@@ -322,6 +384,10 @@ impl std::default::Default for OpenWindows {
 
             nodes: false,
             node_details: false,
+            selection_groups: false,
+            sequence_search: false,
+            graph_info: false,
+            frame_capture: false,
 
             paths: false,
             path_details: false,
@@ -329,6 +395,8 @@ impl std::default::Default for OpenWindows {
             themes: false,
             overlays: false,
             overlay_creator: false,
+
+            reference_path: false,
         }
     }
 }
@@ -425,54 +493,62 @@ impl Gui {
             dropped_file.clone(),
         );
 
-        let menu_bar = MenuBar::new(shared_state.overlay_state().clone());
+        let menu_bar = MenuBar::new(
+            shared_state.overlay_state().clone(),
+            shared_state.ruler().clone(),
+            settings.follow_selection().clone(),
+            shared_state.clone_layout_progress(),
+        );
 
-        let clipboard_ctx = ClipboardProvider::new().unwrap();
+        let clipboard_ctx = match ClipboardProvider::new() {
+            Ok(ctx) => Some(ctx),
+            Err(err) => {
+                warn!(
+                    "Clipboard unavailable, copy and paste are disabled: {:?}",
+                    err
+                );
+                None
+            }
+        };
 
         let mut path_picker_source = PathPickerSource::new(graph_query)?;
 
+        let reference_path_picker = ReferencePathPicker::new(
+            path_picker_source.create_picker(),
+            shared_state.clone_reference_path(),
+        );
+
         let annotation_file_list = AnnotationFileList::new(
             reactor,
             app_msg_tx.clone(),
             gui_msg_tx.clone(),
         )?;
 
-        let gff3_list = {
-            let mut list = RecordList::new(
-                reactor,
-                egui::Id::new("gff3_records_list"),
-                path_picker_source.create_picker(),
-            );
-
-            use Gff3Column as Gff;
-
-            list.set_default_columns(
-                [Gff::Source, Gff::Type, Gff::Frame],
-                [Gff::SeqId, Gff::Start, Gff::End, Gff::Strand],
-            );
-
-            list
-        };
-
-        let bed_list = {
-            let mut list = RecordList::new(
-                reactor,
-                egui::Id::new("bed_records_list"),
-                path_picker_source.create_picker(),
-            );
-
-            use BedColumn as Bed;
-
-            list.set_default_columns([], [Bed::Chr, Bed::Start, Bed::End]);
+        let gff3_list = RecordList::new(
+            reactor,
+            egui::Id::new("gff3_records_list"),
+            path_picker_source.create_picker(),
+            shared_state.clone_reference_path(),
+            settings.url_templates().clone(),
+            settings.default_columns(AnnotationFileType::Gff3).clone(),
+        );
 
-            list
-        };
+        let bed_list = RecordList::new(
+            reactor,
+            egui::Id::new("bed_records_list"),
+            path_picker_source.create_picker(),
+            shared_state.clone_reference_path(),
+            settings.url_templates().clone(),
+            settings.default_columns(AnnotationFileType::Bed).clone(),
+        );
 
         let console = Console::new(
             graph_query,
+            reactor.graph_query_worker(),
             channels.clone(),
             settings.to_owned(),
             shared_state.to_owned(),
+            app.gpu_allocations.clone(),
         );
 
         let gui = Self {
@@ -485,6 +561,8 @@ impl Gui {
             draw_system,
 
             hover_node_id,
+            node_tooltip: NodeTooltip::default(),
+            ruler_view: RulerView::default(),
 
             open_windows,
 
@@ -500,11 +578,17 @@ impl Gui {
             dropped_file,
 
             clipboard_ctx,
+            clipboard_notice: None,
 
             gff3_list,
             bed_list,
 
             path_picker_source,
+            reference_path_picker,
+            selection_group_list: SelectionGroupList::default(),
+            sequence_search: SequenceSearch::default(),
+            graph_info: GraphInfo::default(),
+            frame_capture: FrameCaptureWindow::default(),
 
             annotation_file_list,
 
@@ -561,6 +645,7 @@ impl Gui {
         graph_query: &Arc<GraphQuery>,
         graph_query_worker: &GraphQueryWorker,
         annotations: &Annotations,
+        selection_groups: &SelectionGroups,
     ) {
         let mut raw_input = self.frame_input.into_raw_input();
 
@@ -584,14 +669,7 @@ impl Gui {
             );
         }
 
-        self.shared_state
-            .gui_focus_state
-            .wants_keyboard_input
-            .store(self.ctx.wants_keyboard_input());
-        self.shared_state
-            .gui_focus_state
-            .wants_pointer_input
-            .store(self.ctx.wants_pointer_input());
+        self.update_focus_state();
 
         self.menu_bar
             .ui(&self.ctx, &mut self.open_windows, &self.app_msg_tx);
@@ -600,6 +678,14 @@ impl Gui {
 
         self.view_state.apply_received();
 
+        self.clipboard_notice_ui();
+
+        self.layout_progress_ui();
+
+        self.coverage_export_progress_ui();
+
+        self.overlay_build_progress_ui(reactor);
+
         let scr = self.ctx.input().screen_rect();
 
         let view_state = &mut self.view_state;
@@ -612,14 +698,22 @@ impl Gui {
                 &self.ctx,
                 overlays,
                 overlay_creator,
+                &self.app_msg_tx,
             );
 
-            view_state
-                .overlay_creator
-                .state
-                .ui(&self.ctx, overlay_creator);
+            view_state.overlay_creator.state.ui(
+                &self.ctx,
+                overlay_creator,
+                view_state.overlay_list.state.overlay_names(),
+                &self.app_msg_tx,
+            );
 
             view_state.overlay_list.state.gradient_picker_ui(&self.ctx);
+
+            view_state
+                .overlay_list
+                .state
+                .legend_ui(&self.ctx, self.menu_bar.height());
         }
 
         if let Some(rect) = self.shared_state.active_mouse_rect_screen() {
@@ -641,6 +735,50 @@ impl Gui {
             paint_area.painter().rect_stroke(rect.into(), 0.0, stroke);
         }
 
+        let node_labels = NodeLabels {
+            mode: self.settings.node_label_mode().load(),
+            reference_path: self.shared_state.reference_path(),
+        };
+
+        self.ruler_view.ui(
+            &self.ctx,
+            graph_query,
+            self.shared_state.ruler(),
+            self.shared_state.view(),
+            self.shared_state.mouse_pos(),
+            node_labels.reference_path,
+        );
+
+        if view_state.settings.gui.show_node_tooltips {
+            // the setting can be set to any value from the console,
+            // and from_secs_f32 panics on infinite values
+            let hover_debounce_ms = self
+                .settings
+                .hover_debounce_ms()
+                .load()
+                .max(0.0)
+                .min(10_000.0);
+            let hover_debounce =
+                std::time::Duration::from_secs_f32(hover_debounce_ms / 1000.0);
+
+            let hover_node =
+                if self.shared_state.gui_focus_state.mouse_over_gui() {
+                    None
+                } else {
+                    self.hover_node_id
+                };
+
+            self.node_tooltip.ui(
+                &self.ctx,
+                graph_query,
+                annotations,
+                hover_node,
+                node_labels,
+                self.shared_state.mouse_pos(),
+                hover_debounce,
+            );
+        }
+
         self.annotation_file_list.ui(
             &self.ctx,
             &mut self.open_windows.annotation_files,
@@ -707,9 +845,41 @@ impl Gui {
             annotations,
         );
 
-        view_state
-            .settings
-            .ui(&self.ctx, &mut self.open_windows.settings);
+        self.reference_path_picker
+            .ui(&self.ctx, &mut self.open_windows.reference_path);
+
+        self.selection_group_list.ui(
+            &self.ctx,
+            &mut self.open_windows.selection_groups,
+            selection_groups,
+            &self.app_msg_tx,
+        );
+
+        self.sequence_search.ui(
+            &self.ctx,
+            &mut self.open_windows.sequence_search,
+            graph_query_worker,
+            &self.app_msg_tx,
+        );
+
+        self.graph_info.ui(
+            &self.ctx,
+            &mut self.open_windows.graph_info,
+            graph_query,
+        );
+
+        self.frame_capture.ui(
+            &self.ctx,
+            &mut self.open_windows.frame_capture,
+            &self.shared_state,
+            &self.app_msg_tx,
+        );
+
+        view_state.settings.ui(
+            &self.ctx,
+            &mut self.open_windows.settings,
+            &self.app_msg_tx,
+        );
 
         if view_state.settings.gui.show_fps {
             let top = self.menu_bar.height();
@@ -747,6 +917,7 @@ impl Gui {
                     &self.app_msg_tx,
                     node_details,
                     graph_query,
+                    node_labels,
                 );
             }
 
@@ -757,6 +928,8 @@ impl Gui {
                     &self.ctx,
                     path_details_id_cell,
                     path_details,
+                    node_labels,
+                    annotations,
                 );
             }
         }
@@ -791,6 +964,65 @@ impl Gui {
             }
         }
 
+        {
+            let node_details = &mut self.open_windows.node_details;
+            let path_details = &mut self.open_windows.path_details;
+
+            let node_details_id_cell =
+                view_state.node_details.state.node_id_cell();
+            let path_details_id_cell =
+                view_state.path_details.state.path_details.path_id_cell();
+
+            let ctx = &self.ctx;
+            let app_msg_tx = &self.app_msg_tx;
+
+            // pinned windows that were closed this frame are removed
+            let mut ix = 0;
+            while ix < view_state.pinned_nodes.len() {
+                let open = view_state.pinned_nodes[ix].pinned_ui(
+                    graph_query,
+                    ctx,
+                    path_details_id_cell,
+                    path_details,
+                    node_labels,
+                    annotations,
+                );
+
+                if open {
+                    ix += 1;
+                } else {
+                    view_state.pinned_nodes.remove(ix);
+                }
+            }
+
+            let mut ix = 0;
+            while ix < view_state.pinned_paths.len() {
+                let open = view_state.pinned_paths[ix].pinned_ui(
+                    graph_query,
+                    ctx,
+                    node_details_id_cell,
+                    node_details,
+                    app_msg_tx,
+                );
+
+                if open {
+                    ix += 1;
+                } else {
+                    view_state.pinned_paths.remove(ix);
+                }
+            }
+        }
+
+        if let Some(node_id) = view_state.node_details.state.take_pin_request()
+        {
+            view_state.pin_node(node_id);
+        }
+
+        if let Some(path_id) = view_state.path_details.state.take_pin_request()
+        {
+            view_state.pin_path(reactor, path_id);
+        }
+
         {
             let debug = &mut view_state.settings.debug;
             let inspection = &mut debug.egui_inspection;
@@ -829,13 +1061,234 @@ impl Gui {
     pub fn end_frame(&mut self) -> Vec<egui::ClippedMesh> {
         let (output, shapes) = self.ctx.end_frame();
 
+        // widgets can gain or lose focus during the frame, e.g. when
+        // clicked, so the state from the start of the frame is stale
+        self.update_focus_state();
+
         if !output.copied_text.is_empty() {
-            self.clipboard_ctx.set_contents(output.copied_text).unwrap();
+            self.set_clipboard_contents(output.copied_text);
         }
 
         self.ctx.tessellate(shapes)
     }
 
+    pub fn clipboard_contents(&mut self) -> Option<String> {
+        if let Some(ctx) = self.clipboard_ctx.as_mut() {
+            match ctx.get_contents() {
+                Ok(contents) => return Some(contents),
+                Err(err) => {
+                    warn!("Error getting clipboard contents: {:?}", err)
+                }
+            }
+        }
+
+        self.clipboard_notice = Some(std::time::Instant::now());
+        None
+    }
+
+    pub fn set_clipboard_contents(&mut self, contents: String) {
+        if let Some(ctx) = self.clipboard_ctx.as_mut() {
+            match ctx.set_contents(contents) {
+                Ok(_) => return,
+                Err(err) => {
+                    warn!("Error setting clipboard contents: {:?}", err)
+                }
+            }
+        }
+
+        self.clipboard_notice = Some(std::time::Instant::now());
+    }
+
+    /// Shows how many of the label clusters in view were left out
+    /// because of the label cap, if any were
+    pub fn hidden_labels_notice_ui(&self, hidden: usize) {
+        if hidden == 0 {
+            return;
+        }
+
+        egui::Area::new("hidden_labels_notice")
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+            .interactable(false)
+            .show(&self.ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(format!("{} label clusters hidden", hidden));
+                });
+            });
+    }
+
+    fn layout_progress_ui(&self) {
+        let (algorithm, progress) =
+            if let Some(progress) = self.shared_state.layout_progress() {
+                progress
+            } else {
+                return;
+            };
+
+        egui::Area::new("layout_progress")
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+            .interactable(false)
+            .show(&self.ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(format!(
+                        "Running layout: {}",
+                        algorithm.display_name()
+                    ));
+                    ui.add(
+                        egui::ProgressBar::new(progress)
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                });
+            });
+    }
+
+    fn coverage_export_progress_ui(&self) {
+        let progress = if let Some(progress) =
+            self.shared_state.coverage_export_progress()
+        {
+            progress
+        } else {
+            return;
+        };
+
+        egui::Area::new("coverage_export_progress")
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 100.0])
+            .interactable(false)
+            .show(&self.ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label("Exporting path coverage");
+                    ui.add(
+                        egui::ProgressBar::new(progress)
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                });
+            });
+    }
+
+    /// Shows the progress of the overlay being built on a worker, if
+    /// any, and creates the overlay once the build is done
+    fn overlay_build_progress_ui(&mut self, reactor: &Reactor) {
+        // the result is taken and the slot cleared under the same
+        // lock, so that a build started in between isn't dropped
+        let finished = {
+            let mut overlay_build = self.shared_state.overlay_build.write();
+
+            let data = match overlay_build.as_ref() {
+                Some(build) => build.task.take_result(),
+                None => return,
+            };
+
+            data.and_then(|data| Some((overlay_build.take()?, data)))
+        };
+
+        if let Some((build, data)) = finished {
+            let name = build.name;
+            let is_script = build.script.is_some();
+
+            match data {
+                Some(Ok(data)) => {
+                    let msg = match (build.script, data) {
+                        (Some(script), data) => OverlayCreatorMsg::Script {
+                            name,
+                            overlay_id: build.overlay_id,
+                            script,
+                            data,
+                        },
+                        (None, OverlayData::Value(values)) => {
+                            OverlayCreatorMsg::NewValueOverlay { name, values }
+                        }
+                        (None, data) => OverlayCreatorMsg::NewOverlay {
+                            name,
+                            data,
+                            legend: None,
+                        },
+                    };
+
+                    reactor.overlay_create_tx.send(msg).unwrap();
+                }
+                Some(Err(err)) => {
+                    error!("Error building overlay '{}': {:?}", name, err);
+
+                    if is_script {
+                        self.view_state.overlay_creator.state.set_build_result(
+                            Err(ScriptMsg::from_build_error(&err)),
+                        );
+                    }
+                }
+                None => {
+                    info!("Cancelled building overlay '{}'", name);
+                }
+            }
+
+            return;
+        }
+
+        let (name, progress) =
+            if let Some(build) = self.shared_state.overlay_build() {
+                build
+            } else {
+                return;
+            };
+
+        egui::Area::new("overlay_build_progress")
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 160.0])
+            .show(&self.ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(format!("Building overlay '{}'", name));
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::ProgressBar::new(progress)
+                                .desired_width(200.0)
+                                .show_percentage(),
+                        );
+
+                        if ui.button("Cancel").clicked() {
+                            self.shared_state.cancel_overlay_build();
+                        }
+                    });
+                });
+            });
+    }
+
+    /// Briefly shows a notice at the bottom of the screen after a
+    /// copy or paste failed
+    fn clipboard_notice_ui(&mut self) {
+        const NOTICE_DURATION: std::time::Duration =
+            std::time::Duration::from_secs(3);
+
+        let shown_at = if let Some(shown_at) = self.clipboard_notice {
+            shown_at
+        } else {
+            return;
+        };
+
+        if shown_at.elapsed() > NOTICE_DURATION {
+            self.clipboard_notice = None;
+            return;
+        }
+
+        let text = if self.clipboard_ctx.is_some() {
+            "Clipboard error, see log for details"
+        } else {
+            "Clipboard unavailable"
+        };
+
+        egui::Area::new("clipboard_notice")
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -40.0])
+            .interactable(false)
+            .show(&self.ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(text);
+                });
+            });
+    }
+
     pub fn pointer_over_gui(&self) -> bool {
         self.ctx.is_pointer_over_area()
     }
@@ -899,6 +1352,16 @@ impl Gui {
                         }
                         Windows::Nodes => &mut open_windows.nodes,
                         Windows::NodeDetails => &mut open_windows.node_details,
+                        Windows::SelectionGroups => {
+                            &mut open_windows.selection_groups
+                        }
+                        Windows::SequenceSearch => {
+                            &mut open_windows.sequence_search
+                        }
+                        Windows::GraphInfo => &mut open_windows.graph_info,
+                        Windows::FrameCapture => {
+                            &mut open_windows.frame_capture
+                        }
                         Windows::Paths => &mut open_windows.paths,
                         Windows::Themes => &mut open_windows.themes,
                         Windows::Overlays => &mut open_windows.overlays,
@@ -941,10 +1404,8 @@ impl Gui {
                     self.frame_input.events.push(egui::Event::Copy);
                 }
                 GuiMsg::Paste => {
-                    if let Ok(text) = &self.clipboard_ctx.get_contents() {
-                        self.frame_input
-                            .events
-                            .push(egui::Event::Text(text.clone()));
+                    if let Some(text) = self.clipboard_contents() {
+                        self.frame_input.events.push(egui::Event::Text(text));
                     }
                 }
                 GuiMsg::SetModifiers(mods) => {
@@ -999,21 +1460,13 @@ impl Gui {
                                 .unwrap();
                         }
                         GuiInput::KeyToggleConsole => {
-                            self.console_down = !self.console_down;
-                            if self.console_down {
-                                self.ctx.memory().request_focus(egui::Id::new(
-                                    console::Console::ID_TEXT,
-                                ));
-                            }
+                            self.set_console_down(!self.console_down);
                         }
                         GuiInput::KeyConsoleDown => {
-                            self.console_down = true;
-                            self.ctx.memory().request_focus(egui::Id::new(
-                                console::Console::ID_TEXT,
-                            ));
+                            self.set_console_down(true);
                         }
                         GuiInput::KeyConsoleUp => {
-                            self.console_down = false;
+                            self.set_console_down(false);
                         }
                         _ => (),
                     }
@@ -1031,6 +1484,18 @@ impl Gui {
                     _ => None,
                 };
 
+                // clicking the graph moves keyboard input from the
+                // console to the graph, while leaving the console open
+                let over_gui =
+                    self.shared_state.gui_focus_state.mouse_over_gui();
+
+                if pressed && self.console_down && !over_gui {
+                    self.ctx.memory().surrender_focus(egui::Id::new(
+                        console::Console::ID_TEXT,
+                    ));
+                    self.update_focus_state();
+                }
+
                 if let Some(button) = button {
                     let egui_event = egui::Event::PointerButton {
                         pos: pos.into(),
@@ -1055,6 +1520,37 @@ impl Gui {
         }
     }
 
+    /// Opens or closes the console, giving it keyboard focus when
+    /// opened, and returning keyboard input to the graph when closed
+    fn set_console_down(&mut self, down: bool) {
+        self.console_down = down;
+
+        let id = egui::Id::new(console::Console::ID_TEXT);
+
+        if down {
+            self.ctx.memory().request_focus(id);
+        } else {
+            // the console text box isn't drawn while the console is
+            // up, so egui wouldn't release its focus on its own
+            self.ctx.memory().surrender_focus(id);
+        }
+
+        self.update_focus_state();
+    }
+
+    /// Stores whether egui currently uses the keyboard and pointer,
+    /// so the input manager can route input to the graph instead
+    fn update_focus_state(&self) {
+        let focus_state = &self.shared_state.gui_focus_state;
+
+        focus_state
+            .wants_keyboard_input
+            .store(self.ctx.wants_keyboard_input());
+        focus_state
+            .wants_pointer_input
+            .store(self.ctx.wants_pointer_input());
+    }
+
     fn set_style(ctx: &egui::CtxRef, visuals: egui::style::Visuals) {
         let mut style: egui::Style = (*ctx.style()).clone();
         style.visuals = visuals;
@@ -24,6 +24,7 @@ use rhai::{plugin::*, Func};
 
 use bstr::ByteSlice;
 
+use crate::vulkan::memory::GpuAllocations;
 use crate::{
     app::{
         selection::NodeSelection, AppChannels, AppMsg, OverlayState, Select,
@@ -33,7 +34,8 @@ use crate::{
 };
 use crate::{
     app::{AppSettings, SharedState},
-    graph_query::GraphQuery,
+    graph_query::{GraphQuery, GraphQueryWorker},
+    script::ScriptQuery,
 };
 use crate::{overlays::OverlayKind, vulkan::draw_system::edges::EdgesUBO};
 
@@ -42,6 +44,88 @@ use parking_lot::{Mutex, MutexGuard};
 pub type ScriptEvalResult =
     std::result::Result<rhai::Dynamic, Box<rhai::EvalAltResult>>;
 
+/// Expands a leading `~` to the home directory, and resolves
+/// relative paths against the script directory
+fn resolve_path(script_dir: &std::path::Path, path: &str) -> PathBuf {
+    let path = path.trim();
+
+    let home = || {
+        std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+    };
+
+    let expanded = if path == "~" {
+        home()
+    } else if let Some(rest) =
+        path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\"))
+    {
+        home().map(|home| home.join(rest))
+    } else {
+        None
+    };
+
+    let path = expanded.unwrap_or_else(|| PathBuf::from(path));
+
+    if path.is_absolute() {
+        path
+    } else {
+        script_dir.join(path)
+    }
+}
+
+/// Loads a node table and builds the message creating an overlay
+/// from it, with the columns given by name, and the first line
+/// treated as a header if it looks like one
+fn node_table_overlay_msg(
+    path: &std::path::Path,
+    id_column: &str,
+    value_column: &str,
+    values: &str,
+) -> std::result::Result<AppMsg, String> {
+    use crate::annotations::{NodeTable, NodeTableMapping, NodeTableValues};
+
+    let values = NodeTableValues::from_name(values).ok_or_else(|| {
+        format!(
+            "unknown value kind '{}', expected one of: {}, {}, {}",
+            values,
+            NodeTableValues::Auto.name(),
+            NodeTableValues::Color.name(),
+            NodeTableValues::Numeric.name(),
+        )
+    })?;
+
+    let table = NodeTable::parse_tsv_file(path).map_err(|err| {
+        format!("error loading node table from {}: {}", path.display(), err)
+    })?;
+
+    let has_header = table.guess_header();
+
+    let column = |name: &str| {
+        table.column_index(has_header, name).ok_or_else(|| {
+            format!(
+                "node table {} has no column '{}', expected one of: {}",
+                path.display(),
+                name,
+                table.columns(has_header).join(", ")
+            )
+        })
+    };
+
+    let mapping = NodeTableMapping {
+        has_header,
+        id_column: column(id_column)?,
+        value_column: column(value_column)?,
+        values,
+    };
+
+    Ok(AppMsg::NodeTableOverlay {
+        name: format!("{} - {}", table.file_name(), value_column),
+        table: Arc::new(table),
+        mapping,
+    })
+}
+
 pub struct Console<'a> {
     input_line: String,
 
@@ -68,7 +152,15 @@ pub struct Console<'a> {
     graph: Arc<PackedGraph>,
     path_positions: Arc<PathPositionMap>,
 
+    graph_query_worker: Arc<GraphQueryWorker>,
+
+    gpu_allocations: GpuAllocations,
+
     modules: Vec<Arc<rhai::Module>>,
+
+    /// Relative file paths in console commands are resolved
+    /// against this directory
+    script_dir: PathBuf,
 }
 
 impl Console<'static> {
@@ -77,9 +169,11 @@ impl Console<'static> {
 
     pub fn new(
         graph: &GraphQuery,
+        graph_query_worker: GraphQueryWorker,
         channels: AppChannels,
         settings: AppSettings,
         shared_state: SharedState,
+        gpu_allocations: GpuAllocations,
     ) -> Self {
         let (result_tx, result_rx) =
             crossbeam::channel::unbounded::<ScriptEvalResult>();
@@ -140,6 +234,30 @@ impl Console<'static> {
         }
 
         add_t!(f32, "label_radius", settings.label_radius().clone());
+        get_set.add_arc_atomic_cell_get_set(
+            "max_label_clusters",
+            settings.max_label_clusters().clone(),
+            |x| rhai::Dynamic::from(x as i64),
+            |x: rhai::Dynamic| x.try_cast::<i64>().map(|x| x.max(0) as usize),
+        );
+        add_t!(f32, "zoom_step", settings.zoom_step().clone());
+        add_t!(
+            bool,
+            "follow_selection",
+            settings.follow_selection().clone()
+        );
+        add_t!(f32, "min_view_scale", settings.min_view_scale().clone());
+        add_t!(f32, "max_view_scale", settings.max_view_scale().clone());
+        add_t!(
+            f32,
+            "hover_debounce_ms",
+            settings.hover_debounce_ms().clone()
+        );
+        add_t!(
+            bool,
+            "console_output_to_log",
+            settings.console_output_to_log().clone()
+        );
         add_t!(Point, "mouse_pos", shared_state.mouse_pos.clone());
 
         add_t!(
@@ -237,8 +355,41 @@ impl Console<'static> {
             graph: graph.graph.clone(),
             path_positions: graph.path_positions.clone(),
 
+            graph_query_worker: Arc::new(graph_query_worker),
+
+            gpu_allocations,
+
             modules: Vec::new(),
+
+            script_dir: std::env::current_dir()
+                .unwrap_or_else(|_| PathBuf::from(".")),
+        }
+    }
+
+    pub fn script_dir(&self) -> &std::path::Path {
+        &self.script_dir
+    }
+
+    pub fn set_script_dir<P: Into<PathBuf>>(&mut self, dir: P) {
+        self.script_dir = dir.into();
+    }
+
+    /// Resolves `path` with `resolve_path`, returning an error
+    /// including the resolved path if the file doesn't exist
+    fn resolve_file(&self, path: &str) -> Result<PathBuf> {
+        let resolved = resolve_path(&self.script_dir, path);
+
+        if !resolved.is_file() {
+            let absolute = if resolved.is_absolute() {
+                resolved
+            } else {
+                std::env::current_dir()?.join(resolved)
+            };
+
+            anyhow::bail!("file not found: {}", absolute.display());
         }
+
+        Ok(resolved)
     }
 
     fn create_engine(&self) -> rhai::Engine {
@@ -296,6 +447,458 @@ impl Console<'static> {
         let arc = self.shared_state.hover_node.clone();
         engine.register_fn("get_hover_node", move || arc.load());
 
+        let arc = self.shared_state.clone_reference_path();
+        engine.register_fn("get_reference_path", move || match arc.load() {
+            Some(path) => rhai::Dynamic::from(path),
+            None => rhai::Dynamic::UNIT,
+        });
+
+        let arc = self.shared_state.clone_reference_path();
+        engine.register_fn("set_reference_path", move |path: PathId| {
+            arc.store(Some(path));
+        });
+
+        let arc = self.shared_state.clone_reference_path();
+        engine.register_fn("clear_reference_path", move || {
+            arc.store(None);
+        });
+
+        let gpu_allocations = self.gpu_allocations.clone();
+        engine.register_fn("gpu_memory", move || gpu_allocations.summary());
+
+        let gpu_allocations = self.gpu_allocations.clone();
+        engine.register_fn("gpu_memory_total", move || {
+            gpu_allocations.total() as i64
+        });
+
+        let overlay_state = self.shared_state.overlay_state().clone();
+        engine.register_fn("get_overlay_scope", move || {
+            overlay_state.scope().name().to_string()
+        });
+
+        let overlay_state = self.shared_state.overlay_state().clone();
+        engine.register_result_fn(
+            "set_overlay_scope",
+            move |name: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::app::OverlayScope;
+
+                let scope = OverlayScope::from_name(name).ok_or_else(|| {
+                    format!(
+                        "unknown overlay scope '{}', expected one of: {}, {}",
+                        name,
+                        OverlayScope::Global.name(),
+                        OverlayScope::Selection.name(),
+                    )
+                })?;
+
+                overlay_state.set_scope(scope);
+
+                Ok(())
+            },
+        );
+
+        let overlay_state = self.shared_state.overlay_state().clone();
+        engine.register_fn("set_overlay_unselected_dim", move |dim: f32| {
+            overlay_state.set_unselected_dim(dim);
+        });
+
+        let overlay_state = self.shared_state.overlay_state().clone();
+        engine.register_fn("get_overlay_mode", move || {
+            overlay_state.mode().name().to_string()
+        });
+
+        let overlay_state = self.shared_state.overlay_state().clone();
+        engine.register_result_fn(
+            "set_overlay_mode",
+            move |name: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::app::OverlayMode;
+
+                let mode = OverlayMode::from_name(name).ok_or_else(|| {
+                    format!(
+                        "unknown overlay mode '{}', expected one of: {}, {}",
+                        name,
+                        OverlayMode::Single.name(),
+                        OverlayMode::Stack.name(),
+                    )
+                })?;
+
+                overlay_state.set_mode(mode);
+
+                Ok(())
+            },
+        );
+
+        let overlay_state = self.shared_state.overlay_state().clone();
+        engine.register_fn("set_show_legend", move |show: bool| {
+            overlay_state.set_show_legend(show);
+        });
+
+        let overlay_state = self.shared_state.overlay_state().clone();
+        engine.register_result_fn(
+            "set_legend_position",
+            move |name: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::app::LegendAnchor;
+
+                let anchor = LegendAnchor::from_name(name).ok_or_else(|| {
+                    let names = LegendAnchor::ALL
+                        .iter()
+                        .map(|a| a.name())
+                        .collect::<Vec<_>>();
+                    format!(
+                        "unknown legend position '{}', expected one of: {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+
+                overlay_state.set_legend_anchor(anchor);
+
+                Ok(())
+            },
+        );
+
+        let overlay_state = self.shared_state.overlay_state().clone();
+        engine.register_result_fn(
+            "set_legend_units",
+            move |units: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                let (overlay_id, _) = overlay_state
+                    .current_overlay()
+                    .ok_or("no overlay is active")?;
+
+                if !overlay_state.set_legend_units(overlay_id, units) {
+                    return Err("the active overlay is not a value overlay".into());
+                }
+
+                Ok(())
+            },
+        );
+
+        let templates = self.settings.url_templates().clone();
+        engine.register_fn(
+            "add_url_template",
+            move |name: &str, template: &str| {
+                use crate::app::UrlTemplate;
+
+                let mut templates = templates.write();
+                let template = UrlTemplate::new(name, template);
+
+                if let Some(existing) =
+                    templates.iter_mut().find(|t| t.name == name)
+                {
+                    *existing = template;
+                } else {
+                    templates.push(template);
+                }
+            },
+        );
+
+        let templates = self.settings.url_templates().clone();
+        engine.register_fn("remove_url_template", move |name: &str| {
+            templates.write().retain(|t| t.name != name);
+        });
+
+        let templates = self.settings.url_templates().clone();
+        engine.register_fn("url_templates", move || {
+            templates
+                .read()
+                .iter()
+                .map(|t| format!("{}: {}", t.name, t.template))
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+
+        let settings = self.settings.clone();
+        engine.register_result_fn(
+            "set_default_columns",
+            move |file_type: &str,
+                  enabled: rhai::Array,
+                  hidden: rhai::Array|
+                  -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::annotations::AnnotationFileType;
+                use crate::app::DefaultColumns;
+
+                let file_type = AnnotationFileType::from_name(file_type)
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown file type '{}', expected gff3 or bed",
+                            file_type
+                        )
+                    })?;
+
+                let to_names = |columns: rhai::Array| {
+                    columns
+                        .into_iter()
+                        .map(|column| column.to_string())
+                        .collect::<Vec<_>>()
+                };
+
+                *settings.default_columns(file_type).write() = DefaultColumns {
+                    enabled: to_names(enabled),
+                    hidden: to_names(hidden),
+                };
+
+                Ok(())
+            },
+        );
+
+        let settings = self.settings.clone();
+        engine.register_result_fn(
+            "default_columns",
+            move |file_type: &str| -> std::result::Result<String, Box<rhai::EvalAltResult>> {
+                use crate::annotations::AnnotationFileType;
+
+                let file_type =
+                    AnnotationFileType::from_name(file_type).ok_or_else(|| {
+                        format!(
+                            "unknown file type '{}', expected gff3 or bed",
+                            file_type
+                        )
+                    })?;
+
+                let columns = settings.default_columns(file_type).read();
+
+                Ok(format!(
+                    "shown: {}\nhidden: {}",
+                    columns.enabled.join(", "),
+                    columns.hidden.join(", ")
+                ))
+            },
+        );
+
+        let arc = self.settings.node_label_mode().clone();
+        engine.register_fn("get_node_label_mode", move || {
+            arc.load().name().to_string()
+        });
+
+        let arc = self.settings.node_label_mode().clone();
+        engine.register_result_fn(
+            "set_node_label_mode",
+            move |name: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::app::NodeLabelMode;
+
+                let mode = NodeLabelMode::from_name(name).ok_or_else(|| {
+                    format!(
+                        "unknown node label mode '{}', expected one of: {}, {}",
+                        name,
+                        NodeLabelMode::NodeId.name(),
+                        NodeLabelMode::PathCoordinate.name(),
+                    )
+                })?;
+
+                arc.store(mode);
+
+                Ok(())
+            },
+        );
+
+        let arc = self.settings.mouse_bindings().clone();
+        engine.register_fn("get_mouse_bindings", move || {
+            let bindings = arc.load();
+            format!(
+                "select: {}\npan: {}\nrect_select: {}\nadd_select: {}",
+                bindings.select.name(),
+                bindings.pan.name(),
+                bindings.rect_select.name(),
+                bindings.add_select.name(),
+            )
+        });
+
+        let arc = self.settings.mouse_bindings().clone();
+        engine.register_result_fn(
+            "set_mouse_button",
+            move |gesture: &str, button: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::app::BindableButton;
+
+                let button = BindableButton::from_name(button).ok_or_else(|| {
+                    format!(
+                        "unknown mouse button '{}', expected one of: left, right, middle",
+                        button
+                    )
+                })?;
+
+                let mut bindings = arc.load();
+
+                match gesture {
+                    "select" => bindings.select = button,
+                    "pan" => bindings.pan = button,
+                    _ => {
+                        return Err(format!(
+                            "unknown gesture '{}', expected one of: select, pan",
+                            gesture
+                        )
+                        .into())
+                    }
+                }
+
+                bindings.validate().map_err(|err| err.to_string())?;
+                arc.store(bindings);
+
+                Ok(())
+            },
+        );
+
+        let arc = self.settings.mouse_bindings().clone();
+        engine.register_result_fn(
+            "set_mouse_modifier",
+            move |gesture: &str, modifier: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::app::BindableModifier;
+
+                let modifier = BindableModifier::from_name(modifier).ok_or_else(|| {
+                    format!(
+                        "unknown modifier '{}', expected one of: none, shift, ctrl, alt",
+                        modifier
+                    )
+                })?;
+
+                let mut bindings = arc.load();
+
+                match gesture {
+                    "rect_select" => bindings.rect_select = modifier,
+                    "add_select" => bindings.add_select = modifier,
+                    _ => {
+                        return Err(format!(
+                            "unknown gesture '{}', expected one of: rect_select, add_select",
+                            gesture
+                        )
+                        .into())
+                    }
+                }
+
+                bindings.validate().map_err(|err| err.to_string())?;
+                arc.store(bindings);
+
+                Ok(())
+            },
+        );
+
+        let arc = self.settings.mouse_bindings().clone();
+        engine.register_result_fn(
+            "set_mouse_bindings",
+            move |select: &str,
+                  pan: &str,
+                  rect_select: &str,
+                  add_select: &str|
+                  -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::app::{BindableButton, BindableModifier, MouseBindings};
+
+                let button = |name: &str| {
+                    BindableButton::from_name(name).ok_or_else(|| {
+                        format!(
+                            "unknown mouse button '{}', expected one of: left, right, middle",
+                            name
+                        )
+                    })
+                };
+
+                let modifier = |name: &str| {
+                    BindableModifier::from_name(name).ok_or_else(|| {
+                        format!(
+                            "unknown modifier '{}', expected one of: none, shift, ctrl, alt",
+                            name
+                        )
+                    })
+                };
+
+                let bindings = MouseBindings {
+                    select: button(select)?,
+                    pan: button(pan)?,
+                    rect_select: modifier(rect_select)?,
+                    add_select: modifier(add_select)?,
+                };
+
+                bindings.validate().map_err(|err| err.to_string())?;
+                arc.store(bindings);
+
+                Ok(())
+            },
+        );
+
+        let arc = self.settings.follow_zoom().clone();
+        engine.register_fn("get_follow_zoom", move || {
+            arc.load().name().to_string()
+        });
+
+        let arc = self.settings.follow_zoom().clone();
+        engine.register_result_fn(
+            "set_follow_zoom",
+            move |name: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::app::FollowZoom;
+
+                let zoom = FollowZoom::from_name(name).ok_or_else(|| {
+                    format!(
+                        "unknown follow zoom '{}', expected one of: {}, {}",
+                        name,
+                        FollowZoom::Fit.name(),
+                        FollowZoom::KeepScale.name(),
+                    )
+                })?;
+
+                arc.store(zoom);
+
+                Ok(())
+            },
+        );
+
+        let arc = self.settings.label_priority().clone();
+        engine.register_fn("get_label_priority", move || {
+            arc.load().name().to_string()
+        });
+
+        let arc = self.settings.label_priority().clone();
+        engine.register_result_fn(
+            "set_label_priority",
+            move |name: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::app::LabelPriority;
+
+                let priority = LabelPriority::from_name(name).ok_or_else(|| {
+                    format!(
+                        "unknown label priority '{}', expected one of: {}, {}",
+                        name,
+                        LabelPriority::NearestCenter.name(),
+                        LabelPriority::LargestCluster.name(),
+                    )
+                })?;
+
+                arc.store(priority);
+
+                Ok(())
+            },
+        );
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("new_selection_group", move |name: &str| {
+            app_msg_tx
+                .send(crate::app::AppMsg::NewSelectionGroup {
+                    name: name.to_string(),
+                    color: None,
+                })
+                .unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn(
+            "new_selection_group",
+            move |name: &str, color: rgb::RGB<f32>| {
+                app_msg_tx
+                    .send(crate::app::AppMsg::NewSelectionGroup {
+                        name: name.to_string(),
+                        color: Some(color),
+                    })
+                    .unwrap();
+            },
+        );
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("set_active_selection_group", move |ix: i64| {
+            if ix >= 0 {
+                app_msg_tx
+                    .send(crate::app::AppMsg::SetActiveSelectionGroup(
+                        ix as usize,
+                    ))
+                    .unwrap();
+            }
+        });
+
         let app_msg_tx = self.channels.app_tx.clone();
         engine.register_fn("toggle_dark_mode", move || {
             app_msg_tx.send(crate::app::AppMsg::ToggleDarkMode).unwrap();
@@ -305,6 +908,445 @@ impl Console<'static> {
             app_msg_tx.send(crate::app::AppMsg::ToggleOverlay).unwrap();
         });
 
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("set_overlay_visible", move |visible: bool| {
+            app_msg_tx
+                .send(crate::app::AppMsg::SetOverlayVisible(visible))
+                .unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("set_active_overlay", move |name: &str| {
+            app_msg_tx
+                .send(crate::app::AppMsg::SetActiveOverlay(Some(
+                    name.to_string(),
+                )))
+                .unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("clear_active_overlay", move || {
+            app_msg_tx
+                .send(crate::app::AppMsg::SetActiveOverlay(None))
+                .unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("recompute_overlay", move |name: &str| {
+            app_msg_tx
+                .send(crate::app::AppMsg::RecomputeOverlay(name.to_string()))
+                .unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("zoom_in", move || {
+            app_msg_tx.send(crate::app::AppMsg::ZoomIn).unwrap();
+        });
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("zoom_out", move || {
+            app_msg_tx.send(crate::app::AppMsg::ZoomOut).unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("reset_view", move || {
+            app_msg_tx.send(crate::app::AppMsg::ResetView).unwrap();
+        });
+
+        let view = self.shared_state.clone_view();
+        engine.register_fn("get_view_aspect", move || view.load().aspect);
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("set_view_aspect", move |aspect: f32| {
+            app_msg_tx
+                .send(crate::app::AppMsg::SetViewAspect(aspect))
+                .unwrap();
+        });
+
+        let shared_state = self.shared_state.clone();
+        engine.register_fn("get_layout_spacing", move || {
+            shared_state.layout_spacing()
+        });
+
+        let shared_state = self.shared_state.clone();
+        engine.register_result_fn(
+            "set_layout_spacing",
+            move |factor: f32| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                if !(factor.is_finite() && factor > 0.0) {
+                    return Err(format!(
+                        "layout spacing must be a positive number, got {}",
+                        factor
+                    )
+                    .into());
+                }
+
+                shared_state.set_layout_spacing(factor);
+                Ok(())
+            },
+        );
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("export_svg", move |path: &str| {
+            let path = resolve_path(&script_dir, path);
+            app_msg_tx
+                .send(crate::app::AppMsg::ExportSvg(path))
+                .unwrap();
+        });
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("export_selection_gfa", move |path: &str| {
+            let path = resolve_path(&script_dir, path);
+            app_msg_tx
+                .send(crate::app::AppMsg::ExportSelectionGfa(path))
+                .unwrap();
+        });
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("save_selection", move |path: &str| {
+            let path = resolve_path(&script_dir, path);
+            app_msg_tx
+                .send(crate::app::AppMsg::SaveSelection(path))
+                .unwrap();
+        });
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("load_selection", move |path: &str| {
+            let path = resolve_path(&script_dir, path);
+            app_msg_tx
+                .send(crate::app::AppMsg::LoadSelection(path))
+                .unwrap();
+        });
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("save_settings", move |path: &str| {
+            let path = resolve_path(&script_dir, path);
+            app_msg_tx
+                .send(crate::app::AppMsg::SaveSettings(path))
+                .unwrap();
+        });
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "export_path_coverage",
+            move |path: &str,
+                  format: &str|
+                  -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::export::CoverageFormat;
+
+                let format = CoverageFormat::from_name(format).ok_or_else(|| {
+                    let names = CoverageFormat::ALL
+                        .iter()
+                        .map(|f| f.name())
+                        .collect::<Vec<_>>();
+                    format!(
+                        "unknown coverage format '{}', expected one of: {}",
+                        format,
+                        names.join(", ")
+                    )
+                })?;
+
+                let path = resolve_path(&script_dir, path);
+                app_msg_tx
+                    .send(crate::app::AppMsg::ExportPathCoverage {
+                        path,
+                        format,
+                    })
+                    .unwrap();
+
+                Ok(())
+            },
+        );
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("export_bundle", move |dir: &str| {
+            let dir = resolve_path(&script_dir, dir);
+            app_msg_tx
+                .send(crate::app::AppMsg::ExportBundle(dir))
+                .unwrap();
+        });
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("import_bundle", move |dir: &str| {
+            let dir = resolve_path(&script_dir, dir);
+            app_msg_tx
+                .send(crate::app::AppMsg::ImportBundle(dir))
+                .unwrap();
+        });
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn(
+            "start_frame_capture",
+            move |dir: &str, every: i64| {
+                let dir = resolve_path(&script_dir, dir);
+                app_msg_tx
+                    .send(crate::app::AppMsg::StartFrameCapture {
+                        dir,
+                        every: every.max(1) as usize,
+                    })
+                    .unwrap();
+            },
+        );
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("stop_frame_capture", move || {
+            app_msg_tx
+                .send(crate::app::AppMsg::StopFrameCapture)
+                .unwrap();
+        });
+
+        let frame_capture = self.shared_state.frame_capture.clone();
+        engine.register_fn("captured_frames", move || {
+            frame_capture
+                .load()
+                .map(|frames| frames as i64)
+                .unwrap_or(-1)
+        });
+
+        let graph = self.graph.clone();
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "load_node_metadata",
+            move |path: &str| -> std::result::Result<String, Box<rhai::EvalAltResult>> {
+                use crate::annotations::NodeMetadata;
+
+                let path = resolve_path(&script_dir, path);
+
+                let (metadata, report) =
+                    NodeMetadata::parse_tsv_file(&path, &graph).map_err(
+                        |err| {
+                            format!(
+                                "error loading node metadata from {}: {}",
+                                path.display(),
+                                err
+                            )
+                        },
+                    )?;
+
+                log::info!(
+                    "Loaded node metadata from {}: {}",
+                    path.display(),
+                    report
+                );
+
+                app_msg_tx
+                    .send(crate::app::AppMsg::AddNodeMetadata(metadata))
+                    .unwrap();
+
+                Ok(report.to_string())
+            },
+        );
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn(
+            "node_metadata_overlay",
+            move |file_name: &str, column: &str| {
+                app_msg_tx
+                    .send(crate::app::AppMsg::NodeMetadataOverlay {
+                        file_name: file_name.to_string(),
+                        column: column.to_string(),
+                    })
+                    .unwrap();
+            },
+        );
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "node_table_overlay",
+            move |path: &str, id_column: &str, value_column: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                let msg = node_table_overlay_msg(
+                    &resolve_path(&script_dir, path),
+                    id_column,
+                    value_column,
+                    "auto",
+                )?;
+                app_msg_tx.send(msg).unwrap();
+                Ok(())
+            },
+        );
+
+        let script_dir = self.script_dir.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "node_table_overlay",
+            move |path: &str, id_column: &str, value_column: &str, values: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                let msg = node_table_overlay_msg(
+                    &resolve_path(&script_dir, path),
+                    id_column,
+                    value_column,
+                    values,
+                )?;
+                app_msg_tx.send(msg).unwrap();
+                Ok(())
+            },
+        );
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "distance_overlay",
+            move |metric: &str, max_distance: i64| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::graph_query::DistanceMetric;
+
+                let metric = DistanceMetric::from_name(metric).ok_or_else(|| {
+                    format!(
+                        "unknown distance metric '{}', expected one of: {}, {}",
+                        metric,
+                        DistanceMetric::Hops.name(),
+                        DistanceMetric::BasePairs.name(),
+                    )
+                })?;
+
+                app_msg_tx
+                    .send(crate::app::AppMsg::DistanceOverlay {
+                        metric,
+                        max_distance: max_distance.max(0) as usize,
+                    })
+                    .unwrap();
+
+                Ok(())
+            },
+        );
+
+        let worker = self.graph_query_worker.clone();
+        let shared_state = self.shared_state.clone();
+        engine.register_result_fn(
+            "hash_overlay",
+            move |name: &str, hash_of: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::script::plugins::colors::hash_color;
+                use crate::script::{hash_node_paths, hash_node_seq};
+
+                let hash_fn: fn(&GraphQuery, NodeId) -> u64 = match hash_of {
+                    "sequence" => hash_node_seq,
+                    "paths" => hash_node_paths,
+                    _ => {
+                        return Err(format!(
+                            "unknown hash '{}', expected sequence or paths",
+                            hash_of
+                        )
+                        .into())
+                    }
+                };
+
+                let graph_query = worker.graph_query_arc().clone();
+
+                let task = worker.build_overlay_colors(move |_graph, handle| {
+                    let color = hash_color(hash_fn(&graph_query, handle.id()));
+                    rgb::RGB::new(color.r, color.g, color.b)
+                });
+
+                shared_state.set_overlay_build(name, task);
+
+                Ok(())
+            },
+        );
+
+        let graph = self.graph.clone();
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "highlight",
+            move |ctx: rhai::NativeCallContext,
+                  pred: rhai::FnPtr|
+                  -> std::result::Result<i64, Box<rhai::EvalAltResult>> {
+                let mut nodes = Vec::new();
+
+                for handle in graph.handles() {
+                    let node = handle.id();
+
+                    let result = pred.call_dynamic(
+                        &ctx,
+                        None,
+                        [rhai::Dynamic::from(node)],
+                    )?;
+
+                    let keep = result.as_bool().map_err(|ty| {
+                        format!(
+                            "highlight predicate must return a bool, got {}",
+                            ty
+                        )
+                    })?;
+
+                    if keep {
+                        nodes.push(node);
+                    }
+                }
+
+                let count = nodes.len() as i64;
+                app_msg_tx
+                    .send(crate::app::AppMsg::Highlight(nodes))
+                    .unwrap();
+
+                Ok(count)
+            },
+        );
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("clear_highlight", move || {
+            app_msg_tx.send(crate::app::AppMsg::ClearHighlight).unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("show_path_direction", move |path: PathId| {
+            app_msg_tx
+                .send(crate::app::AppMsg::SetPathEdges(Some(path)))
+                .unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("show_path_tube", move |path: PathId| {
+            app_msg_tx
+                .send(crate::app::AppMsg::SetPathTube(Some(path)))
+                .unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("clear_path_tube", move || {
+            app_msg_tx
+                .send(crate::app::AppMsg::SetPathTube(None))
+                .unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_fn("clear_path_direction", move || {
+            app_msg_tx
+                .send(crate::app::AppMsg::SetPathEdges(None))
+                .unwrap();
+        });
+
+        let app_msg_tx = self.channels.app_tx.clone();
+        engine.register_result_fn(
+            "run_layout",
+            move |name: &str| -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+                use crate::universe::LayoutAlgorithm;
+
+                let algorithm = LayoutAlgorithm::from_name(name).ok_or_else(|| {
+                    let names = LayoutAlgorithm::ALL
+                        .iter()
+                        .map(|a| a.name())
+                        .collect::<Vec<_>>();
+                    format!(
+                        "unknown layout '{}', expected one of: {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+
+                app_msg_tx
+                    .send(crate::app::AppMsg::RunLayout(algorithm))
+                    .unwrap();
+
+                Ok(())
+            },
+        );
+
         engine.register_fn("get", move |name: &str| {
             if let Some(getter) = get_set.getters.get(name) {
                 getter()
@@ -323,6 +1365,48 @@ impl Console<'static> {
 
         let handle = exported_module!(crate::script::plugins::handle_plugin);
 
+        // async graph queries; see `ScriptQuery` for the caveats
+        // of waiting on them from a script
+        engine.register_type::<ScriptQuery>();
+
+        engine.register_fn("is_ready", |query: &mut ScriptQuery| {
+            query.is_ready()
+        });
+
+        engine.register_fn("result", |query: &mut ScriptQuery| {
+            query.result().unwrap_or(rhai::Dynamic::UNIT)
+        });
+
+        engine.register_result_fn(
+            "wait",
+            |query: &mut ScriptQuery, timeout_ms: i64| -> ScriptEvalResult {
+                let timeout =
+                    std::time::Duration::from_millis(timeout_ms.max(0) as u64);
+
+                query.wait(timeout).ok_or_else(|| {
+                    format!("query timed out after {} ms", timeout_ms).into()
+                })
+            },
+        );
+
+        let worker = self.graph_query_worker.clone();
+
+        engine.register_fn("find_sequence", move |pattern: &str| {
+            let pattern = pattern.as_bytes().to_vec();
+
+            let result = worker.run_query(move |graph_query| async move {
+                let nodes = graph_query
+                    .nodes_with_sequence(&pattern)
+                    .into_iter()
+                    .map(rhai::Dynamic::from)
+                    .collect::<rhai::Array>();
+
+                rhai::Dynamic::from(nodes)
+            });
+
+            ScriptQuery::new(result)
+        });
+
         engine.register_fn("test_wait", || {
             println!("sleeping 2 seconds...");
             std::thread::sleep(std::time::Duration::from_millis(2000));
@@ -354,8 +1438,7 @@ impl Console<'static> {
         let _count = file.read_to_string(&mut script)?;
 
         if print {
-            self.output_history
-                .push(format!(">>> Evaluating file '{}'", path));
+            self.push_output(format!(">>> Evaluating file '{}'", path));
         }
 
         self.eval_line(reactor, print, &script)
@@ -415,8 +1498,16 @@ impl Console<'static> {
         Ok(())
     }
 
-    fn stop_interval(&mut self, handle_name: &str) {
-        self.remote_handles.remove(handle_name);
+    /// Returns `true` if there was an interval with the given handle
+    fn stop_interval(&mut self, handle_name: &str) -> bool {
+        self.remote_handles.remove(handle_name).is_some()
+    }
+
+    fn valid_handle_name(handle_name: &str) -> bool {
+        !handle_name.is_empty()
+            && handle_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
     }
 
     fn exec_console_command(&mut self, reactor: &mut Reactor) -> Result<bool> {
@@ -434,29 +1525,40 @@ impl Console<'static> {
 
             return Ok(true);
         } else if self.input_line.starts_with(":exec ") {
-            let file_path = &self.input_line[6..].to_string();
-            let result = self.eval_file(reactor, true, &file_path);
+            let file_path = self.input_line[6..].to_string();
+            self.input_line.clear();
+
+            let result = self.resolve_file(&file_path).and_then(|path| {
+                self.eval_file(reactor, true, &path.to_string_lossy())
+            });
 
             if let Err(err) = result {
+                self.push_output(format!(
+                    " >>> error executing file {}: {}",
+                    file_path.trim(),
+                    err
+                ));
+
                 debug!(
                     "console :exec of file '{}' failed: {:?}",
                     file_path, err
                 );
             }
-            self.input_line.clear();
 
             return Ok(true);
         } else if self.input_line.starts_with(":import ") {
             log::warn!("importing file");
-            let file_path = &self.input_line[8..].to_string();
-            let result = self.import_file(&file_path);
+            let file_path = self.input_line[8..].to_string();
+            let result = self
+                .resolve_file(&file_path)
+                .and_then(|path| self.import_file(&path.to_string_lossy()));
 
             if let Err(err) = result {
                 let msg = format!(
                     " >>> error importing file {}: {:?}",
                     file_path, err
                 );
-                self.output_history.push(msg);
+                self.push_output(msg);
 
                 log::warn!(
                     "console :import of file '{}' failed: {:?}",
@@ -471,19 +1573,119 @@ impl Console<'static> {
             let mut fields = self.input_line.split_ascii_whitespace();
 
             fields.next();
-            let file_name = fields.next();
-            let handle_name = fields.next();
+            let file_name = fields.next().map(String::from);
+            let handle_name = fields.next().map(String::from);
+
+            self.input_line.clear();
+
+            let (file, handle) = match (file_name, handle_name) {
+                (Some(file), Some(handle)) => (file, handle),
+                _ => {
+                    self.push_output(
+                        " >>> usage: :start_interval <file> <handle>"
+                            .to_string(),
+                    );
+                    return Ok(true);
+                }
+            };
+
+            if !Self::valid_handle_name(&handle) {
+                self.push_output(format!(
+                    " >>> invalid interval handle '{}', \
+                     use only letters, digits, '_' and '-'",
+                    handle
+                ));
+                return Ok(true);
+            }
 
-            if let (Some(file), Some(handle)) = (file_name, handle_name) {
-                let file = file.to_string();
-                let handle = handle.to_string();
-                self.eval_file_interval(reactor, &handle, &file)?;
+            if self.remote_handles.contains_key(&handle) {
+                self.push_output(format!(
+                    " >>> warning: replacing existing interval '{}'",
+                    handle
+                ));
+            }
+
+            let result = self.resolve_file(&file).and_then(|path| {
+                self.eval_file_interval(
+                    reactor,
+                    &handle,
+                    &path.to_string_lossy(),
+                )
+            });
+
+            if let Err(err) = result {
+                let msg = format!(
+                    " >>> error starting interval '{}' from file {}: {}",
+                    handle, file, err
+                );
+                self.push_output(msg);
+
+                log::warn!(
+                    "console :start_interval of file '{}' failed: {:?}",
+                    file,
+                    err
+                );
             }
 
+            return Ok(true);
+        } else if self.input_line.starts_with(":cd ") {
+            let dir = self.input_line[":cd ".len()..].to_string();
+            self.input_line.clear();
+
+            let resolved = resolve_path(&self.script_dir, &dir);
+
+            if resolved.is_dir() {
+                let resolved = resolved.canonicalize().unwrap_or(resolved);
+                self.push_output(format!(
+                    " >>> script directory: {}",
+                    resolved.display()
+                ));
+                self.script_dir = resolved;
+            } else {
+                self.push_output(format!(
+                    " >>> not a directory: {}",
+                    resolved.display()
+                ));
+            }
+
+            return Ok(true);
+        } else if self.input_line.starts_with(":pwd") {
+            self.input_line.clear();
+
+            self.push_output(format!(
+                " >>> script directory: {}",
+                self.script_dir.display()
+            ));
+
             return Ok(true);
         } else if self.input_line.starts_with(":end_interval ") {
-            let handle = &self.input_line[":end_interval ".len()..].to_string();
-            self.stop_interval(&handle);
+            let handle =
+                self.input_line[":end_interval ".len()..].trim().to_string();
+            self.input_line.clear();
+
+            if !self.stop_interval(&handle) {
+                self.push_output(format!(
+                    " >>> no interval with handle '{}'",
+                    handle
+                ));
+            }
+
+            return Ok(true);
+        } else if self.input_line.starts_with(":list_intervals") {
+            self.input_line.clear();
+
+            if self.remote_handles.is_empty() {
+                self.push_output(" >>> no active intervals".to_string());
+            } else {
+                let mut handles =
+                    self.remote_handles.keys().cloned().collect::<Vec<_>>();
+                handles.sort();
+
+                self.push_output(format!(
+                    " >>> active intervals: {}",
+                    handles.join(", ")
+                ));
+            }
 
             return Ok(true);
         }
@@ -519,20 +1721,20 @@ impl Console<'static> {
                     if let Some(color) =
                         result.clone().try_cast::<rgb::RGB<f32>>()
                     {
-                        self.output_history.push(format!("{}", color))
+                        self.push_output(format!("{}", color))
                     } else if let Some(color) =
                         result.clone().try_cast::<rgb::RGBA<f32>>()
                     {
-                        self.output_history.push(format!("{}", color));
+                        self.push_output(format!("{}", color));
                     } else {
-                        self.output_history.push(format!("{:?}", result));
+                        self.push_output(format!("{:?}", result));
                     }
                 }
             }
             Err(err) => {
                 debug!("Eval error: {:?}", err);
                 if print {
-                    self.output_history.push(format!("Error: {:?}", err));
+                    self.push_output(format!("Error: {:?}", err));
                 }
             }
         }
@@ -540,6 +1742,13 @@ impl Console<'static> {
         Ok(())
     }
 
+    fn push_output(&mut self, line: String) {
+        if self.settings.console_output_to_log().load() {
+            log::info!("[console] {}", line);
+        }
+        self.output_history.push(line);
+    }
+
     pub fn import_file(&mut self, file: &str) -> Result<()> {
         let engine = self.create_engine();
 
@@ -551,7 +1760,7 @@ impl Console<'static> {
 
         let msg = format!(
             " >>> imported {} variables, {} functions, and {} iterators from '{}'", vars, funcs, iters, file);
-        self.output_history.push(msg);
+        self.push_output(msg);
         self.modules.push(Arc::new(module));
 
         Ok(())
@@ -665,8 +1874,7 @@ impl Console<'static> {
                         self.input_line.pop();
 
                         self.input_history.push(self.input_line.clone());
-                        self.output_history
-                            .push(format!("> {}", self.input_line));
+                        self.push_output(format!("> {}", self.input_line));
 
                         self.eval_input(reactor, true).unwrap();
 
@@ -15,6 +15,7 @@ impl ViewDebugInfo {
                     view.center.x, view.center.y
                 ));
                 ui.label(format!("Scale: {}", view.scale));
+                ui.label(format!("Aspect: {}", view.aspect));
 
                 ui.separator();
 
@@ -1,3 +1,13 @@
+use handlegraph::{
+    handle::NodeId,
+    packedgraph::{paths::StepPtr, PackedGraph},
+    pathhandlegraph::*,
+};
+
+use bstr::ByteSlice;
+
+use crate::app::NodeLabelMode;
+
 pub fn grid_row_label(
     ui: &mut egui::Ui,
     id: egui::Id,
@@ -37,3 +47,130 @@ pub fn grid_row_label(
 
     row
 }
+
+/// Formats node references in the GUI according to the node label
+/// mode, so that tooltips, detail windows, and the node list can show
+/// either node IDs or reference path coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeLabels {
+    pub mode: NodeLabelMode,
+    pub reference_path: Option<PathId>,
+}
+
+impl NodeLabels {
+    /// At most this many positions are listed for a node that is
+    /// visited several times by the reference path
+    const MAX_POSITIONS: usize = 3;
+
+    /// The label of `node`, given its `positions` on all paths, as
+    /// returned by `GraphQuery::handle_positions`
+    pub fn label(
+        &self,
+        graph: &PackedGraph,
+        node: NodeId,
+        positions: &[(PathId, StepPtr, usize)],
+    ) -> String {
+        self.coordinates(graph, positions)
+            .unwrap_or_else(|| format!("Node {}", node))
+    }
+
+    /// Like `label`, but uses the bare node ID in node ID mode, for
+    /// table cells
+    pub fn short_label(
+        &self,
+        graph: &PackedGraph,
+        node: NodeId,
+        positions: &[(PathId, StepPtr, usize)],
+    ) -> String {
+        self.coordinates(graph, positions)
+            .unwrap_or_else(|| node.to_string())
+    }
+
+    /// The positions on the reference path, if in path coordinate
+    /// mode and the node is on the reference path
+    fn coordinates(
+        &self,
+        graph: &PackedGraph,
+        positions: &[(PathId, StepPtr, usize)],
+    ) -> Option<String> {
+        if self.mode != NodeLabelMode::PathCoordinate {
+            return None;
+        }
+
+        let path = self.reference_path?;
+
+        let mut on_path = positions
+            .iter()
+            .filter(|(p, _, _)| *p == path)
+            .map(|(_, _, pos)| *pos)
+            .collect::<Vec<_>>();
+
+        if on_path.is_empty() {
+            return None;
+        }
+
+        let path_name = graph.get_path_name_vec(path)?;
+        let (name, offset) = reference_name_offset(&path_name);
+
+        on_path.iter_mut().for_each(|pos| *pos += offset);
+        on_path.sort_unstable();
+        on_path.dedup();
+
+        Some(format_path_positions(&name, &on_path))
+    }
+}
+
+/// The name of the sequence a path covers, and the offset of the path
+/// start on that sequence, from path names like `chr1:1000-2000`;
+/// path names without a range are returned as-is, with offset 0
+pub fn reference_name_offset(path_name: &[u8]) -> (String, usize) {
+    match crate::annotations::path_name_range(path_name) {
+        Some((start, _)) => {
+            let name = path_name.split_str(":").next().unwrap_or_default();
+            (name.to_str_lossy().to_string(), start)
+        }
+        None => (path_name.to_str_lossy().to_string(), 0),
+    }
+}
+
+/// Formats positions on a sequence as `name:pos`, listing at most a
+/// few positions when there are several
+pub fn format_path_positions(name: &str, positions: &[usize]) -> String {
+    let shown = positions
+        .iter()
+        .take(NodeLabels::MAX_POSITIONS)
+        .map(|pos| pos.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if positions.len() > NodeLabels::MAX_POSITIONS {
+        format!(
+            "{}:{} (+{} more)",
+            name,
+            shown,
+            positions.len() - NodeLabels::MAX_POSITIONS
+        )
+    } else {
+        format!("{}:{}", name, shown)
+    }
+}
+
+/// Opens `url` in the default web browser
+pub fn open_url(url: &str) -> anyhow::Result<()> {
+    use std::process::Command;
+
+    let mut command = if cfg!(target_os = "windows") {
+        // not `cmd /C start`, as cmd would split the URL at each `&`
+        let mut cmd = Command::new("rundll32");
+        cmd.arg("url.dll,FileProtocolHandler");
+        cmd
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+
+    command.arg(url).spawn()?;
+
+    Ok(())
+}
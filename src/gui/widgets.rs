@@ -8,8 +8,24 @@ use handlegraph::{
     pathhandlegraph::*,
 };
 
-use crate::app::AppMsg;
-use crate::{app::OverlayState, geometry::*};
+use bstr::ByteSlice;
+use rustc_hash::FxHashMap;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::annotations::Annotations;
+use crate::app::{AppMsg, NodeLabelMode};
+use crate::graph_query::{GraphQuery, GraphQueryRequest, GraphQueryResp};
+use crate::gui::util::{
+    format_path_positions, reference_name_offset, NodeLabels,
+};
+use crate::universe::LayoutAlgorithm;
+use crate::view::View;
+use crate::{
+    app::{OverlayState, Ruler},
+    geometry::*,
+};
 
 pub trait Widget {
     fn id() -> &'static str;
@@ -24,6 +40,9 @@ pub trait Widget {
 
 pub struct MenuBar {
     overlay_state: OverlayState,
+    ruler: Ruler,
+    follow_selection: Arc<AtomicCell<bool>>,
+    layout_progress: Arc<AtomicCell<Option<(LayoutAlgorithm, f32)>>>,
 
     height: AtomicCell<f32>,
 }
@@ -31,9 +50,17 @@ pub struct MenuBar {
 impl MenuBar {
     pub const ID: &'static str = "app_menu_bar";
 
-    pub fn new(overlay_state: OverlayState) -> Self {
+    pub fn new(
+        overlay_state: OverlayState,
+        ruler: Ruler,
+        follow_selection: Arc<AtomicCell<bool>>,
+        layout_progress: Arc<AtomicCell<Option<(LayoutAlgorithm, f32)>>>,
+    ) -> Self {
         Self {
             overlay_state,
+            ruler,
+            follow_selection,
+            layout_progress,
             height: AtomicCell::new(0.0),
         }
     }
@@ -55,7 +82,12 @@ impl MenuBar {
         let label_set_list = &mut open_windows.label_set_list;
 
         let nodes = &mut open_windows.nodes;
+        let selection_groups = &mut open_windows.selection_groups;
+        let sequence_search = &mut open_windows.sequence_search;
+        let graph_info = &mut open_windows.graph_info;
+        let frame_capture = &mut open_windows.frame_capture;
         let paths = &mut open_windows.paths;
+        let reference_path = &mut open_windows.reference_path;
 
         let _themes = &mut open_windows.themes;
         let overlays = &mut open_windows.overlays;
@@ -67,19 +99,62 @@ impl MenuBar {
 
             menu::bar(ui, |ui| {
                 menu::menu(ui, "Graph", |ui| {
+                    if ui.selectable_label(*graph_info, "Graph info").clicked()
+                    {
+                        *graph_info = !*graph_info;
+                    }
+
                     if ui.selectable_label(*nodes, "Nodes").clicked() {
                         *nodes = !*nodes;
                     }
 
+                    if ui
+                        .selectable_label(*selection_groups, "Selection groups")
+                        .clicked()
+                    {
+                        *selection_groups = !*selection_groups;
+                    }
+
+                    if ui
+                        .selectable_label(*sequence_search, "Sequence search")
+                        .clicked()
+                    {
+                        *sequence_search = !*sequence_search;
+                    }
+
                     if ui.selectable_label(*paths, "Paths").clicked() {
                         *paths = !*paths;
                     }
+
+                    if ui
+                        .selectable_label(*reference_path, "Reference path")
+                        .clicked()
+                    {
+                        *reference_path = !*reference_path;
+                    }
                 });
 
                 // if ui.selectable_label(*themes, "Themes").clicked() {
                 //     *themes = !*themes;
                 // }
 
+                menu::menu(ui, "Layout", |ui| {
+                    let running = self.layout_progress.load().is_some();
+
+                    for &algorithm in LayoutAlgorithm::ALL.iter() {
+                        let button = ui.add(
+                            egui::Button::new(algorithm.display_name())
+                                .enabled(!running),
+                        );
+
+                        if button.clicked() {
+                            app_msg_tx
+                                .send(AppMsg::RunLayout(algorithm))
+                                .unwrap();
+                        }
+                    }
+                });
+
                 menu::menu(ui, "Annotations", |ui| {
                     if ui.selectable_label(*annotation_files, "Files").clicked()
                     {
@@ -114,7 +189,17 @@ impl MenuBar {
                         )
                         .clicked()
                     {
-                        self.overlay_state.toggle_overlay()
+                        app_msg_tx.send(AppMsg::ToggleOverlay).unwrap();
+                    }
+
+                    if ui
+                        .selectable_label(
+                            self.overlay_state.show_legend(),
+                            "Show legend",
+                        )
+                        .clicked()
+                    {
+                        self.overlay_state.toggle_legend()
                     }
                 });
 
@@ -122,12 +207,58 @@ impl MenuBar {
                     if ui.button("Goto selection").clicked() {
                         app_msg_tx.send(AppMsg::GotoSelection).unwrap();
                     }
+
+                    if ui
+                        .selectable_label(
+                            self.follow_selection.load(),
+                            "Follow selection",
+                        )
+                        .on_hover_text(
+                            "Move the view to the selection whenever the \
+                             selection changes",
+                        )
+                        .clicked()
+                    {
+                        self.follow_selection.fetch_xor(true);
+                    }
+
+                    if ui.button("Exit path tube view").clicked() {
+                        app_msg_tx.send(AppMsg::SetPathTube(None)).unwrap();
+                    }
+
+                    if ui.button("Copy view link").clicked() {
+                        app_msg_tx.send(AppMsg::CopyViewLink).unwrap();
+                    }
+
+                    if ui.button("Open view link from clipboard").clicked() {
+                        app_msg_tx
+                            .send(AppMsg::OpenViewLinkFromClipboard)
+                            .unwrap();
+                    }
                 });
 
                 menu::menu(ui, "Tools", |ui| {
                     if ui.selectable_label(*settings, "Settings").clicked() {
                         *settings = !*settings;
                     }
+
+                    if ui
+                        .selectable_label(self.ruler.enabled(), "Ruler")
+                        .on_hover_text(
+                            "Measure between two points placed with the \
+                             select button",
+                        )
+                        .clicked()
+                    {
+                        self.ruler.toggle();
+                    }
+
+                    if ui
+                        .selectable_label(*frame_capture, "Frame capture")
+                        .clicked()
+                    {
+                        *frame_capture = !*frame_capture;
+                    }
                 });
             });
         });
@@ -145,6 +276,245 @@ pub struct NodeInfo {
     coverage: usize,
 }
 
+/// Tooltip with the stats and annotation labels of the hovered node.
+///
+/// The stats are only queried once the cursor has rested on the same
+/// node for the hover debounce interval, and are cached until the
+/// hovered node changes
+#[derive(Debug, Default, Clone)]
+pub struct NodeTooltip {
+    hover: Option<(NodeId, Instant)>,
+    last_mouse_pos: Point,
+    info: Option<NodeInfo>,
+    labels: Vec<String>,
+    /// `column: value` lines from the loaded node metadata tables
+    metadata: Vec<String>,
+
+    /// The reference path the positions were fetched for, the name
+    /// of the sequence it covers, and the positions of the node on it
+    reference: Option<(PathId, String, Vec<usize>)>,
+}
+
+impl NodeTooltip {
+    const ID: &'static str = "node_hover_tooltip";
+
+    /// How far, in pixels, the cursor can move between frames while
+    /// still counting as resting on the node
+    const SETTLE_DISTANCE: f32 = 2.0;
+
+    const MAX_LABELS: usize = 10;
+
+    fn fetch(
+        &mut self,
+        graph_query: &GraphQuery,
+        annotations: &Annotations,
+        node: NodeId,
+        reference_path: Option<PathId>,
+    ) {
+        let resp = graph_query
+            .query_request_blocking(GraphQueryRequest::NodeStats(node));
+
+        match resp {
+            GraphQueryResp::NodeStats {
+                node_id,
+                len,
+                degree,
+                coverage,
+            } => {
+                self.info = Some(NodeInfo {
+                    node_id,
+                    len,
+                    degree,
+                    coverage,
+                });
+            }
+            GraphQueryResp::NodeNotFound(node_id) => {
+                log::debug!("Hovered node {} not found", node_id);
+                return;
+            }
+            _ => (),
+        }
+
+        self.labels.clear();
+
+        for label_set in annotations.visible_label_sets() {
+            if let Some(indices) = label_set.labels().get(&node) {
+                let strings = label_set.label_strings();
+                self.labels.extend(
+                    indices.iter().filter_map(|&ix| strings.get(ix)).cloned(),
+                );
+            }
+        }
+
+        self.metadata.clear();
+
+        for metadata in annotations.node_metadata() {
+            self.metadata.extend(
+                metadata
+                    .node_values(node)
+                    .map(|(column, value)| format!("{}: {}", column, value)),
+            );
+        }
+
+        self.reference = reference_path.and_then(|path| {
+            let graph = graph_query.graph();
+            let path_name = graph.get_path_name_vec(path)?;
+            let (name, offset) = reference_name_offset(&path_name);
+
+            let mut positions = graph_query
+                .handle_positions(Handle::pack(node, false))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(step_path, _, _)| *step_path == path)
+                .map(|(_, _, pos)| pos + offset)
+                .collect::<Vec<_>>();
+            positions.sort_unstable();
+
+            Some((path, name, positions))
+        });
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        graph_query: &GraphQuery,
+        annotations: &Annotations,
+        hover_node: Option<NodeId>,
+        node_labels: NodeLabels,
+        mouse_pos: Point,
+        hover_debounce: Duration,
+    ) {
+        let reference_path = node_labels.reference_path;
+
+        let mouse_moved =
+            mouse_pos.dist(self.last_mouse_pos) > Self::SETTLE_DISTANCE;
+        self.last_mouse_pos = mouse_pos;
+
+        if hover_node != self.hover.map(|(node, _)| node) {
+            self.hover = hover_node.map(|node| (node, Instant::now()));
+            self.info = None;
+            self.labels.clear();
+            self.metadata.clear();
+            self.reference = None;
+            return;
+        }
+
+        let fetched_reference =
+            self.reference.as_ref().map(|(path, _, _)| *path);
+
+        if self.info.is_some() && fetched_reference != reference_path {
+            self.info = None;
+        }
+
+        let (node, hover_start) = if let Some(hover) = self.hover {
+            hover
+        } else {
+            return;
+        };
+
+        if self.info.is_none() {
+            // restart the interval until the cursor settles, so that
+            // sweeping across a node doesn't trigger a query
+            if mouse_moved {
+                self.hover = Some((node, Instant::now()));
+                return;
+            }
+
+            if hover_start.elapsed() < hover_debounce {
+                return;
+            }
+            self.fetch(graph_query, annotations, node, reference_path);
+        }
+
+        let info = if let Some(info) = self.info {
+            info
+        } else {
+            return;
+        };
+
+        let pos = mouse_pos + Point::new(16.0, 16.0);
+
+        egui::Area::new(Self::ID)
+            .order(egui::Order::Tooltip)
+            .fixed_pos(pos)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let coordinates = self
+                        .reference
+                        .as_ref()
+                        .filter(|(_, _, positions)| {
+                            node_labels.mode == NodeLabelMode::PathCoordinate
+                                && !positions.is_empty()
+                        })
+                        .map(|(_, name, positions)| {
+                            format_path_positions(name, positions)
+                        });
+
+                    if let Some(coordinates) = &coordinates {
+                        ui.label(coordinates);
+                    }
+                    ui.label(format!("Node {}", info.node_id));
+                    ui.separator();
+                    ui.label(format!("Length: {}", info.len));
+                    ui.label(format!(
+                        "Degree: ({}, {})",
+                        info.degree.0, info.degree.1
+                    ));
+                    ui.label(format!("Coverage: {}", info.coverage));
+
+                    if let (Some((_, name, positions)), None) =
+                        (&self.reference, &coordinates)
+                    {
+                        ui.separator();
+
+                        let positions = if positions.is_empty() {
+                            "not on path".to_string()
+                        } else {
+                            positions
+                                .iter()
+                                .map(|pos| pos.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        };
+
+                        ui.label(format!("{}: {}", name, positions));
+                    }
+
+                    if !self.labels.is_empty() {
+                        ui.separator();
+
+                        for label in self.labels.iter().take(Self::MAX_LABELS) {
+                            ui.label(label);
+                        }
+
+                        if self.labels.len() > Self::MAX_LABELS {
+                            ui.label(format!(
+                                "... and {} more",
+                                self.labels.len() - Self::MAX_LABELS
+                            ));
+                        }
+                    }
+
+                    if !self.metadata.is_empty() {
+                        ui.separator();
+
+                        for line in self.metadata.iter().take(Self::MAX_LABELS)
+                        {
+                            ui.label(line);
+                        }
+
+                        if self.metadata.len() > Self::MAX_LABELS {
+                            ui.label(format!(
+                                "... and {} more",
+                                self.metadata.len() - Self::MAX_LABELS
+                            ));
+                        }
+                    }
+                });
+            });
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub struct FrameRate {
     pub fps: f32,
@@ -198,6 +568,142 @@ impl Widget for FrameRate {
     }
 }
 
+/// Draws the ruler line in the main view, with a readout of the
+/// world space distance and, if both ends are on nodes that share a
+/// path, the distance in base pairs along that path
+#[derive(Debug, Default, Clone)]
+pub struct RulerView {
+    /// The end nodes of the last measurement, and the path name and
+    /// base pair distance found for them
+    path_distance: Option<((NodeId, NodeId), Option<(String, usize)>)>,
+}
+
+impl RulerView {
+    const ID: &'static str = "ruler_readout";
+
+    /// The distance between the starts of the two nodes along a path
+    /// they are both on, preferring the reference path; if a node is
+    /// visited more than once, the closest pair of visits is used
+    fn path_distance(
+        graph_query: &GraphQuery,
+        reference_path: Option<PathId>,
+        from: NodeId,
+        to: NodeId,
+    ) -> Option<(String, usize)> {
+        let from_pos =
+            graph_query.handle_positions(Handle::pack(from, false))?;
+        let to_pos = graph_query.handle_positions(Handle::pack(to, false))?;
+
+        let mut closest: FxHashMap<PathId, usize> = FxHashMap::default();
+
+        for &(path, _, a) in from_pos.iter() {
+            for &(_, _, b) in to_pos.iter().filter(|(p, _, _)| *p == path) {
+                let dist = if a > b { a - b } else { b - a };
+                let entry = closest.entry(path).or_insert(dist);
+                *entry = (*entry).min(dist);
+            }
+        }
+
+        let (path, dist) = reference_path
+            .and_then(|path| Some((path, *closest.get(&path)?)))
+            .or_else(|| {
+                closest.into_iter().min_by_key(|&(path, dist)| (dist, path))
+            })?;
+
+        let name = graph_query.graph().get_path_name_vec(path)?;
+
+        Some((name.to_str_lossy().into_owned(), dist))
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        graph_query: &GraphQuery,
+        ruler: &Ruler,
+        view: View,
+        mouse_pos: Point,
+        reference_path: Option<PathId>,
+    ) {
+        let start = if let Some(start) = ruler.start() {
+            start
+        } else {
+            self.path_distance = None;
+            return;
+        };
+
+        let end = ruler.end();
+
+        let screen_rect = ctx.input().screen_rect();
+        let dims = Point::new(screen_rect.width(), screen_rect.height());
+
+        let to_screen =
+            |world: Point| view.world_point_to_screen(world) + dims / 2.0;
+
+        let start_screen = to_screen(start.world);
+
+        // until the second point is placed, the line follows the cursor
+        let end_screen = end.map(|p| to_screen(p.world)).unwrap_or(mouse_pos);
+
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new(Self::ID),
+        ));
+
+        let color = ctx.style().visuals.text_color();
+        let stroke = egui::Stroke::new(2.0, color);
+
+        painter.line_segment([start_screen.into(), end_screen.into()], stroke);
+        painter.circle_filled(start_screen.into(), 4.0, color);
+
+        let end = if let Some(end) = end {
+            end
+        } else {
+            return;
+        };
+
+        painter.circle_filled(end_screen.into(), 4.0, color);
+
+        let nodes = start.node.zip(end.node);
+
+        if nodes != self.path_distance.as_ref().map(|(nodes, _)| *nodes) {
+            self.path_distance = nodes.map(|(from, to)| {
+                let dist =
+                    Self::path_distance(graph_query, reference_path, from, to);
+                ((from, to), dist)
+            });
+        }
+
+        let world_dist = start.world.dist(end.world);
+
+        let pos = (start_screen + end_screen) / 2.0 + Point::new(12.0, 12.0);
+
+        egui::Area::new(Self::ID)
+            .order(egui::Order::Foreground)
+            .fixed_pos(pos)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(format!("Distance: {:.2}", world_dist));
+
+                    match &self.path_distance {
+                        Some((_, Some((path_name, dist)))) => {
+                            ui.label(format!("{} bp on {}", dist, path_name));
+                        }
+                        Some((_, None)) => {
+                            ui.label("Nodes share no path");
+                        }
+                        None => {
+                            ui.label("Both ends must be on nodes for bp");
+                        }
+                    }
+
+                    if ui.button("Clear").clicked() {
+                        ruler.clear();
+                    }
+                });
+            });
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub struct GraphStats {
     pub node_count: usize,
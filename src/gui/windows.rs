@@ -1,19 +1,29 @@
 pub mod annotations;
 pub mod file;
 pub mod filters;
+pub mod frame_capture;
 pub mod graph_details;
+pub mod graph_info;
 pub mod graph_picker;
+pub mod legend;
 pub mod overlays;
 pub mod paths;
+pub mod selection_groups;
+pub mod sequence_search;
 pub mod settings;
 pub mod util;
 
 pub use annotations::*;
 pub use file::*;
 pub use filters::*;
+pub use frame_capture::*;
 pub use graph_details::*;
+pub use graph_info::*;
 pub use graph_picker::*;
+pub use legend::*;
 pub use overlays::*;
 pub use paths::*;
+pub use selection_groups::*;
+pub use sequence_search::*;
 pub use settings::*;
 pub use util::*;
@@ -30,15 +30,15 @@ use rustc_hash::FxHashMap;
 
 use crate::{
     annotations::{
-        record_column_hash_color, AnnotationCollection, AnnotationFileType,
-        AnnotationLabelSet, AnnotationRecord, Annotations, BedRecords,
-        ColumnKey, Gff3Records,
+        record_column_hash_color, record_column_label, AnnotationCollection,
+        AnnotationFileType, AnnotationLabelSet, AnnotationRecord, Annotations,
+        BedRecords, ColumnKey, Gff3Records,
     },
     app::AppMsg,
     geometry::Point,
     graph_query::{GraphQuery, GraphQueryWorker},
     gui::{util::grid_row_label, GuiMsg, Windows},
-    overlays::OverlayData,
+    overlays::{OverlayData, OverlayLegend},
     reactor::{Host, Outbox, Reactor},
 };
 
@@ -687,6 +687,11 @@ where
                         ))
                     })?;
 
+                let path_len = graph
+                    .path_positions()
+                    .path_base_len(input.path)
+                    .unwrap_or_default();
+
                 let offset =
                     graph.graph().get_path_name_vec(input.path).and_then(
                         |name| crate::annotations::path_name_offset(&name),
@@ -696,8 +701,8 @@ where
 
                 running_msg("Calculating node colors");
 
-                let colors_vec: Vec<(Vec<NodeId>, rgb::RGBA<f32>)> = rayon_pool
-                    .install(|| {
+                let colors_vec: Vec<(Vec<NodeId>, rgb::RGBA<f32>, String)> =
+                    rayon_pool.install(|| {
                         indices
                             .into_par_iter()
                             .filter_map(|&ix| {
@@ -708,9 +713,13 @@ where
                                     &input.column,
                                 )?;
 
+                                let label =
+                                    record_column_label(record, &input.column);
+
                                 let range =
                                     crate::annotations::path_step_range(
                                         &steps,
+                                        path_len,
                                         offset,
                                         record.start(),
                                         record.end(),
@@ -721,7 +730,7 @@ where
                                     .map(|(h, _, _)| h.id())
                                     .collect();
 
-                                Some((ids, color))
+                                Some((ids, color, label))
                             })
                             .collect::<Vec<_>>()
                     });
@@ -729,10 +738,14 @@ where
                 let mut node_colors: FxHashMap<NodeId, rgb::RGBA<f32>> =
                     FxHashMap::default();
 
-                for (ids, color) in colors_vec {
+                let mut categories: FxHashMap<String, rgb::RGBA<f32>> =
+                    FxHashMap::default();
+
+                for (ids, color, label) in colors_vec {
                     for id in ids {
                         node_colors.insert(id, color);
                     }
+                    categories.insert(label, color);
                 }
 
                 let mut data = vec![
@@ -751,6 +764,9 @@ where
                     .send(OverlayCreatorMsg::NewOverlay {
                         name: input.name,
                         data: overlay_data,
+                        legend: Some(OverlayLegend::from_categories(
+                            categories,
+                        )),
                     })
                     .unwrap();
 
@@ -793,6 +809,7 @@ where
         let offset = crate::annotations::path_name_offset(path_name.as_bytes());
 
         let steps = graph.path_pos_steps(path_id)?;
+        let path_len = graph.path_positions().path_base_len(path_id)?;
 
         let mut label_strings: Vec<String> =
             Vec::with_capacity(record_indices.len());
@@ -804,6 +821,7 @@ where
 
             if let Some(range) = crate::annotations::path_step_range(
                 &steps,
+                path_len,
                 offset,
                 record.start(),
                 record.end(),
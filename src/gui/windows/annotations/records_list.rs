@@ -1,9 +1,10 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use bstr::ByteSlice;
-use crossbeam::channel::Sender;
+use crossbeam::{atomic::AtomicCell, channel::Sender};
 use handlegraph::pathhandlegraph::PathId;
+use parking_lot::RwLock;
 use rustc_hash::FxHashSet;
 
 #[allow(unused_imports)]
@@ -11,11 +12,13 @@ use log::{debug, error, info, trace, warn};
 
 use crate::reactor::Reactor;
 use crate::{
-    annotations::{AnnotationCollection, AnnotationRecord, ColumnKey},
-    app::AppMsg,
+    annotations::{
+        AnnotationCollection, AnnotationRecord, ColumnKey, PathRangeReport,
+    },
+    app::{AppMsg, DefaultColumns, UrlTemplate},
     graph_query::{GraphQuery, GraphQueryWorker},
     gui::{
-        util::grid_row_label,
+        util::{grid_row_label, open_url},
         windows::{graph_picker::PathPicker, overlays::OverlayCreatorMsg},
     },
 };
@@ -39,14 +42,24 @@ where
 
     column_picker_open: bool,
     enabled_columns: HashMap<String, ColumnPickerMany<C::ColumnKey>>,
-    default_enabled_columns: HashSet<C::ColumnKey>,
-    default_hidden_columns: HashSet<C::ColumnKey>,
+    default_columns: Arc<RwLock<DefaultColumns>>,
 
     path_picker_open: bool,
     path_picker: PathPicker,
 
+    /// The picker follows the global reference path whenever it
+    /// changes, but can be set to another path in between
+    reference_path: Arc<AtomicCell<Option<PathId>>>,
+    last_reference_path: Option<PathId>,
+
+    /// How many records of the current file don't fit in the active
+    /// path, for the file and path it was computed for
+    path_range_report: Option<(String, PathId, PathRangeReport)>,
+
     creator_open: bool,
     creator: OverlayLabelSetCreator<C>,
+
+    url_templates: Arc<RwLock<Vec<UrlTemplate>>>,
 }
 
 impl<C> RecordList<C>
@@ -57,6 +70,9 @@ where
         reactor: &mut Reactor,
         id: egui::Id,
         path_picker: PathPicker,
+        reference_path: Arc<AtomicCell<Option<PathId>>>,
+        url_templates: Arc<RwLock<Vec<UrlTemplate>>>,
+        default_columns: Arc<RwLock<DefaultColumns>>,
     ) -> Self {
         let filtered_records = Vec::new();
 
@@ -74,30 +90,24 @@ where
 
             column_picker_open: false,
             enabled_columns: HashMap::default(),
-            default_enabled_columns: Default::default(),
-            default_hidden_columns: Default::default(),
+            default_columns,
 
             path_picker_open: false,
             path_picker,
 
+            reference_path,
+            last_reference_path: None,
+
+            path_range_report: None,
+
             creator_open: false,
             creator: OverlayLabelSetCreator::new(
                 reactor,
                 egui::Id::new("overlay_label_set_creator"),
             ),
-        }
-    }
-
-    pub fn set_default_columns(
-        &mut self,
-        enabled_columns: impl IntoIterator<Item = C::ColumnKey>,
-        hidden_columns: impl IntoIterator<Item = C::ColumnKey>,
-    ) {
-        self.default_enabled_columns.clear();
-        self.default_enabled_columns.extend(enabled_columns);
 
-        self.default_hidden_columns.clear();
-        self.default_hidden_columns.extend(hidden_columns);
+            url_templates,
+        }
     }
 
     pub fn scroll_to_label_record(
@@ -176,6 +186,47 @@ where
         resp
     }
 
+    /// Counts the records of the file that fall outside the active
+    /// path, if the file or the path have changed
+    fn update_path_range_report(
+        &mut self,
+        graph_query: &GraphQuery,
+        file_name: &str,
+        records: &C,
+    ) {
+        let (path_id, path_name) = match self.path_picker.active_path() {
+            Some(active) => active,
+            None => {
+                self.path_range_report = None;
+                return;
+            }
+        };
+
+        if let Some((file, path, _)) = &self.path_range_report {
+            if file == file_name && *path == path_id {
+                return;
+            }
+        }
+
+        let path_len = graph_query.path_positions().path_base_len(path_id);
+
+        let report = path_len.map(|path_len| {
+            let offset =
+                crate::annotations::path_name_offset(path_name.as_bytes());
+
+            PathRangeReport::new(records, path_len, offset)
+        });
+
+        if let Some(report) = report {
+            if report.out_of_range > 0 {
+                warn!("{}: {} of path {}", file_name, report, path_name);
+            }
+        }
+
+        self.path_range_report =
+            report.map(|report| (file_name.to_string(), path_id, report));
+    }
+
     fn select_record(
         &self,
         app_msg_tx: &crossbeam::channel::Sender<AppMsg>,
@@ -185,19 +236,16 @@ where
         let active_path = self.path_picker.active_path();
 
         if let Some((path_id, name)) = active_path {
-            let mut start = record.start();
-            let mut end = record.end();
-
-            if let Some(offset) =
-                crate::annotations::path_name_offset(name.as_bytes())
-            {
-                start -= offset;
-                end -= offset;
-            }
+            let offset = crate::annotations::path_name_offset(name.as_bytes())
+                .unwrap_or(0);
 
-            if let Some(range) =
+            // records that start before the path are out of range
+            let range = record.start().checked_sub(offset).and_then(|start| {
+                let end = record.end().checked_sub(offset)?;
                 graph_query.path_basepair_range(path_id, start, end)
-            {
+            });
+
+            if let Some(range) = range {
                 let nodes = range
                     .into_iter()
                     .map(|(handle, _, _)| handle.id())
@@ -208,6 +256,40 @@ where
                 let select = Select::Many { nodes, clear: true };
                 let msg = AppMsg::Selection(select);
                 app_msg_tx.send(msg).unwrap();
+            } else {
+                warn!(
+                    "Record range {}-{} is outside the range of path {}",
+                    record.start(),
+                    record.end(),
+                    name
+                );
+            }
+        }
+    }
+
+    /// Lists the configured URL templates, opening the one that's
+    /// clicked filled in with the record's range
+    fn open_externally_ui(&self, ui: &mut egui::Ui, record: &C::Record) {
+        let templates = self.url_templates.read();
+
+        if templates.is_empty() {
+            ui.label("No URL templates configured");
+            return;
+        }
+
+        let chr = record.seq_id().to_str_lossy();
+
+        for template in templates.iter() {
+            let label = format!("Open in {}", template.name);
+
+            if ui.button(label).clicked() {
+                let url = template.fill(&chr, record.start(), record.end());
+
+                if let Err(err) = open_url(&url) {
+                    error!("Error opening URL {}: {:?}", url, err);
+                }
+
+                ui.memory().close_popup();
             }
         }
     }
@@ -257,6 +339,15 @@ where
         file_name: &str,
         records: &Arc<C>,
     ) {
+        let reference_path = self.reference_path.load();
+
+        if reference_path != self.last_reference_path {
+            self.last_reference_path = reference_path;
+            if reference_path.is_some() {
+                self.path_picker.set_active_path(reference_path);
+            }
+        }
+
         let active_path_name = self
             .path_picker
             .active_path()
@@ -268,12 +359,18 @@ where
 
             enabled_columns.update_columns(records.as_ref());
 
-            for col in self.default_enabled_columns.iter() {
-                enabled_columns.set_column(col, true);
-            }
+            let default_columns = self.default_columns.read();
 
-            for col in self.default_hidden_columns.iter() {
-                enabled_columns.hide_column_from_gui(col, true);
+            for col in records.all_columns() {
+                let name = col.to_string();
+
+                if default_columns.is_enabled(&name) {
+                    enabled_columns.set_column(&col, true);
+                }
+
+                if default_columns.is_hidden(&name) {
+                    enabled_columns.hide_column_from_gui(&col, true);
+                }
             }
 
             self.enabled_columns
@@ -305,6 +402,12 @@ where
 
         self.path_picker.ui(ui.ctx(), &mut self.path_picker_open);
 
+        self.update_path_range_report(
+            graph_query.graph(),
+            file_name,
+            records.as_ref(),
+        );
+
         if let Some(path) = self.path_picker.active_path().map(|(p, _)| p) {
             if self
                 .creator
@@ -441,6 +544,18 @@ where
             }
         });
 
+        if let Some((_, _, report)) = &self.path_range_report {
+            if report.out_of_range > 0 {
+                ui.colored_label(egui::Color32::YELLOW, report.to_string())
+                    .on_hover_text(
+                        "These records extend past the ends of the path, \
+                         and select only part of their range, or nothing. \
+                         The annotation file may be for another reference \
+                         or assembly.",
+                    );
+            }
+        }
+
         let enabled_columns = self.enabled_columns.get(file_name).unwrap();
 
         let mut spacing = ui.spacing().item_spacing;
@@ -560,6 +675,21 @@ where
                         if row_interact.double_clicked() {
                             app_msg_tx.send(AppMsg::GotoSelection).unwrap();
                         }
+
+                        // right-clicking a row opens a menu for opening
+                        // the record in a genome browser
+                        let popup_id = self.id.with("open_externally").with(i);
+
+                        if row_interact.secondary_clicked() {
+                            ui.memory().toggle_popup(popup_id);
+                        }
+
+                        crate::gui::windows::util::popup_below_widget(
+                            ui,
+                            popup_id,
+                            &row_interact,
+                            |ui| self.open_externally_ui(ui, record),
+                        );
                     }
                 }
             });
@@ -0,0 +1,91 @@
+use crossbeam::channel::Sender;
+
+use std::path::PathBuf;
+
+use crate::app::{AppMsg, SharedState};
+
+/// Controls for saving the drawn frames as numbered PNGs, e.g. while
+/// an interval script animates the view, to be assembled into a
+/// video with an external tool
+pub struct FrameCaptureWindow {
+    dir: String,
+    every: usize,
+}
+
+impl std::default::Default for FrameCaptureWindow {
+    fn default() -> Self {
+        Self {
+            dir: "frames".to_string(),
+            every: 1,
+        }
+    }
+}
+
+impl FrameCaptureWindow {
+    pub const ID: &'static str = "frame_capture_window";
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+        shared_state: &SharedState,
+        app_msg_tx: &Sender<AppMsg>,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        let captured = shared_state.frame_capture();
+
+        egui::Window::new("Frame capture")
+            .id(egui::Id::new(Self::ID))
+            .default_pos(egui::Pos2::new(300.0, 200.0))
+            .open(open)
+            .show(ctx, |ui| {
+                let running = captured.is_some();
+
+                ui.horizontal(|ui| {
+                    ui.label("Directory");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.dir)
+                            .enabled(!running),
+                    );
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Capture every");
+                    ui.add(
+                        egui::DragValue::new::<usize>(&mut self.every)
+                            .clamp_range(1..=1000)
+                            .suffix(" frames"),
+                    );
+                });
+
+                ui.separator();
+
+                if let Some(frames) = captured {
+                    ui.label(format!("Captured {} frames", frames));
+
+                    if ui.button("Stop").clicked() {
+                        app_msg_tx.send(AppMsg::StopFrameCapture).unwrap();
+                    }
+                } else {
+                    let start = ui
+                        .add(
+                            egui::Button::new("Start")
+                                .enabled(!self.dir.trim().is_empty()),
+                        )
+                        .on_hover_text(
+                            "Frames are saved as frame_000000.png, \
+                             frame_000001.png, and so on; existing \
+                             frames in the directory are overwritten",
+                        );
+
+                    if start.clicked() {
+                        app_msg_tx
+                            .send(AppMsg::StartFrameCapture {
+                                dir: PathBuf::from(self.dir.trim()),
+                                every: self.every,
+                            })
+                            .unwrap();
+                    }
+                }
+            })
+    }
+}
@@ -16,8 +16,10 @@ use std::sync::Arc;
 
 use bstr::ByteSlice;
 
+use crate::annotations::Annotations;
+use crate::graph_query::GraphQuery;
+use crate::gui::util::{grid_row_label, NodeLabels};
 use crate::{app::AppMsg, geometry::*};
-use crate::{graph_query::GraphQuery, gui::util::grid_row_label};
 
 #[derive(Debug, Clone)]
 pub struct NodeDetails {
@@ -29,6 +31,12 @@ pub struct NodeDetails {
     paths: Vec<(PathId, StepPtr, usize)>,
 
     unique_paths: Vec<PathId>,
+
+    /// Set when the fetched node isn't in the graph, e.g. when an ID
+    /// from a script or view link is out of range
+    not_found: bool,
+
+    pin_request: Option<NodeId>,
 }
 
 impl std::default::Default for NodeDetails {
@@ -40,6 +48,8 @@ impl std::default::Default for NodeDetails {
             degree: (0, 0),
             paths: Vec::new(),
             unique_paths: Vec::new(),
+            not_found: false,
+            pin_request: None,
         }
     }
 }
@@ -52,10 +62,22 @@ pub enum NodeDetailsMsg {
 impl NodeDetails {
     const ID: &'static str = "node_details_window";
 
+    /// A node details state fixed to a single node, used for pinned
+    /// node details windows
+    pub fn pinned(node_id: NodeId) -> Self {
+        let details = Self::default();
+        details.node_id.store(Some(node_id));
+        details
+    }
+
     pub fn node_id_cell(&self) -> &Arc<AtomicCell<Option<NodeId>>> {
         &self.node_id
     }
 
+    pub fn take_pin_request(&mut self) -> Option<NodeId> {
+        self.pin_request.take()
+    }
+
     pub fn apply_msg(&mut self, msg: NodeDetailsMsg) {
         match msg {
             NodeDetailsMsg::SetNode(node_id) => {
@@ -66,6 +88,7 @@ impl NodeDetails {
                 self.sequence.clear();
                 self.degree = (0, 0);
                 self.paths.clear();
+                self.not_found = false;
             }
         }
     }
@@ -87,6 +110,13 @@ impl NodeDetails {
         self.paths.clear();
         self.unique_paths.clear();
 
+        self.not_found = !graph_query.has_node(node_id);
+        self.fetched_node = Some(node_id);
+
+        if self.not_found {
+            return Some(());
+        }
+
         let graph = graph_query.graph();
 
         let handle = Handle::pack(node_id, false);
@@ -110,8 +140,6 @@ impl NodeDetails {
             self.unique_paths.dedup();
         }
 
-        self.fetched_node = Some(node_id);
-
         Some(())
     }
 
@@ -122,100 +150,219 @@ impl NodeDetails {
         ctx: &egui::CtxRef,
         path_details_id_cell: &AtomicCell<Option<PathId>>,
         open_path_details: &mut bool,
+        node_labels: NodeLabels,
+        annotations: &Annotations,
     ) -> Option<egui::InnerResponse<Option<()>>> {
         if self.need_fetch() {
             self.fetch(graph_query);
         }
 
+        let node_id = self.node_id.load();
+        let not_found = self.not_found;
+        let pin_request = &mut self.pin_request;
+
+        let sequence = &self.sequence;
+        let degree = self.degree;
+        let paths = &self.paths;
+
         egui::Window::new("Node details")
             .id(egui::Id::new(Self::ID))
             .default_pos(egui::Pos2::new(450.0, 200.0))
             .open(open_node_details)
-            .show(ctx, |mut ui| {
-                if let Some(node_id) = self.node_id.load() {
-                    ui.set_min_height(200.0);
-                    ui.set_max_width(200.0);
+            .show(ctx, |ui| {
+                if let Some(node_id) = node_id.filter(|_| not_found) {
+                    ui.label(format!("Node {} not found", node_id));
+                } else if let Some(node_id) = node_id {
+                    ui.horizontal(|ui| {
+                        ui.label(node_labels.label(
+                            graph_query.graph(),
+                            node_id,
+                            paths,
+                        ));
+
+                        if ui
+                            .button("Pin")
+                            .on_hover_text(
+                                "Keep this node open in its own window",
+                            )
+                            .clicked()
+                        {
+                            *pin_request = Some(node_id);
+                        }
+                    });
 
-                    ui.label(format!("Node {}", node_id));
+                    Self::details_ui(
+                        ui,
+                        graph_query,
+                        annotations,
+                        node_id,
+                        sequence,
+                        degree,
+                        paths,
+                        path_details_id_cell,
+                        open_path_details,
+                    );
+                } else {
+                    ui.label("Examine a node by picking it from the node list");
+                }
+            })
+    }
+
+    /// Shows this node in a separate window, returning `false` once
+    /// the window has been closed
+    pub fn pinned_ui(
+        &mut self,
+        graph_query: &GraphQuery,
+        ctx: &egui::CtxRef,
+        path_details_id_cell: &AtomicCell<Option<PathId>>,
+        open_path_details: &mut bool,
+        node_labels: NodeLabels,
+        annotations: &Annotations,
+    ) -> bool {
+        if self.need_fetch() {
+            self.fetch(graph_query);
+        }
 
-                    ui.separator();
+        let node_id = match self.node_id.load() {
+            Some(node_id) => node_id,
+            None => return false,
+        };
 
-                    if self.sequence.len() < 50 {
-                        ui.label(format!("Seq: {}", self.sequence.as_bstr()));
-                    } else {
-                        ui.label(format!("Seq len: {}", self.sequence.len()));
-                    }
+        let label = if self.not_found {
+            format!("Node {} not found", node_id)
+        } else {
+            node_labels.label(graph_query.graph(), node_id, &self.paths)
+        };
+
+        let mut open = true;
 
-                    ui.label(format!(
-                        "Degree ({}, {})",
-                        self.degree.0, self.degree.1
-                    ));
+        egui::Window::new(format!("{} (pinned)", label))
+            .id(egui::Id::new(Self::ID).with(("pinned", node_id.0)))
+            .default_pos(egui::Pos2::new(450.0, 200.0))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(&label);
 
-                    ui.separator();
+                if self.not_found {
+                    return;
+                }
 
-                    let separator = || egui::Separator::default().spacing(1.0);
+                Self::details_ui(
+                    ui,
+                    graph_query,
+                    annotations,
+                    node_id,
+                    &self.sequence,
+                    self.degree,
+                    &self.paths,
+                    path_details_id_cell,
+                    open_path_details,
+                );
+            });
+
+        open
+    }
 
-                    egui::ScrollArea::auto_sized().show(&mut ui, |mut ui| {
-                        egui::Grid::new("node_details_path_list")
-                            .spacing(Point { x: 10.0, y: 5.0 })
-                            .striped(true)
-                            .show(&mut ui, |ui| {
-                                ui.label("Path");
-                                ui.add(separator());
+    #[allow(clippy::too_many_arguments)]
+    fn details_ui(
+        ui: &mut egui::Ui,
+        graph_query: &GraphQuery,
+        annotations: &Annotations,
+        node_id: NodeId,
+        sequence: &[u8],
+        degree: (usize, usize),
+        paths: &[(PathId, StepPtr, usize)],
+        path_details_id_cell: &AtomicCell<Option<PathId>>,
+        open_path_details: &mut bool,
+    ) {
+        ui.set_min_height(200.0);
+        ui.set_max_width(200.0);
 
-                                ui.label("Step");
-                                ui.add(separator());
+        ui.separator();
 
-                                ui.label("Base pos");
-                                ui.end_row();
+        if sequence.len() < 50 {
+            ui.label(format!("Seq: {}", sequence.as_bstr()));
+        } else {
+            ui.label(format!("Seq len: {}", sequence.len()));
+        }
 
-                                for (path_id, step_ptr, pos) in
-                                    self.paths.iter()
-                                {
-                                    let path_name = graph_query
-                                        .graph()
-                                        .get_path_name_vec(*path_id);
+        ui.label(format!("Degree ({}, {})", degree.0, degree.1));
 
-                                    let name = if let Some(name) = path_name {
-                                        format!("{}", name.as_bstr())
-                                    } else {
-                                        format!("Path ID {}", path_id.0)
-                                    };
+        let metadata = annotations
+            .node_metadata()
+            .iter()
+            .flat_map(|metadata| metadata.node_values(node_id))
+            .collect::<Vec<_>>();
 
-                                    let step_str = format!(
-                                        "{}",
-                                        step_ptr.to_vector_value()
-                                    );
+        if !metadata.is_empty() {
+            ui.separator();
 
-                                    let pos_str = format!("{}", pos);
+            egui::Grid::new("node_details_metadata").striped(true).show(
+                ui,
+                |ui| {
+                    for (column, value) in metadata {
+                        ui.label(column);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                },
+            );
+        }
 
-                                    let fields: [&str; 3] =
-                                        [&name, &step_str, &pos_str];
+        ui.separator();
 
-                                    let row = grid_row_label(
-                                        ui,
-                                        egui::Id::new(ui.id().with(format!(
-                                            "path_{}_{}",
-                                            path_id.0,
-                                            step_ptr.to_vector_value()
-                                        ))),
-                                        &fields,
-                                        true,
-                                    );
+        let separator = || egui::Separator::default().spacing(1.0);
 
-                                    if row.clicked() {
-                                        path_details_id_cell
-                                            .store(Some(*path_id));
-                                        *open_path_details = true;
-                                    }
-                                }
-                            });
-                    });
-                    ui.shrink_width_to_current();
-                } else {
-                    ui.label("Examine a node by picking it from the node list");
-                }
-            })
+        egui::ScrollArea::auto_sized().show(ui, |mut ui| {
+            egui::Grid::new("node_details_path_list")
+                .spacing(Point { x: 10.0, y: 5.0 })
+                .striped(true)
+                .show(&mut ui, |ui| {
+                    ui.label("Path");
+                    ui.add(separator());
+
+                    ui.label("Step");
+                    ui.add(separator());
+
+                    ui.label("Base pos");
+                    ui.end_row();
+
+                    for (path_id, step_ptr, pos) in paths.iter() {
+                        let path_name =
+                            graph_query.graph().get_path_name_vec(*path_id);
+
+                        let name = if let Some(name) = path_name {
+                            format!("{}", name.as_bstr())
+                        } else {
+                            format!("Path ID {}", path_id.0)
+                        };
+
+                        let step_str =
+                            format!("{}", step_ptr.to_vector_value());
+
+                        let pos_str = format!("{}", pos);
+
+                        let fields: [&str; 3] = [&name, &step_str, &pos_str];
+
+                        let row = grid_row_label(
+                            ui,
+                            egui::Id::new(ui.id().with(format!(
+                                "path_{}_{}",
+                                path_id.0,
+                                step_ptr.to_vector_value()
+                            ))),
+                            &fields,
+                            true,
+                        );
+
+                        if row.clicked() {
+                            path_details_id_cell.store(Some(*path_id));
+                            *open_path_details = true;
+                        }
+                    }
+                });
+        });
+        ui.shrink_width_to_current();
     }
 }
 
@@ -410,6 +557,7 @@ impl NodeList {
         app_msg_tx: &Sender<AppMsg>,
         open_node_details: &mut bool,
         graph_query: &GraphQuery,
+        node_labels: NodeLabels,
     ) -> Option<egui::InnerResponse<Option<()>>> {
         let filter = self.apply_filter.load();
 
@@ -553,7 +701,11 @@ impl NodeList {
 
                             for (ix, slot) in self.slots.iter().enumerate() {
                                 if slot.visible {
-                                    let node_id = format!("{}", slot.node_id);
+                                    let node_id = node_labels.short_label(
+                                        graph_query.graph(),
+                                        slot.node_id,
+                                        &slot.paths,
+                                    );
 
                                     let degree = format!(
                                         "({}, {})",
@@ -0,0 +1,137 @@
+use handlegraph::pathhandlegraph::*;
+
+use crate::gfa::header::HeaderTag;
+use crate::graph_query::GraphQuery;
+
+/// Shows the GFA header of the loaded graph, and lets its tags be
+/// edited; the edited header is written with exported subgraphs
+#[derive(Default)]
+pub struct GraphInfo {
+    new_tag: String,
+    new_value: String,
+}
+
+impl GraphInfo {
+    pub const ID: &'static str = "graph_info_window";
+
+    /// Header tag names are two characters, a letter followed by a
+    /// letter or digit
+    fn valid_tag(tag: &str) -> bool {
+        let mut chars = tag.chars();
+
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(a), Some(b), None) => {
+                a.is_ascii_alphabetic() && b.is_ascii_alphanumeric()
+            }
+            _ => false,
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+        graph_query: &GraphQuery,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        egui::Window::new("Graph info")
+            .id(egui::Id::new(Self::ID))
+            .default_pos(egui::Pos2::new(300.0, 200.0))
+            .open(open)
+            .show(ctx, |ui| {
+                let mut header = graph_query.header_arc().write();
+
+                egui::Grid::new("graph_info_summary").show(ui, |ui| {
+                    ui.label("GFA version");
+                    ui.label(header.version().unwrap_or("-"));
+                    ui.end_row();
+
+                    ui.label("Program");
+                    ui.label(header.program().unwrap_or("-"));
+                    ui.end_row();
+
+                    ui.label("Nodes");
+                    ui.label(graph_query.node_count().to_string());
+                    ui.end_row();
+
+                    ui.label("Edges");
+                    ui.label(graph_query.edge_count().to_string());
+                    ui.end_row();
+
+                    ui.label("Paths");
+                    ui.label(graph_query.graph().path_count().to_string());
+                    ui.end_row();
+                });
+
+                ui.separator();
+
+                ui.label("Header tags");
+
+                let mut remove = None;
+
+                egui::Grid::new("graph_info_header_tags")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Tag");
+                        ui.label("Type");
+                        ui.label("Value");
+                        ui.end_row();
+
+                        for tag in header.tags() {
+                            ui.label(&tag.tag);
+                            ui.label(tag.kind.to_string());
+                            ui.label(&tag.value);
+
+                            if ui.button("Remove").clicked() {
+                                remove = Some(tag.tag.clone());
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some(tag) = remove {
+                    header.remove_tag(&tag);
+                }
+
+                ui.separator();
+
+                let valid_tag = Self::valid_tag(self.new_tag.trim());
+
+                ui.horizontal(|ui| {
+                    ui.label("Tag");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.new_tag)
+                            .desired_width(30.0),
+                    );
+
+                    ui.label("Value");
+                    ui.text_edit_singleline(&mut self.new_value);
+
+                    let add = ui
+                        .add(egui::Button::new("Set").enabled(valid_tag))
+                        .on_hover_text(
+                            "Add a string (Z) tag, or replace the tag \
+                             with the same name",
+                        );
+
+                    if add.clicked() {
+                        header.set_tag(HeaderTag::new(
+                            self.new_tag.trim(),
+                            'Z',
+                            self.new_value.trim(),
+                        ));
+
+                        self.new_tag.clear();
+                        self.new_value.clear();
+                    }
+                });
+
+                if !self.new_tag.is_empty() && !valid_tag {
+                    ui.label(
+                        "Tags are two characters: a letter, then a \
+                         letter or digit",
+                    );
+                }
+            })
+    }
+}
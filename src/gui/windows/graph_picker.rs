@@ -14,6 +14,8 @@ use std::sync::Arc;
 
 use bstr::ByteSlice;
 
+use crossbeam::atomic::AtomicCell;
+
 use anyhow::Result;
 
 use crate::graph_query::GraphQuery;
@@ -92,86 +94,90 @@ impl PathPicker {
             .id(egui::Id::new(("Path picker", self.id)))
             .open(open)
             .collapsible(false)
-            .show(ctx, |mut ui| {
-                self.name_filter.ui(ui);
-
-                ui.horizontal(|ui| {
-                    if ui.button("Apply filter").clicked() {
-                        self.apply_filter();
-                    }
+            .show(ctx, |ui| {
+                self.path_list_ui(ui);
+            })
+    }
 
-                    if ui.button("Clear filter").clicked() {
-                        self.clear_filter();
-                    }
-                });
-
-                let grid = egui::Grid::new("path_picker_list_grid")
-                    .striped(true)
-                    .show(&mut ui, |ui| {
-                        let active_path_index = self.active_path_index;
-
-                        if self.filtered_paths.is_empty() {
-                            for i in 0..self.slot_count {
-                                let index = self.offset + i;
-
-                                if let Some((_path_id, name)) =
-                                    self.paths.get(index)
-                                {
-                                    if ui
-                                        .selectable_label(
-                                            active_path_index == Some(index),
-                                            name,
-                                        )
-                                        .clicked()
-                                    {
-                                        self.active_path_index = Some(index);
-                                    }
-                                    ui.end_row();
-                                }
+    /// The filter and list of paths, without the surrounding window
+    pub fn path_list_ui(&mut self, ui: &mut egui::Ui) {
+        self.name_filter.ui(ui);
+
+        ui.horizontal(|ui| {
+            if ui.button("Apply filter").clicked() {
+                self.apply_filter();
+            }
+
+            if ui.button("Clear filter").clicked() {
+                self.clear_filter();
+            }
+        });
+
+        let grid = egui::Grid::new("path_picker_list_grid").striped(true).show(
+            ui,
+            |ui| {
+                let active_path_index = self.active_path_index;
+
+                if self.filtered_paths.is_empty() {
+                    for i in 0..self.slot_count {
+                        let index = self.offset + i;
+
+                        if let Some((_path_id, name)) = self.paths.get(index) {
+                            if ui
+                                .selectable_label(
+                                    active_path_index == Some(index),
+                                    name,
+                                )
+                                .clicked()
+                            {
+                                self.active_path_index = Some(index);
                             }
-                        } else {
-                            for i in 0..self.slot_count {
-                                if let Some((index, name)) = self
-                                    .filtered_paths
-                                    .get(self.offset + i)
-                                    .and_then(|&ix| {
-                                        let (_, name) = self.paths.get(ix)?;
-                                        Some((ix, name))
-                                    })
-                                {
-                                    if ui
-                                        .selectable_label(
-                                            active_path_index == Some(index),
-                                            name,
-                                        )
-                                        .clicked()
-                                    {
-                                        self.active_path_index = Some(index);
-                                    }
-                                    ui.end_row();
-                                }
+                            ui.end_row();
+                        }
+                    }
+                } else {
+                    for i in 0..self.slot_count {
+                        if let Some((index, name)) = self
+                            .filtered_paths
+                            .get(self.offset + i)
+                            .and_then(|&ix| {
+                                let (_, name) = self.paths.get(ix)?;
+                                Some((ix, name))
+                            })
+                        {
+                            if ui
+                                .selectable_label(
+                                    active_path_index == Some(index),
+                                    name,
+                                )
+                                .clicked()
+                            {
+                                self.active_path_index = Some(index);
                             }
+                            ui.end_row();
                         }
-                    });
+                    }
+                }
+            },
+        );
 
-                if grid.response.hover_pos().is_some() {
-                    let scroll = ctx.input().scroll_delta;
-                    if scroll.y.abs() >= 4.0 {
-                        let sig = (scroll.y.signum() as isize) * -1;
-                        let delta = sig * ((scroll.y.abs() as isize) / 4);
+        if grid.response.hover_pos().is_some() {
+            let scroll = ui.ctx().input().scroll_delta;
+            if scroll.y.abs() >= 4.0 {
+                let sig = (scroll.y.signum() as isize) * -1;
+                let delta = sig * ((scroll.y.abs() as isize) / 4);
 
-                        let mut offset = self.offset as isize;
+                let mut offset = self.offset as isize;
 
-                        offset += delta;
+                offset += delta;
 
-                        let path_count = self.paths.len() as isize;
-                        let slot_count = self.slot_count as isize;
+                let path_count = self.paths.len() as isize;
+                let slot_count = self.slot_count as isize;
 
-                        offset = offset.clamp(0, path_count - slot_count);
-                        self.offset = offset as usize;
-                    }
-                }
-            })
+                offset = offset.clamp(0, path_count - slot_count);
+                self.offset = offset as usize;
+            }
+        }
     }
 
     pub fn active_path(&self) -> Option<(PathId, &str)> {
@@ -181,6 +187,11 @@ impl PathPicker {
         Some((*id, name))
     }
 
+    pub fn set_active_path(&mut self, path: Option<PathId>) {
+        self.active_path_index = path
+            .and_then(|path| self.paths.iter().position(|(id, _)| *id == path));
+    }
+
     fn apply_filter(&mut self) {
         self.filtered_paths.clear();
 
@@ -205,3 +216,60 @@ impl PathPicker {
         self.filtered_paths.clear();
     }
 }
+
+/// Path picker for the global reference path, kept in sync with the
+/// shared reference path cell, which can also be set from the console
+pub struct ReferencePathPicker {
+    picker: PathPicker,
+    reference_path: Arc<AtomicCell<Option<PathId>>>,
+}
+
+impl ReferencePathPicker {
+    pub fn new(
+        picker: PathPicker,
+        reference_path: Arc<AtomicCell<Option<PathId>>>,
+    ) -> Self {
+        Self {
+            picker,
+            reference_path,
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        let reference = self.reference_path.load();
+
+        if self.picker.active_path().map(|(id, _)| id) != reference {
+            self.picker.set_active_path(reference);
+        }
+
+        let resp = egui::Window::new("Reference path")
+            .id(egui::Id::new("reference_path_window"))
+            .open(open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let label = match self.picker.active_path() {
+                    Some((_, name)) => format!("Reference: {}", name),
+                    None => "No reference path".to_string(),
+                };
+                ui.label(label);
+
+                if ui.button("Clear").clicked() {
+                    self.picker.set_active_path(None);
+                }
+
+                self.picker.path_list_ui(ui);
+            });
+
+        let picked = self.picker.active_path().map(|(id, _)| id);
+
+        if picked != reference {
+            self.reference_path.store(picked);
+        }
+
+        resp
+    }
+}
@@ -0,0 +1,170 @@
+use rustc_hash::FxHashMap;
+
+use crate::app::{LegendAnchor, OverlayState};
+use crate::geometry::Point;
+use crate::overlays::{format_legend_value, OverlayKind, OverlayLegend};
+
+/// Shows what the colors of the overlays that are currently drawn
+/// stand for; a gradient with the value range for value overlays,
+/// and a list of colored labels for the others
+pub struct OverlayLegendWindow {
+    overlay_state: OverlayState,
+}
+
+impl OverlayLegendWindow {
+    pub const ID: &'static str = "overlay_legend_window";
+
+    /// Only this many categories are listed per overlay
+    const MAX_CATEGORIES: usize = 24;
+
+    const GRADIENT_SIZE: Point = Point { x: 200.0, y: 15.0 };
+    const SWATCH_SIZE: f32 = 12.0;
+
+    /// Distance from the edges of the screen, in pixels
+    const MARGIN: f32 = 10.0;
+
+    pub fn new(overlay_state: OverlayState) -> Self {
+        Self { overlay_state }
+    }
+
+    /// `top_margin` is the height of the menu bar, so the legend
+    /// isn't placed under it
+    pub fn ui(
+        &self,
+        ctx: &egui::CtxRef,
+        overlay_names: &FxHashMap<usize, (OverlayKind, String)>,
+        top_margin: f32,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        if !self.overlay_state.show_legend() {
+            return None;
+        }
+
+        let anchor = self.overlay_state.legend_anchor();
+
+        let (align, offset) = {
+            let x = if anchor.is_left() {
+                Self::MARGIN
+            } else {
+                -Self::MARGIN
+            };
+
+            let y = if anchor.is_top() {
+                top_margin + Self::MARGIN
+            } else {
+                -Self::MARGIN
+            };
+
+            let align = match anchor {
+                LegendAnchor::TopLeft => egui::Align2::LEFT_TOP,
+                LegendAnchor::TopRight => egui::Align2::RIGHT_TOP,
+                LegendAnchor::BottomLeft => egui::Align2::LEFT_BOTTOM,
+                LegendAnchor::BottomRight => egui::Align2::RIGHT_BOTTOM,
+            };
+
+            (align, Point::new(x, y))
+        };
+
+        let gradient = self.overlay_state.gradient();
+
+        let mut open = true;
+
+        let resp = egui::Window::new("Legend")
+            .id(egui::Id::new(Self::ID))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(align, offset)
+            .show(ctx, |ui| {
+                let overlays = self.overlay_state.shown_overlays();
+
+                if overlays.is_empty() {
+                    ui.label("No overlay is shown");
+                    return;
+                }
+
+                for (ix, (overlay_id, _)) in overlays.into_iter().enumerate() {
+                    if ix > 0 {
+                        ui.separator();
+                    }
+
+                    if let Some((_, name)) = overlay_names.get(&overlay_id) {
+                        ui.add(egui::Label::new(name).strong());
+                    }
+
+                    match self.overlay_state.legend(overlay_id) {
+                        Some(OverlayLegend::Range { min, max, units }) => {
+                            ui.image(
+                                gradient.texture_id(),
+                                Self::GRADIENT_SIZE,
+                            );
+
+                            ui.horizontal(|ui| {
+                                ui.set_max_width(Self::GRADIENT_SIZE.x);
+
+                                ui.label(format_legend_value(min));
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(),
+                                    |ui| {
+                                        ui.label(format_legend_value(max));
+                                    },
+                                );
+                            });
+
+                            if !units.is_empty() {
+                                ui.label(units);
+                            }
+                        }
+                        Some(OverlayLegend::Categories(categories)) => {
+                            Self::categories_ui(ui, overlay_id, &categories);
+                        }
+                        None => {
+                            ui.label("No legend for this overlay");
+                        }
+                    }
+                }
+            });
+
+        if !open {
+            self.overlay_state.set_show_legend(false);
+        }
+
+        resp
+    }
+
+    fn categories_ui(
+        ui: &mut egui::Ui,
+        overlay_id: usize,
+        categories: &[(String, rgb::RGBA<f32>)],
+    ) {
+        egui::Grid::new(("overlay_legend_categories", overlay_id)).show(
+            ui,
+            |ui| {
+                for (label, color) in
+                    categories.iter().take(Self::MAX_CATEGORIES)
+                {
+                    let (rect, _) = ui.allocate_exact_size(
+                        egui::Vec2::splat(Self::SWATCH_SIZE),
+                        egui::Sense::hover(),
+                    );
+
+                    ui.painter().rect_filled(rect, 2.0, color32(*color));
+
+                    ui.label(label);
+                    ui.end_row();
+                }
+            },
+        );
+
+        if categories.len() > Self::MAX_CATEGORIES {
+            ui.label(format!(
+                "... and {} more",
+                categories.len() - Self::MAX_CATEGORIES
+            ));
+        }
+    }
+}
+
+fn color32(color: rgb::RGBA<f32>) -> egui::Color32 {
+    let to_u8 = |v: f32| (v.max(0.0).min(1.0) * 255.0).round() as u8;
+    egui::Color32::from_rgb(to_u8(color.r), to_u8(color.g), to_u8(color.b))
+}
@@ -1,35 +1,54 @@
-use std::io::Read;
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use crossbeam::atomic::AtomicCell;
+use bstr::ByteSlice;
+use handlegraph::pathhandlegraph::*;
 
-use rustc_hash::FxHashMap;
+use crossbeam::{atomic::AtomicCell, channel::Sender};
+
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use anyhow::Result;
 
 #[allow(unused_imports)]
 use log::{debug, error, info, trace, warn};
 
+use crate::annotations::{
+    NodeTable, NodeTableMapping, NodeTableReport, NodeTableValues,
+};
+use crate::graph_query::{DistanceMetric, GraphQueryWorker};
 use crate::reactor::{Host, Outbox, Reactor};
-use crate::script::{ScriptConfig, ScriptTarget};
+use crate::script::{OverlayScript, ScriptConfig, ScriptTarget};
 use crate::{
     geometry::Point,
     vulkan::texture::{GradientName, Gradients},
 };
 
-use crate::app::OverlayState;
-use crate::overlays::{OverlayData, OverlayKind};
+use crate::app::{
+    AppMsg, LegendAnchor, OverlayMode, OverlayScope, OverlayState, SharedState,
+};
+use crate::overlays::{OverlayData, OverlayKind, OverlayLegend};
 
 use super::file::FilePicker;
+use super::filters::FilterString;
+use super::legend::OverlayLegendWindow;
 
 pub struct OverlayList {
     overlay_state: OverlayState,
 
     overlay_names: FxHashMap<usize, (OverlayKind, String)>,
 
+    /// The overlay currently being renamed, and the new name
+    renaming: Option<(usize, String)>,
+
+    /// The overlay whose stack layer is being dragged
+    dragging: Option<usize>,
+
     gradient_picker: GradientPicker,
 
     gradient_picker_open: AtomicCell<bool>,
+
+    legend: OverlayLegendWindow,
 }
 
 impl OverlayList {
@@ -37,17 +56,28 @@ impl OverlayList {
 
     pub fn new(overlay_state: OverlayState) -> Self {
         let gradient_picker = GradientPicker::new(overlay_state.clone());
+        let legend = OverlayLegendWindow::new(overlay_state.clone());
 
         Self {
             overlay_state,
             overlay_names: Default::default(),
 
+            renaming: None,
+
+            dragging: None,
+
             gradient_picker,
 
             gradient_picker_open: AtomicCell::new(false),
+
+            legend,
         }
     }
 
+    pub fn overlay_names(&self) -> &FxHashMap<usize, (OverlayKind, String)> {
+        &self.overlay_names
+    }
+
     pub fn populate_names<'a>(
         &mut self,
         names: impl Iterator<Item = (usize, OverlayKind, &'a str)>,
@@ -55,13 +85,60 @@ impl OverlayList {
         self.overlay_names.clear();
         self.overlay_names
             .extend(names.map(|(x, k, n)| (x, (k, n.to_string()))));
+
+        self.sync_layers();
+    }
+
+    fn sync_layers(&self) {
+        let mut overlays = self
+            .overlay_names
+            .iter()
+            .map(|(id, (kind, _))| (*id, *kind))
+            .collect::<Vec<_>>();
+        overlays.sort();
+
+        self.overlay_state.sync_layers(overlays.into_iter());
+    }
+
+    pub fn apply_msg(&mut self, msg: OverlayListMsg) {
+        match msg {
+            OverlayListMsg::InsertOverlay {
+                overlay_id,
+                kind,
+                name,
+            } => {
+                self.overlay_names.insert(overlay_id, (kind, name));
+                self.sync_layers();
+            }
+            OverlayListMsg::RenameOverlay { overlay_id, name } => {
+                if let Some((_, old_name)) =
+                    self.overlay_names.get_mut(&overlay_id)
+                {
+                    *old_name = name;
+                }
+            }
+            OverlayListMsg::RemoveOverlay { overlay_id } => {
+                self.overlay_names.remove(&overlay_id);
+                self.sync_layers();
+
+                if self.dragging == Some(overlay_id) {
+                    self.dragging = None;
+                }
+
+                if self.renaming.as_ref().map(|(id, _)| *id) == Some(overlay_id)
+                {
+                    self.renaming = None;
+                }
+            }
+        }
     }
 
     pub fn ui(
-        &self,
+        &mut self,
         ctx: &egui::CtxRef,
         open: &mut bool,
         open_creator: &mut bool,
+        app_msg_tx: &crossbeam::channel::Sender<AppMsg>,
     ) -> Option<egui::InnerResponse<Option<()>>> {
         egui::Window::new("Overlay List")
             .id(egui::Id::new(Self::ID))
@@ -76,7 +153,7 @@ impl OverlayList {
                         .selectable_label(use_overlay, "Overlay enabled")
                         .clicked()
                     {
-                        self.overlay_state.toggle_overlay();
+                        app_msg_tx.send(AppMsg::ToggleOverlay).unwrap();
                     }
 
                     if ui
@@ -87,14 +164,101 @@ impl OverlayList {
                     }
                 });
 
-                let open_gradient_picker = self.gradient_picker_open.load();
-                if ui
-                    .selectable_label(open_gradient_picker, "Gradients")
-                    .clicked()
-                {
-                    self.gradient_picker_open.store(!open_gradient_picker);
+                ui.horizontal(|ui| {
+                    let open_gradient_picker = self.gradient_picker_open.load();
+                    if ui
+                        .selectable_label(open_gradient_picker, "Gradients")
+                        .clicked()
+                    {
+                        self.gradient_picker_open.store(!open_gradient_picker);
+                    }
+
+                    if ui
+                        .selectable_label(
+                            self.overlay_state.show_legend(),
+                            "Legend",
+                        )
+                        .clicked()
+                    {
+                        self.overlay_state.toggle_legend();
+                    }
+                });
+
+                if self.overlay_state.show_legend() {
+                    let anchor = self.overlay_state.legend_anchor();
+
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("Legend position");
+
+                        for &option in LegendAnchor::ALL.iter() {
+                            if ui
+                                .radio(anchor == option, option.display_name())
+                                .clicked()
+                            {
+                                self.overlay_state.set_legend_anchor(option);
+                            }
+                        }
+                    });
                 }
 
+                let scope = self.overlay_state.scope();
+
+                ui.horizontal(|ui| {
+                    ui.label("Apply to");
+
+                    if ui
+                        .radio(scope == OverlayScope::Global, "All nodes")
+                        .clicked()
+                    {
+                        self.overlay_state.set_scope(OverlayScope::Global);
+                    }
+
+                    if ui
+                        .radio(scope == OverlayScope::Selection, "Selection")
+                        .clicked()
+                    {
+                        self.overlay_state.set_scope(OverlayScope::Selection);
+                    }
+                });
+
+                if scope == OverlayScope::Selection {
+                    let mut dim = self.overlay_state.unselected_dim();
+
+                    let slider = ui.add(
+                        egui::Slider::new(&mut dim, 0.0..=1.0)
+                            .text("Dim unselected nodes"),
+                    );
+
+                    if slider.changed() {
+                        self.overlay_state.set_unselected_dim(dim);
+                    }
+                }
+
+                let mode = self.overlay_state.mode();
+
+                ui.horizontal(|ui| {
+                    ui.label("Mode");
+
+                    if ui.radio(mode == OverlayMode::Single, "Single").clicked()
+                    {
+                        self.overlay_state.set_mode(OverlayMode::Single);
+                    }
+
+                    if ui.radio(mode == OverlayMode::Stack, "Stack").clicked() {
+                        self.overlay_state.set_mode(OverlayMode::Stack);
+                    }
+                });
+
+                if mode == OverlayMode::Stack {
+                    ui.separator();
+                    self.stack_ui(ui);
+                    ui.separator();
+                }
+
+                let overlay_state = &self.overlay_state;
+                let overlay_names = &self.overlay_names;
+                let renaming = &mut self.renaming;
+
                 egui::Grid::new("overlay_list_window_grid").show(
                     &mut ui,
                     |ui| {
@@ -102,23 +266,95 @@ impl OverlayList {
                         ui.end_row();
 
                         let mut overlay_names =
-                            self.overlay_names.iter().collect::<Vec<_>>();
+                            overlay_names.iter().collect::<Vec<_>>();
                         overlay_names.sort_by_key(|(id, _)| *id);
 
                         let mut current_overlay =
-                            self.overlay_state.current_overlay();
+                            overlay_state.current_overlay();
+
+                        if ui
+                            .radio_value(&mut current_overlay, None, "None")
+                            .clicked()
+                        {
+                            app_msg_tx
+                                .send(AppMsg::SetActiveOverlay(None))
+                                .unwrap();
+                        }
+                        ui.end_row();
 
                         for (id, (kind, name)) in overlay_names {
-                            if ui
-                                .radio_value(
-                                    &mut current_overlay,
-                                    Some((*id, *kind)),
-                                    name,
-                                )
-                                .clicked()
-                            {
-                                self.overlay_state
-                                    .set_current_overlay(current_overlay);
+                            let is_renaming =
+                                renaming.as_ref().map(|(r_id, _)| r_id)
+                                    == Some(id);
+
+                            if is_renaming {
+                                let mut finished = false;
+                                let mut cancelled = false;
+
+                                if let Some((_, new_name)) = renaming.as_mut() {
+                                    let text_edit =
+                                        ui.text_edit_singleline(new_name);
+
+                                    if text_edit.lost_focus()
+                                        && ui
+                                            .input()
+                                            .key_pressed(egui::Key::Enter)
+                                    {
+                                        finished = true;
+                                    }
+
+                                    if ui.button("OK").clicked() {
+                                        finished = true;
+                                    }
+
+                                    if ui.button("Cancel").clicked() {
+                                        cancelled = true;
+                                    }
+                                }
+
+                                if finished {
+                                    if let Some((overlay_id, new_name)) =
+                                        renaming.take()
+                                    {
+                                        if !new_name.trim().is_empty() {
+                                            app_msg_tx
+                                                .send(AppMsg::RenameOverlay {
+                                                    overlay_id,
+                                                    name: new_name,
+                                                })
+                                                .unwrap();
+                                        }
+                                    }
+                                } else if cancelled {
+                                    *renaming = None;
+                                }
+                            } else {
+                                if ui
+                                    .radio_value(
+                                        &mut current_overlay,
+                                        Some((*id, *kind)),
+                                        name,
+                                    )
+                                    .clicked()
+                                {
+                                    app_msg_tx
+                                        .send(AppMsg::SetActiveOverlay(Some(
+                                            name.clone(),
+                                        )))
+                                        .unwrap();
+                                }
+
+                                if ui.button("Rename").clicked() {
+                                    *renaming = Some((*id, name.clone()));
+                                }
+
+                                if ui.button("Delete").clicked() {
+                                    app_msg_tx
+                                        .send(AppMsg::DeleteOverlay {
+                                            overlay_id: *id,
+                                        })
+                                        .unwrap();
+                                }
                             }
 
                             ui.end_row();
@@ -128,6 +364,79 @@ impl OverlayList {
             })
     }
 
+    /// The overlay stack, top layer first; layers are reordered by
+    /// dragging their handle
+    fn stack_ui(&mut self, ui: &mut egui::Ui) {
+        let overlay_state = &self.overlay_state;
+        let overlay_names = &self.overlay_names;
+        let dragging = &mut self.dragging;
+
+        let layers = overlay_state.layers();
+
+        let pointer_pos = ui.input().pointer.hover_pos();
+
+        ui.label("Overlay stack (top to bottom)");
+
+        let mut drop_target: Option<usize> = None;
+
+        egui::Grid::new("overlay_list_stack_grid").show(ui, |ui| {
+            for (ix, layer) in layers.iter().enumerate() {
+                let overlay_id = layer.overlay.0;
+
+                let name = overlay_names
+                    .get(&overlay_id)
+                    .map(|(_, name)| name.as_str())
+                    .unwrap_or_default();
+
+                let handle = ui.add(egui::Label::new("=").monospace());
+                let handle = ui.interact(
+                    handle.rect,
+                    egui::Id::new("overlay_stack_handle").with(overlay_id),
+                    egui::Sense::drag(),
+                );
+
+                if handle.drag_started() {
+                    *dragging = Some(overlay_id);
+                }
+
+                let mut visible = layer.visible;
+                let checkbox = ui.checkbox(&mut visible, name);
+                if checkbox.changed() {
+                    overlay_state.set_layer_visible(overlay_id, visible);
+                }
+
+                let mut opacity = layer.opacity;
+                let slider = ui.add(
+                    egui::Slider::new(&mut opacity, 0.0..=1.0).text("Opacity"),
+                );
+                if slider.changed() {
+                    overlay_state.set_layer_opacity(overlay_id, opacity);
+                }
+
+                let row = handle.rect.union(checkbox.rect).union(slider.rect);
+
+                if let Some(pos) = pointer_pos {
+                    if dragging.is_some()
+                        && pos.y >= row.top()
+                        && pos.y <= row.bottom()
+                    {
+                        drop_target = Some(ix);
+                    }
+                }
+
+                ui.end_row();
+            }
+        });
+
+        if let (Some(overlay_id), Some(index)) = (*dragging, drop_target) {
+            overlay_state.move_layer(overlay_id, index);
+        }
+
+        if !ui.input().pointer.any_down() {
+            *dragging = None;
+        }
+    }
+
     pub fn gradient_picker_ui(
         &self,
         ctx: &egui::CtxRef,
@@ -137,26 +446,42 @@ impl OverlayList {
         self.gradient_picker_open.store(open);
         resp
     }
+
+    /// `top_margin` is the height of the menu bar
+    pub fn legend_ui(
+        &self,
+        ctx: &egui::CtxRef,
+        top_margin: f32,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        self.legend.ui(ctx, &self.overlay_names, top_margin)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum OverlayListMsg {
-    InsertOverlay { overlay_id: usize, name: String },
-    RemoveOverlay { overlay_id: usize },
+    InsertOverlay {
+        overlay_id: usize,
+        kind: OverlayKind,
+        name: String,
+    },
+    RenameOverlay {
+        overlay_id: usize,
+        name: String,
+    },
+    RemoveOverlay {
+        overlay_id: usize,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub struct ScriptInput {
     name: String,
-    path: PathBuf,
-
-    config: ScriptConfig,
+    script: OverlayScript,
 }
 
 pub enum ScriptMsg {
     IOError(String),
     ScriptError(String),
-    Running(String),
 }
 
 #[allow(dead_code)]
@@ -169,8 +494,14 @@ impl ScriptMsg {
         ScriptMsg::ScriptError(err.to_string())
     }
 
-    fn running(msg: &str) -> Self {
-        ScriptMsg::Running(msg.to_string())
+    /// Errors from loading the script file are IO errors, all other
+    /// errors from building the overlay are script errors
+    pub fn from_build_error(err: &anyhow::Error) -> Self {
+        if err.downcast_ref::<std::io::Error>().is_some() {
+            ScriptMsg::IOError(format!("{:#}", err))
+        } else {
+            ScriptMsg::ScriptError(format!("{:#}", err))
+        }
     }
 }
 
@@ -183,14 +514,25 @@ pub struct OverlayCreator {
     file_picker: FilePicker,
     file_picker_open: bool,
 
-    script_results: Host<ScriptInput, ScriptResult>,
+    graph_query_worker: GraphQueryWorker,
+    rayon_pool: Arc<rayon::ThreadPool>,
+    shared_state: SharedState,
     latest_result: Option<ScriptResult>,
+
+    path_depth: PathDepthCreator,
+    distance: DistanceCreator,
+    node_table: NodeTableCreator,
 }
 
 impl OverlayCreator {
     pub const ID: &'static str = "overlay_creator_window";
 
-    pub fn new(reactor: &mut Reactor) -> Result<Self> {
+    pub fn new(
+        reactor: &mut Reactor,
+        shared_state: &SharedState,
+    ) -> Result<Self> {
+        let overlay_state = shared_state.overlay_state().clone();
+
         let pwd = std::fs::canonicalize("./").unwrap();
 
         let mut file_picker = FilePicker::new(
@@ -199,59 +541,12 @@ impl OverlayCreator {
         )
         .unwrap();
 
-        let script_results = {
-            let tx = reactor.overlay_create_tx.clone();
-            let rayon_pool = reactor.rayon_pool.clone();
-            let graph = reactor.graph_query.clone();
-
-            reactor.create_host(
-                move |outbox: &Outbox<ScriptResult>, input: ScriptInput| {
-                    let running_msg = |msg: &str| {
-                        outbox.insert_blocking(Err(ScriptMsg::running(msg)));
-                    };
-
-                    running_msg("Loading script");
-
-                    let mut file =
-                        std::fs::File::open(input.path).map_err(|_| {
-                            ScriptMsg::io_error("error loading script file")
-                        })?;
-
-                    let mut script = String::new();
-                    file.read_to_string(&mut script).map_err(|_| {
-                        ScriptMsg::io_error("error loading script file")
-                    })?;
-
-                    running_msg("Evaluating script");
-                    let overlay_data = crate::script::overlay_colors_tgt(
-                        &rayon_pool,
-                        &input.config,
-                        &graph,
-                        &script,
-                    );
-
-                    let feedback = match overlay_data {
-                        Ok(data) => {
-                            let msg = OverlayCreatorMsg::NewOverlay {
-                                name: input.name,
-                                data,
-                            };
-                            tx.send(msg).unwrap();
-                            Ok(())
-                        }
-                        Err(err) => {
-                            Err(ScriptMsg::ScriptError(format!("{:?}", err)))
-                        }
-                    };
-
-                    feedback
-                },
-            )
-        };
-
         let extensions: [&str; 1] = ["rhai"];
         file_picker.set_visible_extensions(&extensions).unwrap();
 
+        let path_depth = PathDepthCreator::new(reactor, overlay_state);
+        let node_table = NodeTableCreator::new(reactor);
+
         Ok(Self {
             name: String::new(),
             script_path_input: String::new(),
@@ -259,35 +554,34 @@ impl OverlayCreator {
             file_picker,
             file_picker_open: false,
 
-            script_results,
+            graph_query_worker: reactor.graph_query_worker(),
+            rayon_pool: reactor.rayon_pool.clone(),
+            shared_state: shared_state.clone(),
             latest_result: None,
+
+            path_depth,
+            distance: DistanceCreator::default(),
+            node_table,
         })
     }
 
+    /// Shows the result of a finished script overlay build, if one
+    /// was started from this window
+    pub fn set_build_result(&mut self, result: ScriptResult) {
+        if self.latest_result.is_some() {
+            self.latest_result = Some(result);
+        }
+    }
+
     pub fn ui(
         &mut self,
         ctx: &egui::CtxRef,
         open: &mut bool,
+        overlay_names: &FxHashMap<usize, (OverlayKind, String)>,
+        app_msg_tx: &Sender<AppMsg>,
     ) -> Option<egui::InnerResponse<Option<()>>> {
         let scr = ctx.input().screen_rect();
 
-        if let Some(result) = self.script_results.take() {
-            if result.is_ok() {
-                info!("Created new overlay from Rhai script");
-                self.script_path_input.clear();
-                self.name.clear();
-            }
-
-            if let Err(ScriptMsg::IOError(err)) = &result {
-                error!("Overlay script IO error: {:?}", err);
-            }
-            if let Err(ScriptMsg::ScriptError(err)) = &result {
-                error!("Overlay script execution error: {:?}", err);
-            }
-
-            self.latest_result = Some(result);
-        }
-
         let pos = egui::pos2(scr.center().x - 150.0, scr.center().y - 60.0);
 
         if self.file_picker.selected_path().is_some() {
@@ -301,22 +595,25 @@ impl OverlayCreator {
             self.script_path_input = path_str.to_string();
         }
 
+        self.node_table.file_picker_ui(ctx);
+
         egui::Window::new("Create Overlay")
             .id(egui::Id::new(Self::ID))
             .open(open)
             .default_pos(pos)
             .show(ctx, |ui| {
-                let is_running = matches!(
-                    self.latest_result,
-                    Some(Err(ScriptMsg::Running(_)))
-                );
+                // the progress and cancel button are shown by
+                // `Gui::overlay_build_progress_ui`
+                let is_running = self.shared_state.overlay_build().is_some();
 
                 let name = &mut self.name;
                 let file_picker = &mut self.file_picker;
                 let file_picker_open = &mut self.file_picker_open;
-                let latest_result = &self.latest_result;
+                let latest_result = &mut self.latest_result;
 
-                let script_results = &mut self.script_results;
+                let worker = &self.graph_query_worker;
+                let rayon_pool = &self.rayon_pool;
+                let shared_state = &self.shared_state;
 
                 let _name_box = ui.horizontal(|ui| {
                     ui.label("Overlay name");
@@ -364,11 +661,30 @@ impl OverlayCreator {
 
                         let script_input = ScriptInput {
                             name: name.to_string(),
-                            path,
-                            config,
+                            script: OverlayScript { path, config },
                         };
 
-                        script_results.call(script_input).unwrap();
+                        // the script is loaded and compiled on the
+                        // worker, errors are reported when the build
+                        // is done, see `set_build_result`
+                        let task = script_input
+                            .script
+                            .build(worker, rayon_pool.clone());
+
+                        info!(
+                            "Building overlay '{}' from Rhai script",
+                            script_input.name
+                        );
+                        shared_state.set_script_overlay_build(
+                            &script_input.name,
+                            task,
+                            script_input.script,
+                            None,
+                        );
+                        path_str.clear();
+                        name.clear();
+
+                        *latest_result = Some(Ok(()));
                     }
                 });
 
@@ -379,20 +695,743 @@ impl OverlayCreator {
                     Some(Err(ScriptMsg::ScriptError(err))) => {
                         ui.label(format!("Script Error: {:?}", err));
                     }
-                    Some(Err(ScriptMsg::Running(msg))) => {
-                        ui.label(msg);
+                    Some(Ok(_)) if is_running => {
+                        ui.label("Building overlay");
                     }
                     Some(Ok(_)) => {
                         ui.label("Created new overlay");
                     }
                     _ => (),
                 }
+
+                ui.separator();
+
+                let path_depth = &mut self.path_depth;
+                ui.collapsing("Path depth", |ui| {
+                    path_depth.ui(ui, overlay_names);
+                });
+
+                let distance = &mut self.distance;
+                ui.collapsing("Distance from selection", |ui| {
+                    distance.ui(ui, app_msg_tx);
+                });
+
+                let node_table = &mut self.node_table;
+                ui.collapsing("Node table (TSV)", |ui| {
+                    node_table.ui(ui);
+                });
             })
     }
 }
 
+type PathDepthResult = std::result::Result<usize, String>;
+
+struct PathDepthInput {
+    name: String,
+    paths: Vec<PathId>,
+    /// The path depth overlay to update, or `None` to create a new one
+    overlay_id: Option<usize>,
+}
+
+/// Creates Value overlays showing how many paths, out of a chosen
+/// set of paths, traverse each node.
+///
+/// The path set is kept with each overlay, and when an existing path
+/// depth overlay is picked, changing the path set recomputes that
+/// overlay in place.
+pub struct PathDepthCreator {
+    overlay_state: OverlayState,
+
+    paths: Arc<Vec<(PathId, String)>>,
+    path_filter: FilterString,
+
+    /// The paths that are counted
+    selected: FxHashSet<PathId>,
+
+    /// The path depth overlay being edited, if any
+    target: Option<usize>,
+    /// The path set most recently sent for the target overlay, so
+    /// that it's only recomputed when the set changes
+    submitted: Option<Vec<PathId>>,
+
+    name: String,
+
+    results: Host<PathDepthInput, PathDepthResult>,
+    running: bool,
+    latest_result: Option<PathDepthResult>,
+}
+
+impl PathDepthCreator {
+    pub fn new(reactor: &mut Reactor, overlay_state: OverlayState) -> Self {
+        let graph = reactor.graph_query.clone();
+
+        let paths = graph
+            .graph()
+            .path_ids()
+            .filter_map(|id| {
+                let name = graph.graph().get_path_name_vec(id)?;
+                let name = name.to_str().ok()?;
+                Some((id, name.to_string()))
+            })
+            .collect::<Vec<_>>();
+
+        let selected = paths.iter().map(|(id, _)| *id).collect();
+
+        let results = {
+            let tx = reactor.overlay_create_tx.clone();
+
+            // the depths for the most recently used path set, so that
+            // recreating the overlay doesn't require another pass
+            // over the graph
+            let cache: parking_lot::Mutex<Option<(Vec<PathId>, Vec<f32>)>> =
+                parking_lot::Mutex::new(None);
+
+            reactor.create_host(
+                move |_outbox: &Outbox<PathDepthResult>,
+                      input: PathDepthInput|
+                      -> PathDepthResult {
+                    let mut cache = cache.lock();
+
+                    let cached = cache
+                        .as_ref()
+                        .filter(|(paths, _)| paths == &input.paths)
+                        .map(|(_, depths)| depths.clone());
+
+                    let depths = if let Some(depths) = cached {
+                        depths
+                    } else {
+                        let depths = graph.path_depth_fractions(&input.paths);
+
+                        *cache = Some((input.paths.clone(), depths.clone()));
+                        depths
+                    };
+
+                    let path_count = input.paths.len();
+
+                    let msg = OverlayCreatorMsg::PathDepth {
+                        name: input.name,
+                        overlay_id: input.overlay_id,
+                        paths: input.paths,
+                        depths,
+                    };
+
+                    tx.send(msg).map_err(|err| err.to_string())?;
+
+                    Ok(path_count)
+                },
+            )
+        };
+
+        Self {
+            overlay_state,
+
+            paths: Arc::new(paths),
+            path_filter: FilterString::default(),
+
+            selected,
+
+            target: None,
+            submitted: None,
+
+            name: String::new(),
+
+            results,
+            running: false,
+            latest_result: None,
+        }
+    }
+
+    fn selected_paths(&self) -> Vec<PathId> {
+        let mut paths = self.selected.iter().copied().collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    fn submit(
+        &mut self,
+        name: String,
+        paths: Vec<PathId>,
+        overlay_id: Option<usize>,
+    ) {
+        self.running = true;
+        self.submitted = overlay_id.map(|_| paths.clone());
+
+        self.results
+            .call(PathDepthInput {
+                name,
+                paths,
+                overlay_id,
+            })
+            .unwrap();
+    }
+
+    fn set_target(&mut self, target: Option<usize>) {
+        if let Some(paths) =
+            target.and_then(|id| self.overlay_state.overlay_paths(id))
+        {
+            self.selected = paths.iter().copied().collect();
+            self.submitted = Some(paths);
+        } else {
+            self.submitted = None;
+        }
+
+        self.target = target;
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        overlay_names: &FxHashMap<usize, (OverlayKind, String)>,
+    ) {
+        if let Some(result) = self.results.take() {
+            if let Err(err) = &result {
+                error!("Error creating path depth overlay: {}", err);
+            }
+            self.running = false;
+            self.latest_result = Some(result);
+        }
+
+        // the target overlay may have been deleted
+        if let Some(target) = self.target {
+            if self.overlay_state.overlay_paths(target).is_none() {
+                self.set_target(None);
+            }
+        }
+
+        let targets = self
+            .overlay_state
+            .path_set_overlays()
+            .into_iter()
+            .filter_map(|id| Some((id, overlay_names.get(&id)?.1.clone())))
+            .collect::<Vec<_>>();
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Overlay");
+
+            if ui.radio(self.target.is_none(), "New").clicked() {
+                self.set_target(None);
+            }
+
+            for (id, name) in targets {
+                if ui.radio(self.target == Some(id), name).clicked()
+                    && self.target != Some(id)
+                {
+                    self.set_target(Some(id));
+                }
+            }
+        });
+
+        ui.label("Filter paths by name");
+        self.path_filter.ui(ui);
+
+        let paths = self.paths.clone();
+
+        let shown = paths
+            .iter()
+            .filter(|(_, name)| self.path_filter.filter_str(name))
+            .collect::<Vec<_>>();
+
+        ui.horizontal(|ui| {
+            if ui.button("Select shown").clicked() {
+                self.selected.extend(shown.iter().map(|(id, _)| *id));
+            }
+
+            if ui.button("Deselect shown").clicked() {
+                for (id, _) in shown.iter() {
+                    self.selected.remove(id);
+                }
+            }
+        });
+
+        egui::ScrollArea::from_max_height(200.0).show(ui, |ui| {
+            for (id, name) in shown.iter() {
+                let mut checked = self.selected.contains(id);
+
+                if ui.checkbox(&mut checked, name).changed() {
+                    if checked {
+                        self.selected.insert(*id);
+                    } else {
+                        self.selected.remove(id);
+                    }
+                }
+            }
+        });
+
+        let selected = self.selected_paths();
+
+        ui.label(format!(
+            "{} out of {} paths selected",
+            selected.len(),
+            self.paths.len()
+        ));
+
+        if let Some(target) = self.target {
+            let changed = self.submitted.as_ref() != Some(&selected);
+
+            if changed && !self.running && !selected.is_empty() {
+                let name = overlay_names
+                    .get(&target)
+                    .map(|(_, name)| name.clone())
+                    .unwrap_or_default();
+
+                self.submit(name, selected, Some(target));
+            }
+        } else {
+            ui.horizontal(|ui| {
+                ui.label("Overlay name");
+                ui.separator();
+                ui.text_edit_singleline(&mut self.name);
+            });
+
+            let create_btn = ui.add(
+                egui::Button::new("Create path depth overlay")
+                    .enabled(!self.running && !selected.is_empty()),
+            );
+
+            if create_btn.clicked() {
+                let name = if self.name.trim().is_empty() {
+                    format!("Path depth ({} paths)", selected.len())
+                } else {
+                    self.name.clone()
+                };
+
+                self.submit(name, selected, None);
+            }
+        }
+
+        if self.running {
+            ui.label("Computing path depth");
+        } else {
+            match &self.latest_result {
+                Some(Ok(count)) => {
+                    ui.label(format!(
+                        "Computed path depth overlay for {} paths",
+                        count
+                    ));
+                }
+                Some(Err(err)) => {
+                    ui.label(format!("Error: {}", err));
+                }
+                None => (),
+            }
+        }
+    }
+}
+
+/// Creates Value overlays of the distance of each node from the
+/// selected nodes, computed on a worker thread. Nodes beyond the max
+/// distance are drawn gray
+pub struct DistanceCreator {
+    metric: DistanceMetric,
+    max_hops: usize,
+    max_bp: usize,
+}
+
+impl std::default::Default for DistanceCreator {
+    fn default() -> Self {
+        Self {
+            metric: DistanceMetric::Hops,
+            max_hops: 50,
+            max_bp: 100_000,
+        }
+    }
+}
+
+impl DistanceCreator {
+    pub fn ui(&mut self, ui: &mut egui::Ui, app_msg_tx: &Sender<AppMsg>) {
+        ui.horizontal(|ui| {
+            ui.label("Measure");
+            ui.radio_value(&mut self.metric, DistanceMetric::Hops, "Hops");
+            ui.radio_value(
+                &mut self.metric,
+                DistanceMetric::BasePairs,
+                "Base pairs",
+            );
+        });
+
+        let max_distance = match self.metric {
+            DistanceMetric::Hops => &mut self.max_hops,
+            DistanceMetric::BasePairs => &mut self.max_bp,
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Max distance");
+            ui.add(
+                egui::DragValue::new::<usize>(max_distance)
+                    .clamp_range(1..=10_000_000),
+            );
+        });
+
+        let create_btn = ui.button("Create distance overlay").on_hover_text(
+            "Measures from the closest selected node, following edges \
+             in either direction",
+        );
+
+        if create_btn.clicked() {
+            app_msg_tx
+                .send(AppMsg::DistanceOverlay {
+                    metric: self.metric,
+                    max_distance: *max_distance,
+                })
+                .unwrap();
+        }
+    }
+}
+
+struct NodeTableInput {
+    name: String,
+    table: Arc<NodeTable>,
+    mapping: NodeTableMapping,
+}
+
+type NodeTableLoadResult = std::result::Result<Arc<NodeTable>, String>;
+type NodeTableResult = std::result::Result<NodeTableReport, String>;
+
+/// Creates overlays from per-node TSV tables, such as those written
+/// by `odgi`. Once a table is loaded, one column is picked to hold
+/// the node IDs, and another the hex colors or numbers the overlay
+/// is made from
+pub struct NodeTableCreator {
+    path_input: String,
+
+    file_picker: FilePicker,
+    file_picker_open: bool,
+
+    loader: Host<PathBuf, NodeTableLoadResult>,
+    loading: bool,
+
+    table: Option<Arc<NodeTable>>,
+    columns: Vec<String>,
+    mapping: NodeTableMapping,
+
+    name: String,
+
+    results: Host<NodeTableInput, NodeTableResult>,
+    running: bool,
+    latest_result: Option<NodeTableResult>,
+}
+
+impl NodeTableCreator {
+    const ID: &'static str = "node_table_creator";
+
+    pub fn new(reactor: &mut Reactor) -> Self {
+        let pwd = std::fs::canonicalize("./").unwrap();
+
+        let mut file_picker = FilePicker::new(
+            egui::Id::with(egui::Id::new(Self::ID), "file_picker"),
+            pwd,
+        )
+        .unwrap();
+
+        let extensions: [&str; 3] = ["tsv", "txt", "csv"];
+        file_picker.set_visible_extensions(&extensions).unwrap();
+
+        let loader = reactor.create_host(
+            move |_outbox: &Outbox<NodeTableLoadResult>,
+                  path: PathBuf|
+                  -> NodeTableLoadResult {
+                NodeTable::parse_tsv_file(&path)
+                    .map(Arc::new)
+                    .map_err(|err| format!("{}", err))
+            },
+        );
+
+        let results = {
+            let tx = reactor.overlay_create_tx.clone();
+            let graph = reactor.graph_query.clone();
+
+            reactor.create_host(
+                move |_outbox: &Outbox<NodeTableResult>,
+                      input: NodeTableInput|
+                      -> NodeTableResult {
+                    let (msg, report) = input
+                        .table
+                        .overlay_msg(&input.name, input.mapping, graph.graph())
+                        .map_err(|err| format!("{}", err))?;
+
+                    tx.send(msg).map_err(|err| err.to_string())?;
+
+                    Ok(report)
+                },
+            )
+        };
+
+        Self {
+            path_input: String::new(),
+
+            file_picker,
+            file_picker_open: false,
+
+            loader,
+            loading: false,
+
+            table: None,
+            columns: Vec::new(),
+            mapping: NodeTableMapping {
+                has_header: true,
+                id_column: 0,
+                value_column: 1,
+                values: NodeTableValues::Auto,
+            },
+
+            name: String::new(),
+
+            results,
+            running: false,
+            latest_result: None,
+        }
+    }
+
+    fn set_table(&mut self, table: Arc<NodeTable>) {
+        self.mapping.has_header = table.guess_header();
+        self.mapping.id_column = 0;
+        self.mapping.value_column = 1;
+        self.columns = table.columns(self.mapping.has_header);
+
+        self.name = table.file_name().to_string();
+        self.table = Some(table);
+    }
+
+    pub fn file_picker_ui(&mut self, ctx: &egui::CtxRef) {
+        if self.file_picker.selected_path().is_some() {
+            self.file_picker_open = false;
+        }
+
+        self.file_picker.ui(ctx, &mut self.file_picker_open);
+
+        if let Some(path) = self.file_picker.selected_path() {
+            if let Some(path_str) = path.to_str() {
+                self.path_input = path_str.to_string();
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        if let Some(loaded) = self.loader.take() {
+            self.loading = false;
+
+            match loaded {
+                Ok(table) => {
+                    self.latest_result = None;
+                    self.set_table(table);
+                }
+                Err(err) => {
+                    error!("Error loading node table: {}", err);
+                    self.table = None;
+                    self.latest_result = Some(Err(err));
+                }
+            }
+        }
+
+        if let Some(result) = self.results.take() {
+            match &result {
+                Ok(report) => {
+                    info!("Created node table overlay: {}", report);
+                }
+                Err(err) => {
+                    error!("Error creating node table overlay: {}", err);
+                }
+            }
+            self.running = false;
+            self.latest_result = Some(result);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Table path");
+            ui.separator();
+            ui.add(
+                egui::TextEdit::singleline(&mut self.path_input)
+                    .enabled(!self.loading),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            let file_btn =
+                egui::Button::new("Choose file").enabled(!self.loading);
+
+            if ui.add(file_btn).clicked() {
+                self.file_picker.reset_selection();
+                self.file_picker_open = true;
+            }
+
+            let load_btn =
+                ui.add(egui::Button::new("Load table").enabled(
+                    !self.loading && !self.path_input.trim().is_empty(),
+                ));
+
+            if load_btn.clicked() {
+                self.file_picker.reset_selection();
+                self.loading = true;
+
+                let path = PathBuf::from(self.path_input.trim());
+                self.loader.call(path).unwrap();
+            }
+        });
+
+        if self.loading {
+            ui.label("Loading table");
+        }
+
+        let table = if let Some(table) = self.table.clone() {
+            table
+        } else {
+            self.result_ui(ui);
+            return;
+        };
+
+        ui.separator();
+
+        let header_box = ui
+            .checkbox(&mut self.mapping.has_header, "First line is a header")
+            .on_hover_text(
+                "Without a header, columns are referred to by number",
+            );
+
+        if header_box.changed() {
+            self.columns = table.columns(self.mapping.has_header);
+        }
+
+        let first_row = table.data_rows(self.mapping.has_header).next();
+
+        egui::ScrollArea::from_max_height(200.0).show(ui, |ui| {
+            egui::Grid::new("node_table_columns").striped(true).show(
+                ui,
+                |ui| {
+                    ui.label("Column");
+                    ui.label("Node ID");
+                    ui.label("Value");
+                    ui.label("First row");
+                    ui.end_row();
+
+                    for (ix, column) in self.columns.iter().enumerate() {
+                        ui.label(column);
+                        ui.radio_value(&mut self.mapping.id_column, ix, "");
+                        ui.radio_value(&mut self.mapping.value_column, ix, "");
+
+                        let sample = first_row
+                            .and_then(|row| row.get(ix))
+                            .map(|value| value.as_str())
+                            .unwrap_or_default();
+                        ui.label(sample);
+
+                        ui.end_row();
+                    }
+                },
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Values");
+            ui.radio_value(
+                &mut self.mapping.values,
+                NodeTableValues::Auto,
+                "Detect",
+            )
+            .on_hover_text(
+                "Numbers if every value is a number, otherwise hex colors",
+            );
+            ui.radio_value(
+                &mut self.mapping.values,
+                NodeTableValues::Color,
+                "Hex colors",
+            );
+            ui.radio_value(
+                &mut self.mapping.values,
+                NodeTableValues::Numeric,
+                "Numbers",
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Overlay name");
+            ui.separator();
+            ui.text_edit_singleline(&mut self.name);
+        });
+
+        let same_column = self.mapping.id_column == self.mapping.value_column;
+
+        let create_btn = ui.add(
+            egui::Button::new("Create overlay")
+                .enabled(!self.running && !same_column),
+        );
+
+        if same_column {
+            ui.label("Pick different columns for the node IDs and values");
+        }
+
+        if create_btn.clicked() {
+            let name = if self.name.trim().is_empty() {
+                let value_column = self
+                    .columns
+                    .get(self.mapping.value_column)
+                    .cloned()
+                    .unwrap_or_default();
+                format!("{} - {}", table.file_name(), value_column)
+            } else {
+                self.name.clone()
+            };
+
+            self.running = true;
+
+            self.results
+                .call(NodeTableInput {
+                    name,
+                    table: table.clone(),
+                    mapping: self.mapping,
+                })
+                .unwrap();
+        }
+
+        self.result_ui(ui);
+    }
+
+    fn result_ui(&self, ui: &mut egui::Ui) {
+        if self.running {
+            ui.label("Creating overlay");
+        } else {
+            match &self.latest_result {
+                Some(Ok(report)) => {
+                    ui.label(format!("Created overlay: {}", report));
+                }
+                Some(Err(err)) => {
+                    ui.label(format!("Error: {}", err));
+                }
+                None => (),
+            }
+        }
+    }
+}
+
 pub enum OverlayCreatorMsg {
-    NewOverlay { name: String, data: OverlayData },
+    NewOverlay {
+        name: String,
+        data: OverlayData,
+        /// If `None`, value overlays get a legend covering their
+        /// values, and RGB overlays get no legend
+        legend: Option<OverlayLegend>,
+    },
+    /// A value overlay whose values haven't been normalized to the
+    /// 0..1 range; NaN marks nodes without a value
+    NewValueOverlay { name: String, values: Vec<f32> },
+    /// A path depth overlay, with the paths that were counted, which
+    /// are kept with the overlay; if `overlay_id` is an existing path
+    /// depth overlay, it's updated in place
+    PathDepth {
+        name: String,
+        overlay_id: Option<usize>,
+        paths: Vec<PathId>,
+        depths: Vec<f32>,
+    },
+    /// An overlay built from a script, which is kept with the
+    /// overlay; values are normalized like in `NewValueOverlay`. If
+    /// `overlay_id` is an existing overlay of the same kind, it's
+    /// updated in place
+    Script {
+        name: String,
+        overlay_id: Option<usize>,
+        script: OverlayScript,
+        data: OverlayData,
+    },
 }
 
 pub struct GradientPicker {
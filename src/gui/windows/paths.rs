@@ -119,6 +119,8 @@ impl PathListSlot {
 pub struct PathDetails {
     pub(crate) path_details: PathListSlot,
     pub(crate) step_list: StepList,
+
+    pin_request: Option<PathId>,
 }
 
 impl PathDetails {
@@ -128,19 +130,24 @@ impl PathDetails {
         Self {
             path_details: Default::default(),
             step_list: StepList::new(reactor, 15),
+
+            pin_request: None,
         }
     }
 
-    pub fn ui(
-        &mut self,
-        open_path_details: &mut bool,
-        graph_query: &GraphQuery,
-        graph_query_worker: &GraphQueryWorker,
-        ctx: &egui::CtxRef,
-        node_details_id_cell: &AtomicCell<Option<NodeId>>,
-        open_node_details: &mut bool,
-        app_msg_tx: &Sender<AppMsg>,
-    ) -> Option<egui::InnerResponse<Option<()>>> {
+    /// A path details state fixed to a single path, used for pinned
+    /// path details windows
+    pub fn pinned(reactor: &mut Reactor, path: PathId) -> Self {
+        let details = Self::new(reactor);
+        details.path_details.path_id.store(Some(path));
+        details
+    }
+
+    pub fn take_pin_request(&mut self) -> Option<PathId> {
+        self.pin_request.take()
+    }
+
+    fn update(&mut self, graph_query: &GraphQuery) -> Option<()> {
         self.path_details.fetch(graph_query)?;
 
         if let Some(path) = self.path_details.path_id.load() {
@@ -157,66 +164,185 @@ impl PathDetails {
             }
         }
 
+        Some(())
+    }
+
+    pub fn ui(
+        &mut self,
+        open_path_details: &mut bool,
+        graph_query: &GraphQuery,
+        _graph_query_worker: &GraphQueryWorker,
+        ctx: &egui::CtxRef,
+        node_details_id_cell: &AtomicCell<Option<NodeId>>,
+        open_node_details: &mut bool,
+        app_msg_tx: &Sender<AppMsg>,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        self.update(graph_query)?;
+
         egui::Window::new("Path details")
             .id(egui::Id::new(Self::ID))
             .default_pos(egui::Pos2::new(600.0, 200.0))
             .open(open_path_details)
             .show(ctx, |ui| {
-                if let Some(_path_id) = self.path_details.path_id.load() {
-                    ui.set_min_height(200.0);
-                    ui.set_max_width(300.0);
-
-                    ui.label(format!(
-                        "Path name: {}",
-                        self.path_details.path_name.as_bstr()
-                    ));
-
-                    ui.separator();
-
+                if let Some(path_id) = self.path_details.path_id.load() {
                     ui.horizontal(|ui| {
                         ui.label(format!(
-                            "Step count: {}",
-                            self.path_details.step_count
-                        ));
-
-                        ui.separator();
-
-                        ui.label(format!(
-                            "Base count: {}",
-                            self.path_details.base_count
+                            "Path name: {}",
+                            self.path_details.path_name.as_bstr()
                         ));
-                    });
-
-                    ui.separator();
 
-                    ui.horizontal(|ui| {
-                        ui.label(format!(
-                            "First step: {}",
-                            self.path_details.head.to_vector_value()
-                        ));
-
-                        ui.separator();
-
-                        ui.label(format!(
-                            "Last step: {}",
-                            self.path_details.tail.to_vector_value()
-                        ));
+                        if ui
+                            .button("Pin")
+                            .on_hover_text(
+                                "Keep this path open in its own window",
+                            )
+                            .clicked()
+                        {
+                            self.pin_request = Some(path_id);
+                        }
                     });
 
-                    self.step_list.ui(
+                    self.details_ui(
                         ui,
-                        app_msg_tx,
                         graph_query,
                         node_details_id_cell,
                         open_node_details,
+                        app_msg_tx,
                     );
-
-                    ui.shrink_width_to_current();
                 } else {
                     ui.label("Examine a path by picking it from the path list");
                 }
             })
     }
+
+    /// Shows this path in a separate window, returning `false` once
+    /// the window has been closed
+    pub fn pinned_ui(
+        &mut self,
+        graph_query: &GraphQuery,
+        ctx: &egui::CtxRef,
+        node_details_id_cell: &AtomicCell<Option<NodeId>>,
+        open_node_details: &mut bool,
+        app_msg_tx: &Sender<AppMsg>,
+    ) -> bool {
+        if self.update(graph_query).is_none() {
+            return false;
+        }
+
+        let path_id = match self.path_details.path_id.load() {
+            Some(path_id) => path_id,
+            None => return false,
+        };
+
+        let mut open = true;
+
+        egui::Window::new(format!(
+            "{} (pinned)",
+            self.path_details.path_name.as_bstr()
+        ))
+        .id(egui::Id::new(Self::ID).with(("pinned", path_id.0)))
+        .default_pos(egui::Pos2::new(600.0, 200.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "Path name: {}",
+                self.path_details.path_name.as_bstr()
+            ));
+
+            self.details_ui(
+                ui,
+                graph_query,
+                node_details_id_cell,
+                open_node_details,
+                app_msg_tx,
+            );
+        });
+
+        open
+    }
+
+    fn details_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        graph_query: &GraphQuery,
+        node_details_id_cell: &AtomicCell<Option<NodeId>>,
+        open_node_details: &mut bool,
+        app_msg_tx: &Sender<AppMsg>,
+    ) {
+        ui.set_min_height(200.0);
+        ui.set_max_width(300.0);
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label(format!("Step count: {}", self.path_details.step_count));
+
+            ui.separator();
+
+            ui.label(format!("Base count: {}", self.path_details.base_count));
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "First step: {}",
+                self.path_details.head.to_vector_value()
+            ));
+
+            ui.separator();
+
+            ui.label(format!(
+                "Last step: {}",
+                self.path_details.tail.to_vector_value()
+            ));
+        });
+
+        if let Some(path) = self.path_details.path_id.load() {
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Show direction")
+                    .on_hover_text(
+                        "Highlight the path and color its edges \
+                         from start to end",
+                    )
+                    .clicked()
+                {
+                    app_msg_tx.send(AppMsg::SetPathEdges(Some(path))).unwrap();
+                }
+
+                if ui.button("Clear direction").clicked() {
+                    app_msg_tx.send(AppMsg::SetPathEdges(None)).unwrap();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Tube view")
+                    .on_hover_text(
+                        "Show only this path, with its nodes in a line",
+                    )
+                    .clicked()
+                {
+                    app_msg_tx.send(AppMsg::SetPathTube(Some(path))).unwrap();
+                }
+
+                if ui.button("Exit tube view").clicked() {
+                    app_msg_tx.send(AppMsg::SetPathTube(None)).unwrap();
+                }
+            });
+        }
+
+        self.step_list.ui(
+            ui,
+            app_msg_tx,
+            graph_query,
+            node_details_id_cell,
+            open_node_details,
+        );
+
+        ui.shrink_width_to_current();
+    }
 }
 
 impl PathList {
@@ -0,0 +1,118 @@
+use crossbeam::channel::Sender;
+
+use crate::app::{selection::SelectionGroups, AppMsg};
+
+/// Lists the selection groups, letting the user pick the active
+/// group, change group colors, and add and remove groups
+pub struct SelectionGroupList {
+    new_group_name: String,
+}
+
+impl std::default::Default for SelectionGroupList {
+    fn default() -> Self {
+        Self {
+            new_group_name: String::new(),
+        }
+    }
+}
+
+impl SelectionGroupList {
+    pub const ID: &'static str = "selection_group_list";
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+        groups: &SelectionGroups,
+        app_msg_tx: &Sender<AppMsg>,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        let new_group_name = &mut self.new_group_name;
+
+        egui::Window::new("Selection groups")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                egui::Grid::new("selection_group_list_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Active");
+                        ui.label("Name");
+                        ui.label("Color");
+                        ui.label("Nodes");
+                        ui.end_row();
+
+                        let can_remove = groups.len() > 1;
+
+                        for (ix, group) in groups.groups().iter().enumerate() {
+                            let active = ix == groups.active();
+
+                            if ui.radio(active, "").clicked() && !active {
+                                app_msg_tx
+                                    .send(AppMsg::SetActiveSelectionGroup(ix))
+                                    .unwrap();
+                            }
+
+                            ui.label(&group.name);
+
+                            let mut color =
+                                [group.color.r, group.color.g, group.color.b];
+
+                            if ui.color_edit_button_rgb(&mut color).changed() {
+                                let [r, g, b] = color;
+                                app_msg_tx
+                                    .send(AppMsg::SetSelectionGroupColor {
+                                        group: ix,
+                                        color: rgb::RGB::new(r, g, b),
+                                    })
+                                    .unwrap();
+                            }
+
+                            // the nodes of the active group are kept
+                            // by the app, not in the group list
+                            if active {
+                                ui.label("-");
+                            } else {
+                                ui.label(group.nodes.len().to_string());
+                            }
+
+                            if ui
+                                .add(
+                                    egui::Button::new("Remove")
+                                        .enabled(can_remove),
+                                )
+                                .clicked()
+                            {
+                                app_msg_tx
+                                    .send(AppMsg::RemoveSelectionGroup(ix))
+                                    .unwrap();
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(new_group_name);
+
+                    let name = new_group_name.trim();
+
+                    let add = ui.add(
+                        egui::Button::new("New group")
+                            .enabled(!name.is_empty()),
+                    );
+
+                    if add.clicked() {
+                        app_msg_tx
+                            .send(AppMsg::NewSelectionGroup {
+                                name: name.to_string(),
+                                color: None,
+                            })
+                            .unwrap();
+                        new_group_name.clear();
+                    }
+                });
+            })
+    }
+}
@@ -0,0 +1,317 @@
+use crossbeam::channel::Sender;
+
+use handlegraph::{
+    handle::{Handle, NodeId},
+    handlegraph::*,
+};
+
+use rustc_hash::FxHashSet;
+
+use crate::app::{AppMsg, Select};
+use crate::asynchronous::AsyncResult;
+use crate::graph_query::GraphQueryWorker;
+
+/// Searches the node sequences for a pattern on a worker thread, and
+/// steps the view through the matching nodes one at a time,
+/// selecting and centering each. A short pattern can match most of
+/// the graph, so the results are listed a page at a time
+pub struct SequenceSearch {
+    pattern: String,
+
+    /// The matching nodes and their lengths
+    query: Option<AsyncResult<Vec<(NodeId, usize)>>>,
+    /// The pattern of the running query
+    query_pattern: String,
+
+    /// The results of the latest finished search; these are only
+    /// replaced when a new search finishes, so navigating never
+    /// changes the list
+    results: Vec<(NodeId, usize)>,
+    results_pattern: String,
+
+    /// The page of `results` that is listed
+    page: usize,
+
+    /// Index into `results` of the node that was last navigated to
+    current: Option<usize>,
+    scroll_to_current: bool,
+}
+
+impl std::default::Default for SequenceSearch {
+    fn default() -> Self {
+        Self {
+            pattern: String::new(),
+
+            query: None,
+            query_pattern: String::new(),
+
+            results: Vec::new(),
+            results_pattern: String::new(),
+
+            page: 0,
+
+            current: None,
+            scroll_to_current: false,
+        }
+    }
+}
+
+impl SequenceSearch {
+    pub const ID: &'static str = "sequence_search_window";
+
+    const PAGE_SIZE: usize = 100;
+
+    /// Starts searching for `pattern`, replacing any running search
+    pub fn search(
+        &mut self,
+        graph_query_worker: &GraphQueryWorker,
+        pattern: &str,
+    ) {
+        let pattern = pattern.trim().to_string();
+
+        if pattern.is_empty() {
+            return;
+        }
+
+        self.pattern = pattern.clone();
+        self.query_pattern = pattern.clone();
+
+        let query =
+            graph_query_worker.run_query(move |graph_query| async move {
+                let graph = graph_query.graph();
+
+                graph_query
+                    .nodes_with_sequence(pattern.as_bytes())
+                    .into_iter()
+                    .map(|node| {
+                        (node, graph.node_len(Handle::pack(node, false)))
+                    })
+                    .collect()
+            });
+
+        self.query = Some(query);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.query.is_some()
+    }
+
+    fn take_finished_query(&mut self) {
+        let results = self
+            .query
+            .as_mut()
+            .and_then(|query| query.take_result_if_ready());
+
+        if let Some(results) = results {
+            self.query = None;
+
+            self.results = results;
+            self.results_pattern = std::mem::take(&mut self.query_pattern);
+            self.current = None;
+            self.page = 0;
+        }
+    }
+
+    /// Selects and centers the view on the result at `ix`
+    fn goto_result(&mut self, app_msg_tx: &Sender<AppMsg>, ix: usize) {
+        if let Some(&(node, _)) = self.results.get(ix) {
+            self.current = Some(ix);
+            self.scroll_to_current = true;
+            self.page = ix / Self::PAGE_SIZE;
+
+            app_msg_tx
+                .send(AppMsg::Selection(Select::One { node, clear: true }))
+                .unwrap();
+            app_msg_tx.send(AppMsg::GotoSelection).unwrap();
+        }
+    }
+
+    /// Moves `step` results forward or backward from the current
+    /// one, wrapping around at either end
+    fn step(&mut self, app_msg_tx: &Sender<AppMsg>, step: isize) {
+        let count = self.results.len() as isize;
+
+        if count == 0 {
+            return;
+        }
+
+        let next = match self.current {
+            Some(ix) => (ix as isize + step).rem_euclid(count),
+            None if step < 0 => count - 1,
+            None => 0,
+        };
+
+        self.goto_result(app_msg_tx, next as usize);
+    }
+
+    fn select_all(&self, app_msg_tx: &Sender<AppMsg>) {
+        let nodes = self
+            .results
+            .iter()
+            .map(|(node, _)| *node)
+            .collect::<FxHashSet<_>>();
+
+        app_msg_tx
+            .send(AppMsg::Selection(Select::Many { nodes, clear: true }))
+            .unwrap();
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+        graph_query_worker: &GraphQueryWorker,
+        app_msg_tx: &Sender<AppMsg>,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        self.take_finished_query();
+
+        egui::Window::new("Sequence search")
+            .id(egui::Id::new(Self::ID))
+            .default_pos(egui::Pos2::new(300.0, 200.0))
+            .open(open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let field = ui.text_edit_singleline(&mut self.pattern);
+
+                    let enter = field.lost_focus()
+                        && ui.input().key_pressed(egui::Key::Enter);
+
+                    let search = ui.add(
+                        egui::Button::new("Search")
+                            .enabled(!self.pattern.trim().is_empty()),
+                    );
+
+                    if search.clicked() || enter {
+                        let pattern = self.pattern.clone();
+                        self.search(graph_query_worker, &pattern);
+                    }
+                });
+
+                if self.is_running() {
+                    ui.label(format!(
+                        "Searching for {}...",
+                        self.query_pattern
+                    ));
+                }
+
+                if self.results_pattern.is_empty() {
+                    return;
+                }
+
+                ui.separator();
+
+                if self.results.is_empty() {
+                    ui.label(format!(
+                        "No nodes contain {}",
+                        self.results_pattern
+                    ));
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Prev").clicked() {
+                        self.step(app_msg_tx, -1);
+                    }
+
+                    if ui.button("Next").clicked() {
+                        self.step(app_msg_tx, 1);
+                    }
+
+                    let position = match self.current {
+                        Some(ix) => {
+                            format!("{} of {}", ix + 1, self.results.len())
+                        }
+                        None => format!("- of {}", self.results.len()),
+                    };
+
+                    ui.label(position);
+
+                    if ui.button("Select all").clicked() {
+                        self.select_all(app_msg_tx);
+                    }
+                });
+
+                ui.label(format!("Nodes containing {}", self.results_pattern));
+
+                let page_count = (self.results.len() + Self::PAGE_SIZE - 1)
+                    / Self::PAGE_SIZE;
+
+                if page_count > 1 {
+                    ui.horizontal(|ui| {
+                        if ui.button("First").clicked() {
+                            self.page = 0;
+                        }
+
+                        if ui.button("Prev").clicked() && self.page > 0 {
+                            self.page -= 1;
+                        }
+
+                        ui.label(format!(
+                            "Page {}/{}",
+                            self.page + 1,
+                            page_count
+                        ));
+
+                        if ui.button("Next").clicked()
+                            && self.page + 1 < page_count
+                        {
+                            self.page += 1;
+                        }
+
+                        if ui.button("Last").clicked() {
+                            self.page = page_count - 1;
+                        }
+                    });
+                }
+
+                let page_start = self.page * Self::PAGE_SIZE;
+                let page_end =
+                    (page_start + Self::PAGE_SIZE).min(self.results.len());
+
+                let mut clicked = None;
+
+                egui::ScrollArea::from_max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("sequence_search_results")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("Node");
+                            ui.label("Length");
+                            ui.end_row();
+
+                            let page = &self.results[page_start..page_end];
+
+                            for (offset, &(node, length)) in
+                                page.iter().enumerate()
+                            {
+                                let ix = page_start + offset;
+                                let current = self.current == Some(ix);
+
+                                let label = ui.selectable_label(
+                                    current,
+                                    node.0.to_string(),
+                                );
+
+                                if current && self.scroll_to_current {
+                                    label.scroll_to_me(egui::Align::Center);
+                                }
+
+                                if label.clicked() {
+                                    clicked = Some(ix);
+                                }
+
+                                ui.label(length.to_string());
+
+                                ui.end_row();
+                            }
+                        });
+                });
+
+                self.scroll_to_current = false;
+
+                if let Some(ix) = clicked {
+                    self.goto_result(app_msg_tx, ix);
+                    self.scroll_to_current = false;
+                }
+            })
+    }
+}
@@ -1,14 +1,20 @@
+use crossbeam::channel::Sender;
+
 use crate::{
-    app::{AppSettings, SharedState},
+    app::{AppMsg, AppSettings, SharedState},
     geometry::Point,
 };
 
+pub mod annotations;
 pub mod debug;
 pub mod gui;
+pub mod input;
 pub mod main_view;
 
+use annotations::*;
 use debug::*;
 use gui::*;
+use input::*;
 use main_view::*;
 
 pub struct SettingsWindow {
@@ -17,6 +23,10 @@ pub struct SettingsWindow {
     pub(crate) debug: DebugSettings,
     pub(crate) gui: GuiSettings,
     pub(crate) main_view: MainViewSettings,
+    pub(crate) annotations: AnnotationSettings,
+    pub(crate) input: InputSettings,
+
+    script_path: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
@@ -24,6 +34,8 @@ enum SettingsTab {
     MainView,
     Debug,
     Gui,
+    Annotations,
+    Input,
 }
 
 impl SettingsWindow {
@@ -32,15 +44,23 @@ impl SettingsWindow {
     pub fn new(settings: &AppSettings, shared_state: &SharedState) -> Self {
         let current_tab = SettingsTab::MainView;
 
-        let main_view =
-            MainViewSettings::new(settings, shared_state.clone_edges_enabled());
+        let main_view = MainViewSettings::new(
+            settings,
+            shared_state.clone_edges_enabled(),
+            shared_state.clone_view(),
+            shared_state.clone_layout_spacing(),
+        );
 
         Self {
             current_tab,
 
             debug: Default::default(),
-            gui: Default::default(),
+            gui: GuiSettings::new(settings),
             main_view,
+            annotations: AnnotationSettings::new(settings),
+            input: InputSettings::new(settings),
+
+            script_path: "gfaestus_settings.rhai".to_string(),
         }
     }
 
@@ -48,6 +68,7 @@ impl SettingsWindow {
         &mut self,
         ctx: &egui::CtxRef,
         open: &mut bool,
+        app_msg_tx: &Sender<AppMsg>,
         // ) -> Option<egui::Response> {
     ) -> Option<egui::InnerResponse<Option<()>>> {
         egui::Window::new("Settings")
@@ -66,6 +87,16 @@ impl SettingsWindow {
                         SettingsTab::Gui,
                         "GUI",
                     );
+                    ui.selectable_value(
+                        &mut self.current_tab,
+                        SettingsTab::Annotations,
+                        "Annotations",
+                    );
+                    ui.selectable_value(
+                        &mut self.current_tab,
+                        SettingsTab::Input,
+                        "Input",
+                    );
                     ui.selectable_value(
                         &mut self.current_tab,
                         SettingsTab::Debug,
@@ -75,7 +106,7 @@ impl SettingsWindow {
 
                 match self.current_tab {
                     SettingsTab::MainView => {
-                        self.main_view.ui(ui);
+                        self.main_view.ui(ui, app_msg_tx);
                     }
                     SettingsTab::Debug => {
                         self.debug.ui(ui);
@@ -83,7 +114,32 @@ impl SettingsWindow {
                     SettingsTab::Gui => {
                         self.gui.ui(ui);
                     }
+                    SettingsTab::Annotations => {
+                        self.annotations.ui(ui);
+                    }
+                    SettingsTab::Input => {
+                        self.input.ui(ui);
+                    }
                 }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Settings script");
+                    ui.text_edit_singleline(&mut self.script_path);
+
+                    if ui.button("Save").clicked() {
+                        let path = std::path::PathBuf::from(&self.script_path);
+                        app_msg_tx.send(AppMsg::SaveSettings(path)).unwrap();
+                    }
+                });
+
+                ui.label(
+                    "The mouse bindings and default columns are only kept \
+                     until gfaestus is closed; to keep them, save them to a script and run \
+                     it at startup with --run-script, or with :exec in \
+                     the console",
+                );
             })
     }
 }
@@ -0,0 +1,121 @@
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+use crate::annotations::AnnotationFileType;
+use crate::app::{AppSettings, DefaultColumns};
+
+/// The default record list columns for each annotation file type,
+/// edited as comma-separated lists of column names
+pub struct AnnotationSettings {
+    default_columns: Vec<DefaultColumnsEditor>,
+}
+
+struct DefaultColumnsEditor {
+    file_type: AnnotationFileType,
+    columns: Arc<RwLock<DefaultColumns>>,
+
+    /// The columns the text fields were last filled from, so changes
+    /// made from the console are picked up
+    last_columns: DefaultColumns,
+
+    enabled_text: String,
+    hidden_text: String,
+}
+
+impl DefaultColumnsEditor {
+    fn new(
+        file_type: AnnotationFileType,
+        columns: Arc<RwLock<DefaultColumns>>,
+    ) -> Self {
+        let mut editor = Self {
+            file_type,
+            columns,
+
+            last_columns: DefaultColumns::default(),
+
+            enabled_text: String::new(),
+            hidden_text: String::new(),
+        };
+
+        editor.refresh();
+        editor
+    }
+
+    fn refresh(&mut self) {
+        let columns = self.columns.read().clone();
+
+        if columns != self.last_columns {
+            self.enabled_text = columns.enabled.join(", ");
+            self.hidden_text = columns.hidden.join(", ");
+            self.last_columns = columns;
+        }
+    }
+
+    fn parse_list(text: &str) -> Vec<String> {
+        text.split(',')
+            .map(|column| column.trim())
+            .filter(|column| !column.is_empty())
+            .map(|column| column.to_string())
+            .collect()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        self.refresh();
+
+        let enabled = ui.horizontal(|ui| {
+            ui.label("Shown");
+            ui.text_edit_singleline(&mut self.enabled_text)
+        });
+
+        let hidden = ui.horizontal(|ui| {
+            ui.label("Hidden");
+            ui.text_edit_singleline(&mut self.hidden_text)
+        });
+
+        if enabled.inner.changed() || hidden.inner.changed() {
+            let columns = DefaultColumns {
+                enabled: Self::parse_list(&self.enabled_text),
+                hidden: Self::parse_list(&self.hidden_text),
+            };
+
+            *self.columns.write() = columns.clone();
+            self.last_columns = columns;
+        }
+    }
+}
+
+impl AnnotationSettings {
+    pub fn new(settings: &AppSettings) -> Self {
+        let default_columns = AnnotationFileType::ALL
+            .iter()
+            .map(|&file_type| {
+                DefaultColumnsEditor::new(
+                    file_type,
+                    settings.default_columns(file_type).clone(),
+                )
+            })
+            .collect();
+
+        Self { default_columns }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Default record list columns").on_hover_text(
+            "Comma-separated column names, as shown in the column \
+             picker. Applied when a file is first opened in the \
+             record list",
+        );
+
+        for editor in self.default_columns.iter_mut() {
+            ui.separator();
+
+            let heading = match editor.file_type {
+                AnnotationFileType::Gff3 => "GFF3",
+                AnnotationFileType::Bed => "BED",
+            };
+
+            ui.label(heading);
+            editor.ui(ui);
+        }
+    }
+}
@@ -1,13 +1,31 @@
 use crossbeam::atomic::AtomicCell;
 use std::sync::Arc;
 
-use crate::app::{AppSettings, NodeWidth};
+use crate::app::{AppSettings, NodeLabelMode, NodeWidth};
 
 pub struct GuiSettings {
     // show_fps: Arc<AtomicCell<bool>>,
     // show_graph_stats: Arc<AtomicCell<bool>>,
     pub(crate) show_fps: bool,
     pub(crate) show_graph_stats: bool,
+    pub(crate) show_node_tooltips: bool,
+
+    node_label_mode: Arc<AtomicCell<NodeLabelMode>>,
+
+    hover_debounce_ms: Arc<AtomicCell<f32>>,
+
+    console_output_to_log: Arc<AtomicCell<bool>>,
+}
+
+impl GuiSettings {
+    pub fn new(settings: &AppSettings) -> Self {
+        Self {
+            node_label_mode: settings.node_label_mode().clone(),
+            hover_debounce_ms: settings.hover_debounce_ms().clone(),
+            console_output_to_log: settings.console_output_to_log().clone(),
+            ..Self::default()
+        }
+    }
 }
 
 impl std::default::Default for GuiSettings {
@@ -17,6 +35,13 @@ impl std::default::Default for GuiSettings {
             // show_graph_stats: Arc::new(true.into()),
             show_fps: false,
             show_graph_stats: false,
+            show_node_tooltips: true,
+
+            node_label_mode: Arc::new(NodeLabelMode::NodeId.into()),
+
+            hover_debounce_ms: Arc::new(150.0.into()),
+
+            console_output_to_log: Arc::new(false.into()),
         }
     }
 }
@@ -25,5 +50,49 @@ impl GuiSettings {
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         ui.checkbox(&mut self.show_fps, "Display FPS");
         ui.checkbox(&mut self.show_graph_stats, "Display graph stats");
+        ui.checkbox(&mut self.show_node_tooltips, "Show node tooltips");
+
+        let mut hover_debounce = self.hover_debounce_ms.load();
+
+        let hover_debounce_slider = ui
+            .add(
+                egui::Slider::new::<f32>(&mut hover_debounce, 0.0..=1000.0)
+                    .text("Tooltip delay (ms)"),
+            )
+            .on_hover_text(
+                "How long the cursor must rest on a node before its \
+                 details are fetched for the tooltip. Default: 150",
+            );
+
+        if hover_debounce_slider.changed() {
+            self.hover_debounce_ms.store(hover_debounce);
+        }
+
+        let mut label_mode = self.node_label_mode.load();
+
+        ui.horizontal(|ui| {
+            ui.label("Label nodes by");
+            ui.radio_value(&mut label_mode, NodeLabelMode::NodeId, "Node ID");
+            ui.radio_value(
+                &mut label_mode,
+                NodeLabelMode::PathCoordinate,
+                "Reference coordinate",
+            )
+            .on_hover_text(
+                "Show positions on the reference path, where one is set",
+            );
+        });
+
+        if label_mode != self.node_label_mode.load() {
+            self.node_label_mode.store(label_mode);
+        }
+
+        let mut output_to_log = self.console_output_to_log.load();
+        if ui
+            .checkbox(&mut output_to_log, "Write console output to log")
+            .changed()
+        {
+            self.console_output_to_log.store(output_to_log);
+        }
     }
 }
@@ -0,0 +1,116 @@
+use crossbeam::atomic::AtomicCell;
+use std::sync::Arc;
+
+use crate::app::{
+    AppSettings, BindableButton, BindableModifier, MouseBindings,
+};
+
+/// Which mouse buttons and modifiers drive selection and panning in
+/// the main view; changes are picked up by the input manager
+pub struct InputSettings {
+    mouse_bindings: Arc<AtomicCell<MouseBindings>>,
+
+    /// Bindings edited into a conflicting state, which are kept here
+    /// and not applied until the conflict is resolved
+    invalid_edit: Option<MouseBindings>,
+}
+
+impl InputSettings {
+    pub fn new(settings: &AppSettings) -> Self {
+        Self {
+            mouse_bindings: settings.mouse_bindings().clone(),
+            invalid_edit: None,
+        }
+    }
+
+    fn button_label(button: BindableButton) -> &'static str {
+        match button {
+            BindableButton::Left => "Left",
+            BindableButton::Right => "Right",
+            BindableButton::Middle => "Middle",
+        }
+    }
+
+    fn modifier_label(modifier: BindableModifier) -> &'static str {
+        match modifier {
+            BindableModifier::None => "None",
+            BindableModifier::Shift => "Shift",
+            BindableModifier::Ctrl => "Ctrl",
+            BindableModifier::Alt => "Alt",
+        }
+    }
+
+    fn button_row(ui: &mut egui::Ui, label: &str, value: &mut BindableButton) {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            for &button in BindableButton::ALL.iter() {
+                ui.radio_value(value, button, Self::button_label(button));
+            }
+        });
+    }
+
+    fn modifier_row(
+        ui: &mut egui::Ui,
+        label: &str,
+        options: &[BindableModifier],
+        value: &mut BindableModifier,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            for &modifier in options.iter() {
+                ui.radio_value(value, modifier, Self::modifier_label(modifier));
+            }
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let stored = self.mouse_bindings.load();
+        let mut bindings = self.invalid_edit.unwrap_or(stored);
+
+        ui.label("Main view mouse buttons");
+
+        Self::button_row(ui, "Select", &mut bindings.select);
+        Self::button_row(ui, "Pan", &mut bindings.pan);
+
+        ui.separator();
+
+        ui.label("Modifiers");
+
+        // without a modifier, rectangle select would clash with panning
+        Self::modifier_row(
+            ui,
+            "Rectangle select (with pan)",
+            &BindableModifier::ALL[1..],
+            &mut bindings.rect_select,
+        );
+        Self::modifier_row(
+            ui,
+            "Add to selection (with select)",
+            &BindableModifier::ALL,
+            &mut bindings.add_select,
+        );
+
+        if bindings.add_select == BindableModifier::None {
+            ui.label("Every select click adds to the selection");
+        }
+
+        if ui.button("Reset to defaults").clicked() {
+            bindings = MouseBindings::default();
+        }
+
+        // only valid bindings are applied, as in the console
+        match bindings.validate() {
+            Ok(()) => {
+                self.invalid_edit = None;
+
+                if bindings != stored {
+                    self.mouse_bindings.store(bindings);
+                }
+            }
+            Err(err) => {
+                ui.label(format!("Conflicting bindings, not applied: {}", err));
+                self.invalid_edit = Some(bindings);
+            }
+        }
+    }
+}
@@ -1,39 +1,75 @@
-use crossbeam::atomic::AtomicCell;
+use crossbeam::{atomic::AtomicCell, channel::Sender};
 use std::sync::Arc;
 
 use crate::{
-    app::{AppSettings, NodeWidth},
+    app::{
+        AppMsg, AppSettings, FollowZoom, LabelPriority, NodeWidth,
+        ResetViewMode,
+    },
+    universe::{MAX_LAYOUT_SPACING, MIN_LAYOUT_SPACING},
+    view::View,
     vulkan::draw_system::edges::EdgesUBO,
 };
 
 pub struct MainViewSettings {
     node_width: Arc<NodeWidth>,
     label_radius: Arc<AtomicCell<f32>>,
+    max_label_clusters: Arc<AtomicCell<usize>>,
+    label_priority: Arc<AtomicCell<LabelPriority>>,
+    zoom_step: Arc<AtomicCell<f32>>,
+    reset_view_mode: Arc<AtomicCell<ResetViewMode>>,
+
+    follow_selection: Arc<AtomicCell<bool>>,
+    follow_zoom: Arc<AtomicCell<FollowZoom>>,
 
     edges_enabled: Arc<AtomicCell<bool>>,
     edges_ubo: Arc<AtomicCell<EdgesUBO>>,
+
+    view: Arc<AtomicCell<View>>,
+
+    layout_spacing: Arc<AtomicCell<f32>>,
 }
 
 impl MainViewSettings {
     pub fn new(
         settings: &AppSettings,
         edges_enabled: Arc<AtomicCell<bool>>,
+        view: Arc<AtomicCell<View>>,
+        layout_spacing: Arc<AtomicCell<f32>>,
     ) -> Self {
         let node_width = settings.node_width().clone();
         let label_radius = settings.label_radius().clone();
+        let max_label_clusters = settings.max_label_clusters().clone();
+        let label_priority = settings.label_priority().clone();
+        let zoom_step = settings.zoom_step().clone();
+        let reset_view_mode = settings.reset_view_mode().clone();
+
+        let follow_selection = settings.follow_selection().clone();
+        let follow_zoom = settings.follow_zoom().clone();
 
         let edges_ubo = settings.edge_renderer().clone();
 
         Self {
             node_width,
             label_radius,
+            max_label_clusters,
+            label_priority,
+            zoom_step,
+            reset_view_mode,
+
+            follow_selection,
+            follow_zoom,
 
             edges_enabled,
             edges_ubo,
+
+            view,
+
+            layout_spacing,
         }
     }
 
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
+    pub fn ui(&mut self, ui: &mut egui::Ui, app_msg_tx: &Sender<AppMsg>) {
         let mut min_width = self.node_width.min_node_width();
         let mut max_width = self.node_width.max_node_width();
 
@@ -126,5 +162,161 @@ impl MainViewSettings {
         if label_radius_slider.changed() {
             self.label_radius.store(label_radius);
         }
+
+        let mut max_label_clusters = self.max_label_clusters.load();
+
+        let max_label_clusters_slider = ui
+            .add(
+                egui::Slider::new::<usize>(&mut max_label_clusters, 0..=2000)
+                    .text("Max label clusters"),
+            )
+            .on_hover_text(
+                "The most label clusters drawn at once, across all visible \
+                 label sets. 0 draws all clusters. Default: 250",
+            );
+
+        if max_label_clusters_slider.changed() {
+            self.max_label_clusters.store(max_label_clusters);
+        }
+
+        let mut label_priority = self.label_priority.load();
+
+        ui.horizontal(|ui| {
+            ui.label("Keep labels").on_hover_text(
+                "Which clusters are drawn when there are more than the max. \
+                 Default: Nearest center",
+            );
+            ui.radio_value(
+                &mut label_priority,
+                LabelPriority::NearestCenter,
+                "Nearest center",
+            );
+            ui.radio_value(
+                &mut label_priority,
+                LabelPriority::LargestCluster,
+                "Largest clusters",
+            );
+        });
+
+        if label_priority != self.label_priority.load() {
+            self.label_priority.store(label_priority);
+        }
+
+        let mut zoom_step = self.zoom_step.load();
+
+        let zoom_step_slider = ui
+            .add(
+                egui::Slider::new::<f32>(&mut zoom_step, 1.1..=4.0)
+                    .text("Keyboard zoom step"),
+            )
+            .on_hover_text(
+                "The factor the view scale is changed by with +/-. Default: 1.5",
+            );
+
+        if zoom_step_slider.changed() {
+            self.zoom_step.store(zoom_step);
+        }
+
+        let mut aspect = self.view.load().aspect;
+
+        ui.horizontal(|ui| {
+            let aspect_slider = ui
+                .add(
+                    egui::Slider::new::<f32>(&mut aspect, 0.1..=100.0)
+                        .logarithmic(true)
+                        .text("View aspect"),
+                )
+                .on_hover_text(
+                    "The ratio of the vertical to the horizontal view scale. \
+                     Values above 1 stretch the graph horizontally. Default: 1.0",
+                );
+
+            if aspect_slider.changed() {
+                app_msg_tx.send(AppMsg::SetViewAspect(aspect)).unwrap();
+            }
+
+            if ui.button("Reset").clicked() {
+                app_msg_tx.send(AppMsg::SetViewAspect(1.0)).unwrap();
+            }
+        });
+
+        let mut spacing = self.layout_spacing.load();
+
+        ui.horizontal(|ui| {
+            let spacing_slider = ui
+                .add(
+                    egui::Slider::new::<f32>(
+                        &mut spacing,
+                        MIN_LAYOUT_SPACING..=MAX_LAYOUT_SPACING,
+                    )
+                    .logarithmic(true)
+                    .text("Layout spacing"),
+                )
+                .on_hover_text(
+                    "Scales the distances between the nodes about the center \
+                     of the layout, relative to the loaded or most recently \
+                     computed layout. Default: 1.0",
+                );
+
+            if spacing_slider.changed() {
+                self.layout_spacing.store(spacing);
+            }
+
+            if ui.button("Reset").clicked() {
+                self.layout_spacing.store(1.0);
+            }
+        });
+
+        let mut reset_view_mode = self.reset_view_mode.load();
+
+        ui.horizontal(|ui| {
+            ui.label("Reset view (Home) to")
+                .on_hover_text("Default: Initial view");
+            ui.radio_value(
+                &mut reset_view_mode,
+                ResetViewMode::Initial,
+                "Initial view",
+            );
+            ui.radio_value(
+                &mut reset_view_mode,
+                ResetViewMode::FitGraph,
+                "Fit graph",
+            );
+        });
+
+        if reset_view_mode != self.reset_view_mode.load() {
+            self.reset_view_mode.store(reset_view_mode);
+        }
+
+        let mut follow_selection = self.follow_selection.load();
+
+        let follow_selection_box = ui
+            .checkbox(&mut follow_selection, "Follow selection")
+            .on_hover_text(
+                "Move the view to the selection whenever the selection \
+                 changes. Default: Off",
+            );
+
+        if follow_selection_box.changed() {
+            self.follow_selection.store(follow_selection);
+        }
+
+        let mut follow_zoom = self.follow_zoom.load();
+
+        ui.horizontal(|ui| {
+            ui.set_enabled(follow_selection);
+            ui.label("When following")
+                .on_hover_text("Default: Fit selection");
+            ui.radio_value(&mut follow_zoom, FollowZoom::Fit, "Fit selection");
+            ui.radio_value(
+                &mut follow_zoom,
+                FollowZoom::KeepScale,
+                "Keep zoom",
+            );
+        });
+
+        if follow_zoom != self.follow_zoom.load() {
+            self.follow_zoom.store(follow_zoom);
+        }
     }
 }
@@ -12,7 +12,10 @@ use std::sync::Arc;
 use crate::app::AppInput;
 use crate::gui::GuiInput;
 use crate::{app::mainview::MainViewInput, gui::GuiMsg};
-use crate::{app::SharedState, geometry::*};
+use crate::{
+    app::{AppSettings, MouseBindings, SharedState},
+    geometry::*,
+};
 
 pub mod binds;
 
@@ -52,6 +55,10 @@ pub struct InputManager {
     main_view: SubsystemInput<MainViewInput>,
     gui: SubsystemInput<GuiInput>,
 
+    mouse_bindings: Arc<AtomicCell<MouseBindings>>,
+    /// The mouse bindings the main view bindings were last built from
+    applied_mouse_bindings: MouseBindings,
+
     gui_focus_state: crate::gui::GuiFocusState,
 }
 
@@ -78,7 +85,16 @@ impl InputManager {
         self.mouse_screen_pos.clone()
     }
 
-    pub fn handle_events(&self, gui_msg_tx: &channel::Sender<GuiMsg>) {
+    pub fn handle_events(&mut self, gui_msg_tx: &channel::Sender<GuiMsg>) {
+        let mouse_bindings = self.mouse_bindings.load();
+
+        if mouse_bindings != self.applied_mouse_bindings {
+            self.main_view
+                .bindings
+                .set_mouse_binds(MainViewInput::mouse_binds(mouse_bindings));
+            self.applied_mouse_bindings = mouse_bindings;
+        }
+
         while let Ok(winit_ev) = self.winit_rx.try_recv() {
             if let event::WindowEvent::CursorMoved { position, .. } = winit_ev {
                 self.mouse_screen_pos.store(Point {
@@ -183,15 +199,26 @@ impl InputManager {
     pub fn new(
         winit_rx: channel::Receiver<event::WindowEvent<'static>>,
         shared_state: &SharedState,
+        settings: &AppSettings,
     ) -> Self {
         let mouse_screen_pos = shared_state.clone_mouse_pos();
 
         let gui_focus_state = shared_state.gui_focus_state.clone();
 
         let app = SubsystemInput::<AppInput>::from_default_binds();
-        let main_view = SubsystemInput::<MainViewInput>::from_default_binds();
+        let mut main_view =
+            SubsystemInput::<MainViewInput>::from_default_binds();
         let gui = SubsystemInput::<GuiInput>::from_default_binds();
 
+        let mouse_bindings = settings.mouse_bindings().clone();
+        let applied_mouse_bindings = mouse_bindings.load();
+
+        main_view
+            .bindings
+            .set_mouse_binds(MainViewInput::mouse_binds(
+                applied_mouse_bindings,
+            ));
+
         Self {
             mouse_screen_pos,
             winit_rx,
@@ -202,6 +229,9 @@ impl InputManager {
             main_view,
             gui,
 
+            mouse_bindings,
+            applied_mouse_bindings,
+
             gui_focus_state,
         }
     }
@@ -228,6 +228,17 @@ impl<Inputs: InputPayload> SystemInputBindings<Inputs> {
         }
     }
 
+    /// Replaces all mouse button bindings
+    pub fn set_mouse_binds(
+        &mut self,
+        mouse_binds: FxHashMap<
+            event::MouseButton,
+            Vec<MouseButtonBind<Inputs>>,
+        >,
+    ) {
+        self.mouse_binds = mouse_binds;
+    }
+
     pub fn apply(
         &self,
         // input_state: &mut InputState<Inputs>,
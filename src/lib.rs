@@ -15,6 +15,7 @@ pub mod input;
 pub mod view;
 
 pub mod asynchronous;
+pub mod export;
 // pub mod gluon;
 pub mod script;
 
@@ -1,6 +1,8 @@
 #[allow(unused_imports)]
 use compute::EdgePreprocess;
-use gfaestus::annotations::{BedRecords, ClusterCache, Gff3Records};
+use gfaestus::annotations::{
+    BedRecords, ClusterCache, ClusterCacheLoader, Gff3Records,
+};
 use gfaestus::gui::console::Console;
 use gfaestus::vulkan::draw_system::edges::EdgeRenderer;
 use rustc_hash::FxHashMap;
@@ -17,13 +19,18 @@ use winit::window::{Window, WindowBuilder};
 use argh::FromArgs;
 
 use gfaestus::app::mainview::*;
-use gfaestus::app::{App, AppMsg};
+use gfaestus::app::{
+    saved_selection::SavedSelection, view_link::ViewLink, App, AppMsg,
+    LabelPriority, OverlayState,
+};
+use gfaestus::export::CoverageExporter;
 use gfaestus::geometry::*;
 use gfaestus::graph_query::*;
 use gfaestus::input::*;
 use gfaestus::overlays::*;
 use gfaestus::universe::*;
 use gfaestus::view::View;
+use gfaestus::vulkan::frame_capture::FrameCapture;
 use gfaestus::vulkan::render_pass::Framebuffers;
 
 use gfaestus::gui::{widgets::*, windows::*, *};
@@ -41,7 +48,7 @@ use gfaestus::vulkan::draw_system::selection::{
 };
 
 use gfaestus::vulkan::compute::{
-    ComputeManager, GpuSelection, NodeTranslation,
+    ComputeManager, GpuSelection, NodeTranslation, OverlayValueNormalizer,
 };
 
 use anyhow::Result;
@@ -74,13 +81,26 @@ use flexi_logger::{Duplicate, FileSpec, Logger, LoggerHandle};
 #[allow(unused_imports)]
 use log::{debug, error, info, trace, warn};
 
+/// Without a layout file, the nodes are laid out in breadth-first
+/// order, which force-directed layout can then refine
 fn universe_from_gfa_layout(
     graph_query: &GraphQuery,
-    layout_path: &str,
+    layout_path: Option<&str>,
 ) -> Result<(Universe<FlatLayout>, GraphStats)> {
     let graph = graph_query.graph();
 
-    let universe = Universe::from_laid_out_graph(&graph, layout_path)?;
+    let universe = match layout_path {
+        Some(layout_path) => {
+            Universe::from_laid_out_graph(&graph, layout_path)?
+        }
+        None => {
+            info!("No layout file given, laying out the graph in breadth-first order");
+            Universe::from_layout_algorithm(
+                &graph,
+                LayoutAlgorithm::BreadthFirst,
+            )
+        }
+    };
 
     let stats = GraphStats {
         node_count: graph.node_count(),
@@ -92,8 +112,19 @@ fn universe_from_gfa_layout(
     Ok((universe, stats))
 }
 
-fn set_up_logger() -> Result<LoggerHandle> {
-    let logger = Logger::try_with_env_or_str("info")?
+/// Sets up logging to a file and stderr; the log level can be set
+/// with the `--debug` and `--quiet` switches, and is overridden by
+/// the `RUST_LOG` environment variable
+fn set_up_logger(args: &Args) -> Result<LoggerHandle> {
+    let level = if args.debug {
+        "debug"
+    } else if args.quiet {
+        "warn"
+    } else {
+        "info"
+    };
+
+    let logger = Logger::try_with_env_or_str(level)?
         .log_to_file(FileSpec::default())
         .duplicate_to_stderr(Duplicate::Debug)
         .start()?;
@@ -101,13 +132,39 @@ fn set_up_logger() -> Result<LoggerHandle> {
     Ok(logger)
 }
 
+/// The worker thread pool size set by the `--threads` option or the
+/// `GFAESTUS_THREADS` environment variable, with the option taking
+/// precedence
+fn worker_thread_count(args: &Args) -> Option<usize> {
+    let threads = if let Some(threads) = args.threads {
+        threads
+    } else {
+        let var = std::env::var("GFAESTUS_THREADS").ok()?;
+
+        match var.trim().parse::<usize>() {
+            Ok(threads) => threads,
+            Err(_) => {
+                warn!("Ignoring invalid GFAESTUS_THREADS value '{}'", var);
+                return None;
+            }
+        }
+    };
+
+    if threads == 0 {
+        warn!("Worker thread count must be at least 1, using default");
+        return None;
+    }
+
+    Some(threads)
+}
+
 fn main() {
     let args: Args = argh::from_env();
 
-    let _logger = set_up_logger().unwrap();
+    let _logger = set_up_logger(&args).unwrap();
 
     let gfa_file = &args.gfa;
-    let layout_file = &args.layout;
+    let layout_file = args.layout.as_deref();
 
     let event_loop: EventLoop<()>;
 
@@ -147,26 +204,27 @@ fn main() {
         }
     };
 
-    let num_cpus = num_cpus::get();
+    let num_cpus = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or_else(|_| num_cpus::get());
 
-    let futures_cpus;
     let rayon_cpus;
 
     // TODO this has to be done much more intelligently
     if num_cpus < 4 {
-        futures_cpus = 1;
         rayon_cpus = 1;
     } else if num_cpus == 4 {
-        futures_cpus = 1;
         rayon_cpus = 2;
     } else if num_cpus <= 6 {
-        futures_cpus = 2;
         rayon_cpus = num_cpus - 3;
     } else {
-        futures_cpus = 3;
         rayon_cpus = num_cpus - 4;
     }
 
+    let futures_cpus = worker_thread_count(&args).unwrap_or(num_cpus);
+
+    info!("Using {} worker threads", futures_cpus);
+
     // TODO make sure to set thread pool size to less than number of CPUs
     let thread_pool = ThreadPoolBuilder::new()
         .pool_size(futures_cpus)
@@ -233,6 +291,10 @@ fn main() {
     let node_translation =
         NodeTranslation::new(&gfaestus, graph_query.node_count()).unwrap();
 
+    let overlay_value_normalizer =
+        OverlayValueNormalizer::new(&gfaestus, graph_query.node_count())
+            .unwrap();
+
     let mut select_fence_id: Option<usize> = None;
     let mut translate_fence_id: Option<usize> = None;
 
@@ -241,7 +303,18 @@ fn main() {
 
     let mut app = App::new((100.0, 100.0)).expect("error when creating App");
 
-    let input_manager = InputManager::new(winit_rx, app.shared_state());
+    let mut layout_runner = LayoutRunner::new(
+        &mut reactor,
+        app.shared_state().clone_layout_progress(),
+    );
+
+    let mut coverage_exporter = CoverageExporter::new(
+        &mut reactor,
+        app.shared_state().clone_coverage_export_progress(),
+    );
+
+    let mut input_manager =
+        InputManager::new(winit_rx, app.shared_state(), &app.settings);
 
     let app_rx = input_manager.clone_app_rx();
     let main_view_rx = input_manager.clone_main_view_rx();
@@ -269,6 +342,19 @@ fn main() {
     )
     .unwrap();
 
+    let script_dir = match args.script_dir.as_ref() {
+        Some(dir) => Some(std::path::PathBuf::from(dir)),
+        None => std::path::Path::new(&args.gfa)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| dir.to_path_buf()),
+    };
+
+    if let Some(dir) = script_dir {
+        let dir = dir.canonicalize().unwrap_or(dir);
+        gui.console.set_script_dir(dir);
+    }
+
     if let Some(script_file) = args.run_script.as_ref() {
         warn!("executing script file {}", script_file);
         gui.console
@@ -374,6 +460,11 @@ fn main() {
 
     const FRAME_HISTORY_LEN: usize = 10;
     let mut frame_time_history = [0.0f32; FRAME_HISTORY_LEN];
+    debug!(
+        "GPU allocations after loading the graph:\n{}",
+        gfaestus.gpu_allocations.summary()
+    );
+
     let mut frame = 0;
 
     // hack to make the initial view correct -- we need to have the
@@ -393,7 +484,20 @@ fn main() {
     let mut translate_timer = std::time::Instant::now();
     */
 
+    let gfa_name = std::path::Path::new(gfa_file)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| gfa_file.to_string());
+
+    // the event loop closure can't borrow the arguments
+    let gfa_path = gfa_file.to_string();
+
     let mut cluster_caches: HashMap<String, ClusterCache> = HashMap::default();
+    let mut cluster_loader = ClusterCacheLoader::default();
+    let mut overlay_data: FxHashMap<usize, OverlayData> = FxHashMap::default();
+    let mut highlight: Option<HighlightOverlay> = None;
+    let mut path_tube: Option<PathTube> = None;
+    let mut frame_capture: Option<FrameCaptureSeq> = None;
     let mut step_caches: FxHashMap<PathId, Vec<(Handle, _, usize)>> =
         FxHashMap::default();
 
@@ -438,22 +542,21 @@ fn main() {
                 gui.set_hover_node(hover_node);
 
                 if app.selection_changed() {
-                    if let Some(selected) = app.selected_nodes() {
-                        let mut nodes = selected.iter().copied().collect::<Vec<_>>();
-                        nodes.sort();
-
-                        gui.app_view_state()
-                            .node_list()
-                            .send(NodeListMsg::SetFiltered(nodes));
-
-                        main_view.update_node_selection(selected).unwrap();
-                    } else {
-                        gui.app_view_state()
-                            .node_list()
-                            .send(NodeListMsg::SetFiltered(Vec::new()));
-
-                        main_view.clear_node_selection().unwrap();
-                    }
+                    let mut nodes = app
+                        .selected_nodes()
+                        .map(|selected| selected.iter().copied().collect::<Vec<_>>())
+                        .unwrap_or_default();
+                    nodes.sort();
+
+                    gui.app_view_state()
+                        .node_list()
+                        .send(NodeListMsg::SetFiltered(nodes));
+
+                    // the other selection groups are drawn even if the
+                    // active group is empty
+                    main_view
+                        .update_node_selection(app.selection(), app.selection_groups())
+                        .unwrap();
                 }
 
                 while let Ok(app_in) = app_rx.try_recv() {
@@ -485,6 +588,540 @@ fn main() {
 
                     }
 
+                    if let AppMsg::ExportSvg(path) = &app_msg {
+                        let result = export_svg(
+                            path,
+                            &app,
+                            &main_view,
+                            universe.layout(),
+                            &graph_query,
+                            &overlay_data,
+                            &cluster_caches,
+                        );
+
+                        match result {
+                            Ok(_) => info!("Exported view to SVG file {:?}", path),
+                            Err(err) => error!("Error exporting view to SVG file {:?}: {:?}", path, err),
+                        }
+                    }
+
+                    if let AppMsg::ExportSelectionGfa(path) = &app_msg {
+                        let result = std::fs::File::create(path)
+                            .map_err(anyhow::Error::from)
+                            .and_then(|file| {
+                                gfaestus::export::write_subgraph_gfa(
+                                    graph_query.graph(),
+                                    &graph_query.header(),
+                                    app.selection(),
+                                    file,
+                                )
+                            });
+
+                        match result {
+                            Ok(_) => info!("Exported {} selected nodes to GFA file {:?}", app.selection().len(), path),
+                            Err(err) => error!("Error exporting selection to GFA file {:?}: {:?}", path, err),
+                        }
+                    }
+
+                    if let AppMsg::SaveSelection(path) = &app_msg {
+                        let saved = SavedSelection::new(
+                            graph_query.node_count(),
+                            graph_query.node_checksum(),
+                            app.selection(),
+                        );
+
+                        match saved.save_file(path) {
+                            Ok(_) => info!("Saved {} selected nodes to {:?}", saved.nodes.len(), path),
+                            Err(err) => error!("Error saving selection to {:?}: {:?}", path, err),
+                        }
+                    }
+
+                    if let AppMsg::SaveSettings(path) = &app_msg {
+                        match app.settings.save_script(path) {
+                            Ok(_) => info!("Saved settings script to {:?}", path),
+                            Err(err) => error!("Error saving settings script to {:?}: {:?}", path, err),
+                        }
+                    }
+
+                    if let AppMsg::LoadSelection(path) = &app_msg {
+                        use gfaestus::app::Select;
+
+                        match SavedSelection::load_file(path) {
+                            Ok(saved) => {
+                                let valid = saved.validate(
+                                    graph_query.node_count(),
+                                    graph_query.node_checksum(),
+                                );
+
+                                let select = match valid {
+                                    Ok(_) => {
+                                        info!("Restored {} selected nodes from {:?}", saved.nodes.len(), path);
+                                        Select::Many {
+                                            nodes: saved.nodes.into_iter().collect(),
+                                            clear: true,
+                                        }
+                                    }
+                                    Err(err) => {
+                                        warn!("Clearing the selection, can't restore it from {:?}: {}", path, err);
+                                        Select::Clear
+                                    }
+                                };
+
+                                app.channels()
+                                    .app_tx
+                                    .send(AppMsg::Selection(select))
+                                    .unwrap();
+                            }
+                            Err(err) => error!("Error loading selection from {:?}: {:?}", path, err),
+                        }
+                    }
+
+                    if let AppMsg::ExportPathCoverage { path, format } = &app_msg {
+                        match coverage_exporter.run(path.clone(), *format) {
+                            Ok(true) => {
+                                info!("Exporting path coverage to {:?}", path);
+                            }
+                            Ok(false) => {
+                                warn!(
+                                    "Can't export path coverage to {:?} while the export to {:?} is running",
+                                    path,
+                                    coverage_exporter.running()
+                                );
+                            }
+                            Err(err) => {
+                                error!("Error exporting path coverage: {:?}", err);
+                            }
+                        }
+                    }
+
+                    if let AppMsg::ExportBundle(dir) = &app_msg {
+                        // the tube view's layout is temporary, so the
+                        // tube view is left first, and the bundle gets
+                        // the layout and view from before it
+                        if path_tube.is_some() {
+                            let result = set_path_tube(
+                                &gfaestus,
+                                &mut main_view,
+                                &mut universe,
+                                &app,
+                                graph_query.graph(),
+                                &mut highlight,
+                                &mut overlay_data,
+                                &mut path_tube,
+                                None,
+                            );
+
+                            if let Err(err) = result {
+                                error!("Error leaving the path tube view: {:?}", err);
+                            }
+                        }
+
+                        let result = export_bundle(
+                            dir,
+                            &gfa_path,
+                            &gfa_name,
+                            &app,
+                            &main_view,
+                            universe.layout(),
+                            &graph_query,
+                            &overlay_data,
+                            highlight.as_ref().map(|h| h.overlay_id),
+                        );
+
+                        match result {
+                            Ok(_) => info!("Exported session bundle to {:?}", dir),
+                            Err(err) => error!("Error exporting session bundle to {:?}: {:?}", dir, err),
+                        }
+                    }
+
+                    if let AppMsg::StartFrameCapture { dir, every } = &app_msg {
+                        if let Some(mut capture) = frame_capture.take() {
+                            capture.buffer.destroy(gfaestus.vk_context().device());
+                        }
+
+                        let result = std::fs::create_dir_all(dir)
+                            .map_err(anyhow::Error::from)
+                            .and_then(|_| FrameCapture::new(&gfaestus));
+
+                        match result {
+                            Ok(buffer) => {
+                                let every = (*every).max(1);
+                                info!("Capturing every {} frames to {:?}", every, dir);
+
+                                frame_capture = Some(FrameCaptureSeq {
+                                    dir: dir.clone(),
+                                    every,
+                                    frames_drawn: 0,
+                                    frames_saved: 0,
+                                    buffer,
+                                });
+                                app.shared_state().frame_capture.store(Some(0));
+                            }
+                            Err(err) => {
+                                error!("Error starting frame capture to {:?}: {:?}", dir, err);
+                                app.shared_state().frame_capture.store(None);
+                            }
+                        }
+                    }
+
+                    if let AppMsg::StopFrameCapture = &app_msg {
+                        if let Some(mut capture) = frame_capture.take() {
+                            capture.buffer.destroy(gfaestus.vk_context().device());
+                            info!(
+                                "Stopped frame capture, saved {} frames to {:?}",
+                                capture.frames_saved,
+                                capture.dir
+                            );
+                        }
+                        app.shared_state().frame_capture.store(None);
+                    }
+
+                    if let AppMsg::ImportBundle(dir) = &app_msg {
+                        // the bundle's layout replaces the one from
+                        // before the tube view, so the tube view is
+                        // left first
+                        if path_tube.is_some() {
+                            let result = set_path_tube(
+                                &gfaestus,
+                                &mut main_view,
+                                &mut universe,
+                                &app,
+                                graph_query.graph(),
+                                &mut highlight,
+                                &mut overlay_data,
+                                &mut path_tube,
+                                None,
+                            );
+
+                            if let Err(err) = result {
+                                error!("Error leaving the path tube view: {:?}", err);
+                            }
+                        }
+
+                        let result = import_bundle(
+                            dir,
+                            &gfa_name,
+                            &gfaestus,
+                            &mut main_view,
+                            &mut compute_manager,
+                            &overlay_value_normalizer,
+                            &mut universe,
+                            &app,
+                            &graph_query,
+                            &mut overlay_data,
+                        );
+
+                        match result {
+                            Ok(_) => info!("Imported session bundle from {:?}", dir),
+                            Err(err) => error!("Error importing session bundle from {:?}: {:?}", dir, err),
+                        }
+
+                        gui.populate_overlay_list(
+                            main_view
+                                .node_draw_system
+                                .overlay_pipelines
+                                .overlay_names()
+                                .into_iter(),
+                        );
+                    }
+
+                    if let AppMsg::NodeMetadataOverlay { file_name, column } = &app_msg {
+                        let result = app
+                            .annotations()
+                            .get_node_metadata(file_name)
+                            .ok_or_else(|| anyhow::anyhow!("No node metadata named '{}'", file_name))
+                            .and_then(|metadata| {
+                                metadata.overlay_msg(column, graph_query.node_count())
+                            });
+
+                        match result {
+                            Ok(msg) => reactor.overlay_create_tx.send(msg).unwrap(),
+                            Err(err) => error!("Error creating node metadata overlay: {:?}", err),
+                        }
+                    }
+
+                    if let AppMsg::DistanceOverlay { metric, max_distance } = &app_msg {
+                        let mut sources = app.selection().iter().copied().collect::<Vec<_>>();
+                        sources.sort();
+
+                        if sources.is_empty() {
+                            warn!("Select the nodes to create a distance overlay from");
+                        } else {
+                            let (metric, max_distance) = (*metric, *max_distance);
+
+                            let graph_query = graph_query.clone();
+                            let overlay_tx = reactor.overlay_create_tx.clone();
+
+                            let spawned = reactor.spawn(async move {
+                                let msg = distance_overlay_msg(
+                                    &graph_query,
+                                    &sources,
+                                    metric,
+                                    max_distance,
+                                );
+                                overlay_tx.send(msg).unwrap();
+                            });
+
+                            match spawned {
+                                Ok(handle) => handle.forget(),
+                                Err(err) => error!("Error creating distance overlay: {:?}", err),
+                            }
+                        }
+                    }
+
+                    if let AppMsg::NodeTableOverlay { name, table, mapping } = &app_msg {
+                        let (name, table, mapping) = (name.clone(), table.clone(), *mapping);
+
+                        let graph_query = graph_query.clone();
+                        let overlay_tx = reactor.overlay_create_tx.clone();
+
+                        let spawned = reactor.spawn(async move {
+                            match table.overlay_msg(&name, mapping, graph_query.graph()) {
+                                Ok((msg, report)) => {
+                                    info!("Created node table overlay '{}': {}", name, report);
+                                    overlay_tx.send(msg).unwrap();
+                                }
+                                Err(err) => error!("Error creating node table overlay: {:?}", err),
+                            }
+                        });
+
+                        match spawned {
+                            Ok(handle) => handle.forget(),
+                            Err(err) => error!("Error creating node table overlay: {:?}", err),
+                        }
+                    }
+
+                    if let AppMsg::CopyViewLink = &app_msg {
+                        let link = create_view_link(
+                            &gfa_name,
+                            graph_query.node_count(),
+                            &app,
+                            &main_view,
+                        );
+                        gui.set_clipboard_contents(link.encode());
+                        info!("Copied view link to clipboard");
+                    }
+
+                    let view_link = match &app_msg {
+                        AppMsg::OpenViewLink(link) => Some(link.clone()),
+                        AppMsg::OpenViewLinkFromClipboard => gui.clipboard_contents(),
+                        _ => None,
+                    };
+
+                    if let Some(link) = view_link {
+                        let result = open_view_link(
+                            &link,
+                            &gfa_name,
+                            graph_query.node_count(),
+                            &app,
+                            &main_view,
+                        );
+
+                        if let Err(err) = result {
+                            error!("Error opening view link: {:?}", err);
+                        }
+                    }
+
+                    if let AppMsg::RunLayout(algorithm) = &app_msg {
+                        let nodes = universe.layout().nodes().to_vec();
+
+                        if path_tube.is_some() {
+                            warn!(
+                                "Can't run layout '{}' in the path tube view",
+                                algorithm.name()
+                            );
+                        } else {
+                            match layout_runner.run(*algorithm, nodes) {
+                                Ok(true) => {
+                                    info!("Running layout '{}'", algorithm.name());
+                                }
+                                Ok(false) => {
+                                    warn!(
+                                        "Can't run layout '{}' while '{}' is running",
+                                        algorithm.name(),
+                                        layout_runner.running().map(|a| a.name()).unwrap_or_default()
+                                    );
+                                }
+                                Err(err) => {
+                                    error!("Error running layout: {:?}", err);
+                                }
+                            }
+                        }
+                    }
+
+                    let highlight_result = match &app_msg {
+                        AppMsg::Highlight(nodes) => Some(set_highlight(
+                            &gfaestus,
+                            &mut main_view,
+                            &app,
+                            graph_query.node_count(),
+                            &mut highlight,
+                            &mut overlay_data,
+                            path_tube.as_ref(),
+                            nodes,
+                            HighlightOverlay::BASE_COLOR,
+                        )),
+                        AppMsg::ClearHighlight => Some(reset_highlight(
+                            &gfaestus,
+                            &mut main_view,
+                            &app,
+                            graph_query.node_count(),
+                            &mut highlight,
+                            &mut overlay_data,
+                            path_tube.as_ref(),
+                        )),
+                        AppMsg::SetPathTube(path) => Some(set_path_tube(
+                            &gfaestus,
+                            &mut main_view,
+                            &mut universe,
+                            &app,
+                            graph_query.graph(),
+                            &mut highlight,
+                            &mut overlay_data,
+                            &mut path_tube,
+                            *path,
+                        )),
+                        AppMsg::SetPathEdges(path) => Some(set_path_direction(
+                            &gfaestus,
+                            &mut main_view,
+                            &mut edge_renderer,
+                            &app,
+                            graph_query.graph(),
+                            &mut highlight,
+                            &mut overlay_data,
+                            path_tube.as_ref(),
+                            *path,
+                        )),
+                        _ => None,
+                    };
+
+                    if let Some(result) = highlight_result {
+                        if let Err(err) = result {
+                            error!("Error updating highlight overlay: {:?}", err);
+                        }
+
+                        gui.populate_overlay_list(
+                            main_view
+                                .node_draw_system
+                                .overlay_pipelines
+                                .overlay_names()
+                                .into_iter(),
+                        );
+                    }
+
+                    match &app_msg {
+                        AppMsg::RenameOverlay { overlay_id, name } => {
+                            let new_name = main_view
+                                .node_draw_system
+                                .overlay_pipelines
+                                .rename_overlay(*overlay_id, name);
+
+                            if let Some(name) = new_name {
+                                gui.app_view_state().overlay_list().send(
+                                    OverlayListMsg::RenameOverlay {
+                                        overlay_id: *overlay_id,
+                                        name,
+                                    },
+                                );
+                            }
+                        }
+                        AppMsg::DeleteOverlay { overlay_id } => {
+                            let result = delete_overlay(
+                                &mut main_view,
+                                &app,
+                                &mut highlight,
+                                &mut overlay_data,
+                                *overlay_id,
+                            );
+
+                            match result {
+                                Ok(true) => {
+                                    gui.app_view_state().overlay_list().send(
+                                        OverlayListMsg::RemoveOverlay {
+                                            overlay_id: *overlay_id,
+                                        },
+                                    );
+                                }
+                                Ok(false) => (),
+                                Err(err) => {
+                                    error!("Error deleting overlay: {:?}", err);
+                                }
+                            }
+                        }
+                        AppMsg::SetActiveOverlay(name) => {
+                            let overlay_state = app.shared_state().overlay_state();
+
+                            match name {
+                                Some(name) => {
+                                    let overlay = main_view
+                                        .node_draw_system
+                                        .overlay_pipelines
+                                        .overlay_by_name(name);
+
+                                    if overlay.is_some() {
+                                        overlay_state.set_current_overlay(overlay);
+                                        overlay_state.set_use_overlay(true);
+                                    } else {
+                                        warn!("Overlay '{}' does not exist", name);
+                                    }
+                                }
+                                None => {
+                                    overlay_state.set_current_overlay(None);
+                                    overlay_state.set_use_overlay(false);
+                                }
+                            }
+                        }
+                        AppMsg::RecomputeOverlay(name) => {
+                            let overlay = main_view
+                                .node_draw_system
+                                .overlay_pipelines
+                                .overlay_by_name(name);
+
+                            let paths = overlay.and_then(|(overlay_id, _)| {
+                                let paths = app.shared_state().overlay_state().overlay_paths(overlay_id)?;
+                                Some((overlay_id, paths))
+                            });
+
+                            if overlay.is_none() {
+                                warn!("Overlay '{}' does not exist", name);
+                            } else if let Some((overlay_id, paths)) = paths {
+                                let name = name.clone();
+
+                                let graph_query = graph_query.clone();
+                                let overlay_tx = reactor.overlay_create_tx.clone();
+
+                                let spawned = reactor.spawn(async move {
+                                    let depths = graph_query.path_depth_fractions(&paths);
+
+                                    let msg = OverlayCreatorMsg::PathDepth {
+                                        name,
+                                        overlay_id: Some(overlay_id),
+                                        paths,
+                                        depths,
+                                    };
+                                    overlay_tx.send(msg).unwrap();
+                                });
+
+                                match spawned {
+                                    Ok(handle) => handle.forget(),
+                                    Err(err) => error!("Error recomputing overlay: {:?}", err),
+                                }
+                            } else if let Some((overlay_id, script)) = overlay.and_then(|(overlay_id, _)| {
+                                let script = app.shared_state().overlay_state().overlay_script(overlay_id)?;
+                                Some((overlay_id, script))
+                            }) {
+                                let task = script.build(
+                                    &reactor.graph_query_worker(),
+                                    reactor.rayon_pool.clone(),
+                                );
+                                app.shared_state().set_script_overlay_build(name, task, script, Some(overlay_id));
+                            } else {
+                                warn!("Overlay '{}' can't be recomputed, only path depth and script overlays keep their inputs", name);
+                            }
+                        }
+                        _ => (),
+                    }
+
                     if let AppMsg::TranslateSelected(delta) = &app_msg {
                         if select_fence_id.is_none() && translate_fence_id.is_none() {
 
@@ -516,12 +1153,17 @@ fn main() {
                 }
 
                 while let Ok(new_overlay) = new_overlay_rx.try_recv() {
-                    if let Ok(_) = handle_new_overlay(
+                    if let Ok((overlay_id, data)) = handle_new_overlay(
                         &gfaestus,
                         &mut main_view,
+                        &mut compute_manager,
+                        &overlay_value_normalizer,
+                        app.shared_state().overlay_state(),
                         graph_query.node_count(),
                         new_overlay
                     ) {
+                        overlay_data.insert(overlay_id, data);
+
                         gui.populate_overlay_list(
                             main_view
                                 .node_draw_system
@@ -560,6 +1202,62 @@ fn main() {
                     }
                 }
 
+                // wait with replacing the node positions until no
+                // translation is in progress
+                if translate_fence_id.is_none() {
+                    if let Some((algorithm, nodes)) = layout_runner.take_result() {
+                        // a layout started before entering the path
+                        // tube view replaces the layout that's
+                        // restored when leaving it
+                        if let Some(tube) = path_tube.as_mut() {
+                            if nodes.len() == tube.prev_nodes.len() {
+                                tube.prev_nodes = nodes;
+                                info!("Applied layout '{}', it is shown when leaving the path tube view", algorithm.name());
+                            } else {
+                                error!("Error applying layout: layout has {} nodes, but the graph has {}", nodes.len(), tube.prev_nodes.len());
+                            }
+                        } else {
+                            let result = apply_layout(
+                                &gfaestus,
+                                &main_view,
+                                &mut universe,
+                                &app,
+                                nodes,
+                            );
+
+                            match result {
+                                Ok(()) => info!("Applied layout '{}'", algorithm.name()),
+                                Err(err) => error!("Error applying layout: {:?}", err),
+                            }
+                        }
+                    }
+
+                    let spacing = app.shared_state().layout_spacing();
+
+                    // spacing changes are held until the path tube
+                    // view is left, and then applied to the restored
+                    // layout
+                    if path_tube.is_none() && spacing != universe.node_spacing() {
+                        let result = apply_layout_spacing(
+                            &gfaestus,
+                            &main_view,
+                            &mut universe,
+                            spacing,
+                        );
+
+                        if let Err(err) = result {
+                            error!("Error applying layout spacing: {:?}", err);
+                        }
+                    }
+                }
+
+                if let Some((path, result)) = coverage_exporter.take_result() {
+                    match result {
+                        Ok(()) => info!("Exported path coverage to {:?}", path),
+                        Err(err) => error!("Error exporting path coverage to {:?}: {:?}", path, err),
+                    }
+                }
+
                 if let Some(fid) = select_fence_id {
 
                     if compute_manager.is_fence_ready(fid).unwrap() {
@@ -653,11 +1351,14 @@ fn main() {
                     &graph_query,
                     &graph_query_worker,
                     app.annotations(),
+                    app.selection_groups(),
                 );
 
                 let annotations = app.annotations();
 
 
+                cluster_loader.receive(&mut cluster_caches);
+
                 for label_set in annotations.visible_label_sets() {
 
                     if !step_caches.contains_key(&label_set.path_id) {
@@ -669,6 +1370,115 @@ fn main() {
 
                     let label_radius = app.settings.label_radius().load();
 
+                    if !cluster_caches.contains_key(label_set.name()) {
+                        let loaded = cluster_loader.load(
+                            &mut reactor,
+                            steps.clone(),
+                            universe.layout().nodes().to_vec(),
+                            label_set,
+                            app.shared_state().view(),
+                            label_radius
+                        );
+
+                        let cluster_cache = match loaded {
+                            Ok(cache) => cache,
+                            Err(err) => {
+                                log::warn!("Error spawning label cluster task, clustering on main thread: {:?}", err);
+                                ClusterCache::new_cluster(
+                                    &steps,
+                                    universe.layout().nodes(),
+                                    label_set,
+                                    app.shared_state().view(),
+                                    label_radius
+                                )
+                            }
+                        };
+
+                        cluster_caches.insert(label_set.name().to_string(),
+                                              cluster_cache);
+                    }
+
+                    let cluster_cache = cluster_caches
+                        .get_mut(label_set.name())
+                        .unwrap();
+
+                    cluster_cache
+                        .rebuild_cluster(
+                            &steps,
+                            universe.layout().nodes(),
+                            app.shared_state().view(),
+                            label_radius
+                        );
+                }
+
+                // the clusters of all visible label sets are gathered
+                // first, so that the number drawn can be capped across
+                // all of them; clusters anchored outside the view are
+                // left out, so they don't take the place of ones that
+                // would be drawn, nor count as hidden
+                let label_view = app.shared_state().view();
+                let layout_nodes = universe.layout().nodes();
+
+                let visible_rect = {
+                    let dims = app.dims();
+                    let top_left = label_view.screen_point_to_world(dims, Point::ZERO);
+                    let bottom_right = label_view.screen_point_to_world(
+                        dims,
+                        Point::new(dims.width, dims.height),
+                    );
+                    Rect::new(top_left, bottom_right)
+                };
+
+                let mut label_clusters = annotations
+                    .visible_label_sets()
+                    .filter_map(|label_set| {
+                        let cache = cluster_caches.get(label_set.name())?;
+                        Some((label_set, cache))
+                    })
+                    .flat_map(|(label_set, cache)| {
+                        cache.node_labels.iter().map(move |(node, indices)| {
+                            (label_set, cache, *node, indices)
+                        })
+                    })
+                    .filter(|(_, _, node, _)| {
+                        layout_nodes
+                            .get((node.0 - 1) as usize)
+                            .map(|n| visible_rect.contains(n.center()))
+                            .unwrap_or(false)
+                    })
+                    .collect::<Vec<_>>();
+
+                let max_label_clusters = app.settings.max_label_clusters().load();
+                let mut hidden_label_clusters = 0;
+
+                if max_label_clusters > 0 && label_clusters.len() > max_label_clusters {
+                    match app.settings.label_priority().load() {
+                        LabelPriority::NearestCenter => {
+                            let dist = |node: NodeId| {
+                                layout_nodes
+                                    .get((node.0 - 1) as usize)
+                                    .map(|n| n.center().dist_sqr(label_view.center))
+                                    .unwrap_or(std::f32::INFINITY)
+                            };
+
+                            label_clusters.sort_by(|(_, _, a, _), (_, _, b, _)| {
+                                dist(*a)
+                                    .partial_cmp(&dist(*b))
+                                    .unwrap_or(std::cmp::Ordering::Equal)
+                            });
+                        }
+                        LabelPriority::LargestCluster => {
+                            label_clusters.sort_by_key(|(_, _, node, indices)| {
+                                (std::cmp::Reverse(indices.label_indices.len()), *node)
+                            });
+                        }
+                    }
+
+                    hidden_label_clusters = label_clusters.len() - max_label_clusters;
+                    label_clusters.truncate(max_label_clusters);
+                }
+
+                for (label_set, cluster_cache, node, cluster_indices) in label_clusters {
                     use gfaestus::annotations::AnnotationColumn;
 
                     let column = &label_set.column;
@@ -694,107 +1504,81 @@ fn main() {
                         }
                     };
 
+                    let mut y_offset = 20.0;
+                    let mut count = 0;
 
-                    if !cluster_caches.contains_key(label_set.name()) {
-                        let cluster_cache = ClusterCache::new_cluster(
-                            &steps,
-                            universe.layout().nodes(),
-                            label_set,
-                            app.shared_state().view(),
-                            label_radius
-                        );
+                    let label_indices = &cluster_indices.label_indices;
 
-                        cluster_caches.insert(label_set.name().to_string(),
-                                              cluster_cache);
-                    }
+                    for &label_ix in label_indices.iter() {
 
-                    let cluster_cache = cluster_caches
-                        .get_mut(label_set.name())
-                        .unwrap();
+                        let label = &cluster_cache.label_set.label_strings()[label_ix];
+                        let offset = &cluster_cache
+                            .cluster_offsets[cluster_indices.offset_ix];
 
-                    cluster_cache
-                        .rebuild_cluster(
-                            &steps,
+                        let anchor_dir = Point::new(-offset.x, -offset.y);
+                        let offset = *offset * 20.0;
+
+                        let rect = gfaestus::gui::text::draw_text_at_node_anchor(
+                            &gui.ctx,
                             universe.layout().nodes(),
                             app.shared_state().view(),
-                            label_radius
+                            node,
+                            offset + Point::new(0.0, y_offset),
+                            anchor_dir,
+                            label
                         );
 
-                    for (node, cluster_indices) in cluster_cache.node_labels.iter() {
-                        let mut y_offset = 20.0;
-                        let mut count = 0;
-
-                        let label_indices = &cluster_indices.label_indices;
-
-                        for &label_ix in label_indices.iter() {
-
-                            let label = &cluster_cache.label_set.label_strings()[label_ix];
-                            let offset = &cluster_cache
-                                .cluster_offsets[cluster_indices.offset_ix];
-
-                            let anchor_dir = Point::new(-offset.x, -offset.y);
-                            let offset = *offset * 20.0;
-
-                            let rect = gfaestus::gui::text::draw_text_at_node_anchor(
-                                &gui.ctx,
-                                universe.layout().nodes(),
-                                app.shared_state().view(),
-                                *node,
-                                offset + Point::new(0.0, y_offset),
-                                anchor_dir,
-                                label
-                            );
-
-                            if let Some(rect) = rect {
-                                let rect = rect.resize(0.98);
-                                if rect.contains(app.mouse_pos()) {
-                                    gfaestus::gui::text::draw_rect(&gui.ctx, rect);
-
-                                    // hacky way to check for a click
-                                    // for now, because i can't figure
-                                    // egui out
-                                    if gui.ctx.input().pointer.any_click() {
-                                        match column {
-                                            AnnotationColumn::Gff3(col) => {
-                                                if let Some(gff) = records.downcast_ref::<Gff3Records>() {
-                                                    gui.scroll_to_gff_record(gff, col, label.as_bytes());
-                                                }
+                        if let Some(rect) = rect {
+                            let rect = rect.resize(0.98);
+                            if rect.contains(app.mouse_pos()) {
+                                gfaestus::gui::text::draw_rect(&gui.ctx, rect);
+
+                                // hacky way to check for a click
+                                // for now, because i can't figure
+                                // egui out
+                                if gui.ctx.input().pointer.any_click() {
+                                    match column {
+                                        AnnotationColumn::Gff3(col) => {
+                                            if let Some(gff) = records.downcast_ref::<Gff3Records>() {
+                                                gui.scroll_to_gff_record(gff, col, label.as_bytes());
                                             }
-                                            AnnotationColumn::Bed(col) => {
-                                                if let Some(bed) = records.downcast_ref::<BedRecords>() {
-                                                    gui.scroll_to_bed_record(bed, col, label.as_bytes());
-                                                }
+                                        }
+                                        AnnotationColumn::Bed(col) => {
+                                            if let Some(bed) = records.downcast_ref::<BedRecords>() {
+                                                gui.scroll_to_bed_record(bed, col, label.as_bytes());
                                             }
                                         }
                                     }
                                 }
                             }
+                        }
 
-                            y_offset += 15.0;
-                            count += 1;
-
-                            if count > 10 {
-                                let count = count.min(label_indices.len());
-                                let rem = label_indices.len() - count;
-
-                                if rem > 0 {
-                                    let more_label = format!("and {} more", rem);
-
-                                    gfaestus::gui::text::draw_text_at_node_anchor(
-                                        &gui.ctx,
-                                        universe.layout().nodes(),
-                                        app.shared_state().view(),
-                                        *node,
-                                        offset + Point::new(0.0, y_offset),
-                                        anchor_dir,
-                                        &more_label
-                                    );
-                                }
-                                break;
+                        y_offset += 15.0;
+                        count += 1;
+
+                        if count > 10 {
+                            let count = count.min(label_indices.len());
+                            let rem = label_indices.len() - count;
+
+                            if rem > 0 {
+                                let more_label = format!("and {} more", rem);
+
+                                gfaestus::gui::text::draw_text_at_node_anchor(
+                                    &gui.ctx,
+                                    universe.layout().nodes(),
+                                    app.shared_state().view(),
+                                    node,
+                                    offset + Point::new(0.0, y_offset),
+                                    anchor_dir,
+                                    &more_label
+                                );
                             }
+                            break;
                         }
                     }
-                }
+            }
+
+                gui.hidden_labels_notice_ui(hidden_label_clusters);
 
 
                 let meshes = gui.end_frame();
@@ -1060,8 +1844,35 @@ fn main() {
 
                     };
 
+                if let Some(capture) = frame_capture.as_mut() {
+                    if !capture.buffer.matches_swapchain(&gfaestus) {
+                        capture.buffer.destroy(gfaestus.vk_context().device());
+                        capture.buffer = FrameCapture::new(&gfaestus).unwrap();
+                    }
+                }
+
+                let capture_buffer = frame_capture
+                    .as_ref()
+                    .filter(|capture| capture.frames_drawn % capture.every == 0)
+                    .map(|capture| &capture.buffer);
+
                 let size = window.inner_size();
-                dirty_swapchain = gfaestus.draw_frame_from([size.width, size.height], draw).unwrap();
+                dirty_swapchain = gfaestus
+                    .draw_frame_from([size.width, size.height], capture_buffer, draw)
+                    .unwrap();
+
+                if let Some(capture) = frame_capture.as_mut() {
+                    if !dirty_swapchain && capture.frames_drawn % capture.every == 0 {
+                        save_captured_frame(
+                            &mut reactor,
+                            gfaestus.vk_context().device(),
+                            capture,
+                        );
+                        app.shared_state().frame_capture.store(Some(capture.frames_saved));
+                    }
+
+                    capture.frames_drawn += 1;
+                }
 
                 if !dirty_swapchain {
                     let screen_dims = app.dims();
@@ -1119,21 +1930,191 @@ fn main() {
 
                 selection_edge.destroy(device);
                 selection_blur.destroy(device);
+
+                if let Some(mut capture) = frame_capture.take() {
+                    capture.buffer.destroy(device);
+                }
             }
             _ => (),
         }
     });
 }
 
+/// A value overlay of the distance of each node from the closest
+/// source node, scaled by the largest distance reached; unreached
+/// nodes are NaN, which the overlay shader draws in a neutral color
+fn distance_overlay_msg(
+    graph_query: &GraphQuery,
+    sources: &[NodeId],
+    metric: DistanceMetric,
+    max_distance: usize,
+) -> OverlayCreatorMsg {
+    let dists = graph_query.node_distances(sources, metric, max_distance);
+
+    let max = dists.iter().filter_map(|&d| d).max().unwrap_or(0).max(1);
+
+    let values = dists
+        .iter()
+        .map(|d| match d {
+            Some(d) => *d as f32 / max as f32,
+            None => std::f32::NAN,
+        })
+        .collect::<Vec<_>>();
+
+    let from = if sources.len() == 1 {
+        format!("node {}", sources[0])
+    } else {
+        format!("{} nodes", sources.len())
+    };
+
+    OverlayCreatorMsg::NewOverlay {
+        name: format!("Distance from {}", from),
+        data: OverlayData::Value(values),
+        legend: Some(OverlayLegend::Range {
+            min: 0.0,
+            max: max as f32,
+            units: format!("{} from {}", metric.name(), from),
+        }),
+    }
+}
+
 fn handle_new_overlay(
     app: &GfaestusVk,
     main_view: &mut MainView,
+    compute_manager: &mut ComputeManager,
+    value_normalizer: &OverlayValueNormalizer,
+    overlay_state: &OverlayState,
     node_count: usize,
     msg: OverlayCreatorMsg,
-) -> Result<()> {
-    let OverlayCreatorMsg::NewOverlay { name, data } = msg;
+) -> Result<(usize, OverlayData)> {
+    let (name, data, normalize, legend, paths, script) = match msg {
+        OverlayCreatorMsg::NewOverlay { name, data, legend } => {
+            (name, data, false, legend, None, None)
+        }
+        OverlayCreatorMsg::NewValueOverlay { name, values } => {
+            (name, OverlayData::Value(values), true, None, None, None)
+        }
+        OverlayCreatorMsg::PathDepth {
+            name,
+            overlay_id,
+            paths,
+            depths,
+        } => {
+            let legend = OverlayLegend::Range {
+                min: 0.0,
+                max: 1.0,
+                units: format!("fraction of {} paths", paths.len()),
+            };
+
+            // path depth overlays are updated in place when their
+            // path set is changed
+            let existing = overlay_id
+                .filter(|&id| overlay_state.overlay_paths(id).is_some());
+
+            if let Some(overlay_id) = existing {
+                let pipelines =
+                    &mut main_view.node_draw_system.overlay_pipelines;
+
+                if let Some(overlay) = pipelines.value_overlay_mut(overlay_id) {
+                    overlay.update_overlay(
+                        app.vk_context().device(),
+                        depths
+                            .iter()
+                            .enumerate()
+                            .map(|(ix, v)| (NodeId::from((ix as u64) + 1), *v)),
+                    )?;
+
+                    overlay_state.set_legend(overlay_id, Some(legend));
+                    overlay_state.set_overlay_paths(overlay_id, Some(paths));
+
+                    return Ok((overlay_id, OverlayData::Value(depths)));
+                }
+            }
+
+            let data = OverlayData::Value(depths);
+            (name, data, false, Some(legend), Some(paths), None)
+        }
+        OverlayCreatorMsg::Script {
+            name,
+            overlay_id,
+            script,
+            data,
+        } => {
+            // script overlays are updated in place when they're
+            // recomputed, unless the script now returns the other
+            // kind of data
+            let existing = overlay_id
+                .filter(|&id| overlay_state.overlay_script(id).is_some());
+
+            if let Some(overlay_id) = existing {
+                let device = app.vk_context().device();
+                let pipelines =
+                    &mut main_view.node_draw_system.overlay_pipelines;
+
+                match &data {
+                    OverlayData::RGB(colors) => {
+                        if let Some(overlay) =
+                            pipelines.rgb_overlay_mut(overlay_id)
+                        {
+                            overlay.update_overlay(
+                                device,
+                                colors.iter().enumerate().map(|(ix, col)| {
+                                    (NodeId::from((ix as u64) + 1), *col)
+                                }),
+                            )?;
+
+                            overlay_state
+                                .set_overlay_script(overlay_id, Some(script));
+
+                            return Ok((overlay_id, data));
+                        }
+                    }
+                    OverlayData::Value(values) => {
+                        if let Some(overlay) =
+                            pipelines.value_overlay_mut(overlay_id)
+                        {
+                            overlay.update_overlay(
+                                device,
+                                values.iter().enumerate().map(|(ix, v)| {
+                                    (NodeId::from((ix as u64) + 1), *v)
+                                }),
+                            )?;
+
+                            let (data, legend) = normalize_overlay_values(
+                                app,
+                                compute_manager,
+                                value_normalizer,
+                                overlay,
+                                values,
+                                node_count,
+                            )?;
+
+                            overlay_state.set_legend(overlay_id, legend);
+                            overlay_state
+                                .set_overlay_script(overlay_id, Some(script));
+
+                            return Ok((overlay_id, OverlayData::Value(data)));
+                        }
+                    }
+                }
+
+                warn!(
+                    "Overlay script '{}' returned a different kind of data, creating a new overlay",
+                    script.path.display()
+                );
+            }
 
-    let overlay = match data {
+            let normalize = matches!(data, OverlayData::Value(_));
+            (name, data, normalize, None, None, Some(script))
+        }
+    };
+
+    let name = main_view
+        .node_draw_system
+        .overlay_pipelines
+        .unique_name(&name);
+
+    let (overlay, data, legend) = match data {
         OverlayData::RGB(data) => {
             let mut overlay =
                 NodeOverlay::new_empty_rgb(&name, app, node_count).unwrap();
@@ -1147,7 +2128,7 @@ fn handle_new_overlay(
                 )
                 .unwrap();
 
-            Overlay::RGB(overlay)
+            (Overlay::RGB(overlay), OverlayData::RGB(data), legend)
         }
         OverlayData::Value(data) => {
             let mut overlay =
@@ -1163,15 +2144,1165 @@ fn handle_new_overlay(
                 )
                 .unwrap();
 
-            Overlay::Value(overlay)
+            let (data, legend) = if normalize {
+                normalize_overlay_values(
+                    app,
+                    compute_manager,
+                    value_normalizer,
+                    &overlay,
+                    &data,
+                    node_count,
+                )?
+            } else {
+                let legend =
+                    legend.or_else(|| OverlayLegend::from_values(&data));
+                (data, legend)
+            };
+
+            (Overlay::Value(overlay), OverlayData::Value(data), legend)
         }
     };
 
-    main_view
+    let overlay_id = main_view
         .node_draw_system
         .overlay_pipelines
         .create_overlay(overlay);
 
+    overlay_state.set_legend(overlay_id, legend);
+    overlay_state.set_overlay_paths(overlay_id, paths);
+    overlay_state.set_overlay_script(overlay_id, script);
+
+    Ok((overlay_id, data))
+}
+
+/// Normalizes the values of a value overlay to the 0..1 range on the
+/// GPU, returning the normalized values, and a legend covering the
+/// values from before normalization
+fn normalize_overlay_values(
+    app: &GfaestusVk,
+    compute_manager: &mut ComputeManager,
+    value_normalizer: &OverlayValueNormalizer,
+    overlay: &NodeOverlayValue,
+    values: &[f32],
+    node_count: usize,
+) -> Result<(Vec<f32>, Option<OverlayLegend>)> {
+    let fence_id = value_normalizer.normalize(compute_manager, overlay)?;
+    compute_manager.block_on_fence(fence_id)?;
+    compute_manager.free_fence(fence_id, false)?;
+
+    // the range is computed from the values before normalization, so
+    // it's used for the legend
+    let legend = OverlayLegend::from_values(values);
+
+    let (min, max) = value_normalizer.latest_range()?;
+    debug!("Overlay values, min: {}, max: {}", min, max);
+
+    // the normalized values are also used by the SVG export
+    let data = overlay.read_values(app.vk_context().device(), node_count)?;
+
+    Ok((data, legend))
+}
+
+/// Returns false if there was no overlay with the given ID; if the
+/// deleted overlay was active, no overlay will be used afterward
+fn delete_overlay(
+    main_view: &mut MainView,
+    app: &App,
+    highlight: &mut Option<HighlightOverlay>,
+    overlay_data: &mut FxHashMap<usize, OverlayData>,
+    overlay_id: usize,
+) -> Result<bool> {
+    if highlight.as_ref().map(|h| h.overlay_id) == Some(overlay_id) {
+        *highlight = None;
+    }
+
+    let overlay_state = app.shared_state().overlay_state();
+
+    if overlay_state.current_overlay().map(|(id, _)| id) == Some(overlay_id) {
+        overlay_state.set_current_overlay(None);
+        overlay_state.set_use_overlay(false);
+    }
+
+    overlay_data.remove(&overlay_id);
+    overlay_state.set_legend(overlay_id, None);
+    overlay_state.set_overlay_paths(overlay_id, None);
+    overlay_state.set_overlay_script(overlay_id, None);
+
+    main_view
+        .node_draw_system
+        .overlay_pipelines
+        .remove_overlay(overlay_id)
+}
+
+/// The overlay used by the console `highlight` function, along with
+/// the overlay state to restore when the highlight is cleared
+struct HighlightOverlay {
+    overlay_id: usize,
+    prev_overlay: Option<(usize, OverlayKind)>,
+    prev_use_overlay: bool,
+}
+
+impl HighlightOverlay {
+    const NAME: &'static str = "Highlight";
+
+    const HIGHLIGHT_COLOR: rgb::RGBA<f32> = rgb::RGBA {
+        r: 1.0,
+        g: 0.35,
+        b: 0.0,
+        a: 1.0,
+    };
+
+    const BASE_COLOR: rgb::RGBA<f32> = rgb::RGBA {
+        r: 0.6,
+        g: 0.6,
+        b: 0.6,
+        a: 1.0,
+    };
+
+    /// Used instead of `BASE_COLOR` to hide the other nodes
+    const HIDDEN_COLOR: rgb::RGBA<f32> = rgb::RGBA {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+        a: 0.0,
+    };
+}
+
+#[allow(clippy::too_many_arguments)]
+fn set_highlight(
+    gfaestus: &GfaestusVk,
+    main_view: &mut MainView,
+    app: &App,
+    node_count: usize,
+    highlight: &mut Option<HighlightOverlay>,
+    overlay_data: &mut FxHashMap<usize, OverlayData>,
+    path_tube: Option<&PathTube>,
+    nodes: &[NodeId],
+    base_color: rgb::RGBA<f32>,
+) -> Result<()> {
+    // in the path tube view, the nodes that aren't on the path stay
+    // hidden
+    let mut colors = if let Some(tube) = path_tube {
+        let mut colors = vec![HighlightOverlay::HIDDEN_COLOR; node_count];
+
+        for node in tube.path_nodes.iter() {
+            if let Some(color) = colors.get_mut((node.0 - 1) as usize) {
+                *color = base_color;
+            }
+        }
+
+        colors
+    } else {
+        vec![base_color; node_count]
+    };
+
+    for node in nodes {
+        if let Some(color) = colors.get_mut((node.0 - 1) as usize) {
+            *color = HighlightOverlay::HIGHLIGHT_COLOR;
+        }
+    }
+
+    let overlay_state = app.shared_state().overlay_state();
+
+    let device = gfaestus.vk_context().device();
+    let node_colors = || {
+        colors
+            .iter()
+            .enumerate()
+            .map(|(ix, col)| (NodeId::from((ix as u64) + 1), *col))
+    };
+
+    let pipelines = &mut main_view.node_draw_system.overlay_pipelines;
+
+    let existing = match highlight.as_ref() {
+        Some(h) => pipelines
+            .rgb_overlay_mut(h.overlay_id)
+            .map(|overlay| (h.overlay_id, overlay)),
+        None => None,
+    };
+
+    let overlay_id = if let Some((overlay_id, overlay)) = existing {
+        overlay.update_overlay(device, node_colors())?;
+        overlay_id
+    } else {
+        let mut overlay = NodeOverlay::new_empty_rgb(
+            HighlightOverlay::NAME,
+            gfaestus,
+            node_count,
+        )?;
+        overlay.update_overlay(device, node_colors())?;
+
+        let overlay_id = pipelines.create_overlay(Overlay::RGB(overlay));
+
+        *highlight = Some(HighlightOverlay {
+            overlay_id,
+            prev_overlay: overlay_state.current_overlay(),
+            prev_use_overlay: overlay_state.use_overlay(),
+        });
+
+        overlay_id
+    };
+
+    overlay_data.insert(overlay_id, OverlayData::RGB(colors));
+
+    let mut categories =
+        vec![("Highlighted".to_string(), HighlightOverlay::HIGHLIGHT_COLOR)];
+
+    if base_color.a > 0.0 {
+        categories.push(("Other nodes".to_string(), base_color));
+    }
+
+    overlay_state
+        .set_legend(overlay_id, Some(OverlayLegend::Categories(categories)));
+
+    overlay_state.set_current_overlay(Some((overlay_id, OverlayKind::RGB)));
+    overlay_state.set_use_overlay(true);
+
+    info!("Highlighted {} nodes", nodes.len());
+
+    Ok(())
+}
+
+fn clear_highlight(
+    main_view: &mut MainView,
+    app: &App,
+    highlight: &mut Option<HighlightOverlay>,
+    overlay_data: &mut FxHashMap<usize, OverlayData>,
+) -> Result<()> {
+    let highlight = if let Some(highlight) = highlight.take() {
+        highlight
+    } else {
+        return Ok(());
+    };
+
+    let overlay_state = app.shared_state().overlay_state();
+
+    // only restore the previous overlay if the user hasn't switched
+    // away from the highlight in the meantime
+    let current_id = overlay_state.current_overlay().map(|(id, _)| id);
+
+    if current_id == Some(highlight.overlay_id) {
+        overlay_state.set_current_overlay(highlight.prev_overlay);
+        overlay_state.set_use_overlay(
+            highlight.prev_use_overlay && highlight.prev_overlay.is_some(),
+        );
+    }
+
+    overlay_data.remove(&highlight.overlay_id);
+    overlay_state.set_legend(highlight.overlay_id, None);
+
+    main_view
+        .node_draw_system
+        .overlay_pipelines
+        .remove_overlay(highlight.overlay_id)?;
+
+    Ok(())
+}
+
+/// Clears the highlight, or, in the path tube view, goes back to only
+/// showing the nodes on the path
+#[allow(clippy::too_many_arguments)]
+fn reset_highlight(
+    gfaestus: &GfaestusVk,
+    main_view: &mut MainView,
+    app: &App,
+    node_count: usize,
+    highlight: &mut Option<HighlightOverlay>,
+    overlay_data: &mut FxHashMap<usize, OverlayData>,
+    path_tube: Option<&PathTube>,
+) -> Result<()> {
+    if let Some(tube) = path_tube {
+        set_highlight(
+            gfaestus,
+            main_view,
+            app,
+            node_count,
+            highlight,
+            overlay_data,
+            None,
+            &tube.path_nodes,
+            HighlightOverlay::HIDDEN_COLOR,
+        )
+    } else {
+        clear_highlight(main_view, app, highlight, overlay_data)
+    }
+}
+
+/// A running frame capture: every `every`th frame drawn is saved as
+/// a numbered PNG in `dir`, so the frames can be assembled into a
+/// video
+struct FrameCaptureSeq {
+    dir: std::path::PathBuf,
+    every: usize,
+
+    /// Frames drawn since the capture started
+    frames_drawn: usize,
+    /// Frames saved so far, which numbers the next file
+    frames_saved: usize,
+
+    buffer: FrameCapture,
+}
+
+/// Reads the frame that was just drawn into the capture buffer, and
+/// writes it to the capture directory on a worker thread
+fn save_captured_frame(
+    reactor: &mut gfaestus::reactor::Reactor,
+    device: &Device,
+    capture: &mut FrameCaptureSeq,
+) {
+    let pixels = match capture.buffer.read_rgba(device) {
+        Ok(pixels) => pixels,
+        Err(err) => {
+            error!("Error reading captured frame: {:?}", err);
+            return;
+        }
+    };
+
+    let path = capture
+        .dir
+        .join(format!("frame_{:06}.png", capture.frames_saved));
+    let (width, height) = (capture.buffer.width, capture.buffer.height);
+
+    capture.frames_saved += 1;
+
+    let spawned = reactor.spawn(async move {
+        if let Err(err) =
+            gfaestus::export::write_png(&path, width, height, &pixels)
+        {
+            error!("Error writing captured frame {:?}: {:?}", path, err);
+        }
+    });
+
+    match spawned {
+        Ok(handle) => handle.forget(),
+        Err(err) => error!("Error writing captured frame: {:?}", err),
+    }
+}
+
+/// The layout and edge visibility to restore when leaving the path
+/// tube view, and the nodes on the path that is shown
+struct PathTube {
+    prev_nodes: Vec<Node>,
+    prev_edges_enabled: bool,
+    path_nodes: Vec<NodeId>,
+}
+
+/// Lays out only the nodes of `path` in a line, hiding the other
+/// nodes and the edges, or restores the layout from before the tube
+/// view if `path` is `None`
+#[allow(clippy::too_many_arguments)]
+fn set_path_tube(
+    gfaestus: &GfaestusVk,
+    main_view: &mut MainView,
+    universe: &mut Universe<FlatLayout>,
+    app: &App,
+    graph: &PackedGraph,
+    highlight: &mut Option<HighlightOverlay>,
+    overlay_data: &mut FxHashMap<usize, OverlayData>,
+    path_tube: &mut Option<PathTube>,
+    path: Option<PathId>,
+) -> Result<()> {
+    let shared_state = app.shared_state();
+
+    let path = if let Some(path) = path {
+        path
+    } else {
+        if let Some(tube) = path_tube.take() {
+            // keep a spacing change made in the tube view, to apply
+            // it to the restored layout
+            let spacing = shared_state.layout_spacing();
+
+            apply_layout(gfaestus, main_view, universe, app, tube.prev_nodes)?;
+            shared_state.set_layout_spacing(spacing);
+            shared_state.set_edges_enabled(tube.prev_edges_enabled);
+            clear_highlight(main_view, app, highlight, overlay_data)?;
+        }
+
+        return Ok(());
+    };
+
+    let nodes = path_tube_layout(graph, path)
+        .ok_or_else(|| anyhow::anyhow!("Path {} not found", path.0))?;
+
+    let path_nodes = graph
+        .path_steps(path)
+        .map(|steps| steps.map(|step| step.handle().id()).collect())
+        .unwrap_or_else(Vec::new);
+
+    // switching between paths keeps the layout from before the
+    // first one
+    match path_tube.as_mut() {
+        Some(tube) => tube.path_nodes = path_nodes,
+        None => {
+            *path_tube = Some(PathTube {
+                prev_nodes: universe.layout().nodes().to_vec(),
+                prev_edges_enabled: shared_state.edges_enabled(),
+                path_nodes,
+            });
+        }
+    }
+
+    apply_layout(gfaestus, main_view, universe, app, nodes)?;
+    shared_state.set_edges_enabled(false);
+
+    reset_highlight(
+        gfaestus,
+        main_view,
+        app,
+        graph.node_count(),
+        highlight,
+        overlay_data,
+        path_tube.as_ref(),
+    )
+}
+
+/// Draws the edges of `path` colored by the current overlay gradient
+/// from its first step to its last, and highlights its nodes, or
+/// clears both if `path` is `None`
+#[allow(clippy::too_many_arguments)]
+fn set_path_direction(
+    gfaestus: &GfaestusVk,
+    main_view: &mut MainView,
+    edge_renderer: &mut EdgeRenderer,
+    app: &App,
+    graph: &PackedGraph,
+    highlight: &mut Option<HighlightOverlay>,
+    overlay_data: &mut FxHashMap<usize, OverlayData>,
+    path_tube: Option<&PathTube>,
+    path: Option<PathId>,
+) -> Result<()> {
+    let gradient = app.shared_state().overlay_state().gradient().gradient();
+
+    // the previous path edge buffers may still be in use
+    gfaestus.wait_gpu_idle()?;
+
+    edge_renderer.set_path_edges(gfaestus, graph, path, |t| {
+        let color = gradient.eval_continuous(t as f64);
+        rgb::RGB::new(
+            color.r as f32 / 255.0,
+            color.g as f32 / 255.0,
+            color.b as f32 / 255.0,
+        )
+    })?;
+
+    if let Some(path) = path {
+        let nodes = graph
+            .path_steps(path)
+            .map(|steps| steps.map(|step| step.handle().id()).collect())
+            .unwrap_or_else(Vec::new);
+
+        set_highlight(
+            gfaestus,
+            main_view,
+            app,
+            graph.node_count(),
+            highlight,
+            overlay_data,
+            path_tube,
+            &nodes,
+            HighlightOverlay::BASE_COLOR,
+        )
+    } else {
+        reset_highlight(
+            gfaestus,
+            main_view,
+            app,
+            graph.node_count(),
+            highlight,
+            overlay_data,
+            path_tube,
+        )
+    }
+}
+
+fn export_svg(
+    path: &std::path::Path,
+    app: &App,
+    main_view: &MainView,
+    layout: &FlatLayout,
+    graph_query: &GraphQuery,
+    overlay_data: &FxHashMap<usize, OverlayData>,
+    cluster_caches: &HashMap<String, ClusterCache>,
+) -> Result<()> {
+    use gfaestus::export::{SvgEdgeStyle, SvgExport, SvgLabel, SvgLegend};
+
+    /// The number of color stops used for the legend gradient
+    const SVG_LEGEND_GRADIENT_STOPS: usize = 16;
+
+    let shared_state = app.shared_state();
+
+    let view = shared_state.view();
+
+    let background = if shared_state.dark_mode.load() {
+        app.settings.background_color_dark().load()
+    } else {
+        app.settings.background_color_light().load()
+    };
+
+    let edges = if shared_state.edges_enabled() {
+        let edge_ubo = app.settings.edge_renderer().load();
+        let style = SvgEdgeStyle {
+            color: edge_ubo.edge_color,
+            width: edge_ubo.edge_width,
+        };
+        Some((graph_query.graph(), style))
+    } else {
+        None
+    };
+
+    let overlay_state = shared_state.overlay_state();
+
+    let overlay_id = overlay_state
+        .current_overlay()
+        .filter(|_| overlay_state.use_overlay())
+        .map(|(overlay_id, _)| overlay_id);
+
+    let overlay =
+        overlay_id.and_then(|overlay_id| overlay_data.get(&overlay_id));
+
+    let gradient = overlay_state.gradient().gradient();
+
+    // only the active overlay is drawn, so only its legend is included
+    let legend = overlay_id.filter(|_| overlay_state.show_legend()).and_then(
+        |overlay_id| {
+            let legend = overlay_state.legend(overlay_id)?;
+
+            let name = main_view
+                .node_draw_system
+                .overlay_pipelines
+                .overlay_names()
+                .into_iter()
+                .find(|(id, _, _)| *id == overlay_id)
+                .map(|(_, _, name)| name.to_string())
+                .unwrap_or_default();
+
+            let gradient = (0..=SVG_LEGEND_GRADIENT_STOPS)
+                .map(|ix| {
+                    let t = ix as f64 / SVG_LEGEND_GRADIENT_STOPS as f64;
+                    let color = gradient.eval_continuous(t);
+                    rgb::RGB::new(
+                        color.r as f32 / 255.0,
+                        color.g as f32 / 255.0,
+                        color.b as f32 / 255.0,
+                    )
+                })
+                .collect();
+
+            Some(SvgLegend {
+                anchor: overlay_state.legend_anchor(),
+                entries: vec![(name, legend)],
+                gradient,
+            })
+        },
+    );
+
+    let svg = SvgExport {
+        view,
+        dims: app.dims(),
+        background,
+        node_width: main_view.scaled_node_width(view) / view.scale,
+        layout,
+        edges,
+        legend,
+    };
+
+    let theme_colors = app
+        .themes
+        .active_theme_def()
+        .map(|theme| theme.node_colors.clone())
+        .unwrap_or_default();
+
+    let node_color = |node: NodeId| -> rgb::RGB<f32> {
+        let ix = (node.0 - 1) as usize;
+
+        match overlay {
+            Some(OverlayData::RGB(colors)) => {
+                if let Some(color) = colors.get(ix) {
+                    return color.rgb();
+                }
+            }
+            Some(OverlayData::Value(values)) => {
+                // nodes without a value are gray, as in the main view
+                if values.get(ix).map_or(false, |v| v.is_nan()) {
+                    return rgb::RGB::new(0.5, 0.5, 0.5);
+                }
+
+                if let Some(&value) = values.get(ix) {
+                    let t = value.max(0.0).min(1.0) as f64;
+                    let color = gradient.eval_continuous(t);
+                    return rgb::RGB::new(
+                        color.r as f32 / 255.0,
+                        color.g as f32 / 255.0,
+                        color.b as f32 / 255.0,
+                    );
+                }
+            }
+            None => (),
+        }
+
+        if theme_colors.is_empty() {
+            rgb::RGB::new(0.0, 0.0, 0.0)
+        } else {
+            theme_colors[ix % theme_colors.len()]
+        }
+    };
+
+    let nodes = layout.nodes();
+
+    let mut labels: Vec<SvgLabel> = Vec::new();
+
+    for cluster_cache in cluster_caches.values() {
+        if !cluster_cache.label_set.is_visible() {
+            continue;
+        }
+
+        let label_strings = cluster_cache.label_set.label_strings();
+
+        for (node, cluster_indices) in cluster_cache.node_labels.iter() {
+            let world = if let Some(pos) = nodes.get((node.0 - 1) as usize) {
+                pos.center()
+            } else {
+                continue;
+            };
+
+            let offset =
+                cluster_cache.cluster_offsets[cluster_indices.offset_ix];
+
+            let anchor_dir = Point::new(-offset.x, -offset.y);
+            let offset = offset * 20.0;
+
+            let label_indices = &cluster_indices.label_indices;
+
+            let mut y_offset = 20.0;
+
+            for &label_ix in label_indices.iter().take(10) {
+                labels.push(SvgLabel {
+                    world,
+                    screen_offset: offset + Point::new(0.0, y_offset),
+                    anchor_dir,
+                    text: label_strings[label_ix].clone(),
+                });
+                y_offset += 15.0;
+            }
+
+            if label_indices.len() > 10 {
+                labels.push(SvgLabel {
+                    world,
+                    screen_offset: offset + Point::new(0.0, y_offset),
+                    anchor_dir,
+                    text: format!("and {} more", label_indices.len() - 10),
+                });
+            }
+        }
+    }
+
+    let file = std::fs::File::create(path)?;
+    let writer = std::io::BufWriter::new(file);
+
+    svg.write(writer, node_color, &labels)?;
+
+    Ok(())
+}
+
+/// Writes the current layout, overlays, annotation files, label sets,
+/// and view to a bundle directory, along with a script that imports
+/// the bundle
+#[allow(clippy::too_many_arguments)]
+fn export_bundle(
+    dir: &std::path::Path,
+    gfa_file: &str,
+    gfa_name: &str,
+    app: &App,
+    main_view: &MainView,
+    layout: &FlatLayout,
+    graph_query: &GraphQuery,
+    overlay_data: &FxHashMap<usize, OverlayData>,
+    highlight_id: Option<usize>,
+) -> Result<()> {
+    use gfaestus::annotations::AnnotationFileType;
+    use gfaestus::export::*;
+    use std::fs::{self, File};
+
+    for subdir in
+        [BUNDLE_OVERLAY_DIR, BUNDLE_ANNOTATION_DIR, BUNDLE_LABEL_DIR].iter()
+    {
+        fs::create_dir_all(dir.join(subdir))?;
+    }
+
+    let gfa = std::path::Path::new(gfa_file);
+    let gfa = gfa.canonicalize().unwrap_or_else(|_| gfa.to_path_buf());
+
+    let view_link =
+        create_view_link(gfa_name, graph_query.node_count(), app, main_view);
+
+    let mut manifest = BundleManifest {
+        gfa: gfa.display().to_string(),
+        node_count: graph_query.node_count(),
+        view_link: view_link.encode(),
+        ..BundleManifest::default()
+    };
+
+    write_bundle_layout(layout, File::create(dir.join(BUNDLE_LAYOUT))?)?;
+
+    let overlay_state = app.shared_state().overlay_state();
+
+    let mut overlays =
+        main_view.node_draw_system.overlay_pipelines.overlay_names();
+    overlays.sort_by_key(|(overlay_id, _, _)| *overlay_id);
+
+    for (overlay_id, kind, name) in overlays {
+        // the highlight overlay is temporary
+        if Some(overlay_id) == highlight_id {
+            continue;
+        }
+
+        let data = match overlay_data.get(&overlay_id) {
+            Some(data) => data,
+            None => continue,
+        };
+
+        let file = format!("{}/{}.csv", BUNDLE_OVERLAY_DIR, overlay_id);
+        write_bundle_overlay(data, File::create(dir.join(&file))?)?;
+
+        let paths = overlay_state
+            .overlay_paths(overlay_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|path| {
+                let name = graph_query.graph().get_path_name_vec(path)?;
+                Some(std::str::from_utf8(&name).ok()?.to_string())
+            })
+            .collect();
+
+        manifest.overlays.push(BundleOverlay {
+            file,
+            kind,
+            name: name.to_string(),
+            legend: overlay_state.legend(overlay_id),
+            paths,
+        });
+    }
+
+    let annotations = app.annotations();
+
+    for (name, kind) in annotations.annot_names() {
+        let source = match kind {
+            AnnotationFileType::Gff3 => {
+                annotations.get_gff3(name).map(|r| r.file_path())
+            }
+            AnnotationFileType::Bed => {
+                annotations.get_bed(name).map(|r| r.file_path())
+            }
+        };
+
+        if let Some(source) = source {
+            let file = format!("{}/{}", BUNDLE_ANNOTATION_DIR, name);
+            fs::copy(source, dir.join(&file))?;
+
+            manifest
+                .annotations
+                .push(BundleAnnotation { file, kind: *kind });
+        }
+    }
+
+    let mut label_sets = annotations.label_sets().values().collect::<Vec<_>>();
+    label_sets.sort_by(|a, b| a.name().cmp(b.name()));
+
+    for (ix, label_set) in label_sets.into_iter().enumerate() {
+        let file = format!("{}/{}.tsv", BUNDLE_LABEL_DIR, ix);
+        write_bundle_labels(label_set, File::create(dir.join(&file))?)?;
+
+        manifest.label_sets.push(BundleLabelSet {
+            file,
+            name: label_set.name().to_string(),
+            annotation: label_set.annotation_name.clone(),
+            column: label_set.column_str.clone(),
+            path: label_set.path_name.clone(),
+        });
+    }
+
+    manifest.write(File::create(dir.join(BUNDLE_MANIFEST))?)?;
+    write_bundle_script(&manifest, File::create(dir.join(BUNDLE_SCRIPT))?)?;
+
+    Ok(())
+}
+
+/// Restores a bundle written by `export_bundle`. Overlays, annotation
+/// files, and label sets that are already loaded, by name, are kept
+/// as they are.
+///
+/// Everything in the bundle is read and validated before any of it
+/// is applied, so an invalid bundle leaves the session unchanged.
+#[allow(clippy::too_many_arguments)]
+fn import_bundle(
+    dir: &std::path::Path,
+    gfa_name: &str,
+    gfaestus: &GfaestusVk,
+    main_view: &mut MainView,
+    compute_manager: &mut ComputeManager,
+    value_normalizer: &OverlayValueNormalizer,
+    universe: &mut Universe<FlatLayout>,
+    app: &App,
+    graph_query: &GraphQuery,
+    overlay_data: &mut FxHashMap<usize, OverlayData>,
+) -> Result<()> {
+    use gfaestus::annotations::{
+        AnnotationCollection, AnnotationFileType, AnnotationLabelSet,
+    };
+    use gfaestus::export::*;
+    use std::fs::File;
+
+    let manifest =
+        BundleManifest::read(File::open(dir.join(BUNDLE_MANIFEST))?)?;
+
+    let node_count = graph_query.node_count();
+
+    if manifest.node_count != node_count {
+        anyhow::bail!(
+            "Bundle is for a graph with {} nodes, but the loaded graph has {}",
+            manifest.node_count,
+            node_count
+        );
+    }
+
+    let view_link = ViewLink::decode(&manifest.view_link)?;
+
+    if view_link.node_count != node_count {
+        anyhow::bail!(
+            "Bundle view is for a graph with {} nodes, but the loaded graph has {}",
+            view_link.node_count,
+            node_count
+        );
+    }
+
+    let layout_path = dir.join(BUNDLE_LAYOUT);
+    let layout_path = layout_path.to_str().ok_or_else(|| {
+        anyhow::anyhow!("Invalid layout path {:?}", layout_path)
+    })?;
+
+    let bundle_universe =
+        Universe::from_laid_out_graph(graph_query.graph(), layout_path)?;
+
+    let layout = bundle_universe.layout().nodes().to_vec();
+
+    if layout.len() != node_count {
+        anyhow::bail!(
+            "Bundle layout has {} nodes, but the graph has {}",
+            layout.len(),
+            node_count
+        );
+    }
+
+    let mut overlays = Vec::new();
+
+    for entry in manifest.overlays.iter() {
+        let exists = main_view
+            .node_draw_system
+            .overlay_pipelines
+            .overlay_by_name(&entry.name)
+            .is_some();
+
+        if exists {
+            info!("Overlay '{}' already exists, skipping", entry.name);
+            continue;
+        }
+
+        let file = File::open(dir.join(&entry.file))?;
+        let data = read_bundle_overlay(entry.kind, node_count, file)?;
+
+        let paths = entry
+            .paths
+            .iter()
+            .map(|name| {
+                graph_query.graph().get_path_id(name.as_bytes()).ok_or_else(
+                    || anyhow::anyhow!("Overlay path '{}' not found", name),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        overlays.push((entry, data, paths));
+    }
+
+    let annotations = app.annotations();
+
+    let mut gff3_records: FxHashMap<String, Gff3Records> = FxHashMap::default();
+    let mut bed_records: FxHashMap<String, BedRecords> = FxHashMap::default();
+
+    for entry in manifest.annotations.iter() {
+        let path = dir.join(&entry.file);
+
+        match entry.kind {
+            AnnotationFileType::Gff3 => {
+                let records = Gff3Records::parse_gff3_file(&path)?;
+                let name = records.file_name().to_string();
+                if annotations.get_gff3(&name).is_none() {
+                    gff3_records.insert(name, records);
+                }
+            }
+            AnnotationFileType::Bed => {
+                let records = BedRecords::parse_bed_file(&path)?;
+                let name = records.file_name().to_string();
+                if annotations.get_bed(&name).is_none() {
+                    bed_records.insert(name, records);
+                }
+            }
+        }
+    }
+
+    let mut label_sets: Vec<AnnotationLabelSet> = Vec::new();
+
+    for entry in manifest.label_sets.iter() {
+        if annotations.label_sets().contains_key(&entry.name) {
+            info!("Label set '{}' already exists, skipping", entry.name);
+            continue;
+        }
+
+        let graph = graph_query.graph();
+        let file = File::open(dir.join(&entry.file))?;
+
+        let gff3 = gff3_records
+            .get(&entry.annotation)
+            .or_else(|| annotations.get_gff3(&entry.annotation).map(|r| &**r));
+
+        let bed = bed_records
+            .get(&entry.annotation)
+            .or_else(|| annotations.get_bed(&entry.annotation).map(|r| &**r));
+
+        let label_set = if let Some(records) = gff3 {
+            read_bundle_label_set(records, graph, entry, file)?
+        } else if let Some(records) = bed {
+            read_bundle_label_set(records, graph, entry, file)?
+        } else {
+            anyhow::bail!(
+                "Label set '{}' refers to missing annotations '{}'",
+                entry.name,
+                entry.annotation
+            );
+        };
+
+        label_sets.push(label_set);
+    }
+
+    // everything has been read, so the bundle can be applied
+    apply_layout(gfaestus, main_view, universe, app, layout)?;
+
+    let overlay_state = app.shared_state().overlay_state();
+
+    for (entry, data, paths) in overlays {
+        let msg = OverlayCreatorMsg::NewOverlay {
+            name: entry.name.clone(),
+            data,
+            legend: entry.legend.clone(),
+        };
+
+        let (overlay_id, data) = handle_new_overlay(
+            gfaestus,
+            main_view,
+            compute_manager,
+            value_normalizer,
+            overlay_state,
+            node_count,
+            msg,
+        )?;
+
+        if !paths.is_empty() {
+            overlay_state.set_overlay_paths(overlay_id, Some(paths));
+        }
+
+        overlay_data.insert(overlay_id, data);
+    }
+
+    // the label sets must be added after their annotations
+    let app_tx = &app.channels().app_tx;
+
+    for (_, records) in gff3_records {
+        app_tx.send(AppMsg::AddGff3Records(records)).unwrap();
+    }
+
+    for (_, records) in bed_records {
+        app_tx.send(AppMsg::AddBedRecords(records)).unwrap();
+    }
+
+    for label_set in label_sets {
+        app_tx
+            .send(AppMsg::NewNodeLabels {
+                name: label_set.name().to_string(),
+                label_set,
+            })
+            .unwrap();
+    }
+
+    apply_view_link(view_link, gfa_name, node_count, app, main_view)
+}
+
+/// Replaces the node positions with the result of a layout
+/// algorithm, and moves the view to fit the new layout
+fn apply_layout(
+    gfaestus: &GfaestusVk,
+    main_view: &MainView,
+    universe: &mut Universe<FlatLayout>,
+    app: &App,
+    nodes: Vec<Node>,
+) -> Result<()> {
+    let node_count = universe.layout().nodes().len();
+
+    if nodes.len() != node_count {
+        anyhow::bail!(
+            "Layout has {} nodes, but the graph has {}",
+            nodes.len(),
+            node_count
+        );
+    }
+
+    universe.set_node_positions(nodes);
+    app.shared_state()
+        .set_layout_spacing(universe.node_spacing());
+
+    let vertices = universe.new_vertices();
+
+    // the vertex buffer may still be in use by the previous frame
+    gfaestus.wait_gpu_idle()?;
+
+    main_view
+        .node_draw_system
+        .vertices
+        .update_vertices(gfaestus, &vertices)?;
+
+    let (top_left, bottom_right) = universe.layout().bounding_box();
+    let view = View::from_dims_and_target(app.dims(), top_left, bottom_right);
+
+    main_view.set_initial_view(Some(view.center), Some(view.scale));
+    main_view
+        .main_view_msg_tx()
+        .send(MainViewMsg::GotoView(view))
+        .unwrap();
+
+    Ok(())
+}
+
+/// Scales the spacing between the nodes relative to the current
+/// layout, without moving the view
+fn apply_layout_spacing(
+    gfaestus: &GfaestusVk,
+    main_view: &MainView,
+    universe: &mut Universe<FlatLayout>,
+    spacing: f32,
+) -> Result<()> {
+    universe.set_node_spacing(spacing);
+
+    let vertices = universe.new_vertices();
+
+    // the vertex buffer may still be in use by the previous frame
+    gfaestus.wait_gpu_idle()?;
+
+    main_view
+        .node_draw_system
+        .vertices
+        .update_vertices(gfaestus, &vertices)
+}
+
+fn create_view_link(
+    graph_name: &str,
+    node_count: usize,
+    app: &App,
+    main_view: &MainView,
+) -> ViewLink {
+    let overlay_state = app.shared_state().overlay_state();
+
+    let overlay = overlay_state
+        .current_overlay()
+        .filter(|_| overlay_state.use_overlay())
+        .and_then(|(overlay_id, _)| {
+            main_view
+                .node_draw_system
+                .overlay_pipelines
+                .overlay_names()
+                .into_iter()
+                .find(|(id, _, _)| *id == overlay_id)
+                .map(|(_, _, name)| name.to_string())
+        });
+
+    ViewLink {
+        graph_name: graph_name.to_string(),
+        node_count,
+        view: app.shared_state().view(),
+        overlay,
+        selection: app.selection().iter().copied().collect(),
+    }
+}
+
+fn open_view_link(
+    link: &str,
+    graph_name: &str,
+    node_count: usize,
+    app: &App,
+    main_view: &MainView,
+) -> Result<()> {
+    let link = ViewLink::decode(link)?;
+    apply_view_link(link, graph_name, node_count, app, main_view)
+}
+
+fn apply_view_link(
+    link: ViewLink,
+    graph_name: &str,
+    node_count: usize,
+    app: &App,
+    main_view: &MainView,
+) -> Result<()> {
+    use gfaestus::app::Select;
+
+    if link.node_count != node_count {
+        anyhow::bail!(
+            "View link is for a graph with {} nodes, but the loaded graph has {}",
+            link.node_count,
+            node_count
+        );
+    }
+
+    if link.graph_name != graph_name {
+        warn!(
+            "View link was created for graph '{}', but '{}' is loaded",
+            link.graph_name, graph_name
+        );
+    }
+
+    main_view
+        .main_view_msg_tx()
+        .send(MainViewMsg::SetView(link.view))
+        .unwrap();
+
+    let overlay_state = app.shared_state().overlay_state();
+
+    if let Some(name) = &link.overlay {
+        let overlay = main_view
+            .node_draw_system
+            .overlay_pipelines
+            .overlay_by_name(name);
+
+        if overlay.is_some() {
+            overlay_state.set_current_overlay(overlay);
+            overlay_state.set_use_overlay(true);
+        } else {
+            warn!("View link overlay '{}' does not exist", name);
+        }
+    } else {
+        overlay_state.set_use_overlay(false);
+    }
+
+    let select = if link.selection.is_empty() {
+        Select::Clear
+    } else {
+        Select::Many {
+            nodes: link.selection.into_iter().collect(),
+            clear: true,
+        }
+    };
+
+    app.channels()
+        .app_tx
+        .send(AppMsg::Selection(select))
+        .unwrap();
+
     Ok(())
 }
 
@@ -1182,14 +3313,25 @@ pub struct Args {
     #[argh(positional)]
     gfa: String,
 
-    /// the layout file to use
+    /// the layout file to use; if omitted, an initial layout is
+    /// computed from the graph
     #[argh(positional)]
-    layout: String,
+    layout: Option<String>,
 
     /// load and run a script file at startup, e.g. for configuration
     #[argh(option)]
     run_script: Option<String>,
 
+    /// the directory relative paths in console commands are resolved
+    /// against, defaults to the directory of the GFA file
+    #[argh(option)]
+    script_dir: Option<String>,
+
+    /// number of threads in the background worker pool, defaults to
+    /// the available parallelism (env: GFAESTUS_THREADS)
+    #[argh(option)]
+    threads: Option<usize>,
+
     #[cfg(target_os = "linux")]
     /// force use of x11 window (debugging)
     #[argh(switch)]
@@ -1199,7 +3341,7 @@ pub struct Args {
     #[argh(switch, short = 'q')]
     quiet: bool,
 
-    /// log debug messages
+    /// log debug messages, including the sizes of GPU allocations
     #[argh(switch, short = 'd')]
     debug: bool,
 
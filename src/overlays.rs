@@ -17,6 +17,63 @@ pub enum OverlayData {
     Value(Vec<f32>),
 }
 
+/// What the colors of an overlay stand for, as shown in the legend
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayLegend {
+    /// The range of a value overlay's values before they were
+    /// normalized, which is mapped onto the active gradient
+    Range { min: f32, max: f32, units: String },
+    /// Labeled colors, e.g. one for each category or path, sorted
+    /// by label
+    Categories(Vec<(String, rgb::RGBA<f32>)>),
+}
+
+impl OverlayLegend {
+    /// A range legend covering the values, ignoring NaN; `None` if
+    /// there are no values
+    pub fn from_values(values: &[f32]) -> Option<Self> {
+        let (min, max) = values.iter().filter(|v| !v.is_nan()).fold(
+            (std::f32::INFINITY, std::f32::NEG_INFINITY),
+            |(min, max), &v| (min.min(v), max.max(v)),
+        );
+
+        if min > max {
+            return None;
+        }
+
+        Some(OverlayLegend::Range {
+            min,
+            max,
+            units: String::new(),
+        })
+    }
+
+    /// A category legend with one entry per distinct label; if a
+    /// label is given more than one color, the first is used
+    pub fn from_categories(
+        categories: impl IntoIterator<Item = (String, rgb::RGBA<f32>)>,
+    ) -> Self {
+        let mut categories = categories.into_iter().collect::<Vec<_>>();
+
+        categories.sort_by(|(a, _), (b, _)| a.cmp(b));
+        categories.dedup_by(|(a, _), (b, _)| a == b);
+
+        OverlayLegend::Categories(categories)
+    }
+}
+
+/// Formats a legend range endpoint, leaving out the decimals of
+/// whole numbers
+pub fn format_legend_value(value: f32) -> String {
+    if value.fract() == 0.0 && value.abs() < 1.0e9 {
+        format!("{}", value as i64)
+    } else if value.abs() >= 1000.0 {
+        format!("{:.1}", value)
+    } else {
+        format!("{:.3}", value)
+    }
+}
+
 /*
 pub type OverlayScriptType<T> = Function<
     RootedThread,
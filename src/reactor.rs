@@ -9,7 +9,10 @@ pub use paired::{create_host_pair, Host, Inbox, Outbox, Processor};
 
 use paired::*;
 
-use crate::{graph_query::GraphQuery, gui::windows::OverlayCreatorMsg};
+use crate::{
+    graph_query::{GraphQuery, GraphQueryWorker},
+    gui::windows::OverlayCreatorMsg,
+};
 
 pub struct Reactor {
     thread_pool: futures::executor::ThreadPool,
@@ -42,6 +45,14 @@ impl Reactor {
         }
     }
 
+    /// A query worker that runs its queries on the reactor thread pool
+    pub fn graph_query_worker(&self) -> GraphQueryWorker {
+        GraphQueryWorker::new(
+            self.graph_query.clone(),
+            self.thread_pool.clone(),
+        )
+    }
+
     pub fn create_host<F, I, T>(&mut self, func: F) -> Host<I, T>
     where
         T: Send + Sync + 'static,
@@ -1,6 +1,6 @@
 use rhai::{Engine, EvalAltResult};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use rayon::prelude::*;
 
@@ -9,14 +9,26 @@ use handlegraph::{
     handlegraph::*,
     mutablehandlegraph::*,
     packed::*,
-    packedgraph::index::OneBasedIndex,
+    packedgraph::{index::OneBasedIndex, PackedGraph},
     pathhandlegraph::*,
 };
 
-use rustc_hash::FxHashMap;
+use rustc_hash::FxHashSet;
 
+use parking_lot::Mutex;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::asynchronous::AsyncResult;
 use crate::overlays::{OverlayData, OverlayKind};
-use crate::{app::selection::NodeSelection, graph_query::GraphQuery};
+use crate::{
+    app::selection::NodeSelection,
+    graph_query::{
+        GraphQuery, GraphQueryWorker, OverlayBuildProgress, OverlayColorsTask,
+    },
+};
 
 use rhai::plugin::*;
 
@@ -138,6 +150,60 @@ pub fn create_engine() -> Engine {
     engine
 }
 
+/// Handle to a graph query started from a script, e.g. with the
+/// console's `find_sequence`, which can be polled with `is_ready` and
+/// `result`, or waited on with `wait`.
+///
+/// Scripts are evaluated on the same thread pool that runs the
+/// queries, and rhai has no way to yield, so waiting blocks one of
+/// the pool threads until the query is done. If every other pool
+/// thread is busy, e.g. with intervals or other waiting scripts, the
+/// query can't start until one is freed, which is why `wait` always
+/// takes a timeout. Interval scripts should poll instead of waiting,
+/// as they are run every frame.
+#[derive(Clone)]
+pub struct ScriptQuery {
+    result: Arc<Mutex<AsyncResult<rhai::Dynamic>>>,
+}
+
+impl ScriptQuery {
+    /// How often `wait` checks whether the query is done
+    const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+    pub fn new(result: AsyncResult<rhai::Dynamic>) -> Self {
+        Self {
+            result: Arc::new(Mutex::new(result)),
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.result.lock().is_ready()
+    }
+
+    /// The result of the query, or `None` if it isn't done yet
+    pub fn result(&self) -> Option<rhai::Dynamic> {
+        self.result.lock().get_result_if_ready().cloned()
+    }
+
+    /// Blocks the current thread until the query is done, returning
+    /// `None` if it took longer than `timeout`
+    pub fn wait(&self, timeout: Duration) -> Option<rhai::Dynamic> {
+        let start = Instant::now();
+
+        loop {
+            if let Some(result) = self.result() {
+                return Some(result);
+            }
+
+            if start.elapsed() >= timeout {
+                return None;
+            }
+
+            std::thread::sleep(Self::POLL_INTERVAL);
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ScriptTarget {
     Nodes,
@@ -150,6 +216,32 @@ pub struct ScriptConfig {
     pub target: ScriptTarget,
 }
 
+/// An overlay script and the configuration it's run with, which is
+/// kept with the overlays created from scripts so that they can be
+/// recomputed
+#[derive(Debug, Clone)]
+pub struct OverlayScript {
+    pub path: PathBuf,
+    pub config: ScriptConfig,
+}
+
+impl OverlayScript {
+    /// Starts building the overlay from the script on the graph query
+    /// worker, which loads and compiles the script, and runs it on
+    /// the nodes in parallel on `rayon_pool`; see `run_overlay_script`
+    pub fn build(
+        &self,
+        worker: &GraphQueryWorker,
+        rayon_pool: Arc<rayon::ThreadPool>,
+    ) -> OverlayColorsTask {
+        let script = self.clone();
+
+        worker.spawn_overlay_build(move |graph, progress| {
+            run_overlay_script(graph, &rayon_pool, &script, progress)
+        })
+    }
+}
+
 pub fn check_overlay_kind(data: rhai::Dynamic) -> Option<OverlayKind> {
     if let Some(_rgb) = data.clone().try_cast::<rgb::RGBA<f32>>() {
         Some(OverlayKind::RGB)
@@ -173,42 +265,40 @@ pub fn cast_overlay_data(data: Vec<rhai::Dynamic>) -> Option<OverlayData> {
 
         return Some(OverlayData::RGB(data));
     } else if let Some(_val) = first.try_cast::<f32>() {
-        let mut min = std::f32::MAX;
-        let mut max = std::f32::MIN;
-
-        let mut data = data
+        // value overlays are normalized on the GPU when the overlay
+        // is created, see `OverlayValueNormalizer`
+        let data = data
             .into_iter()
-            // .filter_map(|v| v.try_cast::<f32>())
-            .map(|v| {
-                let x = v.try_cast::<f32>().unwrap();
-                min = min.min(x);
-                max = max.max(x);
-                x
-            })
+            .map(|v| v.try_cast::<f32>().unwrap())
             .collect::<Vec<_>>();
 
-        let range = max - min;
-
-        for val in data.iter_mut() {
-            *val = (*val - min) / range;
-        }
-
-        log::debug!("Overlay values, min: {}, max: {}", min, max);
-
         return Some(OverlayData::Value(data));
     }
 
     None
 }
 
-pub fn overlay_colors_tgt(
-    rayon_pool: &rayon::ThreadPool,
-    config: &ScriptConfig,
+/// A compiled overlay script, with the scope each call of its
+/// `node_color` function is given
+struct CompiledOverlayScript {
+    engine: Engine,
+    ast: rhai::AST,
+    scope: rhai::Scope<'static>,
+
+    /// The nodes on the target path, if the script targets a path
+    path_nodes: Option<FxHashSet<NodeId>>,
+}
+
+fn compile_overlay_script(
     graph: &GraphQuery,
-    script: &str,
-) -> std::result::Result<OverlayData, Box<EvalAltResult>> {
+    script: &OverlayScript,
+) -> Result<CompiledOverlayScript> {
     use rhai::Scope;
 
+    let source = std::fs::read_to_string(&script.path).with_context(|| {
+        format!("Error loading script file {}", script.path.display())
+    })?;
+
     let mut scope = Scope::new();
     scope
         .push("graph", graph.graph.clone())
@@ -220,128 +310,143 @@ pub fn overlay_colors_tgt(
 
     engine.register_fn("get_graph", move || graph_.clone());
 
-    let node_color_ast = engine.compile(script)?;
-
-    match config.target.clone() {
-        ScriptTarget::Nodes => {
-            let mut node_ids =
-                graph.graph().handles().map(|h| h.id()).collect::<Vec<_>>();
-            node_ids.sort();
-
-            let values = rayon_pool.install(|| {
-                let mut values: Vec<rhai::Dynamic> =
-                    Vec::with_capacity(node_ids.len());
-                node_ids
-                    .into_par_iter()
-                    .map_with(scope, |mut thread_scope, node_id| {
-                        let value = engine
-                            .call_fn(
-                                &mut thread_scope,
-                                &node_color_ast,
-                                "node_color",
-                                (node_id,),
-                            )
-                            .unwrap();
+    let ast = engine
+        .compile(&source)
+        .map_err(|err| anyhow::anyhow!("Overlay script error: {}", err))?;
 
-                        value
-                    })
-                    .collect_into_vec(&mut values);
+    let path_nodes = match &script.config.target {
+        ScriptTarget::Nodes => None,
+        ScriptTarget::Path { name } => {
+            let steps = graph
+                .graph()
+                .get_path_id(name.as_bytes())
+                .and_then(|path_id| graph.path_pos_steps(path_id))
+                .ok_or_else(|| anyhow::anyhow!("Path '{}' not found", name))?;
 
-                values
-            });
+            Some(
+                steps
+                    .into_iter()
+                    .map(|(handle, _, _)| handle.id())
+                    .collect::<FxHashSet<_>>(),
+            )
+        }
+    };
+
+    Ok(CompiledOverlayScript {
+        engine,
+        ast,
+        scope,
+        path_nodes,
+    })
+}
 
-            let data = cast_overlay_data(values)
-                .ok_or("Couldn't process overlay data")?;
+/// Builds an overlay from the `node_color` function of the script,
+/// calling it on chunks of the nodes in parallel on `rayon_pool`,
+/// while reporting the progress through `progress`; returns `None`
+/// if the build was cancelled.
+///
+/// Nodes that aren't on the target path get the default color, or
+/// no value. The first error raised by the script fails the build.
+fn run_overlay_script(
+    graph: &GraphQuery,
+    rayon_pool: &rayon::ThreadPool,
+    script: &OverlayScript,
+    progress: &OverlayBuildProgress,
+) -> Option<Result<OverlayData>> {
+    let compiled = match compile_overlay_script(graph, script) {
+        Ok(compiled) => compiled,
+        Err(err) => return Some(Err(err)),
+    };
+
+    let CompiledOverlayScript {
+        engine,
+        ast,
+        scope,
+        path_nodes,
+    } = compiled;
+
+    let error: Mutex<Option<String>> = Mutex::new(None);
+
+    let node_value = |scope: &mut rhai::Scope<'static>,
+                      _graph: &PackedGraph,
+                      handle: Handle| {
+        let node_id = handle.id();
+
+        if let Some(path_nodes) = path_nodes.as_ref() {
+            if !path_nodes.contains(&node_id) {
+                return None;
+            }
+        }
 
-            Ok(data)
+        let value = engine.call_fn::<rhai::Dynamic>(
+            scope,
+            &ast,
+            "node_color",
+            (node_id,),
+        );
+
+        match value {
+            Ok(value) => Some(value),
+            Err(err) => {
+                error.lock().get_or_insert_with(|| err.to_string());
+                None
+            }
         }
-        ScriptTarget::Path { name } => {
-            let path_id = graph
-                .graph()
-                .get_path_id(name.as_bytes())
-                .ok_or("Path not found")?;
+    };
 
-            let steps =
-                graph.path_pos_steps(path_id).ok_or("Path not found")?;
+    let values = graph.build_overlay_par_with_progress(
+        rayon_pool, scope, node_value, progress,
+    )?;
 
-            let node_value_map = rayon_pool.install(|| {
-                let mut id_values: Vec<(NodeId, rhai::Dynamic)> =
-                    Vec::with_capacity(steps.len());
+    if let Some(err) = error.into_inner() {
+        return Some(Err(anyhow::anyhow!("Overlay script error: {}", err)));
+    }
 
-                steps
-                    .into_par_iter()
-                    .map_with(scope, |mut thread_scope, step| {
-                        let (handle, _, pos) = step;
-                        let node_id = handle.id();
+    Some(fill_overlay_values(values, script.config.default_color))
+}
 
-                        let value = engine
-                            .call_fn(
-                                &mut thread_scope,
-                                &node_color_ast,
-                                "node_color",
-                                (node_id,),
-                            )
-                            .unwrap();
+/// Casts the values returned by an overlay script to overlay data,
+/// using `default_color`, or no value, for the nodes without a value
+fn fill_overlay_values(
+    values: Vec<Option<rhai::Dynamic>>,
+    default_color: rgb::RGBA<f32>,
+) -> Result<OverlayData> {
+    let has_value = values.iter().map(Option::is_some).collect::<Vec<_>>();
 
-                        value
-                    })
-                    .collect_into_vec(&mut id_values);
+    let data = cast_overlay_data(values.into_iter().flatten().collect())
+        .ok_or_else(|| anyhow::anyhow!("Couldn't process overlay data"))?;
 
-                id_values
-            });
+    let data = match data {
+        OverlayData::RGB(colors) => {
+            let mut colors = colors.into_iter();
 
-            let (nodes, values): (Vec<_>, Vec<_>) =
-                node_value_map.into_iter().unzip();
+            let data = has_value
+                .into_iter()
+                .map(|has_value| {
+                    let value = if has_value { colors.next() } else { None };
+                    value.unwrap_or(default_color)
+                })
+                .collect();
 
-            let data = cast_overlay_data(values)
-                .ok_or("Couldn't process overlay data")?;
+            OverlayData::RGB(data)
+        }
+        OverlayData::Value(values) => {
+            let mut values = values.into_iter();
 
-            let mut node_ids =
-                graph.graph().handles().map(|h| h.id()).collect::<Vec<_>>();
-            node_ids.sort();
+            // nodes that aren't on the path have no value
+            let data = has_value
+                .into_iter()
+                .map(|has_value| {
+                    let value = if has_value { values.next() } else { None };
+                    value.unwrap_or(std::f32::NAN)
+                })
+                .collect();
 
-            let default_color = config.default_color;
-
-            match data {
-                OverlayData::RGB(rgb) => {
-                    let node_rgb_map: FxHashMap<NodeId, rgb::RGBA<f32>> = nodes
-                        .into_iter()
-                        .zip(rgb.into_iter())
-                        .collect::<FxHashMap<_, _>>();
-
-                    let data = node_ids
-                        .into_iter()
-                        .map(|node_id| {
-                            node_rgb_map
-                                .get(&node_id)
-                                .copied()
-                                .unwrap_or(default_color)
-                        })
-                        .collect();
-
-                    Ok(OverlayData::RGB(data))
-                }
-                OverlayData::Value(val) => {
-                    let node_val_map: FxHashMap<NodeId, f32> = nodes
-                        .into_iter()
-                        .zip(val.into_iter())
-                        .collect::<FxHashMap<_, _>>();
-
-                    let data = node_ids
-                        .into_iter()
-                        .map(|node_id| {
-                            node_val_map
-                                .get(&node_id)
-                                .copied()
-                                .unwrap_or_default()
-                        })
-                        .collect();
-
-                    Ok(OverlayData::Value(data))
-                }
-            }
+            OverlayData::Value(data)
         }
-    }
+    };
+
+    Ok(data)
 }
 
 /*
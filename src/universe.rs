@@ -23,11 +23,13 @@ use crate::{geometry::*, vulkan::draw_system::nodes::NodeVertices};
 pub mod config;
 pub mod graph_layout;
 pub mod grid;
+pub mod layout_algorithm;
 pub mod physics;
 pub mod selection;
 
 pub use config::*;
 pub use graph_layout::*;
+pub use layout_algorithm::*;
 pub use selection::*;
 
 // Trait abstracting over Grid and FlatLayout -- this definition only
@@ -61,6 +63,11 @@ pub trait GraphLayout {
     }
 }
 
+/// The range of factors the node spacing can be scaled by, see
+/// `Universe::set_node_spacing`
+pub const MIN_LAYOUT_SPACING: f32 = 0.05;
+pub const MAX_LAYOUT_SPACING: f32 = 20.0;
+
 #[derive(Debug, Clone)]
 pub struct Universe<G: GraphLayout> {
     // TODO bp_per_world_unit isn't used yet; and it should probably
@@ -71,6 +78,9 @@ pub struct Universe<G: GraphLayout> {
     // node_ids: Vec<NodeId>,
     pub offset: Point,
     pub angle: f32,
+    /// The factor the node spacing has been scaled by since the node
+    /// positions were last replaced
+    spacing: f32,
     // physics_config: PhysicsConfig,
     // layout_config: LayoutConfig,
     // view_config: ViewConfig,
@@ -102,9 +112,37 @@ impl Universe<FlatLayout> {
             graph_layout,
             offset,
             angle,
+            spacing: 1.0,
         })
     }
 
+    /// Creates a universe for a graph that came without a layout
+    /// file, with the nodes placed by `algorithm`
+    pub fn from_layout_algorithm(
+        graph: &PackedGraph,
+        algorithm: LayoutAlgorithm,
+    ) -> Self {
+        let empty = vec![
+            Node {
+                p0: Point::ZERO,
+                p1: Point::ZERO,
+            };
+            graph.node_count()
+        ];
+
+        let nodes = algorithm.run(graph, &empty, |_| ());
+
+        let graph_layout = FlatLayout::from_nodes(nodes);
+
+        Self {
+            bp_per_world_unit: 1.0,
+            graph_layout,
+            offset: Point::new(0.0, 0.0),
+            angle: 0.0,
+            spacing: 1.0,
+        }
+    }
+
     pub fn update_positions_from_gpu(
         &mut self,
         app: &GfaestusVk,
@@ -153,6 +191,54 @@ impl Universe<FlatLayout> {
     }
     */
 
+    /// Replaces the node positions, e.g. with the result of a
+    /// `LayoutAlgorithm`; `nodes` must be in node ID order
+    pub fn set_node_positions(&mut self, nodes: Vec<Node>) {
+        self.graph_layout.set_nodes(nodes);
+        self.spacing = 1.0;
+    }
+
+    pub fn node_spacing(&self) -> f32 {
+        self.spacing
+    }
+
+    /// Scales the distances between the node centers about the
+    /// centroid of the layout, so that they're `spacing` times what
+    /// they were when the node positions were last replaced; the
+    /// nodes themselves keep their length and orientation
+    pub fn set_node_spacing(&mut self, spacing: f32) {
+        let spacing = spacing.max(MIN_LAYOUT_SPACING).min(MAX_LAYOUT_SPACING);
+
+        let factor = spacing / self.spacing;
+        self.spacing = spacing;
+
+        let nodes = self.graph_layout.nodes();
+
+        if nodes.is_empty() {
+            return;
+        }
+
+        let centroid = nodes
+            .iter()
+            .fold(Point::ZERO, |sum, node| sum + node.center())
+            / nodes.len() as f32;
+
+        let nodes = nodes
+            .iter()
+            .map(|node| {
+                let center = node.center();
+                let delta = (centroid + (center - centroid) * factor) - center;
+
+                Node {
+                    p0: node.p0 + delta,
+                    p1: node.p1 + delta,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        self.graph_layout.set_nodes(nodes);
+    }
+
     pub fn new_vertices(&self) -> Vec<Vertex> {
         let mut vertices = Vec::new();
 
@@ -171,6 +257,62 @@ impl Universe<FlatLayout> {
     }
 }
 
+/// Lays out the nodes of `path` end to end along the x-axis, in the
+/// order the path first visits them, with each node pointing in the
+/// direction it's traversed; nodes the path visits more than once
+/// keep their first position.
+///
+/// The nodes that aren't on the path are collapsed to a point just
+/// before the start of the path, so they can be hidden. Returns
+/// `None` if the path doesn't exist.
+pub fn path_tube_layout(
+    graph: &PackedGraph,
+    path: PathId,
+) -> Option<Vec<Node>> {
+    /// The distance between the hidden nodes and the path
+    const HIDDEN_GAP: f32 = 100.0;
+
+    let hidden = Point::new(-HIDDEN_GAP, 0.0);
+
+    let mut nodes = vec![
+        Node {
+            p0: hidden,
+            p1: hidden,
+        };
+        graph.node_count()
+    ];
+
+    let mut placed: FxHashSet<NodeId> = FxHashSet::default();
+
+    let mut x = 0.0;
+
+    for step in graph.path_steps(path)? {
+        let handle = step.handle();
+        let len = graph.node_len(handle) as f32;
+
+        if placed.insert(handle.id()) {
+            let start = Point::new(x, 0.0);
+            let end = Point::new(x + len, 0.0);
+
+            let (p0, p1) = if handle.is_reverse() {
+                (end, start)
+            } else {
+                (start, end)
+            };
+
+            let ix = (handle.id().0 - 1) as usize;
+
+            if let Some(node) = nodes.get_mut(ix) {
+                *node = Node { p0, p1 };
+            }
+        }
+
+        x += len;
+    }
+
+    Some(nodes)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 pub struct Node {
     pub p0: Point,
@@ -209,6 +351,41 @@ impl GraphLayout for FlatLayout {
 }
 
 impl FlatLayout {
+    /// A single-component layout with the given positions, which must
+    /// be in node ID order
+    fn from_nodes(nodes: Vec<Node>) -> Self {
+        let node_ids = (1..=nodes.len() as u64).map(NodeId::from).collect();
+
+        let mut layout = FlatLayout {
+            node_ids,
+            nodes: Vec::new(),
+            component_offsets: Vec::new(),
+            top_left: Point::ZERO,
+            bottom_right: Point::ZERO,
+        };
+
+        layout.set_nodes(nodes);
+        layout
+    }
+
+    fn set_nodes(&mut self, nodes: Vec<Node>) {
+        let mut min = Point::new(std::f32::MAX, std::f32::MAX);
+        let mut max = Point::new(std::f32::MIN, std::f32::MIN);
+
+        for node in nodes.iter() {
+            for p in [node.p0, node.p1].iter() {
+                min.x = min.x.min(p.x);
+                min.y = min.y.min(p.y);
+                max.x = max.x.max(p.x);
+                max.y = max.y.max(p.y);
+            }
+        }
+
+        self.nodes = nodes;
+        self.top_left = min;
+        self.bottom_right = max;
+    }
+
     pub fn node_component(&self, node_id: NodeId) -> usize {
         let offset =
             self.component_offsets.iter().enumerate().find(|(_, o)| {
@@ -0,0 +1,570 @@
+#[allow(unused_imports)]
+use handlegraph::{
+    handle::{Direction, Edge, Handle, NodeId},
+    handlegraph::*,
+    packedgraph::PackedGraph,
+};
+
+use anyhow::Result;
+use crossbeam::atomic::AtomicCell;
+use rustc_hash::FxHashMap;
+use std::{collections::VecDeque, sync::Arc};
+
+use crate::geometry::Point;
+use crate::reactor::{Host, Outbox, Reactor};
+
+use super::Node;
+
+/// Layout algorithms that can be applied to the loaded graph from
+/// within gfaestus, producing new positions for all nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutAlgorithm {
+    /// Places the nodes in a row, in node ID order
+    NodeOrder,
+    /// Places the nodes of each connected component in a row, in
+    /// breadth-first order, with the components stacked vertically
+    BreadthFirst,
+    /// Iteratively refines the current layout using attraction along
+    /// nodes and edges, and repulsion between nearby nodes
+    ForceDirected,
+}
+
+impl LayoutAlgorithm {
+    pub const ALL: [LayoutAlgorithm; 3] = [
+        LayoutAlgorithm::NodeOrder,
+        LayoutAlgorithm::BreadthFirst,
+        LayoutAlgorithm::ForceDirected,
+    ];
+
+    /// The horizontal space between consecutive nodes in the 1D layouts
+    const NODE_GAP: f32 = 10.0;
+
+    /// The vertical space between components, matching the offset
+    /// used when loading a layout file
+    const COMPONENT_SPACING: f32 = 10_000.0;
+
+    const FORCE_ITERATIONS: usize = 200;
+
+    /// The name used to refer to the algorithm in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            LayoutAlgorithm::NodeOrder => "node_order",
+            LayoutAlgorithm::BreadthFirst => "bfs",
+            LayoutAlgorithm::ForceDirected => "force_directed",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|alg| alg.name() == name)
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            LayoutAlgorithm::NodeOrder => "Node order",
+            LayoutAlgorithm::BreadthFirst => "Breadth-first order",
+            LayoutAlgorithm::ForceDirected => "Force-directed",
+        }
+    }
+
+    /// Computes the new node positions, in node ID order.
+    ///
+    /// `nodes` is the current layout, which iterative algorithms use
+    /// as their starting point. `progress` is called with values
+    /// between 0.0 and 1.0 as the algorithm runs.
+    pub fn run<F>(
+        &self,
+        graph: &PackedGraph,
+        nodes: &[Node],
+        progress: F,
+    ) -> Vec<Node>
+    where
+        F: Fn(f32),
+    {
+        match self {
+            LayoutAlgorithm::NodeOrder => {
+                let mut handles = graph.handles().collect::<Vec<_>>();
+                handles.sort();
+
+                let result = linear_layout(graph, &[handles], nodes.len());
+                progress(1.0);
+                result
+            }
+            LayoutAlgorithm::BreadthFirst => {
+                let components = bfs_components(graph, &progress);
+
+                let result = linear_layout(graph, &components, nodes.len());
+                progress(1.0);
+                result
+            }
+            LayoutAlgorithm::ForceDirected => {
+                // if every node is at the same point, no force moves
+                // anything, so start from a 1D layout instead
+                let mut seed = if coincident(nodes) {
+                    LayoutAlgorithm::BreadthFirst.run(graph, nodes, |_| ())
+                } else {
+                    nodes.to_vec()
+                };
+
+                // in a layout where every node is horizontal, all
+                // forces are horizontal too, and the layout could
+                // never leave its rows
+                if horizontal(&seed) {
+                    jitter_y(&mut seed, Self::NODE_GAP * 2.0);
+                }
+
+                force_directed(graph, &seed, Self::FORCE_ITERATIONS, &progress)
+            }
+        }
+    }
+}
+
+/// True if all node endpoints are at the same point, e.g. when
+/// there is no layout yet
+fn coincident(nodes: &[Node]) -> bool {
+    match nodes.first() {
+        Some(first) => nodes
+            .iter()
+            .all(|node| node.p0 == first.p0 && node.p1 == first.p0),
+        None => true,
+    }
+}
+
+/// True if both endpoints of every node have the same y coordinate,
+/// as in the 1D layouts
+fn horizontal(nodes: &[Node]) -> bool {
+    nodes.iter().all(|node| node.p0.y == node.p1.y)
+}
+
+/// Moves each node endpoint vertically by up to `amount`, using a
+/// hash of the endpoint index so that runs are reproducible
+fn jitter_y(nodes: &mut [Node], amount: f32) {
+    let offset = |ix: usize| {
+        // splitmix64
+        let mut z = (ix as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        let unit = (z >> 40) as f32 / (1u64 << 24) as f32;
+        (unit * 2.0 - 1.0) * amount
+    };
+
+    for (ix, node) in nodes.iter_mut().enumerate() {
+        node.p0.y += offset(ix * 2);
+        node.p1.y += offset(ix * 2 + 1);
+    }
+}
+
+fn node_index(id: NodeId) -> usize {
+    (id.0 - 1) as usize
+}
+
+/// Lays out each list of handles as a row, with each node as long as
+/// its sequence, and reversed handles pointing to the left
+fn linear_layout(
+    graph: &PackedGraph,
+    rows: &[Vec<Handle>],
+    node_count: usize,
+) -> Vec<Node> {
+    let mut nodes = vec![
+        Node {
+            p0: Point::ZERO,
+            p1: Point::ZERO,
+        };
+        node_count
+    ];
+
+    for (row_ix, row) in rows.iter().enumerate() {
+        let y = (row_ix as f32) * LayoutAlgorithm::COMPONENT_SPACING;
+
+        let mut x = 0.0f32;
+
+        for &handle in row {
+            let len = (graph.node_len(handle) as f32).max(1.0);
+
+            let left = Point::new(x, y);
+            let right = Point::new(x + len, y);
+
+            let node = if handle.is_reverse() {
+                Node {
+                    p0: right,
+                    p1: left,
+                }
+            } else {
+                Node {
+                    p0: left,
+                    p1: right,
+                }
+            };
+
+            if let Some(slot) = nodes.get_mut(node_index(handle.id())) {
+                *slot = node;
+            }
+
+            x += len + LayoutAlgorithm::NODE_GAP;
+        }
+    }
+
+    nodes
+}
+
+/// Returns the handles of each connected component in breadth-first
+/// order, starting from the component's lowest node ID. A node is
+/// reversed if it was first reached through its reverse handle.
+fn bfs_components<F>(graph: &PackedGraph, progress: &F) -> Vec<Vec<Handle>>
+where
+    F: Fn(f32),
+{
+    let node_count = graph.node_count();
+
+    let mut handles = graph.handles().collect::<Vec<_>>();
+    handles.sort();
+
+    let mut visited = vec![false; node_count];
+    let mut visited_count = 0usize;
+
+    let mut components = Vec::new();
+    let mut queue: VecDeque<Handle> = VecDeque::new();
+
+    let report_every = (node_count / 100).max(1);
+
+    for start in handles {
+        if visited[node_index(start.id())] {
+            continue;
+        }
+
+        let mut component = Vec::new();
+
+        visited[node_index(start.id())] = true;
+        queue.push_back(start);
+
+        while let Some(handle) = queue.pop_front() {
+            component.push(handle);
+
+            visited_count += 1;
+            if visited_count % report_every == 0 {
+                progress(visited_count as f32 / node_count as f32);
+            }
+
+            // neighbors on both sides are returned in the orientation
+            // consistent with the current handle
+            let next = graph
+                .neighbors(handle, Direction::Right)
+                .chain(graph.neighbors(handle, Direction::Left));
+
+            for other in next {
+                let ix = node_index(other.id());
+                if !visited[ix] {
+                    visited[ix] = true;
+                    queue.push_back(other);
+                }
+            }
+        }
+
+        components.push(component);
+    }
+
+    components
+}
+
+/// The endpoint indices (p0 = 2 * node_ix, p1 = 2 * node_ix + 1)
+/// connected by an edge, matching the edge renderer
+fn edge_endpoints(Edge(left, right): Edge) -> (usize, usize) {
+    let left_l = node_index(left.id()) * 2;
+    let left_r = left_l + 1;
+
+    let right_l = node_index(right.id()) * 2;
+    let right_r = right_l + 1;
+
+    match (left.is_reverse(), right.is_reverse()) {
+        (false, false) => (left_r, right_l),
+        (true, false) => (left_l, right_l),
+        (false, true) => (left_r, right_r),
+        (true, true) => (left_l, right_r),
+    }
+}
+
+fn force_directed<F>(
+    graph: &PackedGraph,
+    nodes: &[Node],
+    iterations: usize,
+    progress: &F,
+) -> Vec<Node>
+where
+    F: Fn(f32),
+{
+    // the target length of an edge, and the distance within which
+    // node endpoints repel each other
+    const EDGE_LENGTH: f32 = 10.0;
+    const REPULSION_RADIUS: f32 = 50.0;
+
+    let mut points: Vec<Point> = Vec::with_capacity(nodes.len() * 2);
+    for node in nodes {
+        points.push(node.p0);
+        points.push(node.p1);
+    }
+
+    // (endpoint a, endpoint b, target length)
+    let mut springs: Vec<(usize, usize, f32)> =
+        Vec::with_capacity(nodes.len() + graph.edge_count());
+
+    let mut handles = graph.handles().collect::<Vec<_>>();
+    handles.sort();
+
+    for handle in handles {
+        let ix = node_index(handle.id()) * 2;
+        if ix + 1 < points.len() {
+            let len = (graph.node_len(handle) as f32).max(1.0);
+            springs.push((ix, ix + 1, len));
+        }
+    }
+
+    for edge in graph.edges() {
+        let (a, b) = edge_endpoints(edge);
+        if a < points.len() && b < points.len() && a != b {
+            springs.push((a, b, EDGE_LENGTH));
+        }
+    }
+
+    let mut forces = vec![Point::ZERO; points.len()];
+
+    let mut grid: FxHashMap<(i32, i32), Vec<usize>> = FxHashMap::default();
+
+    let cell = |p: Point| {
+        (
+            (p.x / REPULSION_RADIUS).floor() as i32,
+            (p.y / REPULSION_RADIUS).floor() as i32,
+        )
+    };
+
+    for iteration in 0..iterations {
+        // the maximum distance a point can move decreases linearly
+        let temperature =
+            REPULSION_RADIUS * (1.0 - (iteration as f32 / iterations as f32));
+
+        forces.iter_mut().for_each(|f| *f = Point::ZERO);
+
+        grid.values_mut().for_each(|v| v.clear());
+        for (ix, &p) in points.iter().enumerate() {
+            grid.entry(cell(p)).or_default().push(ix);
+        }
+
+        for (ix, &p) in points.iter().enumerate() {
+            let (cx, cy) = cell(p);
+
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let others = match grid.get(&(cx + dx, cy + dy)) {
+                        Some(others) => others,
+                        None => continue,
+                    };
+
+                    for &other in others {
+                        if other == ix {
+                            continue;
+                        }
+
+                        let delta = p - points[other];
+                        let dist = delta.length().max(0.01);
+
+                        if dist < REPULSION_RADIUS {
+                            let mag =
+                                (REPULSION_RADIUS - dist) / REPULSION_RADIUS;
+                            forces[ix] += delta * (mag / dist);
+                        }
+                    }
+                }
+            }
+        }
+
+        for &(a, b, target) in springs.iter() {
+            let delta = points[b] - points[a];
+            let dist = delta.length().max(0.01);
+
+            // pull or push both endpoints toward the target distance
+            let mag = 0.5 * (dist - target) / dist;
+            let force = delta * mag;
+
+            forces[a] += force;
+            forces[b] -= force;
+        }
+
+        for (p, f) in points.iter_mut().zip(forces.iter()) {
+            let len = f.length();
+            if len > temperature {
+                *p += *f * (temperature / len);
+            } else {
+                *p += *f;
+            }
+        }
+
+        progress((iteration + 1) as f32 / iterations as f32);
+    }
+
+    points
+        .chunks(2)
+        .map(|ps| Node {
+            p0: ps[0],
+            p1: ps[1],
+        })
+        .collect()
+}
+
+/// Runs layout algorithms on the reactor thread pool, one at a time,
+/// reporting the progress of the current run through `progress`
+pub struct LayoutRunner {
+    host: Host<(LayoutAlgorithm, Vec<Node>), (LayoutAlgorithm, Vec<Node>)>,
+    running: Option<LayoutAlgorithm>,
+
+    progress: Arc<AtomicCell<Option<(LayoutAlgorithm, f32)>>>,
+}
+
+impl LayoutRunner {
+    pub fn new(
+        reactor: &mut Reactor,
+        progress: Arc<AtomicCell<Option<(LayoutAlgorithm, f32)>>>,
+    ) -> Self {
+        let graph_query = reactor.graph_query.clone();
+        let progress_ = progress.clone();
+
+        let host = reactor.create_host(
+            move |_outbox: &Outbox<(LayoutAlgorithm, Vec<Node>)>,
+                  (algorithm, nodes): (LayoutAlgorithm, Vec<Node>)| {
+                let graph = graph_query.graph();
+
+                let result = algorithm.run(graph, &nodes, |p| {
+                    progress_.store(Some((algorithm, p)));
+                });
+
+                (algorithm, result)
+            },
+        );
+
+        Self {
+            host,
+            running: None,
+            progress,
+        }
+    }
+
+    pub fn running(&self) -> Option<LayoutAlgorithm> {
+        self.running
+    }
+
+    /// Starts running `algorithm` using `nodes` as the current
+    /// layout; returns `false` if a layout is already running
+    pub fn run(
+        &mut self,
+        algorithm: LayoutAlgorithm,
+        nodes: Vec<Node>,
+    ) -> Result<bool> {
+        if self.running.is_some() {
+            return Ok(false);
+        }
+
+        self.progress.store(Some((algorithm, 0.0)));
+        self.host.call((algorithm, nodes))?;
+        self.running = Some(algorithm);
+
+        Ok(true)
+    }
+
+    /// Returns the finished layout, if the running algorithm is done
+    pub fn take_result(&mut self) -> Option<(LayoutAlgorithm, Vec<Node>)> {
+        let result = self.host.take()?;
+
+        self.running = None;
+        self.progress.store(None);
+
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use handlegraph::mutablehandlegraph::*;
+
+    /// A bubble between nodes 1 and 4, followed by a chain
+    fn graph() -> PackedGraph {
+        let mut graph = PackedGraph::default();
+
+        let handles = (1..=8u64)
+            .map(|id| graph.create_handle(b"ACGTACGT", id))
+            .collect::<Vec<_>>();
+
+        let edges = [
+            (0, 1),
+            (0, 2),
+            (1, 3),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+        ];
+
+        for &(a, b) in edges.iter() {
+            graph.create_edge(Edge(handles[a], handles[b]));
+        }
+
+        graph
+    }
+
+    fn unplaced() -> Vec<Node> {
+        let node = Node {
+            p0: Point::ZERO,
+            p1: Point::ZERO,
+        };
+        vec![node; 8]
+    }
+
+    fn y_extent(nodes: &[Node]) -> f32 {
+        let ys = nodes.iter().flat_map(|n| vec![n.p0.y, n.p1.y]);
+
+        let (min, max) = ys.fold((f32::MAX, f32::MIN), |(min, max), y| {
+            (min.min(y), max.max(y))
+        });
+
+        max - min
+    }
+
+    #[test]
+    fn linear_layouts_are_horizontal() {
+        let graph = graph();
+        let empty = unplaced();
+
+        let nodes = LayoutAlgorithm::NodeOrder.run(&graph, &empty, |_| ());
+
+        assert!(horizontal(&nodes));
+        assert_eq!(y_extent(&nodes), 0.0);
+        assert_eq!(nodes[1].p0.x, nodes[0].p1.x + LayoutAlgorithm::NODE_GAP);
+    }
+
+    #[test]
+    fn force_directed_leaves_the_line() {
+        let graph = graph();
+        let empty = unplaced();
+
+        let nodes = LayoutAlgorithm::ForceDirected.run(&graph, &empty, |_| ());
+
+        assert_eq!(nodes.len(), 8);
+        assert!(nodes
+            .iter()
+            .all(|n| n.p0.x.is_finite() && n.p0.y.is_finite()));
+        assert!(y_extent(&nodes) > 0.0);
+        assert!(!horizontal(&nodes));
+    }
+
+    #[test]
+    fn force_directed_from_a_1d_layout() {
+        let graph = graph();
+        let empty = unplaced();
+
+        let linear = LayoutAlgorithm::NodeOrder.run(&graph, &empty, |_| ());
+        let nodes = LayoutAlgorithm::ForceDirected.run(&graph, &linear, |_| ());
+
+        assert!(y_extent(&nodes) > 0.0);
+    }
+}
@@ -83,7 +83,11 @@ impl Into<[u32; 2]> for ScreenDims {
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 pub struct View {
     pub center: Point,
+    /// World units per pixel along the x-axis
     pub scale: f32,
+    /// The ratio of the y-axis scale to the x-axis scale; values
+    /// above 1.0 stretch the graph horizontally relative to vertically
+    pub aspect: f32,
 }
 
 impl Default for View {
@@ -92,6 +96,7 @@ impl Default for View {
         Self {
             center: Point::new(0.0, 0.0),
             scale: 10.0,
+            aspect: 1.0,
         }
     }
 }
@@ -131,18 +136,39 @@ impl View {
 
         let center = top_left + (target_dims * 0.5);
 
-        View { center, scale }
+        View {
+            center,
+            scale,
+            aspect: 1.0,
+        }
+    }
+
+    #[inline]
+    pub fn scale_x(&self) -> f32 {
+        self.scale
+    }
+
+    #[inline]
+    pub fn scale_y(&self) -> f32 {
+        self.scale * self.aspect
+    }
+
+    /// The per-axis scale, as world units per pixel
+    #[inline]
+    pub fn scale_xy(&self) -> Point {
+        Point::new(self.scale_x(), self.scale_y())
     }
 
     #[rustfmt::skip]
     #[inline]
     pub fn to_scaled_matrix(&self) -> glm::Mat4 {
 
-        let scale = 1.0 / self.scale;
+        let sx = 1.0 / self.scale_x();
+        let sy = 1.0 / self.scale_y();
 
         let scaling =
-            glm::mat4(scale, 0.0,   0.0, 0.0,
-                      0.0,   scale, 0.0, 0.0,
+            glm::mat4(sx,  0.0, 0.0, 0.0,
+                      0.0, sy,  0.0, 0.0,
                       0.0,   0.0,   1.0, 1.0,
                       0.0,   0.0,   0.0, 1.0);
 
@@ -161,13 +187,14 @@ impl View {
     #[rustfmt::skip]
     #[inline]
     pub fn world_to_screen_map(&self) -> glm::Mat4 {
-        let s = 1.0 / self.scale;
+        let sx = 1.0 / self.scale_x();
+        let sy = 1.0 / self.scale_y();
         let vcx = self.center.x;
         let vcy = self.center.y;
 
         let view_scale_screen =
-            glm::mat4(s,   0.0, 0.0, (s * 0.5) - vcx,
-                      0.0, s,   0.0, (s * 0.5) - vcx,
+            glm::mat4(sx,  0.0, 0.0, (sx * 0.5) - vcx,
+                      0.0, sy,  0.0, (sy * 0.5) - vcx,
                       0.0, 0.0, 1.0, 0.0,
                       0.0, 0.0, 0.0, 1.0);
 
@@ -182,21 +209,22 @@ impl View {
         let w = dims.width;
         let h = dims.height;
 
-        let s = self.scale;
+        let sx = self.scale_x();
+        let sy = self.scale_y();
         let vcx = self.center.x;
         let vcy = self.center.y;
 
         // transform from screen coords (top left (0, 0), bottom right (w, h))
         // to screen center = (0, 0), bottom right (w/2, h/2);
         //
-        // then scale so bottom right = (s*w/2, s*h/2);
+        // then scale so bottom right = (sx*w/2, sy*h/2);
         //
         // finally translate by view center to world coordinates
         //
         // i.e. view_offset * scale * screen_center
         let view_scale_screen =
-            glm::mat4(s,   0.0, 0.0, vcx - (w * s * 0.5),
-                      0.0, s,   0.0, vcy - (h * s * 0.5),
+            glm::mat4(sx,  0.0, 0.0, vcx - (w * sx * 0.5),
+                      0.0, sy,  0.0, vcy - (h * sy * 0.5),
                       0.0, 0.0, 1.0, 0.0,
                       0.0, 0.0, 0.0, 1.0);
 
@@ -2,6 +2,8 @@ pub mod compute;
 pub mod context;
 pub mod debug;
 pub mod draw_system;
+pub mod frame_capture;
+pub mod memory;
 pub mod render_pass;
 pub mod texture;
 
@@ -9,8 +11,11 @@ mod init;
 
 use context::*;
 use init::*;
+use memory::*;
 use render_pass::*;
 
+use frame_capture::FrameCapture;
+
 use ash::{
     extensions::khr::{Surface, Swapchain},
     version::DeviceV1_0,
@@ -113,6 +118,8 @@ impl Queues {
 pub struct GfaestusVk {
     pub allocator: Allocator,
 
+    pub gpu_allocations: GpuAllocations,
+
     pub graphics_queue: vk::Queue,
     pub present_queue: vk::Queue,
 
@@ -265,10 +272,14 @@ impl GfaestusVk {
             })
             .collect::<Vec<_>>();
 
+        let gpu_allocations =
+            GpuAllocations::new(&vk_context.get_mem_properties());
+
         let result = Self {
             vk_context,
 
             allocator,
+            gpu_allocations,
 
             graphics_queue,
             present_queue,
@@ -325,9 +336,13 @@ impl GfaestusVk {
         &self.vk_context
     }
 
+    /// Draws and presents a frame. If `capture` is given, the
+    /// finished frame is also copied into it, and can be read once
+    /// this returns `Ok(false)`
     pub fn draw_frame_from<F>(
         &mut self,
         window_size: [u32; 2],
+        capture: Option<&FrameCapture>,
         commands: F,
     ) -> Result<bool>
     where
@@ -382,6 +397,7 @@ impl GfaestusVk {
         let queue = self.graphics_queue;
 
         let framebuffers = &self.framebuffers[img_index as usize];
+        let swapchain_image = self.swapchain_images[img_index as usize];
 
         let cmd_buf = self.execute_one_time_commands_semaphores(
             device,
@@ -393,6 +409,10 @@ impl GfaestusVk {
             in_flight_fence,
             |cmd_buf| {
                 commands(device, cmd_buf, framebuffers);
+
+                if let Some(capture) = capture {
+                    capture.record_copy(device, cmd_buf, swapchain_image);
+                }
             },
         )?;
 
@@ -9,10 +9,12 @@ use super::GfaestusVk;
 
 pub mod edges;
 pub mod node_motion;
+pub mod overlay_values;
 pub mod selection;
 
 pub use edges::*;
 pub use node_motion::*;
+pub use overlay_values::*;
 pub use selection::*;
 
 pub struct ComputeManager {
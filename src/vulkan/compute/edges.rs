@@ -385,6 +385,11 @@ impl EdgeBuffers {
             app.create_buffer(size, usage, mem_props)
         }?;
 
+        app.gpu_allocations
+            .record("Edge Compute ID Buffer", edges_by_id_size);
+        app.gpu_allocations
+            .record("Edge Compute Position Buffer", edges_pos_size);
+
         let edge_count_data: [u8; 4] = [0u8; 4];
 
         let edge_count_usage = vk::BufferUsageFlags::STORAGE_BUFFER;
@@ -0,0 +1,362 @@
+use ash::version::DeviceV1_0;
+use ash::{vk, Device};
+
+use anyhow::Result;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use crate::vulkan::{draw_system::nodes::NodeOverlayValue, GfaestusVk};
+
+use super::{ComputeManager, ComputePipeline};
+
+/// Normalizes the values of a value overlay to the 0..1 range on the
+/// GPU, in place, so the overlay buffer can be used directly with the
+/// color scheme.
+///
+/// The first pass finds the min and max values with a per-workgroup
+/// reduction, the second maps each value using that range. NaN is
+/// treated as "no value"; it's left out of the range, and kept as NaN
+/// so that those nodes are drawn in gray.
+pub struct OverlayValueNormalizer {
+    range_pipeline: ComputePipeline,
+    normalize_pipeline: vk::Pipeline,
+
+    descriptor_set: vk::DescriptorSet,
+
+    range_buffer: vk::Buffer,
+    range_memory: vk::DeviceMemory,
+    range_size: vk::DeviceSize,
+
+    node_count: usize,
+}
+
+impl OverlayValueNormalizer {
+    pub fn new(app: &GfaestusVk, node_count: usize) -> Result<Self> {
+        let device = app.vk_context().device();
+
+        let desc_set_layout = Self::create_descriptor_set_layout(device)?;
+
+        let pipeline_layout = {
+            use vk::ShaderStageFlags as Flags;
+
+            let pc_range = vk::PushConstantRange::builder()
+                .stage_flags(Flags::COMPUTE)
+                .offset(0)
+                .size(4)
+                .build();
+
+            let pc_ranges = [pc_range];
+
+            let layouts = [desc_set_layout];
+
+            let layout_info = vk::PipelineLayoutCreateInfo::builder()
+                .set_layouts(&layouts)
+                .push_constant_ranges(&pc_ranges)
+                .build();
+
+            unsafe { device.create_pipeline_layout(&layout_info, None) }
+        }?;
+
+        let range_pipeline = ComputePipeline::new(
+            device,
+            desc_set_layout,
+            pipeline_layout,
+            crate::include_shader!("compute/value_range.comp.spv"),
+        )?;
+
+        // both passes use the same descriptor set and layout
+        let normalize_pipeline = ComputePipeline::create_pipeline(
+            device,
+            pipeline_layout,
+            crate::include_shader!("compute/value_normalize.comp.spv"),
+        )?;
+
+        let descriptor_sets = {
+            let layouts = vec![desc_set_layout];
+
+            let alloc_info = vk::DescriptorSetAllocateInfo::builder()
+                .descriptor_pool(range_pipeline.descriptor_pool)
+                .set_layouts(&layouts)
+                .build();
+
+            unsafe { device.allocate_descriptor_sets(&alloc_info) }
+        }?;
+
+        let (range_buffer, range_memory, range_size) = {
+            let usage = vk::BufferUsageFlags::STORAGE_BUFFER;
+
+            let mem_props = vk::MemoryPropertyFlags::HOST_VISIBLE
+                | vk::MemoryPropertyFlags::HOST_COHERENT;
+
+            let size = (2 * std::mem::size_of::<u32>()) as vk::DeviceSize;
+
+            app.create_buffer(size, usage, mem_props)
+        }?;
+
+        app.set_debug_object_name(range_buffer, "Overlay Value Range")?;
+
+        Ok(Self {
+            range_pipeline,
+            normalize_pipeline,
+
+            descriptor_set: descriptor_sets[0],
+
+            range_buffer,
+            range_memory,
+            range_size,
+
+            node_count,
+        })
+    }
+
+    /// Normalizes the values in `overlay`, returning the fence ID of
+    /// the dispatch
+    pub fn normalize(
+        &self,
+        comp_manager: &mut ComputeManager,
+        overlay: &NodeOverlayValue,
+    ) -> Result<usize> {
+        self.reset_range()?;
+
+        self.write_descriptor_set(overlay);
+
+        let fence_id = comp_manager.dispatch_with(|_device, cmd_buf| {
+            self.normalize_cmd(cmd_buf);
+        })?;
+
+        Ok(fence_id)
+    }
+
+    /// The min and max values found by the latest normalization;
+    /// only valid after its fence has been signaled
+    pub fn latest_range(&self) -> Result<(f32, f32)> {
+        let device = &self.range_pipeline.device;
+
+        let (min_key, max_key) = unsafe {
+            let ptr = device.map_memory(
+                self.range_memory,
+                0,
+                self.range_size,
+                vk::MemoryMapFlags::empty(),
+            )?;
+
+            let key_ptr = ptr as *const u32;
+            let keys = (key_ptr.read(), key_ptr.add(1).read());
+
+            device.unmap_memory(self.range_memory);
+
+            keys
+        };
+
+        Ok((key_to_float(min_key), key_to_float(max_key)))
+    }
+
+    fn reset_range(&self) -> Result<()> {
+        let device = &self.range_pipeline.device;
+
+        unsafe {
+            let ptr = device.map_memory(
+                self.range_memory,
+                0,
+                self.range_size,
+                vk::MemoryMapFlags::empty(),
+            )?;
+
+            // the keys are combined with atomicMin and atomicMax
+            let key_ptr = ptr as *mut u32;
+            key_ptr.write(std::u32::MAX);
+            key_ptr.add(1).write(0);
+
+            device.unmap_memory(self.range_memory);
+        }
+
+        Ok(())
+    }
+
+    fn normalize_cmd(&self, cmd_buf: vk::CommandBuffer) {
+        let device = &self.range_pipeline.device;
+        let pipeline_layout = self.range_pipeline.pipeline_layout;
+
+        let pc_bytes = (self.node_count as u32).to_ne_bytes();
+
+        let x_group_count = {
+            let div = self.node_count / 256;
+            let rem = self.node_count % 256;
+
+            let mut count = div;
+            if rem > 0 {
+                count += 1;
+            }
+            count as u32
+        };
+
+        trace!(
+            "Overlay value normalization dispatch with x_group_count {}",
+            x_group_count
+        );
+
+        unsafe {
+            let desc_sets = [self.descriptor_set];
+
+            let null = [];
+            device.cmd_bind_descriptor_sets(
+                cmd_buf,
+                vk::PipelineBindPoint::COMPUTE,
+                pipeline_layout,
+                0,
+                &desc_sets[0..=0],
+                &null,
+            );
+
+            device.cmd_push_constants(
+                cmd_buf,
+                pipeline_layout,
+                vk::ShaderStageFlags::COMPUTE,
+                0,
+                &pc_bytes,
+            );
+
+            device.cmd_bind_pipeline(
+                cmd_buf,
+                vk::PipelineBindPoint::COMPUTE,
+                self.range_pipeline.pipeline,
+            );
+
+            device.cmd_dispatch(cmd_buf, x_group_count, 1, 1);
+
+            // the range must be complete before any value is normalized
+            let memory_barrier = vk::MemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::SHADER_WRITE)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .build();
+
+            let memory_barriers = [memory_barrier];
+            let buffer_barriers = [];
+            let image_barriers = [];
+
+            device.cmd_pipeline_barrier(
+                cmd_buf,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::DependencyFlags::empty(),
+                &memory_barriers,
+                &buffer_barriers,
+                &image_barriers,
+            );
+
+            device.cmd_bind_pipeline(
+                cmd_buf,
+                vk::PipelineBindPoint::COMPUTE,
+                self.normalize_pipeline,
+            );
+
+            device.cmd_dispatch(cmd_buf, x_group_count, 1, 1);
+
+            // the normalized values and the range are read back by the host
+            let host_barrier = vk::MemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::SHADER_WRITE)
+                .dst_access_mask(vk::AccessFlags::HOST_READ)
+                .build();
+
+            let host_barriers = [host_barrier];
+
+            device.cmd_pipeline_barrier(
+                cmd_buf,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::HOST,
+                vk::DependencyFlags::empty(),
+                &host_barriers,
+                &buffer_barriers,
+                &image_barriers,
+            );
+        }
+    }
+
+    fn write_descriptor_set(&self, overlay: &NodeOverlayValue) {
+        let values_buf_info = vk::DescriptorBufferInfo::builder()
+            .buffer(overlay.buffer())
+            .offset(0)
+            .range(vk::WHOLE_SIZE)
+            .build();
+
+        let values_buf_infos = [values_buf_info];
+
+        let values_write = vk::WriteDescriptorSet::builder()
+            .dst_set(self.descriptor_set)
+            .dst_binding(0)
+            .dst_array_element(0)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .buffer_info(&values_buf_infos)
+            .build();
+
+        let range_buf_info = vk::DescriptorBufferInfo::builder()
+            .buffer(self.range_buffer)
+            .offset(0)
+            .range(vk::WHOLE_SIZE)
+            .build();
+
+        let range_buf_infos = [range_buf_info];
+
+        let range_write = vk::WriteDescriptorSet::builder()
+            .dst_set(self.descriptor_set)
+            .dst_binding(1)
+            .dst_array_element(0)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .buffer_info(&range_buf_infos)
+            .build();
+
+        let desc_writes = [values_write, range_write];
+
+        unsafe {
+            self.range_pipeline
+                .device
+                .update_descriptor_sets(&desc_writes, &[])
+        };
+    }
+
+    fn layout_binding() -> [vk::DescriptorSetLayoutBinding; 2] {
+        use vk::ShaderStageFlags as Stages;
+
+        let values = vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(Stages::COMPUTE)
+            .build();
+
+        let range = vk::DescriptorSetLayoutBinding::builder()
+            .binding(1)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(Stages::COMPUTE)
+            .build();
+
+        [values, range]
+    }
+
+    fn create_descriptor_set_layout(
+        device: &Device,
+    ) -> Result<vk::DescriptorSetLayout> {
+        let bindings = Self::layout_binding();
+
+        let layout_info = vk::DescriptorSetLayoutCreateInfo::builder()
+            .bindings(&bindings)
+            .build();
+
+        let layout =
+            unsafe { device.create_descriptor_set_layout(&layout_info, None) }?;
+
+        Ok(layout)
+    }
+}
+
+/// Inverse of the order-preserving float to uint mapping used by the
+/// range shader
+fn key_to_float(key: u32) -> f32 {
+    if key & 0x8000_0000 != 0 {
+        f32::from_bits(key & 0x7FFF_FFFF)
+    } else {
+        f32::from_bits(!key)
+    }
+}
@@ -5,8 +5,9 @@ use crate::{
 };
 
 use handlegraph::{
-    handle::{Edge, NodeId},
+    handle::{Edge, Handle, NodeId},
     handlegraph::*,
+    pathhandlegraph::*,
 };
 
 use handlegraph::packedgraph::PackedGraph;
@@ -17,6 +18,7 @@ use ash::{vk, Device};
 use anyhow::Result;
 
 use nalgebra_glm as glm;
+use rustc_hash::FxHashMap;
 
 use std::ffi::CString;
 
@@ -43,13 +45,22 @@ pub struct EdgeRenderer {
     pub(crate) edge_index_buffer: EdgeIndices,
 
     wide_lines: bool,
+
+    path_descriptor_pool: vk::DescriptorPool,
+    path_descriptor_set_layout: vk::DescriptorSetLayout,
+    path_descriptor_set: vk::DescriptorSet,
+
+    path_pipeline_layout: vk::PipelineLayout,
+    path_pipeline: vk::Pipeline,
+
+    path_edges: Option<PathEdges>,
 }
 
 impl EdgeRenderer {
-    fn layout_binding() -> vk::DescriptorSetLayoutBinding {
+    fn layout_binding() -> [vk::DescriptorSetLayoutBinding; 2] {
         use vk::ShaderStageFlags as Stages;
 
-        vk::DescriptorSetLayoutBinding::builder()
+        let ubo = vk::DescriptorSetLayoutBinding::builder()
             .binding(0)
             .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
             .descriptor_count(1)
@@ -59,14 +70,55 @@ impl EdgeRenderer {
                     | Stages::TESSELLATION_EVALUATION
                     | Stages::FRAGMENT,
             )
-            .build()
+            .build();
+
+        let curves = vk::DescriptorSetLayoutBinding::builder()
+            .binding(1)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(
+                Stages::TESSELLATION_CONTROL | Stages::TESSELLATION_EVALUATION,
+            )
+            .build();
+
+        [ubo, curves]
     }
 
     fn create_descriptor_set_layout(
         device: &Device,
     ) -> Result<vk::DescriptorSetLayout> {
-        let binding = Self::layout_binding();
-        let bindings = [binding];
+        let bindings = Self::layout_binding();
+
+        let layout_info = vk::DescriptorSetLayoutCreateInfo::builder()
+            .bindings(&bindings)
+            .build();
+
+        let layout =
+            unsafe { device.create_descriptor_set_layout(&layout_info, None) }?;
+
+        Ok(layout)
+    }
+
+    /// The same bindings as the other edges, plus the edge colors
+    fn path_layout_binding() -> [vk::DescriptorSetLayoutBinding; 3] {
+        use vk::ShaderStageFlags as Stages;
+
+        let [ubo, curves] = Self::layout_binding();
+
+        let colors = vk::DescriptorSetLayoutBinding::builder()
+            .binding(2)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(Stages::TESSELLATION_EVALUATION)
+            .build();
+
+        [ubo, curves, colors]
+    }
+
+    fn create_path_descriptor_set_layout(
+        device: &Device,
+    ) -> Result<vk::DescriptorSetLayout> {
+        let bindings = Self::path_layout_binding();
 
         let layout_info = vk::DescriptorSetLayoutCreateInfo::builder()
             .bindings(&bindings)
@@ -103,6 +155,33 @@ impl EdgeRenderer {
         )
     }
 
+    /// Path edges are always drawn as isolines, as they're only a
+    /// small part of the graph
+    fn create_path_pipeline(
+        device: &Device,
+        msaa_samples: vk::SampleCountFlags,
+        render_pass: vk::RenderPass,
+        layouts: &[vk::DescriptorSetLayout],
+        wide_lines: bool,
+    ) -> (vk::Pipeline, vk::PipelineLayout) {
+        let vert_src = crate::load_shader!("edges/edges.vert.spv");
+        let tesc_src = crate::load_shader!("edges/edges.tesc.spv");
+        let tese_src = crate::load_shader!("edges/path_edges.tese.spv");
+        let frag_src = crate::load_shader!("edges/path_edges.frag.spv");
+
+        Self::create_pipeline(
+            device,
+            msaa_samples,
+            render_pass,
+            layouts,
+            wide_lines,
+            &vert_src,
+            &tesc_src,
+            &tese_src,
+            &frag_src,
+        )
+    }
+
     fn create_quad_pipeline(
         device: &Device,
         msaa_samples: vk::SampleCountFlags,
@@ -265,7 +344,7 @@ impl EdgeRenderer {
                         | Flags::FRAGMENT,
                 )
                 .offset(0)
-                .size(84)
+                .size(96)
                 .build();
 
             let pc_ranges = [pc_range];
@@ -333,15 +412,23 @@ impl EdgeRenderer {
 
         let desc_set_layout = Self::create_descriptor_set_layout(device)?;
 
+        let edge_index_buffer =
+            EdgeIndices::new_with_components(app, graph, layout)?;
+
         let image_count = 1;
 
         let descriptor_pool = {
-            let pool_size = vk::DescriptorPoolSize {
+            let ubo_size = vk::DescriptorPoolSize {
                 ty: vk::DescriptorType::UNIFORM_BUFFER,
                 descriptor_count: image_count,
             };
 
-            let pool_sizes = [pool_size];
+            let curves_size = vk::DescriptorPoolSize {
+                ty: vk::DescriptorType::STORAGE_BUFFER,
+                descriptor_count: image_count,
+            };
+
+            let pool_sizes = [ubo_size, curves_size];
 
             let pool_info = vk::DescriptorPoolCreateInfo::builder()
                 .pool_sizes(&pool_sizes)
@@ -379,7 +466,23 @@ impl EdgeRenderer {
                 .buffer_info(&buf_infos)
                 .build();
 
-            let descriptor_writes = [descriptor_write];
+            let curves_info = vk::DescriptorBufferInfo::builder()
+                .buffer(edge_index_buffer.curves_buffer)
+                .offset(0)
+                .range(vk::WHOLE_SIZE)
+                .build();
+
+            let curves_infos = [curves_info];
+
+            let curves_write = vk::WriteDescriptorSet::builder()
+                .dst_set(*set)
+                .dst_binding(1)
+                .dst_array_element(0)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .buffer_info(&curves_infos)
+                .build();
+
+            let descriptor_writes = [descriptor_write, curves_write];
 
             unsafe { device.update_descriptor_sets(&descriptor_writes, &[]) }
         }
@@ -413,8 +516,48 @@ impl EdgeRenderer {
             )
         };
 
-        let edge_index_buffer =
-            EdgeIndices::new_with_components(app, graph, layout)?;
+        let path_desc_set_layout =
+            Self::create_path_descriptor_set_layout(device)?;
+
+        let path_descriptor_pool = {
+            let ubo_size = vk::DescriptorPoolSize {
+                ty: vk::DescriptorType::UNIFORM_BUFFER,
+                descriptor_count: 1,
+            };
+
+            let storage_size = vk::DescriptorPoolSize {
+                ty: vk::DescriptorType::STORAGE_BUFFER,
+                descriptor_count: 2,
+            };
+
+            let pool_sizes = [ubo_size, storage_size];
+
+            let pool_info = vk::DescriptorPoolCreateInfo::builder()
+                .pool_sizes(&pool_sizes)
+                .max_sets(1)
+                .build();
+
+            unsafe { device.create_descriptor_pool(&pool_info, None) }
+        }?;
+
+        let path_descriptor_sets = {
+            let layouts = vec![path_desc_set_layout];
+
+            let alloc_info = vk::DescriptorSetAllocateInfo::builder()
+                .descriptor_pool(path_descriptor_pool)
+                .set_layouts(&layouts)
+                .build();
+
+            unsafe { device.allocate_descriptor_sets(&alloc_info) }
+        }?;
+
+        let (path_pipeline, path_pipeline_layout) = Self::create_path_pipeline(
+            device,
+            msaa_samples,
+            render_pass,
+            &[path_desc_set_layout],
+            wide_lines,
+        );
 
         Ok(Self {
             descriptor_pool,
@@ -430,9 +573,99 @@ impl EdgeRenderer {
             device: device.clone(),
 
             wide_lines,
+
+            path_descriptor_pool,
+            path_descriptor_set_layout: path_desc_set_layout,
+            path_descriptor_set: path_descriptor_sets[0],
+
+            path_pipeline_layout,
+            path_pipeline,
+
+            path_edges: None,
         })
     }
 
+    /// The path whose edges are drawn in the order of its steps, if
+    /// any
+    pub fn path_edges(&self) -> Option<PathId> {
+        self.path_edges.as_ref().map(|edges| edges.path)
+    }
+
+    /// Draws the edges between the consecutive steps of `path` on
+    /// top of the other edges, colored by `color_at` from 0.0 at the
+    /// first edge to 1.0 at the last, or stops drawing them if `path`
+    /// is `None`.
+    ///
+    /// The GPU must be idle, as the previous path edge buffers are
+    /// freed.
+    pub fn set_path_edges<F>(
+        &mut self,
+        app: &GfaestusVk,
+        graph: &PackedGraph,
+        path: Option<PathId>,
+        color_at: F,
+    ) -> Result<()>
+    where
+        F: Fn(f32) -> rgb::RGB<f32>,
+    {
+        if let Some(mut path_edges) = self.path_edges.take() {
+            path_edges.destroy(app)?;
+        }
+
+        if let Some(path) = path {
+            let path_edges = PathEdges::new(app, graph, path, color_at)?;
+            self.write_path_descriptor_set(&path_edges);
+            self.path_edges = Some(path_edges);
+        }
+
+        Ok(())
+    }
+
+    fn write_path_descriptor_set(&self, path_edges: &PathEdges) {
+        let buffer_write = |binding: u32, ty, buffer| {
+            let buf_info = vk::DescriptorBufferInfo::builder()
+                .buffer(buffer)
+                .offset(0)
+                .range(vk::WHOLE_SIZE)
+                .build();
+
+            (binding, ty, [buf_info])
+        };
+
+        let buffers = [
+            buffer_write(
+                0,
+                vk::DescriptorType::UNIFORM_BUFFER,
+                self.ubo.buffer,
+            ),
+            buffer_write(
+                1,
+                vk::DescriptorType::STORAGE_BUFFER,
+                path_edges.curves_buffer,
+            ),
+            buffer_write(
+                2,
+                vk::DescriptorType::STORAGE_BUFFER,
+                path_edges.colors_buffer,
+            ),
+        ];
+
+        let descriptor_writes = buffers
+            .iter()
+            .map(|(binding, ty, buf_infos)| {
+                vk::WriteDescriptorSet::builder()
+                    .dst_set(self.path_descriptor_set)
+                    .dst_binding(*binding)
+                    .dst_array_element(0)
+                    .descriptor_type(*ty)
+                    .buffer_info(buf_infos)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        unsafe { self.device.update_descriptor_sets(&descriptor_writes, &[]) }
+    }
+
     pub fn destroy(&mut self) {
         unsafe {
             self.device.destroy_descriptor_set_layout(
@@ -446,6 +679,18 @@ impl EdgeRenderer {
 
             self.device
                 .destroy_descriptor_pool(self.descriptor_pool, None);
+
+            self.device.destroy_descriptor_set_layout(
+                self.path_descriptor_set_layout,
+                None,
+            );
+
+            self.device
+                .destroy_pipeline_layout(self.path_pipeline_layout, None);
+            self.device.destroy_pipeline(self.path_pipeline, None);
+
+            self.device
+                .destroy_descriptor_pool(self.path_descriptor_pool, None);
         }
     }
 
@@ -570,6 +815,63 @@ impl EdgeRenderer {
             )
         };
 
+        if let Some(path_edges) = &self.path_edges {
+            unsafe {
+                use vk::ShaderStageFlags as Flags;
+
+                if self.wide_lines {
+                    device.cmd_set_line_width(
+                        cmd_buf,
+                        edge_width * PathEdges::WIDTH_SCALE,
+                    );
+                }
+
+                device.cmd_bind_pipeline(
+                    cmd_buf,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    self.path_pipeline,
+                );
+
+                device.cmd_bind_index_buffer(
+                    cmd_buf,
+                    path_edges.buffer,
+                    0,
+                    vk::IndexType::UINT32,
+                );
+
+                let path_desc_sets = [self.path_descriptor_set];
+
+                device.cmd_bind_descriptor_sets(
+                    cmd_buf,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    self.path_pipeline_layout,
+                    0,
+                    &path_desc_sets,
+                    &[],
+                );
+
+                device.cmd_push_constants(
+                    cmd_buf,
+                    self.path_pipeline_layout,
+                    Flags::VERTEX
+                        | Flags::TESSELLATION_CONTROL
+                        | Flags::TESSELLATION_EVALUATION
+                        | Flags::FRAGMENT,
+                    0,
+                    &pc_bytes,
+                );
+
+                device.cmd_draw_indexed(
+                    cmd_buf,
+                    (path_edges.edge_count * 2) as u32,
+                    1,
+                    0,
+                    0,
+                    0,
+                );
+            }
+        }
+
         // End render pass
         unsafe { device.cmd_end_render_pass(cmd_buf) };
 
@@ -584,10 +886,21 @@ pub struct EdgeIndices {
     allocation: vk_mem::Allocation,
     allocation_info: vk_mem::AllocationInfo,
 
+    /// One `[curve multiplier, loop size]` pair per edge, used by the
+    /// tessellation shaders to fan out parallel edges and to draw
+    /// self-loops
+    curves_buffer: vk::Buffer,
+    curves_allocation: vk_mem::Allocation,
+    curves_allocation_info: vk_mem::AllocationInfo,
+
     edge_count: usize,
 }
 
 impl EdgeIndices {
+    /// The curve multiplier for edges connecting the two ends of a
+    /// single node
+    const NODE_LOOP_CURVE: f32 = 3.0;
+
     fn new_with_components(
         app: &GfaestusVk,
         graph: &PackedGraph,
@@ -595,6 +908,15 @@ impl EdgeIndices {
     ) -> Result<Self> {
         let mut edge_count = 0;
         let mut edges: Vec<u32> = Vec::with_capacity(graph.edge_count() * 2);
+        let mut curves: Vec<[f32; 2]> = Vec::with_capacity(graph.edge_count());
+
+        // the number of edges seen so far between each pair of nodes,
+        // and whether the first of them went from the lower node ID
+        let mut node_pairs: FxHashMap<(u64, u64), (usize, bool)> =
+            FxHashMap::default();
+
+        // the number of self-loops seen so far on each endpoint
+        let mut endpoint_loops: FxHashMap<u64, usize> = FxHashMap::default();
 
         for Edge(left, right) in graph.edges() {
             let left_comp = layout.node_component(left.id());
@@ -604,23 +926,48 @@ impl EdgeIndices {
                 continue;
             }
 
-            let left_l = (left.id().0 - 1) * 2;
-            let left_r = left_l + 1;
-
-            let right_l = (right.id().0 - 1) * 2;
-            let right_r = right_l + 1;
-
-            let (left_ix, right_ix) =
-                match (left.is_reverse(), right.is_reverse()) {
-                    (false, false) => (left_r, right_l),
-                    (true, false) => (left_l, right_l),
-                    (false, true) => (left_r, right_r),
-                    (true, true) => (left_l, right_r),
-                };
+            let (left_ix, right_ix) = edge_vertex_indices(left, right);
 
             edges.push(left_ix as u32);
             edges.push(right_ix as u32);
             edge_count += 1;
+
+            if left_ix == right_ix {
+                // both ends of the edge are at the same point, so it's
+                // drawn as a loop instead, growing with each loop
+                let count = endpoint_loops.entry(left_ix).or_default();
+                *count += 1;
+                curves.push([0.0, *count as f32]);
+                continue;
+            }
+
+            let (left_id, right_id) = (left.id().0, right.id().0);
+            let forward = left_id <= right_id;
+
+            let key = (left_id.min(right_id), left_id.max(right_id));
+            let (count, first_forward) =
+                node_pairs.entry(key).or_insert((0, forward));
+
+            // the edges between two nodes alternate between the two
+            // sides of the line between the endpoints, moving further
+            // out: 1, -1, 2, -2, ...
+            let step = (*count / 2 + 1) as f32;
+            let mut multiplier = if *count % 2 == 0 { step } else { -step };
+            *count += 1;
+
+            // the side is relative to the edge direction, so edges
+            // going the other way need to be flipped to fan out
+            if forward != *first_forward {
+                multiplier = -multiplier;
+            }
+
+            // an edge between the two ends of the same node would
+            // otherwise be drawn on top of the node
+            if left_id == right_id {
+                multiplier *= Self::NODE_LOOP_CURVE;
+            }
+
+            curves.push([multiplier, 0.0]);
         }
 
         let usage = vk::BufferUsageFlags::TRANSFER_DST
@@ -637,15 +984,199 @@ impl EdgeIndices {
             )?;
 
         app.set_debug_object_name(buffer, "Edge Indices Buffer")?;
+        app.gpu_allocations.record(
+            "Edge Indices Buffer",
+            (edges.len() * std::mem::size_of::<u32>()) as vk::DeviceSize,
+        );
+
+        // the buffer can't be empty, even if there are no edges
+        if curves.is_empty() {
+            curves.push([0.0, 0.0]);
+        }
+
+        let (curves_buffer, curves_allocation, curves_allocation_info) = app
+            .create_buffer_with_data::<f32, _>(
+            vk::BufferUsageFlags::TRANSFER_DST
+                | vk::BufferUsageFlags::STORAGE_BUFFER,
+            memory_usage,
+            false,
+            &curves,
+        )?;
+
+        app.set_debug_object_name(curves_buffer, "Edge Curves Buffer")?;
+        app.gpu_allocations.record(
+            "Edge Curves Buffer",
+            (curves.len() * std::mem::size_of::<[f32; 2]>()) as vk::DeviceSize,
+        );
 
         Ok(Self {
             buffer,
             allocation,
             allocation_info,
 
+            curves_buffer,
+            curves_allocation,
+            curves_allocation_info,
+
+            edge_count,
+        })
+    }
+}
+
+/// The indices into the node vertex buffer of the endpoints of the
+/// edge from `left` to `right`
+fn edge_vertex_indices(left: Handle, right: Handle) -> (u64, u64) {
+    let left_l = (left.id().0 - 1) * 2;
+    let left_r = left_l + 1;
+
+    let right_l = (right.id().0 - 1) * 2;
+    let right_r = right_l + 1;
+
+    match (left.is_reverse(), right.is_reverse()) {
+        (false, false) => (left_r, right_l),
+        (true, false) => (left_l, right_l),
+        (false, true) => (left_r, right_r),
+        (true, true) => (left_l, right_r),
+    }
+}
+
+/// The edges between the consecutive steps of a path, in path order,
+/// each with its own color
+pub struct PathEdges {
+    path: PathId,
+
+    buffer: vk::Buffer,
+    allocation: vk_mem::Allocation,
+
+    /// As in `EdgeIndices`, though the edges are only curved when
+    /// they're self-loops
+    curves_buffer: vk::Buffer,
+    curves_allocation: vk_mem::Allocation,
+
+    colors_buffer: vk::Buffer,
+    colors_allocation: vk_mem::Allocation,
+
+    edge_count: usize,
+}
+
+impl PathEdges {
+    /// Path edges are drawn wider than the other edges, so they
+    /// stand out
+    const WIDTH_SCALE: f32 = 2.0;
+
+    fn new<F>(
+        app: &GfaestusVk,
+        graph: &PackedGraph,
+        path: PathId,
+        color_at: F,
+    ) -> Result<Self>
+    where
+        F: Fn(f32) -> rgb::RGB<f32>,
+    {
+        let steps = graph
+            .path_steps(path)
+            .ok_or_else(|| anyhow::anyhow!("Path {} not found", path.0))?;
+
+        let handles = steps.map(|step| step.handle()).collect::<Vec<_>>();
+
+        let edge_count = handles.len().saturating_sub(1);
+        let last_ix = edge_count.saturating_sub(1).max(1) as f32;
+
+        let mut edges: Vec<u32> = Vec::with_capacity(edge_count * 2);
+        let mut curves: Vec<[f32; 2]> = Vec::with_capacity(edge_count);
+        let mut colors: Vec<[f32; 4]> = Vec::with_capacity(edge_count);
+
+        for (ix, pair) in handles.windows(2).enumerate() {
+            let (left_ix, right_ix) = edge_vertex_indices(pair[0], pair[1]);
+
+            edges.push(left_ix as u32);
+            edges.push(right_ix as u32);
+
+            if left_ix == right_ix {
+                curves.push([0.0, 1.0]);
+            } else {
+                curves.push([0.0, 0.0]);
+            }
+
+            let color = color_at(ix as f32 / last_ix);
+            colors.push([color.r, color.g, color.b, 1.0]);
+        }
+
+        // the buffers can't be empty, even if the path has no edges
+        if edge_count == 0 {
+            edges.extend([0, 0].iter());
+            curves.push([0.0, 0.0]);
+            colors.push([0.0, 0.0, 0.0, 0.0]);
+        }
+
+        let memory_usage = vk_mem::MemoryUsage::GpuOnly;
+        let storage_usage = vk::BufferUsageFlags::TRANSFER_DST
+            | vk::BufferUsageFlags::STORAGE_BUFFER;
+
+        let (buffer, allocation, _) = app.create_buffer_with_data::<u32, _>(
+            vk::BufferUsageFlags::TRANSFER_DST
+                | vk::BufferUsageFlags::INDEX_BUFFER,
+            memory_usage,
+            false,
+            &edges,
+        )?;
+
+        let (curves_buffer, curves_allocation, _) = app
+            .create_buffer_with_data::<f32, _>(
+                storage_usage,
+                memory_usage,
+                false,
+                &curves,
+            )?;
+
+        let (colors_buffer, colors_allocation, _) = app
+            .create_buffer_with_data::<f32, _>(
+                storage_usage,
+                memory_usage,
+                false,
+                &colors,
+            )?;
+
+        app.set_debug_object_name(buffer, "Path Edge Indices Buffer")?;
+        app.set_debug_object_name(curves_buffer, "Path Edge Curves Buffer")?;
+        app.set_debug_object_name(colors_buffer, "Path Edge Colors Buffer")?;
+
+        app.gpu_allocations.record(
+            "Path Edge Buffers",
+            (edges.len() * std::mem::size_of::<u32>()
+                + curves.len() * std::mem::size_of::<[f32; 2]>()
+                + colors.len() * std::mem::size_of::<[f32; 4]>())
+                as vk::DeviceSize,
+        );
+
+        Ok(Self {
+            path,
+
+            buffer,
+            allocation,
+
+            curves_buffer,
+            curves_allocation,
+
+            colors_buffer,
+            colors_allocation,
+
             edge_count,
         })
     }
+
+    fn destroy(&mut self, app: &GfaestusVk) -> Result<()> {
+        app.allocator
+            .destroy_buffer(self.buffer, &self.allocation)?;
+        app.allocator
+            .destroy_buffer(self.curves_buffer, &self.curves_allocation)?;
+        app.allocator
+            .destroy_buffer(self.colors_buffer, &self.colors_allocation)?;
+
+        app.gpu_allocations.remove("Path Edge Buffers");
+
+        Ok(())
+    }
 }
 
 pub struct PreprocessPushConstants {
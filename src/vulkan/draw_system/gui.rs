@@ -304,6 +304,9 @@ impl GuiPipeline {
             pixels,
         )?;
 
+        app.gpu_allocations
+            .record("GUI Texture", pixels.len() as u64);
+
         self.texture = texture;
         self.texture_version = version;
 
@@ -664,6 +667,16 @@ impl GuiVertices {
         app.set_debug_object_name(vx_buf, "GUI Vertex Buffer")?;
         app.set_debug_object_name(ix_buf, "GUI Index Buffer")?;
 
+        // the GUI meshes are uploaded every frame
+        app.gpu_allocations.record_transient(
+            "GUI Vertex Buffer",
+            (vertices.len() * std::mem::size_of::<GuiVertex>()) as u64,
+        );
+        app.gpu_allocations.record_transient(
+            "GUI Index Buffer",
+            (indices.len() * std::mem::size_of::<u32>()) as u64,
+        );
+
         self.vertex_buffer = vx_buf;
         self.vertex_alloc = vx_alloc;
         self.vertex_alloc_info = Some(vx_alloc_info);
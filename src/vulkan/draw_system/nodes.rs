@@ -12,7 +12,7 @@ use nalgebra_glm as glm;
 
 use anyhow::*;
 
-use crate::app::AppSettings;
+use crate::app::{AppSettings, OverlayLayer, OverlayScope};
 use crate::view::View;
 use crate::vulkan::GfaestusVk;
 use crate::{
@@ -155,6 +155,31 @@ impl NodePipelines {
             )
         };
 
+        let push_constants = NodePushConstants::new(
+            [offset.x, offset.y],
+            viewport_dims,
+            view,
+            node_width,
+            7,
+        );
+
+        self.draw_theme_nodes(cmd_buf, &push_constants);
+
+        // End render pass
+        unsafe { device.cmd_end_render_pass(cmd_buf) };
+
+        Ok(())
+    }
+
+    /// Records the commands drawing the nodes using the active theme;
+    /// must be called inside the node render pass
+    fn draw_theme_nodes(
+        &self,
+        cmd_buf: vk::CommandBuffer,
+        push_constants: &NodePushConstants,
+    ) {
+        let device = &self.theme_pipeline.device;
+
         unsafe {
             device.cmd_bind_pipeline(
                 cmd_buf,
@@ -184,14 +209,6 @@ impl NodePipelines {
             );
         };
 
-        let push_constants = NodePushConstants::new(
-            [offset.x, offset.y],
-            viewport_dims,
-            view,
-            node_width,
-            7,
-        );
-
         let pc_bytes = push_constants.bytes();
 
         unsafe {
@@ -211,6 +228,89 @@ impl NodePipelines {
         unsafe {
             device.cmd_draw(cmd_buf, self.vertices.vertex_count as u32, 1, 0, 0)
         };
+    }
+
+    pub fn draw_overlay_new(
+        &mut self,
+        cmd_buf: vk::CommandBuffer,
+        render_pass: vk::RenderPass,
+        framebuffers: &Framebuffers,
+        viewport_dims: [f32; 2],
+        node_width: f32,
+        view: View,
+        offset: Point,
+        background_color: rgb::RGB<f32>,
+        overlay: (usize, OverlayKind),
+        color_scheme: &GradientTexture,
+        scope: OverlayScope,
+        unselected_dim: f32,
+    ) -> Result<()> {
+        self.overlay_pipelines
+            .write_overlay(overlay, color_scheme)?;
+
+        let device = &self.overlay_pipeline.device;
+
+        let clear_values = {
+            let bg = background_color;
+            [
+                vk::ClearValue {
+                    color: vk::ClearColorValue {
+                        float32: [bg.r, bg.g, bg.b, 1.0],
+                    },
+                },
+                vk::ClearValue {
+                    color: vk::ClearColorValue {
+                        uint32: [0, 0, 0, 0],
+                    },
+                },
+                vk::ClearValue {
+                    color: vk::ClearColorValue {
+                        float32: [0.0, 0.0, 0.0, 1.0],
+                    },
+                },
+            ]
+        };
+
+        let extent = vk::Extent2D {
+            width: viewport_dims[0] as u32,
+            height: viewport_dims[1] as u32,
+        };
+
+        let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
+            .render_pass(render_pass)
+            .framebuffer(framebuffers.nodes)
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent,
+            })
+            .clear_values(&clear_values)
+            .build();
+
+        unsafe {
+            device.cmd_begin_render_pass(
+                cmd_buf,
+                &render_pass_begin_info,
+                vk::SubpassContents::INLINE,
+            )
+        };
+
+        let mut push_constants = NodePushConstants::new(
+            [offset.x, offset.y],
+            viewport_dims,
+            view,
+            node_width,
+            7,
+        );
+
+        // when scoped to the selection, all nodes are first drawn
+        // using the theme, and the overlay pipeline then only draws
+        // over the selected nodes
+        if scope == OverlayScope::Selection {
+            push_constants.scope_to_selection(unselected_dim);
+            self.draw_theme_nodes(cmd_buf, &push_constants);
+        }
+
+        self.draw_overlay_nodes(cmd_buf, overlay, None, &push_constants)?;
 
         // End render pass
         unsafe { device.cmd_end_render_pass(cmd_buf) };
@@ -218,7 +318,14 @@ impl NodePipelines {
         Ok(())
     }
 
-    pub fn draw_overlay_new(
+    /// Draws the nodes using the theme, and then each visible layer
+    /// of the overlay stack on top, from the bottom layer up, blended
+    /// using the layer opacity
+    ///
+    /// `layers` is ordered from the top layer to the bottom; at most
+    /// `OverlayPipelines::MAX_STACK_LAYERS` layers of each overlay
+    /// kind are drawn, counting from the top
+    pub fn draw_overlay_stack(
         &mut self,
         cmd_buf: vk::CommandBuffer,
         render_pass: vk::RenderPass,
@@ -228,11 +335,41 @@ impl NodePipelines {
         view: View,
         offset: Point,
         background_color: rgb::RGB<f32>,
-        overlay: (usize, OverlayKind),
+        layers: &[OverlayLayer],
         color_scheme: &GradientTexture,
+        scope: OverlayScope,
+        unselected_dim: f32,
     ) -> Result<()> {
-        self.overlay_pipelines
-            .write_overlay(overlay, color_scheme)?;
+        // each drawn layer needs its own descriptor set, as they're
+        // all used by the same command buffer
+        let mut rgb_slots = 0;
+        let mut value_slots = 0;
+
+        let mut stack: Vec<(OverlayLayer, usize)> = Vec::new();
+
+        for layer in layers {
+            if !layer.visible || layer.opacity <= 0.0 {
+                continue;
+            }
+
+            let slots = match layer.overlay.1 {
+                OverlayKind::RGB => &mut rgb_slots,
+                OverlayKind::Value => &mut value_slots,
+            };
+
+            if *slots >= OverlayPipelines::MAX_STACK_LAYERS {
+                continue;
+            }
+
+            if self.overlay_pipelines.write_stack_layer(
+                *slots,
+                layer.overlay,
+                color_scheme,
+            )? {
+                stack.push((*layer, *slots));
+                *slots += 1;
+            }
+        }
 
         let device = &self.overlay_pipeline.device;
 
@@ -280,6 +417,48 @@ impl NodePipelines {
             )
         };
 
+        let mut push_constants = NodePushConstants::new(
+            [offset.x, offset.y],
+            viewport_dims,
+            view,
+            node_width,
+            7,
+        );
+
+        if scope == OverlayScope::Selection {
+            push_constants.scope_to_selection(unselected_dim);
+        }
+
+        self.draw_theme_nodes(cmd_buf, &push_constants);
+
+        for (layer, slot) in stack.into_iter().rev() {
+            push_constants.set_overlay_opacity(layer.opacity);
+
+            self.draw_overlay_nodes(
+                cmd_buf,
+                layer.overlay,
+                Some(slot),
+                &push_constants,
+            )?;
+        }
+
+        // End render pass
+        unsafe { device.cmd_end_render_pass(cmd_buf) };
+
+        Ok(())
+    }
+
+    /// Records the commands drawing the nodes using an overlay; must
+    /// be called inside the node render pass
+    fn draw_overlay_nodes(
+        &self,
+        cmd_buf: vk::CommandBuffer,
+        overlay: (usize, OverlayKind),
+        stack_slot: Option<usize>,
+        push_constants: &NodePushConstants,
+    ) -> Result<()> {
+        let device = &self.overlay_pipeline.device;
+
         self.overlay_pipelines
             .bind_pipeline(device, cmd_buf, overlay.1);
 
@@ -294,17 +473,10 @@ impl NodePipelines {
             device,
             cmd_buf,
             overlay,
+            stack_slot,
             self.selection_descriptors.descriptor_set,
         )?;
 
-        let push_constants = NodePushConstants::new(
-            [offset.x, offset.y],
-            viewport_dims,
-            view,
-            node_width,
-            7,
-        );
-
         let pc_bytes = push_constants.bytes();
 
         let layout = self.overlay_pipelines.pipeline_layout_kind(overlay.1);
@@ -328,9 +500,6 @@ impl NodePipelines {
             device.cmd_draw(cmd_buf, self.vertices.vertex_count as u32, 1, 0, 0)
         };
 
-        // End render pass
-        unsafe { device.cmd_end_render_pass(cmd_buf) };
-
         Ok(())
     }
 
@@ -728,6 +897,7 @@ impl NodeIdBuffer {
             app.create_buffer(img_size, usage, mem_props)?;
 
         app.set_debug_object_name(buffer, "Node ID Buffer")?;
+        app.gpu_allocations.record("Node ID Buffer", img_size);
 
         Ok(Self {
             buffer,
@@ -777,6 +947,7 @@ impl NodeIdBuffer {
             app.create_buffer(img_size, usage, mem_props)?;
 
         app.set_debug_object_name(buffer, "Node ID Buffer")?;
+        app.gpu_allocations.record("Node ID Buffer", img_size);
 
         self.buffer = buffer;
         self.memory = memory;
@@ -859,6 +1030,10 @@ impl NodeVertices {
             )?;
 
         app.set_debug_object_name(buffer, "Node Vertex Buffer")?;
+        app.gpu_allocations.record(
+            "Node Vertex Buffer",
+            (vertices.len() * std::mem::size_of::<Vertex>()) as vk::DeviceSize,
+        );
 
         self.vertex_count = vertices.len();
 
@@ -869,6 +1044,69 @@ impl NodeVertices {
         Ok(())
     }
 
+    /// Overwrites the contents of the existing vertex buffer, so that
+    /// descriptor sets referring to it stay valid; `vertices` must
+    /// have the same length as the uploaded vertices
+    pub fn update_vertices(
+        &self,
+        app: &super::super::GfaestusVk,
+        vertices: &[Vertex],
+    ) -> Result<()> {
+        if !self.has_vertices() || vertices.len() != self.vertex_count {
+            anyhow::bail!(
+                "Can't update node vertex buffer with {} vertices, expected {}",
+                vertices.len(),
+                self.vertex_count
+            );
+        }
+
+        let size = (vertices.len() * std::mem::size_of::<Vertex>()) as u64;
+
+        let staging_buffer_info = vk::BufferCreateInfo::builder()
+            .size(size)
+            .usage(vk::BufferUsageFlags::TRANSFER_SRC)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .build();
+
+        let staging_create_info = vk_mem::AllocationCreateInfo {
+            usage: vk_mem::MemoryUsage::CpuToGpu,
+            flags: vk_mem::AllocationCreateFlags::MAPPED,
+            ..Default::default()
+        };
+
+        let (staging_buf, staging_alloc, staging_alloc_info) = app
+            .allocator
+            .create_buffer(&staging_buffer_info, &staging_create_info)?;
+
+        app.set_debug_object_name(
+            staging_buf,
+            "Node Position Upload Staging Buffer",
+        )?;
+
+        unsafe {
+            let mapped_ptr = staging_alloc_info.get_mapped_data();
+
+            let val_ptr = mapped_ptr as *mut Vertex;
+
+            let slice = std::slice::from_raw_parts_mut(val_ptr, vertices.len());
+
+            slice.copy_from_slice(vertices);
+        }
+
+        GfaestusVk::copy_buffer(
+            app.vk_context().device(),
+            app.transient_command_pool,
+            app.graphics_queue,
+            staging_buf,
+            self.buffer(),
+            size,
+        );
+
+        app.allocator.destroy_buffer(staging_buf, &staging_alloc)?;
+
+        Ok(())
+    }
+
     pub fn download_vertices(
         &self,
         app: &super::super::GfaestusVk,
@@ -937,6 +1175,9 @@ pub struct NodePushConstants {
     scale: f32,
     viewport_dims: [f32; 2],
     texture_period: u32,
+    selection_only: u32,
+    unselected_dim: f32,
+    overlay_opacity: f32,
 }
 
 impl NodePushConstants {
@@ -970,14 +1211,31 @@ impl NodePushConstants {
             viewport_dims,
             scale: view.scale,
             texture_period,
+            selection_only: 0,
+            unselected_dim: 0.0,
+            overlay_opacity: 1.0,
         }
     }
 
+    /// Makes the overlay shaders skip the nodes outside the active
+    /// selection group, and the theme shader fade those nodes by
+    /// `unselected_dim`
+    pub fn scope_to_selection(&mut self, unselected_dim: f32) {
+        self.selection_only = 1;
+        self.unselected_dim = unselected_dim;
+    }
+
+    /// Multiplies the alpha of the colors drawn by the overlay
+    /// shaders, used when blending the overlay stack
+    pub fn set_overlay_opacity(&mut self, opacity: f32) {
+        self.overlay_opacity = opacity;
+    }
+
     #[inline]
-    pub fn bytes(&self) -> [u8; 84] {
+    pub fn bytes(&self) -> [u8; 96] {
         use crate::view;
 
-        let mut bytes = [0u8; 84];
+        let mut bytes = [0u8; 96];
 
         let view_transform_array = view::mat4_to_array(&self.view_transform);
 
@@ -1007,13 +1265,32 @@ impl NodePushConstants {
             add_float(self.viewport_dims[1]);
         }
 
-        let u_bytes = self.texture_period.to_ne_bytes();
         let mut offset = 80;
+
+        let u_bytes = self.texture_period.to_ne_bytes();
         for i in 0..4 {
             bytes[offset] = u_bytes[i];
             offset += 1;
         }
 
+        let u_bytes = self.selection_only.to_ne_bytes();
+        for i in 0..4 {
+            bytes[offset] = u_bytes[i];
+            offset += 1;
+        }
+
+        let f_bytes = self.unselected_dim.to_ne_bytes();
+        for i in 0..4 {
+            bytes[offset] = f_bytes[i];
+            offset += 1;
+        }
+
+        let f_bytes = self.overlay_opacity.to_ne_bytes();
+        for i in 0..4 {
+            bytes[offset] = f_bytes[i];
+            offset += 1;
+        }
+
         bytes
     }
 }
@@ -1172,7 +1449,7 @@ fn create_tess_pipeline(
                     | Flags::FRAGMENT,
             )
             .offset(0)
-            .size(84)
+            .size(96)
             .build();
 
         let pc_ranges = [pc_range];
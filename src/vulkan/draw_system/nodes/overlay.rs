@@ -4,7 +4,7 @@ use rustc_hash::FxHashMap;
 
 use anyhow::Result;
 
-use crate::vulkan::texture::GradientTexture;
+use crate::vulkan::{memory::GpuAllocations, texture::GradientTexture};
 use crate::{overlays::OverlayKind, vulkan::GfaestusVk};
 
 pub struct OverlayPipelines {
@@ -15,11 +15,16 @@ pub struct OverlayPipelines {
 
     next_overlay_id: usize,
 
-    #[allow(dead_code)]
+    gpu_allocations: GpuAllocations,
+
     pub(super) device: Device,
 }
 
 impl OverlayPipelines {
+    /// The number of overlays of each kind that can be drawn at
+    /// once in stack mode
+    pub const MAX_STACK_LAYERS: usize = 16;
+
     pub(super) fn new(
         app: &GfaestusVk,
         device: &Device,
@@ -50,6 +55,8 @@ impl OverlayPipelines {
 
             next_overlay_id: 0,
 
+            gpu_allocations: app.gpu_allocations.clone(),
+
             device: device.clone(),
         })
     }
@@ -107,24 +114,54 @@ impl OverlayPipelines {
         Ok(())
     }
 
+    /// Writes the descriptor set used by the given stack layer slot,
+    /// returning false if the overlay doesn't exist
+    pub(super) fn write_stack_layer(
+        &mut self,
+        slot: usize,
+        overlay: (usize, OverlayKind),
+        color_scheme: &GradientTexture,
+    ) -> Result<bool> {
+        match overlay.1 {
+            OverlayKind::RGB => {
+                self.pipeline_rgb.write_stack_overlay(slot, overlay.0)
+            }
+            OverlayKind::Value => self.pipeline_value.write_stack_overlay(
+                color_scheme,
+                slot,
+                overlay.0,
+            ),
+        }
+    }
+
+    /// Binds the descriptor sets for drawing an overlay; if
+    /// `stack_slot` is given, the set written by `write_stack_layer`
+    /// for that slot is used, otherwise the active overlay's
     pub(super) fn bind_descriptor_sets(
         &self,
         device: &Device,
         cmd_buf: vk::CommandBuffer,
         overlay: (usize, OverlayKind),
+        stack_slot: Option<usize>,
         selection_descriptor: vk::DescriptorSet,
     ) -> Result<()> {
         unsafe {
             let (desc_sets, layout) = match overlay.1 {
                 OverlayKind::RGB => {
-                    let sets =
-                        [self.pipeline_rgb.overlay_set, selection_descriptor];
+                    let overlay_set = stack_slot
+                        .map(|slot| self.pipeline_rgb.stack_sets[slot])
+                        .unwrap_or(self.pipeline_rgb.overlay_set);
+
+                    let sets = [overlay_set, selection_descriptor];
                     let layout = self.pipeline_rgb.pipeline_layout;
                     (sets, layout)
                 }
                 OverlayKind::Value => {
-                    let sets =
-                        [self.pipeline_value.overlay_set, selection_descriptor];
+                    let overlay_set = stack_slot
+                        .map(|slot| self.pipeline_value.stack_sets[slot])
+                        .unwrap_or(self.pipeline_value.overlay_set);
+
+                    let sets = [overlay_set, selection_descriptor];
                     let layout = self.pipeline_value.pipeline_layout;
                     (sets, layout)
                 }
@@ -163,10 +200,26 @@ impl OverlayPipelines {
         overlays
     }
 
+    /// The ID and kind of the overlay with the given name
+    pub fn overlay_by_name(&self, name: &str) -> Option<(usize, OverlayKind)> {
+        self.overlay_names()
+            .into_iter()
+            .find(|(_, _, overlay_name)| *overlay_name == name)
+            .map(|(overlay_id, kind, _)| (overlay_id, kind))
+    }
+
     pub fn create_overlay(&mut self, overlay: Overlay) -> usize {
         let overlay_id = self.next_overlay_id;
         self.next_overlay_id += 1;
 
+        let size = match &overlay {
+            Overlay::RGB(o) => o.size,
+            Overlay::Value(o) => o.size,
+        };
+
+        self.gpu_allocations
+            .record(&Self::allocation_name(overlay_id), size);
+
         match overlay {
             Overlay::RGB(o) => self.update_rgb_overlay(overlay_id, o),
             Overlay::Value(o) => self.update_value_overlay(overlay_id, o),
@@ -175,6 +228,132 @@ impl OverlayPipelines {
         overlay_id
     }
 
+    /// The name the overlay's buffer is recorded under in the GPU
+    /// allocation log
+    fn allocation_name(overlay_id: usize) -> String {
+        format!("Node Overlay {}", overlay_id)
+    }
+
+    /// Returns `name` if no overlay has that name, otherwise appends
+    /// the lowest number that makes it unique; an empty name is
+    /// replaced with a numbered default
+    pub fn unique_name(&self, name: &str) -> String {
+        let name = name.trim();
+
+        let taken = |candidate: &str| {
+            self.overlay_names()
+                .into_iter()
+                .any(|(_, _, existing)| existing == candidate)
+        };
+
+        if name.is_empty() {
+            let mut ix = self.next_overlay_id + 1;
+            loop {
+                let candidate = format!("Overlay {}", ix);
+                if !taken(&candidate) {
+                    return candidate;
+                }
+                ix += 1;
+            }
+        }
+
+        if !taken(name) {
+            return name.to_string();
+        }
+
+        let mut ix = 2;
+        loop {
+            let candidate = format!("{} ({})", name, ix);
+            if !taken(&candidate) {
+                return candidate;
+            }
+            ix += 1;
+        }
+    }
+
+    /// Rename an overlay, returning the new name, made unique with
+    /// `unique_name`, or `None` if there was no overlay with the
+    /// given ID
+    pub fn rename_overlay(
+        &mut self,
+        overlay_id: usize,
+        name: &str,
+    ) -> Option<String> {
+        let current = self
+            .overlay_names()
+            .into_iter()
+            .find(|(id, _, _)| *id == overlay_id)
+            .map(|(_, _, name)| name.to_string())?;
+
+        let new_name = if current == name.trim() {
+            current
+        } else {
+            self.unique_name(name)
+        };
+
+        if let Some(overlay) = self.pipeline_rgb.overlays.get_mut(&overlay_id) {
+            overlay.name = new_name.clone();
+        }
+
+        if let Some(overlay) = self.pipeline_value.overlays.get_mut(&overlay_id)
+        {
+            overlay.name = new_name.clone();
+        }
+
+        Some(new_name)
+    }
+
+    /// Get a host-visible RGB overlay so its colors can be updated in place
+    pub fn rgb_overlay_mut(
+        &mut self,
+        overlay_id: usize,
+    ) -> Option<&mut NodeOverlay> {
+        self.pipeline_rgb.overlays.get_mut(&overlay_id)
+    }
+
+    /// Get a host-visible value overlay so its values can be updated
+    /// in place
+    pub fn value_overlay_mut(
+        &mut self,
+        overlay_id: usize,
+    ) -> Option<&mut NodeOverlayValue> {
+        self.pipeline_value.overlays.get_mut(&overlay_id)
+    }
+
+    /// Remove an overlay and free its buffers, returning false if
+    /// there was no overlay with the given ID
+    ///
+    /// Waits for the device to be idle, as the overlay may be in use
+    /// by a frame in flight
+    pub fn remove_overlay(&mut self, overlay_id: usize) -> Result<bool> {
+        let in_rgb = self.pipeline_rgb.overlays.contains_key(&overlay_id);
+        let in_value = self.pipeline_value.overlays.contains_key(&overlay_id);
+
+        if !in_rgb && !in_value {
+            return Ok(false);
+        }
+
+        unsafe { self.device.device_wait_idle() }?;
+
+        if let Some(overlay) = self.pipeline_rgb.overlays.remove(&overlay_id) {
+            overlay.destroy(&self.device);
+        }
+
+        if let Some(overlay) = self.pipeline_value.overlays.remove(&overlay_id)
+        {
+            overlay.destroy(&self.device);
+        }
+
+        if self.overlay_set_id.map(|(id, _)| id) == Some(overlay_id) {
+            self.overlay_set_id = None;
+        }
+
+        self.gpu_allocations
+            .remove(&Self::allocation_name(overlay_id));
+
+        Ok(true)
+    }
+
     fn update_rgb_overlay(&mut self, overlay_id: usize, overlay: NodeOverlay) {
         if self.pipeline_value.overlays.contains_key(&overlay_id) {
             panic!("Tried to update a Value overlay ID with an RGB overlay");
@@ -201,6 +380,8 @@ pub struct OverlayPipelineRGB {
     pub(super) descriptor_set_layout: vk::DescriptorSetLayout,
 
     pub(super) overlay_set: vk::DescriptorSet,
+    /// One set per layer slot, used in stack mode
+    pub(super) stack_sets: Vec<vk::DescriptorSet>,
 
     pub(super) pipeline_layout: vk::PipelineLayout,
     pub(super) pipeline: vk::Pipeline,
@@ -217,6 +398,8 @@ pub struct OverlayPipelineValue {
     sampler: vk::Sampler,
 
     pub(super) overlay_set: vk::DescriptorSet,
+    /// One set per layer slot, used in stack mode
+    pub(super) stack_sets: Vec<vk::DescriptorSet>,
 
     pub(super) pipeline_layout: vk::PipelineLayout,
     pub(super) pipeline: vk::Pipeline,
@@ -244,6 +427,25 @@ impl OverlayPipelineValue {
         Ok(())
     }
 
+    fn write_stack_overlay(
+        &mut self,
+        color_scheme: &GradientTexture,
+        slot: usize,
+        overlay_id: usize,
+    ) -> Result<bool> {
+        if let Some(overlay) = self.overlays.get(&overlay_id) {
+            overlay.write_descriptor_set(
+                &self.device,
+                color_scheme,
+                self.sampler,
+                &self.stack_sets[slot],
+            )?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
     fn layout_bindings() -> [vk::DescriptorSetLayoutBinding; 2] {
         use vk::ShaderStageFlags as Stages;
 
@@ -312,7 +514,8 @@ impl OverlayPipelineValue {
             selection_set_layout,
         );
 
-        let image_count = 1;
+        // the active overlay's set, and one per stack layer
+        let image_count = 1 + OverlayPipelines::MAX_STACK_LAYERS as u32;
 
         let descriptor_pool = {
             let sampler_size = vk::DescriptorPoolSize {
@@ -336,7 +539,7 @@ impl OverlayPipelineValue {
         }?;
 
         let descriptor_sets = {
-            let layouts = vec![desc_set_layout];
+            let layouts = vec![desc_set_layout; image_count as usize];
 
             let alloc_info = vk::DescriptorSetAllocateInfo::builder()
                 .descriptor_pool(descriptor_pool)
@@ -365,6 +568,7 @@ impl OverlayPipelineValue {
             descriptor_set_layout: desc_set_layout,
 
             overlay_set: descriptor_sets[0],
+            stack_sets: descriptor_sets[1..].to_vec(),
 
             sampler,
 
@@ -403,6 +607,20 @@ impl OverlayPipelineRGB {
         Ok(())
     }
 
+    fn write_stack_overlay(
+        &mut self,
+        slot: usize,
+        overlay_id: usize,
+    ) -> Result<bool> {
+        if let Some(overlay) = self.overlays.get(&overlay_id) {
+            overlay
+                .write_descriptor_set(&self.device, &self.stack_sets[slot])?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
     fn layout_binding() -> vk::DescriptorSetLayoutBinding {
         use vk::ShaderStageFlags as Stages;
 
@@ -463,7 +681,8 @@ impl OverlayPipelineRGB {
             selection_set_layout,
         );
 
-        let image_count = 1;
+        // the active overlay's set, and one per stack layer
+        let image_count = 1 + OverlayPipelines::MAX_STACK_LAYERS as u32;
 
         let descriptor_pool = {
             let pool_size = vk::DescriptorPoolSize {
@@ -482,7 +701,7 @@ impl OverlayPipelineRGB {
         }?;
 
         let descriptor_sets = {
-            let layouts = vec![desc_set_layout];
+            let layouts = vec![desc_set_layout; image_count as usize];
 
             let alloc_info = vk::DescriptorSetAllocateInfo::builder()
                 .descriptor_pool(descriptor_pool)
@@ -507,6 +726,7 @@ impl OverlayPipelineRGB {
             descriptor_set_layout: desc_set_layout,
 
             overlay_set: descriptor_sets[0],
+            stack_sets: descriptor_sets[1..].to_vec(),
 
             pipeline_layout,
             pipeline,
@@ -793,6 +1013,40 @@ impl NodeOverlayValue {
         Ok(())
     }
 
+    /// Read the values of a host-visible overlay back from the buffer,
+    /// e.g. after they've been normalized by a compute shader
+    pub fn read_values(
+        &self,
+        device: &Device,
+        node_count: usize,
+    ) -> Result<Vec<f32>> {
+        assert!(self.host_visible);
+
+        let mut values = Vec::with_capacity(node_count);
+
+        unsafe {
+            let ptr = device.map_memory(
+                self.memory,
+                0,
+                self.size,
+                vk::MemoryMapFlags::empty(),
+            )?;
+
+            let val_ptr = ptr as *const f32;
+            let val_slice = std::slice::from_raw_parts(val_ptr, node_count);
+
+            values.extend_from_slice(val_slice);
+
+            device.unmap_memory(self.memory);
+        }
+
+        Ok(values)
+    }
+
+    pub fn buffer(&self) -> vk::Buffer {
+        self.buffer
+    }
+
     /// Create a new overlay that's filled during construction and immutable afterward
     ///
     /// Uses device memory if available
@@ -0,0 +1,213 @@
+use ash::version::DeviceV1_0;
+use ash::{vk, Device};
+
+use anyhow::Result;
+
+use super::GfaestusVk;
+
+/// A host-visible buffer that a rendered swapchain image can be
+/// copied into, so that frames can be saved to disk
+pub struct FrameCapture {
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    size: vk::DeviceSize,
+
+    pub width: u32,
+    pub height: u32,
+
+    /// Whether the swapchain stores the red and blue channels in
+    /// reverse order
+    bgra: bool,
+}
+
+impl FrameCapture {
+    /// Creates a capture buffer matching the current swapchain
+    pub fn new(app: &GfaestusVk) -> Result<Self> {
+        let extent = app.swapchain_props.extent;
+
+        let bgra = match app.swapchain_props.format.format {
+            vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => true,
+            vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_SRGB => false,
+            format => anyhow::bail!(
+                "Can't capture frames with swapchain format {:?}",
+                format
+            ),
+        };
+
+        let img_size = (extent.width * extent.height * 4) as vk::DeviceSize;
+
+        let usage = vk::BufferUsageFlags::TRANSFER_DST;
+
+        let mem_props = vk::MemoryPropertyFlags::HOST_VISIBLE
+            | vk::MemoryPropertyFlags::HOST_COHERENT;
+
+        let (buffer, memory, size) =
+            app.create_buffer(img_size, usage, mem_props)?;
+
+        app.set_debug_object_name(buffer, "Frame Capture Buffer")?;
+        app.gpu_allocations.record("Frame Capture Buffer", size);
+
+        Ok(Self {
+            buffer,
+            memory,
+            size,
+
+            width: extent.width,
+            height: extent.height,
+
+            bgra,
+        })
+    }
+
+    /// True if the buffer has the size of the current swapchain
+    /// images, i.e. it doesn't have to be recreated after a resize
+    pub fn matches_swapchain(&self, app: &GfaestusVk) -> bool {
+        let extent = app.swapchain_props.extent;
+        self.width == extent.width && self.height == extent.height
+    }
+
+    /// Records copying the swapchain image, which must be in the
+    /// `PRESENT_SRC_KHR` layout, into the buffer, and transitioning
+    /// the image back for presentation
+    pub(super) fn record_copy(
+        &self,
+        device: &Device,
+        cmd_buf: vk::CommandBuffer,
+        image: vk::Image,
+    ) {
+        let subresource_range = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+
+        let to_transfer = vk::ImageMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+            .dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+            .old_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+            .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource_range)
+            .build();
+
+        let to_present = vk::ImageMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::TRANSFER_READ)
+            .dst_access_mask(vk::AccessFlags::MEMORY_READ)
+            .old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .new_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource_range)
+            .build();
+
+        let buffer_barrier = vk::BufferMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+            .dst_access_mask(vk::AccessFlags::HOST_READ)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .buffer(self.buffer)
+            .offset(0)
+            .size(vk::WHOLE_SIZE)
+            .build();
+
+        let region = vk::BufferImageCopy::builder()
+            .buffer_offset(0)
+            .buffer_row_length(0)
+            .buffer_image_height(0)
+            .image_subresource(vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1,
+            })
+            .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+            .image_extent(vk::Extent3D {
+                width: self.width,
+                height: self.height,
+                depth: 1,
+            })
+            .build();
+
+        unsafe {
+            device.cmd_pipeline_barrier(
+                cmd_buf,
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_transfer],
+            );
+
+            device.cmd_copy_image_to_buffer(
+                cmd_buf,
+                image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                self.buffer,
+                &[region],
+            );
+
+            device.cmd_pipeline_barrier(
+                cmd_buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE
+                    | vk::PipelineStageFlags::HOST,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[buffer_barrier],
+                &[to_present],
+            );
+        }
+    }
+
+    /// Reads the most recently captured frame as 8-bit RGBA pixels,
+    /// row by row from the top left. Must only be called once the
+    /// frame has finished rendering
+    pub fn read_rgba(&self, device: &Device) -> Result<Vec<u8>> {
+        let len = (self.width * self.height * 4) as usize;
+
+        let mut pixels = vec![0u8; len];
+
+        unsafe {
+            let data_ptr = device.map_memory(
+                self.memory,
+                0,
+                self.size,
+                vk::MemoryMapFlags::empty(),
+            )?;
+
+            let slice = std::slice::from_raw_parts(data_ptr as *const u8, len);
+            pixels.copy_from_slice(slice);
+
+            device.unmap_memory(self.memory);
+        }
+
+        for pixel in pixels.chunks_exact_mut(4) {
+            if self.bgra {
+                pixel.swap(0, 2);
+            }
+            // the swapchain is composited as opaque
+            pixel[3] = 255;
+        }
+
+        Ok(pixels)
+    }
+
+    pub fn destroy(&mut self, device: &Device) {
+        unsafe {
+            device.destroy_buffer(self.buffer, None);
+            device.free_memory(self.memory, None);
+        }
+
+        self.buffer = vk::Buffer::null();
+        self.memory = vk::DeviceMemory::null();
+        self.size = 0 as vk::DeviceSize;
+        self.width = 0;
+        self.height = 0;
+    }
+}
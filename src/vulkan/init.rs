@@ -281,7 +281,12 @@ pub(super) fn create_swapchain_and_images(
             .image_color_space(props.format.color_space)
             .image_extent(props.extent)
             .image_array_layers(1)
-            .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT);
+            // frames are copied out of the swapchain images when
+            // capturing frames
+            .image_usage(
+                vk::ImageUsageFlags::COLOR_ATTACHMENT
+                    | vk::ImageUsageFlags::TRANSFER_SRC,
+            );
 
         builder = if graphics_ix != present_ix {
             builder
@@ -0,0 +1,176 @@
+use ash::vk;
+
+use crossbeam::atomic::AtomicCell;
+use parking_lot::Mutex;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+/// Keeps track of the sizes of the major GPU buffers and textures,
+/// so that memory use can be reported when diagnosing why a graph
+/// doesn't fit on the GPU.
+///
+/// Allocations are identified by name; recording an allocation with
+/// a name that's already in use replaces the previous entry, which
+/// matches how the buffers are reallocated.
+#[derive(Clone)]
+pub struct GpuAllocations {
+    sizes: Arc<Mutex<BTreeMap<String, vk::DeviceSize>>>,
+    device_local_memory: Arc<AtomicCell<vk::DeviceSize>>,
+}
+
+impl std::default::Default for GpuAllocations {
+    fn default() -> Self {
+        Self {
+            sizes: Arc::new(Mutex::new(BTreeMap::new())),
+            device_local_memory: Arc::new(AtomicCell::new(0)),
+        }
+    }
+}
+
+impl GpuAllocations {
+    pub fn new(mem_props: &vk::PhysicalDeviceMemoryProperties) -> Self {
+        let heaps =
+            &mem_props.memory_heaps[..mem_props.memory_heap_count as usize];
+
+        let device_local_memory = heaps
+            .iter()
+            .filter(|heap| {
+                heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL)
+            })
+            .map(|heap| heap.size)
+            .sum();
+
+        debug!(
+            "GPU memory: device_local_bytes={} ({})",
+            device_local_memory,
+            format_bytes(device_local_memory)
+        );
+
+        let result = Self::default();
+        result.device_local_memory.store(device_local_memory);
+        result
+    }
+
+    /// The total size of the device local memory heaps
+    pub fn device_local_memory(&self) -> vk::DeviceSize {
+        self.device_local_memory.load()
+    }
+
+    /// Records the size of an allocation, logging it at debug level
+    /// if it's new or its size has changed
+    pub fn record(&self, name: &str, size: vk::DeviceSize) {
+        let prev = self.sizes.lock().insert(name.to_string(), size);
+
+        if prev != Some(size) {
+            debug!(
+                "GPU allocation: name=\"{}\" bytes={} ({})",
+                name,
+                size,
+                format_bytes(size)
+            );
+        }
+    }
+
+    /// Records the size of an allocation that's recreated often,
+    /// e.g. every frame, logging it at trace level
+    pub fn record_transient(&self, name: &str, size: vk::DeviceSize) {
+        let prev = self.sizes.lock().insert(name.to_string(), size);
+
+        if prev != Some(size) {
+            trace!(
+                "GPU allocation: name=\"{}\" bytes={} ({})",
+                name,
+                size,
+                format_bytes(size)
+            );
+        }
+    }
+
+    pub fn remove(&self, name: &str) {
+        if let Some(size) = self.sizes.lock().remove(name) {
+            debug!(
+                "GPU free: name=\"{}\" bytes={} ({})",
+                name,
+                size,
+                format_bytes(size)
+            );
+        }
+    }
+
+    /// The recorded allocations, sorted by name
+    pub fn entries(&self) -> Vec<(String, vk::DeviceSize)> {
+        self.sizes
+            .lock()
+            .iter()
+            .map(|(name, &size)| (name.clone(), size))
+            .collect()
+    }
+
+    pub fn total(&self) -> vk::DeviceSize {
+        self.sizes.lock().values().sum()
+    }
+
+    /// A human readable report of the recorded allocations, largest
+    /// first, and their total compared to the device local memory
+    pub fn summary(&self) -> String {
+        let mut entries = self.entries();
+        entries.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        let name_width = entries
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = Vec::with_capacity(entries.len() + 1);
+
+        for (name, size) in entries {
+            lines.push(format!(
+                "{:width$}  {:>10}",
+                name,
+                format_bytes(size),
+                width = name_width
+            ));
+        }
+
+        let total = self.total();
+        let device_local = self.device_local_memory();
+
+        if device_local > 0 {
+            let percent = 100.0 * total as f64 / device_local as f64;
+            lines.push(format!(
+                "Total: {} of {} device local memory ({:.1}%)",
+                format_bytes(total),
+                format_bytes(device_local),
+                percent
+            ));
+        } else {
+            lines.push(format!("Total: {}", format_bytes(total)));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Formats a byte count using binary units, e.g. "1.50 MiB"
+pub fn format_bytes(bytes: vk::DeviceSize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.2} {}", value, UNITS[unit])
+}